@@ -0,0 +1,118 @@
+use chrono::{Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_decimal::Decimal;
+use rusto::config::{RiskConfig, SimulatorConfig};
+use rusto::risk::RiskManager;
+use rusto::simulator::trade_log::TradeLogger;
+use rusto::simulator::SimulatorEngine;
+use rusto::types::{
+    DepthLevel, DepthUpdate, MarketEvent, NormalizedTrade, ProcessingEvent, SetupType, Side,
+    TradeSignal,
+};
+
+/// One long open per symbol, all priced around 1000
+const SYMBOLS: [&str; 5] = ["btcusdt", "ethusdt", "solusdt", "bnbusdt", "xrpusdt"];
+
+fn engine(exit_check_interval_ms: u64) -> SimulatorEngine {
+    let config: SimulatorConfig = toml::from_str(&format!(
+        r#"
+        slippage_ticks = 1
+        maker_fee = 0.0002
+        taker_fee = 0.0004
+        order_book_depth = 20
+        leverage = 20.0
+        margin_type = "Isolated"
+        maintenance_margin_rate = 0.004
+        max_spread_bps = 1000.0
+        min_depth_imbalance_ratio = 0.01
+        slippage_model_enabled = false
+        exit_check_interval_ms = {}
+        "#,
+        exit_check_interval_ms
+    ))
+    .unwrap();
+    let risk: RiskConfig = toml::from_str(&format!(
+        r#"
+        initial_balance = 10000.0
+        max_risk_per_trade = 0.001
+        daily_loss_limit_pct = 0.03
+        max_concurrent_positions = {}
+        break_even_ticks = 1000
+        default_stop_ticks = 10
+        default_target_multiplier = 2.0
+        confidence_sizing_enabled = false
+        "#,
+        SYMBOLS.len()
+    ))
+    .unwrap();
+    let dir = std::env::temp_dir();
+    let logger = TradeLogger::new(
+        dir.join("rusto-bench.csv").display().to_string(),
+        dir.join("rusto-bench.json").display().to_string(),
+        ":memory:".to_string(),
+    );
+    let leverage = Decimal::try_from(config.leverage).unwrap();
+    let mut sim = SimulatorEngine::new(config, RiskManager::new(&risk, leverage), logger);
+
+    let level = |price: i64| DepthLevel {
+        price: Decimal::from(price),
+        quantity: Decimal::from(1000),
+    };
+    for symbol in SYMBOLS {
+        sim.handle_market_event(MarketEvent::Depth(DepthUpdate {
+            symbol: symbol.to_string(),
+            bids: vec![level(999)],
+            asks: vec![level(1001)],
+            timestamp: Utc::now(),
+        }));
+        sim.handle_processing_event(ProcessingEvent::Signal(TradeSignal::new(
+            symbol.to_string(),
+            Side::Buy,
+            SetupType::MomentumSqueeze,
+            Decimal::from(1000),
+            Decimal::from(980),
+            Decimal::from(1040),
+            Decimal::ONE,
+        )));
+    }
+    sim
+}
+
+/// One trade per millisecond across the symbols, oscillating between the stops and targets
+fn trades(count: usize) -> Vec<MarketEvent> {
+    let start = Utc::now();
+    (0..count)
+        .map(|i| {
+            MarketEvent::Trade(NormalizedTrade {
+                symbol: SYMBOLS[i % SYMBOLS.len()].to_string(),
+                price: Decimal::from(990 + (i % 40) as i64),
+                quantity: Decimal::ONE,
+                side: if i % 2 == 0 { Side::Buy } else { Side::Sell },
+                timestamp: start + Duration::milliseconds(i as i64),
+                trade_id: i as u64,
+            })
+        })
+        .collect()
+}
+
+fn bench_exit_checks(c: &mut Criterion) {
+    let trades = trades(10_000);
+
+    for interval_ms in [0, 100] {
+        c.bench_function(&format!("on_trade_10k_interval_{}ms", interval_ms), |b| {
+            b.iter_batched(
+                || engine(interval_ms),
+                |mut sim| {
+                    for trade in &trades {
+                        sim.handle_market_event(black_box(trade.clone()));
+                    }
+                    sim
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+criterion_group!(benches, bench_exit_checks);
+criterion_main!(benches);
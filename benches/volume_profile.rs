@@ -0,0 +1,83 @@
+use chrono::{Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_decimal::Decimal;
+use rusto::config::VolumeProfileConfig;
+use rusto::types::{NormalizedTrade, Side};
+use rusto::volume_profile::VolumeProfiler;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts heap allocations so the per-trade cost is visible alongside timings
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn trades(count: usize) -> Vec<NormalizedTrade> {
+    let start = Utc::now();
+    (0..count)
+        .map(|i| NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            // Cycle over ~200 levels around a large price
+            price: Decimal::new(9_876_543_210 + (i % 200) as i64, 2),
+            quantity: Decimal::ONE,
+            side: if i % 2 == 0 { Side::Buy } else { Side::Sell },
+            timestamp: start + Duration::milliseconds(i as i64),
+            trade_id: i as u64,
+        })
+        .collect()
+}
+
+fn profiler() -> VolumeProfiler {
+    let config: VolumeProfileConfig = toml::from_str(
+        r#"
+        tick_size = 0.01
+        value_area_pct = 0.70
+        session_reset_hours = 24
+        min_levels = 1000000
+        "#,
+    )
+    .unwrap();
+    VolumeProfiler::new(&config)
+}
+
+fn bench_process_trade(c: &mut Criterion) {
+    let trades = trades(10_000);
+
+    // Snapshots are suppressed (min_levels), so this isolates the per-trade update path
+    let mut vp = profiler();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for trade in &trades {
+        vp.process_trade(trade);
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "process_trade: {:.2} allocations/trade",
+        allocations as f64 / trades.len() as f64
+    );
+
+    c.bench_function("process_trade_10k", |b| {
+        b.iter(|| {
+            let mut vp = profiler();
+            for trade in &trades {
+                black_box(vp.process_trade(trade));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_process_trade);
+criterion_main!(benches);
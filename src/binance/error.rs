@@ -0,0 +1,190 @@
+use crate::exchange::ExchangeError;
+use reqwest::{Response, StatusCode};
+use std::time::Duration;
+
+/// Base delay for retrying transient network / 5xx failures
+const NETWORK_BACKOFF_BASE: Duration = Duration::from_secs(1);
+/// Base delay for a 429 that did not send `Retry-After`
+const RATE_LIMIT_BACKOFF_BASE: Duration = Duration::from_secs(30);
+/// Upper bound for computed (non-server-specified) backoff
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Errors returned by Binance REST calls
+#[derive(Debug, thiserror::Error)]
+pub enum BinanceError {
+    /// Connection, DNS, TLS or timeout failure before a response arrived
+    #[error("{context}: network error: {source}")]
+    Network {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// HTTP 429: request weight limit exceeded
+    #[error("{context}: rate limited (429), retry after {retry_after:?}")]
+    RateLimited {
+        context: &'static str,
+        retry_after: Option<Duration>,
+    },
+
+    /// HTTP 418: IP auto-banned after repeated 429s
+    #[error("{context}: IP banned (418), retry after {retry_after:?}")]
+    IpBanned {
+        context: &'static str,
+        retry_after: Option<Duration>,
+    },
+
+    /// Any other non-success status
+    #[error("{context}: request failed with status {status}")]
+    Status {
+        context: &'static str,
+        status: StatusCode,
+    },
+
+    /// Response body could not be decoded
+    #[error("{context}: failed to parse response: {message}")]
+    Parse {
+        context: &'static str,
+        message: String,
+    },
+
+    #[error("No successful ping responses")]
+    NoPingResponses,
+
+    #[error("Time offset {offset_ms}ms exceeds maximum {max_ms}ms. Please sync your system clock.")]
+    TimeOffset { offset_ms: i64, max_ms: i64 },
+}
+
+impl BinanceError {
+    pub(crate) fn network(context: &'static str, source: reqwest::Error) -> Self {
+        BinanceError::Network { context, source }
+    }
+
+    pub(crate) fn parse(context: &'static str, source: impl std::fmt::Display) -> Self {
+        BinanceError::Parse {
+            context,
+            message: source.to_string(),
+        }
+    }
+
+    /// Map a non-success response to an error, honouring `Retry-After`
+    pub(crate) fn from_response(context: &'static str, response: &Response) -> Self {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => BinanceError::RateLimited {
+                context,
+                retry_after,
+            },
+            StatusCode::IM_A_TEAPOT => BinanceError::IpBanned {
+                context,
+                retry_after,
+            },
+            status => BinanceError::Status { context, status },
+        }
+    }
+
+    /// How long to wait before retry `attempt` (0-based), or None if retrying is pointless.
+    /// - 429: server `Retry-After`, else exponential from 30s
+    /// - 418: only when the server says when the ban lifts
+    /// - network / 5xx: exponential from 1s
+    pub fn retry_delay(&self, attempt: u32) -> Option<Duration> {
+        let exponential =
+            |base: Duration| base.saturating_mul(1u32 << attempt.min(16)).min(MAX_BACKOFF);
+
+        match self {
+            BinanceError::RateLimited { retry_after, .. } => {
+                Some(retry_after.unwrap_or_else(|| exponential(RATE_LIMIT_BACKOFF_BASE)))
+            }
+            BinanceError::IpBanned { retry_after, .. } => *retry_after,
+            BinanceError::Network { .. } | BinanceError::NoPingResponses => {
+                Some(exponential(NETWORK_BACKOFF_BASE))
+            }
+            BinanceError::Status { status, .. } if status.is_server_error() => {
+                Some(exponential(NETWORK_BACKOFF_BASE))
+            }
+            BinanceError::Status { .. }
+            | BinanceError::Parse { .. }
+            | BinanceError::TimeOffset { .. } => None,
+        }
+    }
+}
+
+impl From<BinanceError> for ExchangeError {
+    fn from(e: BinanceError) -> Self {
+        ExchangeError {
+            venue: "binance",
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::ExchangeInfoManager;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one canned HTTP response per connection
+    async fn serve(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_429_is_rate_limited_and_backs_off() {
+        let url = serve(
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let mut manager = ExchangeInfoManager::new(url);
+
+        let err = manager.sync().await.unwrap_err();
+        assert!(matches!(
+            err,
+            BinanceError::RateLimited {
+                retry_after: Some(d),
+                ..
+            } if d == Duration::from_secs(7)
+        ));
+        // Server-specified wait wins over the short network backoff
+        assert_eq!(err.retry_delay(0), Some(Duration::from_secs(7)));
+    }
+
+    #[tokio::test]
+    async fn test_418_without_retry_after_is_not_retried() {
+        let url = serve("HTTP/1.1 418 I'm a teapot\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .await;
+        let manager = ExchangeInfoManager::new(url);
+
+        let err = manager.fetch_top_symbols(10).await.unwrap_err();
+        assert!(matches!(err, BinanceError::IpBanned { retry_after: None, .. }));
+        assert_eq!(err.retry_delay(0), None);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let err = BinanceError::RateLimited {
+            context: "test",
+            retry_after: None,
+        };
+        assert_eq!(err.retry_delay(0), Some(Duration::from_secs(30)));
+        assert_eq!(err.retry_delay(1), Some(Duration::from_secs(60)));
+        assert_eq!(err.retry_delay(10), Some(MAX_BACKOFF));
+        assert!(BinanceError::parse("test", "bad json").retry_delay(0).is_none());
+    }
+}
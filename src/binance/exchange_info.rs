@@ -1,3 +1,7 @@
+use crate::binance::error::BinanceError;
+use crate::binance::time_sync::fetch_server_time;
+use crate::exchange::{Exchange, ExchangeError, ExchangeFuture, SymbolRules};
+use crate::market_data::binance_ws::stream_url;
 use reqwest::Client;
 use rust_decimal::Decimal;
 use serde::Deserialize;
@@ -70,118 +74,12 @@ enum Filter {
     Other,
 }
 
-/// Symbol trading rules and filters
+/// A Binance symbol: name and status plus its venue-neutral rules
 #[derive(Debug, Clone)]
 pub struct SymbolInfo {
     pub symbol: String,
     pub status: String,
-    pub base_asset: String,
-    pub quote_asset: String,
-    // Price filter
-    pub price_tick_size: Decimal,
-    pub min_price: Decimal,
-    pub max_price: Decimal,
-    // Lot size filter
-    pub quantity_step_size: Decimal,
-    pub min_quantity: Decimal,
-    pub max_quantity: Decimal,
-    // Min notional
-    pub min_notional: Decimal,
-}
-
-impl SymbolInfo {
-    /// Validate and round price to comply with tick size
-    pub fn round_price(&self, price: Decimal) -> Result<Decimal, OrderValidationError> {
-        if price < self.min_price {
-            return Err(OrderValidationError::PriceTooLow {
-                price,
-                min: self.min_price,
-            });
-        }
-
-        if price > self.max_price {
-            return Err(OrderValidationError::PriceTooHigh {
-                price,
-                max: self.max_price,
-            });
-        }
-
-        // Round to tick size
-        let rounded = (price / self.price_tick_size)
-            .round_dp(0)
-            * self.price_tick_size;
-
-        Ok(rounded)
-    }
-
-    /// Validate and round quantity to comply with step size
-    pub fn round_quantity(&self, quantity: Decimal) -> Result<Decimal, OrderValidationError> {
-        if quantity < self.min_quantity {
-            return Err(OrderValidationError::QuantityTooLow {
-                quantity,
-                min: self.min_quantity,
-            });
-        }
-
-        if quantity > self.max_quantity {
-            return Err(OrderValidationError::QuantityTooHigh {
-                quantity,
-                max: self.max_quantity,
-            });
-        }
-
-        // Round to step size
-        let rounded = (quantity / self.quantity_step_size)
-            .round_dp(0)
-            * self.quantity_step_size;
-
-        Ok(rounded)
-    }
-
-    /// Validate notional value (price * quantity)
-    pub fn validate_notional(&self, price: Decimal, quantity: Decimal) -> Result<(), OrderValidationError> {
-        let notional = price * quantity;
-
-        if notional < self.min_notional {
-            return Err(OrderValidationError::NotionalTooLow {
-                notional,
-                min: self.min_notional,
-            });
-        }
-
-        Ok(())
-    }
-
-    /// Full order validation (price, quantity, and notional)
-    pub fn validate_order(
-        &self,
-        price: Decimal,
-        quantity: Decimal,
-    ) -> Result<(Decimal, Decimal), OrderValidationError> {
-        let rounded_price = self.round_price(price)?;
-        let rounded_quantity = self.round_quantity(quantity)?;
-        self.validate_notional(rounded_price, rounded_quantity)?;
-
-        Ok((rounded_price, rounded_quantity))
-    }
-}
-
-#[derive(Debug, thiserror::Error)]
-pub enum OrderValidationError {
-    #[error("Price {price} is below minimum {min}")]
-    PriceTooLow { price: Decimal, min: Decimal },
-
-    #[error("Price {price} is above maximum {max}")]
-    PriceTooHigh { price: Decimal, max: Decimal },
-
-    #[error("Quantity {quantity} is below minimum {min}")]
-    QuantityTooLow { quantity: Decimal, min: Decimal },
-
-    #[error("Quantity {quantity} is above maximum {max}")]
-    QuantityTooHigh { quantity: Decimal, max: Decimal },
-
-    #[error("Notional value {notional} is below minimum {min}")]
-    NotionalTooLow { notional: Decimal, min: Decimal },
+    pub rules: SymbolRules,
 }
 
 /// Manages exchange information and symbol filters
@@ -189,6 +87,12 @@ pub struct ExchangeInfoManager {
     client: Client,
     base_url: String,
     symbols: HashMap<String, SymbolInfo>,
+    /// Always included in auto-selection (lowercase)
+    pinned_symbols: Vec<String>,
+    /// Never included in auto-selection (lowercase)
+    blacklisted_symbols: Vec<String>,
+    /// Unparsable filter values fall back to 0/max instead of rejecting the symbol
+    lenient_filters: bool,
 }
 
 impl ExchangeInfoManager {
@@ -197,11 +101,31 @@ impl ExchangeInfoManager {
             client: Client::new(),
             base_url,
             symbols: HashMap::new(),
+            pinned_symbols: Vec::new(),
+            blacklisted_symbols: Vec::new(),
+            lenient_filters: false,
         }
     }
 
+    /// Accept malformed filter values (legacy 0/max fallbacks) instead of skipping the symbol
+    pub fn set_lenient_filters(&mut self, lenient: bool) {
+        self.lenient_filters = lenient;
+    }
+
+    /// Pin symbols into (and blacklist symbols out of) `fetch_top_symbols`
+    pub fn set_symbol_overrides(&mut self, pinned: &[String], blacklisted: &[String]) {
+        self.pinned_symbols = pinned.iter().map(|s| s.to_lowercase()).collect();
+        self.blacklisted_symbols = blacklisted.iter().map(|s| s.to_lowercase()).collect();
+    }
+
+    /// Use a shared, pre-configured HTTP client
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Fetch and parse exchange info from Binance Futures API
-    pub async fn sync(&mut self) -> Result<(), String> {
+    pub async fn sync(&mut self) -> Result<(), BinanceError> {
         let url = format!("{}/fapi/v1/exchangeInfo", self.base_url);
 
         info!("Fetching exchange info from {}...", url);
@@ -211,27 +135,32 @@ impl ExchangeInfoManager {
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch exchange info: {}", e))?;
+            .map_err(|e| BinanceError::network("exchange info", e))?;
 
         if !response.status().is_success() {
-            return Err(format!(
-                "Exchange info request failed with status: {}",
-                response.status()
-            ));
+            return Err(BinanceError::from_response("exchange info", &response));
         }
 
         let exchange_info: ExchangeInfoResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse exchange info: {}", e))?;
+            .map_err(|e| BinanceError::parse("exchange info", e))?;
 
         info!(
             "Received exchange info for {} symbols",
             exchange_info.symbols.len()
         );
 
-        // Parse and store symbol info
-        for symbol_data in exchange_info.symbols {
+        self.load_symbols(exchange_info.symbols);
+
+        info!("Exchange info sync completed: {} symbols loaded", self.symbols.len());
+
+        Ok(())
+    }
+
+    /// Parse and store TRADING symbols, skipping any with invalid filters
+    fn load_symbols(&mut self, symbols: Vec<SymbolData>) {
+        for symbol_data in symbols {
             if symbol_data.status != "TRADING" {
                 warn!(
                     "Symbol {} is not trading (status: {}), skipping",
@@ -246,9 +175,9 @@ impl ExchangeInfoManager {
                     let symbol_lower = info.symbol.to_lowercase();
                     info!(
                         symbol = %info.symbol,
-                        tick_size = %info.price_tick_size,
-                        step_size = %info.quantity_step_size,
-                        min_notional = %info.min_notional,
+                        tick_size = %info.rules.price_tick_size,
+                        step_size = %info.rules.quantity_step_size,
+                        min_notional = %info.rules.min_notional,
                         "Symbol info loaded"
                     );
                     self.symbols.insert(symbol_lower, info);
@@ -261,10 +190,15 @@ impl ExchangeInfoManager {
                 }
             }
         }
+    }
 
-        info!("Exchange info sync completed: {} symbols loaded", self.symbols.len());
-
-        Ok(())
+    /// Parse one filter value; malformed input is an error unless lenient
+    fn parse_filter_value(&self, field: &str, raw: &str, fallback: Decimal) -> Result<Decimal, String> {
+        match Decimal::from_str(raw) {
+            Ok(value) => Ok(value),
+            Err(_) if self.lenient_filters => Ok(fallback),
+            Err(e) => Err(format!("invalid {} {:?}: {}", field, raw, e)),
+        }
     }
 
     /// Parse symbol data into SymbolInfo
@@ -284,41 +218,60 @@ impl ExchangeInfoManager {
                     max_price: max,
                     tick_size,
                 } => {
-                    price_tick_size = Some(Decimal::from_str(&tick_size).unwrap_or(Decimal::ZERO));
-                    min_price = Some(Decimal::from_str(&min).unwrap_or(Decimal::ZERO));
-                    max_price = Some(Decimal::from_str(&max).unwrap_or(Decimal::MAX));
+                    price_tick_size = Some(self.parse_filter_value("tick size", &tick_size, Decimal::ZERO)?);
+                    min_price = Some(self.parse_filter_value("min price", &min, Decimal::ZERO)?);
+                    max_price = Some(self.parse_filter_value("max price", &max, Decimal::MAX)?);
                 }
                 Filter::LotSize {
                     min_qty,
                     max_qty,
                     step_size,
                 } => {
-                    quantity_step_size = Some(Decimal::from_str(&step_size).unwrap_or(Decimal::ZERO));
-                    min_quantity = Some(Decimal::from_str(&min_qty).unwrap_or(Decimal::ZERO));
-                    max_quantity = Some(Decimal::from_str(&max_qty).unwrap_or(Decimal::MAX));
+                    quantity_step_size = Some(self.parse_filter_value("step size", &step_size, Decimal::ZERO)?);
+                    min_quantity = Some(self.parse_filter_value("min quantity", &min_qty, Decimal::ZERO)?);
+                    max_quantity = Some(self.parse_filter_value("max quantity", &max_qty, Decimal::MAX)?);
                 }
                 Filter::MinNotional { notional } => {
-                    min_notional = Some(Decimal::from_str(&notional).unwrap_or(Decimal::ZERO));
+                    min_notional = Some(self.parse_filter_value("min notional", &notional, Decimal::ZERO)?);
                 }
                 _ => {}
             }
         }
 
+        // A zero tick/step parses fine but divides by zero in rounding
+        if !self.lenient_filters {
+            if price_tick_size.is_some_and(|tick| tick <= Decimal::ZERO) {
+                return Err("tick size must be > 0".into());
+            }
+            if quantity_step_size.is_some_and(|step| step <= Decimal::ZERO) {
+                return Err("step size must be > 0".into());
+            }
+        }
+
         Ok(SymbolInfo {
             symbol: data.symbol,
             status: data.status,
-            base_asset: data.base_asset,
-            quote_asset: data.quote_asset,
-            price_tick_size: price_tick_size.ok_or("Missing price tick size")?,
-            min_price: min_price.ok_or("Missing min price")?,
-            max_price: max_price.ok_or("Missing max price")?,
-            quantity_step_size: quantity_step_size.ok_or("Missing quantity step size")?,
-            min_quantity: min_quantity.ok_or("Missing min quantity")?,
-            max_quantity: max_quantity.ok_or("Missing max quantity")?,
-            min_notional: min_notional.unwrap_or(Decimal::ZERO),
+            rules: SymbolRules {
+                base_asset: data.base_asset,
+                quote_asset: data.quote_asset,
+                price_tick_size: price_tick_size.ok_or("Missing price tick size")?,
+                min_price: min_price.ok_or("Missing min price")?,
+                max_price: max_price.ok_or("Missing max price")?,
+                quantity_step_size: quantity_step_size.ok_or("Missing quantity step size")?,
+                min_quantity: min_quantity.ok_or("Missing min quantity")?,
+                max_quantity: max_quantity.ok_or("Missing max quantity")?,
+                min_notional: min_notional.unwrap_or(Decimal::ZERO),
+                // Leverage brackets come from a signed endpoint, not exchangeInfo
+                max_leverage: None,
+            },
         })
     }
 
+    /// Register filters for a symbol without a REST sync (offline runs, tests)
+    pub fn insert_symbol_info(&mut self, info: SymbolInfo) {
+        self.symbols.insert(info.symbol.to_lowercase(), info);
+    }
+
     /// Get symbol info by symbol name (case-insensitive)
     pub fn get_symbol_info(&self, symbol: &str) -> Option<&SymbolInfo> {
         self.symbols.get(&symbol.to_lowercase())
@@ -340,7 +293,7 @@ impl ExchangeInfoManager {
     pub async fn fetch_top_symbols(
         &self,
         top_n: usize,
-    ) -> Result<Vec<(String, Decimal)>, String> {
+    ) -> Result<Vec<(String, Decimal)>, BinanceError> {
         let url = format!("{}/fapi/v1/ticker/24hr", self.base_url);
         info!("Fetching 24hr tickers from {}...", url);
 
@@ -349,22 +302,33 @@ impl ExchangeInfoManager {
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch 24hr tickers: {}", e))?;
+            .map_err(|e| BinanceError::network("24hr tickers", e))?;
 
         if !response.status().is_success() {
-            return Err(format!(
-                "24hr ticker request failed with status: {}",
-                response.status()
-            ));
+            return Err(BinanceError::from_response("24hr tickers", &response));
         }
 
         let tickers: Vec<TickerData> = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse 24hr tickers: {}", e))?;
+            .map_err(|e| BinanceError::parse("24hr tickers", e))?;
 
         info!("Received {} tickers", tickers.len());
 
+        let result = self.select_top_symbols(tickers, top_n);
+
+        info!(
+            "Auto-selected {} symbols by volume: {:?}",
+            result.len(),
+            result.iter().map(|(s, _)| s.as_str()).collect::<Vec<_>>()
+        );
+
+        Ok(result)
+    }
+
+    /// Rank tickers: pinned symbols first, then the rest by quote volume,
+    /// never including blacklisted symbols.
+    fn select_top_symbols(&self, tickers: Vec<TickerData>, top_n: usize) -> Vec<(String, Decimal)> {
         // Filter: USDT pairs that are TRADING (present in self.symbols), then sort by quote volume
         let mut candidates: Vec<(String, Decimal, Decimal)> = tickers
             .into_iter()
@@ -377,6 +341,9 @@ impl ExchangeInfoManager {
                 if !self.symbols.contains_key(&sym_lower) {
                     return None;
                 }
+                if self.blacklisted_symbols.contains(&sym_lower) {
+                    return None;
+                }
                 let volume = t.quote_volume.as_deref().and_then(|v| Decimal::from_str(v).ok())?;
                 let price = t.last_price.as_deref().and_then(|p| Decimal::from_str(p).ok())?;
                 if price <= Decimal::ZERO {
@@ -389,20 +356,46 @@ impl ExchangeInfoManager {
         // Sort by quote volume descending
         candidates.sort_by(|a, b| b.1.cmp(&a.1));
 
-        // Take top N
-        let result: Vec<(String, Decimal)> = candidates
-            .into_iter()
-            .take(top_n)
-            .map(|(sym, _vol, price)| (sym, price))
-            .collect();
+        // Pinned symbols first (in configured order), then fill by volume
+        let mut result: Vec<(String, Decimal)> = Vec::with_capacity(top_n);
+        for pinned in &self.pinned_symbols {
+            if self.blacklisted_symbols.contains(pinned) {
+                warn!(symbol = %pinned, "Pinned symbol is also blacklisted, skipping");
+                continue;
+            }
+            match candidates.iter().find(|(sym, _, _)| sym == pinned) {
+                Some((sym, _, price)) => result.push((sym.clone(), *price)),
+                None => warn!(symbol = %pinned, "Pinned symbol not tradable, skipping"),
+            }
+        }
+        for (sym, _vol, price) in candidates {
+            if result.len() >= top_n {
+                break;
+            }
+            if !result.iter().any(|(s, _)| *s == sym) {
+                result.push((sym, price));
+            }
+        }
 
-        info!(
-            "Auto-selected {} symbols by volume: {:?}",
-            result.len(),
-            result.iter().map(|(s, _)| s.as_str()).collect::<Vec<_>>()
-        );
+        result
+    }
+}
 
-        Ok(result)
+impl Exchange for ExchangeInfoManager {
+    fn symbol_info(&self, symbol: &str) -> Option<&SymbolRules> {
+        self.get_symbol_info(symbol).map(|info| &info.rules)
+    }
+
+    fn ws_url(&self, symbols: &[String], book_ticker: bool) -> String {
+        stream_url(symbols, book_ticker)
+    }
+
+    fn server_time(&self) -> ExchangeFuture<'_, Result<i64, ExchangeError>> {
+        Box::pin(async move {
+            fetch_server_time(&self.client, &format!("{}/fapi/v1/time", self.base_url))
+                .await
+                .map_err(ExchangeError::from)
+        })
     }
 }
 
@@ -421,8 +414,8 @@ mod tests {
                 // Check if common symbols exist
                 if let Some(btc_info) = manager.get_symbol_info("btcusdt") {
                     println!("BTCUSDT info: {:?}", btc_info);
-                    assert!(btc_info.price_tick_size > Decimal::ZERO);
-                    assert!(btc_info.quantity_step_size > Decimal::ZERO);
+                    assert!(btc_info.rules.price_tick_size > Decimal::ZERO);
+                    assert!(btc_info.rules.quantity_step_size > Decimal::ZERO);
                 }
             }
             Err(e) => {
@@ -431,36 +424,118 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_price_rounding() {
-        let info = SymbolInfo {
-            symbol: "BTCUSDT".to_string(),
+    fn symbol_info(symbol: &str) -> SymbolInfo {
+        SymbolInfo {
+            symbol: symbol.to_uppercase(),
             status: "TRADING".to_string(),
-            base_asset: "BTC".to_string(),
-            quote_asset: "USDT".to_string(),
-            price_tick_size: Decimal::new(1, 1), // 0.1
-            min_price: Decimal::from(100),
-            max_price: Decimal::from(100000),
-            quantity_step_size: Decimal::new(1, 3), // 0.001
-            min_quantity: Decimal::new(1, 3),
-            max_quantity: Decimal::from(1000),
-            min_notional: Decimal::from(5),
+            rules: SymbolRules {
+                base_asset: symbol.trim_end_matches("usdt").to_uppercase(),
+                quote_asset: "USDT".to_string(),
+                price_tick_size: Decimal::new(1, 1),
+                min_price: Decimal::new(1, 1),
+                max_price: Decimal::from(100000),
+                quantity_step_size: Decimal::new(1, 3),
+                min_quantity: Decimal::new(1, 3),
+                max_quantity: Decimal::from(1000),
+                min_notional: Decimal::from(5),
+                max_leverage: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_pinned_and_blacklisted_symbols() {
+        let mut manager = ExchangeInfoManager::new("http://localhost".to_string());
+        let volumes = [
+            ("btcusdt", "9000"),
+            ("ethusdt", "8000"),
+            ("solusdt", "7000"),
+            ("xrpusdt", "6000"),
+            ("dogeusdt", "100"),
+        ];
+        for (sym, _) in volumes {
+            manager.symbols.insert(sym.to_string(), symbol_info(sym));
+        }
+        manager.set_symbol_overrides(&["DOGEUSDT".to_string()], &["ethusdt".to_string()]);
+
+        let tickers = volumes
+            .iter()
+            .map(|(sym, vol)| TickerData {
+                symbol: sym.to_uppercase(),
+                quote_volume: Some(vol.to_string()),
+                last_price: Some("1.5".to_string()),
+            })
+            .collect();
+        let selected: Vec<String> = manager
+            .select_top_symbols(tickers, 3)
+            .into_iter()
+            .map(|(s, _)| s)
+            .collect();
+
+        // doge is far outside the volume top-3 but pinned; eth is blacklisted
+        assert_eq!(selected, vec!["dogeusdt", "btcusdt", "solusdt"]);
+    }
+
+    #[test]
+    fn test_top_n_selects_exactly_n_symbols() {
+        let mut manager = ExchangeInfoManager::new("http://localhost".to_string());
+        let symbols = [
+            "btcusdt", "ethusdt", "solusdt", "xrpusdt", "dogeusdt", "adausdt", "linkusdt", "avaxusdt",
+        ];
+        for sym in symbols {
+            manager.symbols.insert(sym.to_string(), symbol_info(sym));
+        }
+        let tickers = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, sym)| TickerData {
+                symbol: sym.to_uppercase(),
+                quote_volume: Some((1000 - i * 100).to_string()),
+                last_price: Some("1.5".to_string()),
+            })
+            .collect();
+
+        let selected: Vec<String> = manager
+            .select_top_symbols(tickers, 5)
+            .into_iter()
+            .map(|(s, _)| s)
+            .collect();
+        assert_eq!(selected, vec!["btcusdt", "ethusdt", "solusdt", "xrpusdt", "dogeusdt"]);
+    }
+
+    #[test]
+    fn test_malformed_tick_size_skips_symbol() {
+        let symbols = |tick_size: &str| -> Vec<SymbolData> {
+            serde_json::from_value(serde_json::json!([
+                {
+                    "symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                    "filters": [
+                        { "filterType": "PRICE_FILTER", "minPrice": "0.1", "maxPrice": "1000000", "tickSize": "0.1" },
+                        { "filterType": "LOT_SIZE", "minQty": "0.001", "maxQty": "1000", "stepSize": "0.001" }
+                    ]
+                },
+                {
+                    "symbol": "ETHUSDT", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "USDT",
+                    "filters": [
+                        { "filterType": "PRICE_FILTER", "minPrice": "0.01", "maxPrice": "100000", "tickSize": tick_size },
+                        { "filterType": "LOT_SIZE", "minQty": "0.001", "maxQty": "1000", "stepSize": "0.001" }
+                    ]
+                }
+            ]))
+            .unwrap()
         };
 
-        // Test price rounding (tick_size = 0.1)
-        // 50002.56 should round to 50002.6
-        let price = Decimal::new(5000256, 2); // 50002.56
-        let rounded = info.round_price(price).unwrap();
-        assert_eq!(rounded, Decimal::new(500026, 1)); // Should round to 50002.6
-
-        // 50002.54 should round to 50002.5
-        let price2 = Decimal::new(5000254, 2); // 50002.54
-        let rounded2 = info.round_price(price2).unwrap();
-        assert_eq!(rounded2, Decimal::new(500025, 1)); // Should round to 50002.5
-
-        // Test quantity rounding
-        let qty = Decimal::new(12345, 4); // 1.2345
-        let rounded_qty = info.round_quantity(qty).unwrap();
-        assert_eq!(rounded_qty, Decimal::new(1234, 3)); // Should round to 1.234
+        for bad in ["0.0l", "0"] {
+            let mut manager = ExchangeInfoManager::new("http://localhost".to_string());
+            manager.load_symbols(symbols(bad));
+            assert!(manager.get_symbol_info("btcusdt").is_some());
+            assert!(manager.get_symbol_info("ethusdt").is_none(), "tick {:?} loaded", bad);
+        }
+
+        // Lenient mode keeps the legacy zero-tick fallback
+        let mut manager = ExchangeInfoManager::new("http://localhost".to_string());
+        manager.set_lenient_filters(true);
+        manager.load_symbols(symbols("0.0l"));
+        assert_eq!(manager.get_symbol_info("ethusdt").unwrap().rules.price_tick_size, Decimal::ZERO);
     }
 }
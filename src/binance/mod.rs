@@ -1,5 +1,7 @@
+pub mod error;
 pub mod time_sync;
 pub mod exchange_info;
 
+pub use error::BinanceError;
 pub use time_sync::{TimeSyncChecker, NetworkStats};
-pub use exchange_info::{ExchangeInfoManager, SymbolInfo, OrderValidationError};
+pub use exchange_info::{ExchangeInfoManager, SymbolInfo};
@@ -1,3 +1,4 @@
+use crate::binance::error::BinanceError;
 use chrono::Utc;
 use reqwest::Client;
 use serde::Deserialize;
@@ -21,6 +22,7 @@ pub struct NetworkStats {
 }
 
 /// Checks time synchronization with Binance Futures API
+#[derive(Clone)]
 pub struct TimeSyncChecker {
     client: Client,
     base_url: String,
@@ -45,8 +47,14 @@ impl TimeSyncChecker {
         }
     }
 
+    /// Use a shared, pre-configured HTTP client
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Perform full network and time synchronization check
-    pub async fn check(&self) -> Result<NetworkStats, String> {
+    pub async fn check(&self) -> Result<NetworkStats, BinanceError> {
         info!("Starting Binance time synchronization check...");
 
         // 1. Measure RTT (Round Trip Time)
@@ -69,10 +77,10 @@ impl TimeSyncChecker {
                 "Time offset too large: {}ms (max: {}ms)",
                 stats.time_offset_ms, self.max_time_offset_ms
             );
-            return Err(format!(
-                "Time offset {}ms exceeds maximum {}ms. Please sync your system clock.",
-                stats.time_offset_ms, self.max_time_offset_ms
-            ));
+            return Err(BinanceError::TimeOffset {
+                offset_ms: stats.time_offset_ms,
+                max_ms: self.max_time_offset_ms,
+            });
         }
 
         if stats.avg_latency_ms > self.max_latency_ms {
@@ -90,8 +98,13 @@ impl TimeSyncChecker {
         Ok(stats)
     }
 
+    /// Average RTT over `ping_samples` pings, for periodic sampling after startup
+    pub async fn sample_latency(&self) -> Result<f64, BinanceError> {
+        self.measure_rtt().await.map(|(avg, _, _)| avg)
+    }
+
     /// Measure RTT by pinging /fapi/v1/ping multiple times
-    async fn measure_rtt(&self) -> Result<(f64, f64, f64), String> {
+    async fn measure_rtt(&self) -> Result<(f64, f64, f64), BinanceError> {
         let ping_url = format!("{}/fapi/v1/ping", self.base_url);
         let mut latencies = Vec::new();
 
@@ -105,7 +118,13 @@ impl TimeSyncChecker {
 
             match self.client.get(&ping_url).send().await {
                 Ok(response) => {
-                    if response.status().is_success() {
+                    let status = response.status();
+                    if status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        || status == reqwest::StatusCode::IM_A_TEAPOT
+                    {
+                        return Err(BinanceError::from_response("ping", &response));
+                    }
+                    if status.is_success() {
                         let elapsed = start.elapsed();
                         let latency_ms = elapsed.as_secs_f64() * 1000.0;
                         latencies.push(latency_ms);
@@ -114,12 +133,12 @@ impl TimeSyncChecker {
                             info!("First ping successful: {:.2}ms", latency_ms);
                         }
                     } else {
-                        warn!("Ping failed with status: {}", response.status());
+                        warn!("Ping failed with status: {}", status);
                     }
                 }
                 Err(e) => {
                     error!("Ping request failed: {}", e);
-                    return Err(format!("Failed to ping Binance API: {}", e));
+                    return Err(BinanceError::network("ping", e));
                 }
             }
 
@@ -130,7 +149,7 @@ impl TimeSyncChecker {
         }
 
         if latencies.is_empty() {
-            return Err("No successful ping responses".to_string());
+            return Err(BinanceError::NoPingResponses);
         }
 
         let avg = latencies.iter().sum::<f64>() / latencies.len() as f64;
@@ -141,47 +160,48 @@ impl TimeSyncChecker {
     }
 
     /// Check time offset between local and Binance server
-    async fn check_time_offset(&self) -> Result<i64, String> {
+    async fn check_time_offset(&self) -> Result<i64, BinanceError> {
         let time_url = format!("{}/fapi/v1/time", self.base_url);
 
         info!("Checking time offset with Binance server...");
 
         let local_before = Utc::now().timestamp_millis();
-
-        let response = self
-            .client
-            .get(&time_url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to get server time: {}", e))?;
-
+        let server_time = fetch_server_time(&self.client, &time_url).await?;
         let local_after = Utc::now().timestamp_millis();
 
-        if !response.status().is_success() {
-            return Err(format!(
-                "Server time request failed with status: {}",
-                response.status()
-            ));
-        }
-
-        let server_time: ServerTime = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse server time: {}", e))?;
-
         // Estimate local time at moment of server response
         let local_estimate = (local_before + local_after) / 2;
-        let offset = server_time.server_time - local_estimate;
+        let offset = server_time - local_estimate;
 
         info!(
             "Server time: {}, Local time: {}, Offset: {}ms",
-            server_time.server_time, local_estimate, offset
+            server_time, local_estimate, offset
         );
 
         Ok(offset)
     }
 }
 
+/// GET /fapi/v1/time, in epoch milliseconds
+pub(crate) async fn fetch_server_time(client: &Client, time_url: &str) -> Result<i64, BinanceError> {
+    let response = client
+        .get(time_url)
+        .send()
+        .await
+        .map_err(|e| BinanceError::network("server time", e))?;
+
+    if !response.status().is_success() {
+        return Err(BinanceError::from_response("server time", &response));
+    }
+
+    let server_time: ServerTime = response
+        .json()
+        .await
+        .map_err(|e| BinanceError::parse("server time", e))?;
+
+    Ok(server_time.server_time)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
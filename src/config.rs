@@ -1,8 +1,10 @@
+use crate::types::SetupType;
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct AppConfig {
     pub general: GeneralConfig,
     pub range_bar: RangeBarConfig,
@@ -14,9 +16,21 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     pub discord: DiscordConfig,
     pub binance: BinanceConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub replay: ReplayConfig,
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
+    #[serde(default)]
+    pub statsd: StatsdConfig,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct GeneralConfig {
     pub symbols: Vec<String>,
     pub log_level: String,
@@ -24,15 +38,80 @@ pub struct GeneralConfig {
     pub auto_select_symbols: bool,
     #[serde(default = "default_top_n")]
     pub top_n_symbols: usize,
+    /// Always included in auto-selection, ahead of the volume ranking
+    #[serde(default)]
+    pub pinned_symbols: Vec<String>,
+    /// Never selected by auto-selection
+    #[serde(default)]
+    pub blacklisted_symbols: Vec<String>,
+    /// Signals-only mode: bypass the simulator and forward signals as alerts
+    #[serde(default)]
+    pub alert_mode: bool,
+    /// Sequential signal/position ids instead of random UUIDs (reproducible replays).
+    /// Ids repeat across runs, so give each replay a fresh trade database
+    #[serde(default)]
+    pub deterministic_ids: bool,
+    /// Alert on new session highs/lows and prior-session VAH/VAL breaks
+    #[serde(default)]
+    pub session_break_alerts: bool,
+    /// Pullback (% from the extreme) before a session high/low alert re-arms
+    #[serde(default = "default_session_break_rearm_pct")]
+    pub session_break_rearm_pct: f64,
+    /// Alert the symbols added/removed by the KST 09:00 reselection before restarting
+    #[serde(default)]
+    pub reselection_alerts: bool,
+    /// Entries dropped from `symbols` as case-insensitive duplicates (filled by `AppConfig::load`)
+    #[serde(skip)]
+    pub duplicate_symbols: Vec<String>,
+}
+
+impl GeneralConfig {
+    /// Keep the first spelling of each symbol (case-insensitive); returns the dropped entries
+    pub fn dedup_symbols(&mut self) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        let mut dropped = Vec::new();
+        self.symbols.retain(|symbol| {
+            let first = seen.insert(symbol.to_lowercase());
+            if !first {
+                dropped.push(symbol.clone());
+            }
+            first
+        });
+        dropped
+    }
+}
+
+fn default_session_break_rearm_pct() -> f64 {
+    0.1
+}
+
+fn default_on_reconnect() -> String {
+    "Discard".to_string()
+}
+
+fn default_footprint_price_dp() -> u32 {
+    1
 }
 
 fn default_top_n() -> usize {
-    20
+    10
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Upper bound on auto-selected symbols (each adds trade + depth streams)
+pub const MAX_TOP_N_SYMBOLS: usize = 50;
+
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct RangeBarConfig {
     pub default_pct: Option<f64>,
+    /// Per-symbol range as a percent of price, e.g. `btcusdt = 0.05`
+    #[serde(default)]
+    pub symbol_pct: HashMap<String, f64>,
+    /// In-progress bar on feed reconnect: "Keep", "Close" (emit as-is) or "Discard"
+    #[serde(default = "default_on_reconnect")]
+    pub on_reconnect: String,
+    /// Decimal places of footprint price levels (raise for sub-cent symbols)
+    #[serde(default = "default_footprint_price_dp")]
+    pub footprint_price_dp: u32,
     #[serde(flatten)]
     pub symbol_ranges: HashMap<String, f64>,
 }
@@ -49,40 +128,80 @@ impl RangeBarConfig {
     }
 
     /// Calculate range for a symbol using its current price.
-    /// Priority: symbol override → default_pct × price → config default.
+    /// Priority: symbol override → symbol_pct × price → default_pct × price → config default.
     pub fn range_for_with_price(&self, symbol: &str, price: Decimal) -> Decimal {
         // 1. Symbol-specific override
         if let Some(&val) = self.symbol_ranges.get(symbol) {
             return Decimal::try_from(val).unwrap_or(Decimal::TEN);
         }
-        // 2. Dynamic: default_pct% of price
-        if let Some(pct) = self.default_pct {
-            if let Ok(pct_dec) = Decimal::try_from(pct) {
-                let range = price * pct_dec / Decimal::from(100);
-                if range > Decimal::ZERO {
-                    return range;
-                }
-            }
+        // 2. Symbol-specific percent of price
+        if let Some(range) = self
+            .symbol_pct
+            .get(symbol)
+            .and_then(|&pct| Self::pct_of_price(pct, price))
+        {
+            return range;
+        }
+        // 3. Dynamic: default_pct% of price
+        if let Some(range) = self.default_pct.and_then(|pct| Self::pct_of_price(pct, price)) {
+            return range;
         }
-        // 3. Fallback to default key
+        // 4. Fallback to default key
         self.range_for(symbol)
     }
+
+    fn pct_of_price(pct: f64, price: Decimal) -> Option<Decimal> {
+        let pct_dec = Decimal::try_from(pct).ok()?;
+        let range = price * pct_dec / Decimal::from(100);
+        (range > Decimal::ZERO).then_some(range)
+    }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct VolumeProfileConfig {
     pub tick_size: f64,
+    #[schemars(extend("exclusiveMinimum" = 0.0, "maximum" = 1.0))]
     pub value_area_pct: f64,
     pub session_reset_hours: u64,
     #[serde(default = "default_tick_multiplier")]
     pub tick_multiplier: u32,
+    /// Profile input: "Trades" (raw aggTrades) or "Footprint" (completed range bars)
+    #[serde(default = "default_profile_source")]
+    pub source: String,
+    /// Minimum distinct price levels before snapshots are emitted
+    #[serde(default = "default_profile_min_levels")]
+    pub min_levels: usize,
+    /// Minimum session volume before snapshots are emitted (0 = no minimum)
+    #[serde(default)]
+    pub min_total_volume: f64,
+    /// Half-life of the time-decayed VWAP in seconds (0 = flat VWAP)
+    #[serde(default)]
+    pub vwap_half_life_secs: f64,
+    /// VWAP basis: "Rolling" (last hour) or "Session" (flat, since the session reset)
+    #[serde(default = "default_vwap_anchor")]
+    pub vwap_anchor: String,
+    /// Per-symbol value-area fraction overriding `value_area_pct`, e.g. `solusdt = 0.68`
+    #[serde(default)]
+    pub symbol_value_area_pct: HashMap<String, f64>,
+}
+
+fn default_vwap_anchor() -> String {
+    "Rolling".to_string()
 }
 
 fn default_tick_multiplier() -> u32 {
     10
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_profile_source() -> String {
+    "Trades".to_string()
+}
+
+fn default_profile_min_levels() -> usize {
+    3
+}
+
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct OrderFlowConfig {
     pub absorption_delta_ratio: f64,
     pub max_price_delta_ticks: u32,
@@ -91,6 +210,25 @@ pub struct OrderFlowConfig {
     pub volume_baseline_bars: usize,
     #[serde(default = "default_volume_burst_multiplier")]
     pub volume_burst_multiplier: f64,
+    /// Minimum footprint level volume, as a fraction of the average bar volume,
+    /// for a high-ratio level to count as absorption (0 = ratio only)
+    #[serde(default)]
+    pub absorption_min_volume_ratio: f64,
+    /// Distinct footprint levels (at `range_bar.footprint_price_dp`) a bar needs
+    /// before absorption is checked; coarse buckets can put a whole bar in one level
+    #[serde(default = "default_absorption_min_levels")]
+    pub absorption_min_levels: usize,
+    /// Recent bars whose volume per second is compared to the baseline rate
+    #[serde(default = "default_volume_rate_window_bars")]
+    pub volume_rate_window_bars: usize,
+}
+
+fn default_absorption_min_levels() -> usize {
+    1
+}
+
+fn default_volume_rate_window_bars() -> usize {
+    5
 }
 
 fn default_volume_baseline_bars() -> usize {
@@ -101,10 +239,16 @@ fn default_volume_burst_multiplier() -> f64 {
     1.8
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct StrategyConfig {
     pub enabled_setups: Vec<String>,
     pub aaa_poc_distance_ticks: u32,
+    /// AAA target: "ValueArea" (opposite VAH/VAL), "POC", or "RMultiple"
+    #[serde(default = "default_aaa_target_mode")]
+    pub aaa_target_mode: String,
+    /// Reward-to-risk multiple used when aaa_target_mode = "RMultiple"
+    #[serde(default = "default_aaa_target_r_multiple")]
+    pub aaa_target_r_multiple: f64,
     pub momentum_lookback_bars: usize,
     pub min_delta_confirmation: f64,
     #[serde(default = "default_advanced_zone_ticks")]
@@ -119,6 +263,9 @@ pub struct StrategyConfig {
     pub advanced_cooldown_bars: usize,
     #[serde(default = "default_advanced_require_reversal_bar")]
     pub advanced_require_reversal_bar: bool,
+    /// VWAP (the TP1 target) must sit at least this % of close beyond the close (0 = any distance)
+    #[serde(default)]
+    pub advanced_min_vwap_distance_pct: f64,
     #[serde(default = "default_advanced_min_volume_burst_ratio")]
     pub advanced_min_volume_burst_ratio: f64,
     #[serde(default = "default_advanced_auto_tune_volume_burst")]
@@ -133,6 +280,17 @@ pub struct StrategyConfig {
     pub advanced_tuning_target_pct: f64,
     #[serde(default = "default_advanced_tuning_min_trades")]
     pub advanced_tuning_min_trades: usize,
+    /// Volume burst ratios the tuner tries
+    #[serde(default = "default_advanced_tuning_ratio_candidates")]
+    pub advanced_tuning_ratio_candidates: Vec<f64>,
+    /// Per-symbol bounds on the tuned ratio, e.g. { btcusdt = 1.8 }
+    #[serde(default)]
+    pub advanced_tuning_ratio_floor_by_symbol: HashMap<String, f64>,
+    #[serde(default)]
+    pub advanced_tuning_ratio_ceiling_by_symbol: HashMap<String, f64>,
+    /// Seed each selected symbol's tuned ratio from its latest tuning log row at startup
+    #[serde(default)]
+    pub advanced_restore_tuned_ratios: bool,
     #[serde(default = "default_regime_switching_enabled")]
     pub regime_switching_enabled: bool,
     #[serde(default = "default_regime_window_bars")]
@@ -149,6 +307,59 @@ pub struct StrategyConfig {
     pub regime_aggressive_cooldown_mult: f64,
     #[serde(default = "default_regime_conservative_cooldown_mult")]
     pub regime_conservative_cooldown_mult: f64,
+    /// Record the regime detected at the signal bar on each signal and position
+    #[serde(default)]
+    pub annotate_regime: bool,
+    /// AdvancedOrderFlow stop: "Percent" (flat 0.4%) or "BarExtreme" (beyond the signal bar's low/high)
+    #[serde(default = "default_advanced_stop_mode")]
+    pub advanced_stop_mode: String,
+    /// Buffer beyond the bar extreme, as % of entry (BarExtreme mode)
+    #[serde(default = "default_advanced_stop_buffer_pct")]
+    pub advanced_stop_buffer_pct: f64,
+    /// Signals below this confidence are dropped (0 = keep all)
+    #[serde(default)]
+    pub min_confidence: f64,
+    /// Per-setup confidence floors (setup names, case-insensitive); override `min_confidence`
+    #[serde(default)]
+    pub min_confidence_by_setup: HashMap<String, f64>,
+    /// Setups ("AAA", "AbsorptionReversal") that also need a CVD rapid drop (long) / rise (short)
+    #[serde(default)]
+    pub cvd_confirmation_setups: Vec<String>,
+    /// Setups whose entries must land on a volume-burst bar (AdvancedOrderFlow always does)
+    #[serde(default)]
+    pub volume_burst_required_setups: Vec<String>,
+    /// Only take longs while market-wide CVD is rising and shorts while it is falling
+    #[serde(default)]
+    pub market_cvd_gate: bool,
+    /// Aggregate CVD change (in average-bar volumes, summed over symbols) the gate needs past zero
+    #[serde(default)]
+    pub market_cvd_gate_threshold: f64,
+    /// Only enter while the symbol's recent volume rate is at least this multiple of
+    /// its baseline rate (0 = off; symbols without a baseline yet pass)
+    #[serde(default)]
+    pub min_volume_rate_ratio: f64,
+    /// Bars per symbol to skip after a volume profile session reset (0 = off)
+    #[serde(default)]
+    pub session_warmup_bars: usize,
+    /// Skip signals while the session profile holds less volume than this (0 = off)
+    #[serde(default)]
+    pub session_warmup_min_volume: f64,
+}
+
+fn default_advanced_stop_mode() -> String {
+    "Percent".to_string()
+}
+
+fn default_advanced_stop_buffer_pct() -> f64 {
+    0.02
+}
+
+fn default_aaa_target_mode() -> String {
+    "ValueArea".to_string()
+}
+
+fn default_aaa_target_r_multiple() -> f64 {
+    2.0
 }
 
 fn default_advanced_zone_ticks() -> u32 {
@@ -203,6 +414,10 @@ fn default_advanced_tuning_min_trades() -> usize {
     8
 }
 
+fn default_advanced_tuning_ratio_candidates() -> Vec<f64> {
+    vec![1.2, 1.4, 1.6, 1.8, 2.1, 2.4, 2.8, 3.2]
+}
+
 fn default_regime_switching_enabled() -> bool {
     true
 }
@@ -235,11 +450,16 @@ fn default_regime_conservative_cooldown_mult() -> f64 {
     1.4
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct RiskConfig {
     pub initial_balance: f64,
+    #[schemars(extend("exclusiveMinimum" = 0.0, "maximum" = 0.1))]
     pub max_risk_per_trade: f64,
+    #[schemars(extend("exclusiveMinimum" = 0.0, "maximum" = 0.5))]
     pub daily_loss_limit_pct: f64,
+    /// Count open positions' unrealized PnL toward the daily loss limit (mark-to-market)
+    #[serde(default)]
+    pub daily_limit_mark_to_market: bool,
     pub max_concurrent_positions: usize,
     pub break_even_ticks: u32,
     #[serde(default = "default_break_even_min_hold_secs")]
@@ -258,10 +478,57 @@ pub struct RiskConfig {
     pub consecutive_loss_limit: u32,
     #[serde(default = "default_symbol_cooldown_minutes")]
     pub symbol_cooldown_minutes: u64,
+    /// Cap on total open notional as a multiple of balance (clamped to leverage)
+    #[serde(default = "default_max_open_notional_ratio")]
+    pub max_open_notional_ratio: f64,
+    /// Pause all entries for this many minutes after any liquidation (0 = off)
+    #[serde(default)]
+    pub liquidation_cooldown_minutes: u64,
+    /// Normal stop is not evaluated until the position is this old (0 = immediately)
+    #[serde(default)]
+    pub stop_min_hold_secs: u64,
+    /// Maximum entries per day, reset with the daily stats (0 = unlimited)
+    #[serde(default)]
+    pub max_daily_trades: u32,
+    /// Per-setup open position caps on top of the global cap, e.g. `{ MomentumSqueeze = 2 }`
+    #[serde(default)]
+    pub max_positions_by_setup: HashMap<String, usize>,
+    /// Emergency stop (fires even inside the min-hold window):
+    /// "StopMultiple" (N x stop distance), "Percent" (N% from entry) or "Off"
+    #[serde(default = "default_emergency_stop_mode")]
+    pub emergency_stop_mode: String,
+    /// Stop-distance multiple or percent, depending on emergency_stop_mode
+    #[serde(default = "default_emergency_stop_value")]
+    pub emergency_stop_value: f64,
+    /// Size tiers by drawdown from peak balance, e.g.
+    /// `[{ drawdown_pct = 5.0, size_scale = 0.75 }]` (empty = always full size)
+    #[serde(default)]
+    pub drawdown_tiers: Vec<DrawdownTier>,
+    /// Halt all trading once balance falls below this absolute amount (0 = off)
+    #[serde(default)]
+    pub balance_floor: f64,
+    /// Halt all trading once balance falls below this percent of initial_balance (0 = off)
+    #[serde(default)]
+    pub balance_floor_pct: f64,
     pub default_stop_ticks: u32,
     pub default_target_multiplier: f64,
 }
 
+/// Once drawdown from peak reaches `drawdown_pct`, position size is multiplied by `size_scale`
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct DrawdownTier {
+    pub drawdown_pct: f64,
+    pub size_scale: f64,
+}
+
+fn default_emergency_stop_mode() -> String {
+    "StopMultiple".to_string()
+}
+
+fn default_emergency_stop_value() -> f64 {
+    2.0
+}
+
 fn default_break_even_min_hold_secs() -> u64 {
     45
 }
@@ -294,25 +561,62 @@ fn default_symbol_cooldown_minutes() -> u64 {
     30
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_max_open_notional_ratio() -> f64 {
+    20.0
+}
+
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct SimulatorConfig {
     pub slippage_ticks: u32,
     pub maker_fee: f64,
     pub taker_fee: f64,
+    /// Charge `maker_fee` (negative = rebate) on take-profit exits and resting entry fills;
+    /// off = every fill pays `taker_fee`
+    #[serde(default)]
+    pub maker_fee_on_limit_fills: bool,
     pub order_book_depth: usize,
     pub leverage: f64,
+    /// Per-symbol leverage overrides (lowercase symbol -> leverage); others use `leverage`
+    #[serde(default)]
+    pub symbol_leverage: HashMap<String, f64>,
+    /// Leverage cap for symbols whose exchange rules don't publish one
+    #[serde(default = "default_max_leverage")]
+    pub max_leverage: f64,
     pub margin_type: String,
     pub maintenance_margin_rate: f64,
+    /// Liquidation price model: "Simplified" (flat rate and fees) or "Binance"
+    /// (isolated-margin formula with wallet balance and bracket maintenance amount)
+    #[serde(default = "default_liquidation_formula")]
+    pub liquidation_formula: String,
+    /// Binance maintenance brackets by notional, ascending, e.g.
+    /// `[{ notional_cap = 50000.0, rate = 0.004, amount = 0.0 }]`
+    /// (empty = `maintenance_margin_rate` with no maintenance amount)
+    #[serde(default)]
+    pub maintenance_brackets: Vec<MaintenanceBracketConfig>,
     #[serde(default = "default_soft_stop_seconds")]
     pub soft_stop_seconds: u64,
     #[serde(default = "default_soft_stop_drawdown_pct")]
     pub soft_stop_drawdown_pct: f64,
     #[serde(default = "default_require_orderbook_for_entry")]
     pub require_orderbook_for_entry: bool,
+    /// Seconds after the first market event during which a missing book is logged
+    /// as warmup (reason "book_warmup") rather than an execution-quality rejection
+    #[serde(default = "default_orderbook_warmup_secs")]
+    pub orderbook_warmup_secs: u64,
+    /// Pause new entries while the sampled RTT to Binance exceeds this (ms, 0 = off)
+    #[serde(default)]
+    pub max_entry_latency_ms: f64,
     #[serde(default = "default_max_spread_bps")]
     pub max_spread_bps: f64,
     #[serde(default = "default_min_depth_imbalance_ratio")]
     pub min_depth_imbalance_ratio: f64,
+    /// Realized volatility (bar range bps per minute) at which `min_depth_imbalance_ratio`
+    /// applies unscaled; the edge over 1.0 grows/shrinks with volatility (0 = flat threshold)
+    #[serde(default)]
+    pub imbalance_vol_reference_bps: f64,
+    /// Recent bars per symbol used to measure realized volatility
+    #[serde(default = "default_imbalance_vol_window_bars")]
+    pub imbalance_vol_window_bars: usize,
     #[serde(default = "default_expectancy_filter_enabled")]
     pub expectancy_filter_enabled: bool,
     #[serde(default = "default_expectancy_min_trades_per_hour")]
@@ -321,14 +625,108 @@ pub struct SimulatorConfig {
     pub expectancy_min_avg_pnl: f64,
     #[serde(default = "default_expectancy_lookback_trades")]
     pub expectancy_lookback_trades: usize,
+    /// Per-hour PnL statistic: "Mean", "Median" or "TrimmedMean" (robust to one outlier)
+    #[serde(default = "default_expectancy_estimator")]
+    pub expectancy_estimator: String,
+    /// Percent of samples dropped from each tail in TrimmedMean mode
+    #[serde(default = "default_expectancy_trim_pct")]
+    pub expectancy_trim_pct: f64,
+    /// Only reject when estimate + z * standard error is still below the threshold (0 = raw estimate)
+    #[serde(default)]
+    pub expectancy_confidence_z: f64,
     #[serde(default = "default_slippage_model_enabled")]
     pub slippage_model_enabled: bool,
     #[serde(default = "default_max_model_slippage_bps")]
     pub max_model_slippage_bps: f64,
+    /// Floor the slippage estimate at this many price ticks (0 = off)
+    #[serde(default)]
+    pub min_model_slippage_ticks: u32,
+    /// Also reject when the estimate exceeds this many price ticks (0 = off)
+    #[serde(default)]
+    pub max_model_slippage_ticks: u32,
     #[serde(default = "default_impact_depth_levels")]
     pub impact_depth_levels: usize,
     #[serde(default = "default_impact_weight_bps")]
     pub impact_weight_bps: f64,
+    /// Price that entry/stop/target are anchored to: "LastTrade" or "Microprice"
+    #[serde(default = "default_price_reference")]
+    pub price_reference: String,
+    /// Tick rounding for entry price: "Nearest", "Down" or "Up"
+    #[serde(default = "default_price_rounding")]
+    pub price_rounding: String,
+    /// Step rounding for quantity: "Nearest", "Down" or "Up"
+    #[serde(default = "default_quantity_rounding")]
+    pub quantity_rounding: String,
+    /// Coalesce depth updates per symbol and apply them at most this often (0 = every update)
+    #[serde(default)]
+    pub depth_aggregation_ms: u64,
+    /// Run full exit evaluation at most this often per symbol; trades crossing a stop, target
+    /// or liquidation price are always evaluated (0 = every trade)
+    #[serde(default)]
+    pub exit_check_interval_ms: u64,
+    /// Hold maker entries (priced inside the book, not marketable) this long for the book
+    /// imbalance to persist; cancel if it reverses (0 = off). Marketable entries fill at once.
+    #[serde(default)]
+    pub entry_confirmation_ms: u64,
+    /// Round stop and take-profit to the symbol's tick size when opening a position
+    #[serde(default = "default_round_stop_target_to_tick")]
+    pub round_stop_target_to_tick: bool,
+    /// Fill entries by walking the opposite side of the local book (average price over levels)
+    #[serde(default)]
+    pub book_walk_fills: bool,
+    /// Size the book could not fill: "Reject" (drop it) or "Rest" (limit order at the worst
+    /// level taken, added to the position as trades print through it)
+    #[serde(default = "default_unfilled_remainder")]
+    pub unfilled_remainder: String,
+    /// Cancel a resting remainder this long after the first trade it sees (0 = never)
+    #[serde(default = "default_resting_remainder_ttl_ms")]
+    pub resting_remainder_ttl_ms: u64,
+    /// Close a position when a bar shows absorption against it (buy absorption for longs)
+    #[serde(default)]
+    pub exit_on_opposite_absorption: bool,
+    /// Minimum volume burst ratio for that bar to count as strong (0 = any absorption)
+    #[serde(default)]
+    pub absorption_exit_min_burst_ratio: f64,
+    /// Stop not between entry and the liquidation price: "Reject", "Warn" or "Off"
+    #[serde(default = "default_stop_liquidation_check")]
+    pub stop_liquidation_check: String,
+    /// Bars after a rejected signal before its hypothetical outcome is logged (0 = off)
+    #[serde(default = "default_what_if_lookahead_bars")]
+    pub what_if_lookahead_bars: usize,
+    /// Most rejected signals awaiting a what-if outcome; the oldest is dropped past this
+    #[serde(default = "default_what_if_max_pending")]
+    pub what_if_max_pending: usize,
+    /// Reject entries whose liquidation distance is below this multiple of the stop distance (0 = off)
+    #[serde(default)]
+    pub min_liquidation_buffer_ratio: f64,
+    /// AdvancedOrderFlow TP1 (50% close): "VWAP" or "POC"
+    #[serde(default = "default_tp1_source")]
+    pub tp1_source: String,
+    /// Close the whole position at TP1 when the 50% split would leave a part below
+    /// the exchange minimum quantity (unclosable dust)
+    #[serde(default = "default_close_dust_remainder")]
+    pub close_dust_remainder: bool,
+    /// Also close the whole position at TP1 when either half would be below the
+    /// exchange minimum notional at the TP1 price
+    #[serde(default = "default_tp1_check_min_notional")]
+    pub tp1_check_min_notional: bool,
+    /// AdvancedOrderFlow TP2 (full close): "ValueArea" (VAH long / VAL short) or "RMultiple".
+    /// ValueArea needs a fresh profile; RMultiple always replaces the signal's take-profit
+    #[serde(default = "default_tp2_source")]
+    pub tp2_source: String,
+    /// Reward-to-risk multiple used when tp2_source = "RMultiple"
+    #[serde(default = "default_tp2_r_multiple")]
+    pub tp2_r_multiple: f64,
+    /// Ignore profiles older than this (by snapshot time) when setting TP1/TP2; the
+    /// position keeps the signal's own target instead (0 = off)
+    #[serde(default)]
+    pub max_profile_age_secs: u64,
+    /// Setups whose remainder trails after TP1 instead of targeting TP2
+    #[serde(default)]
+    pub trail_after_tp1_setups: Vec<String>,
+    /// Trailing distance behind the best price since TP1, in % of price
+    #[serde(default = "default_trail_after_tp1_pct")]
+    pub trail_after_tp1_pct: f64,
 }
 
 fn default_soft_stop_seconds() -> u64 {
@@ -343,6 +741,10 @@ fn default_require_orderbook_for_entry() -> bool {
     true
 }
 
+fn default_orderbook_warmup_secs() -> u64 {
+    30
+}
+
 fn default_max_spread_bps() -> f64 {
     4.0
 }
@@ -351,6 +753,10 @@ fn default_min_depth_imbalance_ratio() -> f64 {
     1.05
 }
 
+fn default_imbalance_vol_window_bars() -> usize {
+    20
+}
+
 fn default_expectancy_filter_enabled() -> bool {
     true
 }
@@ -367,6 +773,14 @@ fn default_expectancy_lookback_trades() -> usize {
     80
 }
 
+fn default_expectancy_estimator() -> String {
+    "Mean".to_string()
+}
+
+fn default_expectancy_trim_pct() -> f64 {
+    10.0
+}
+
 fn default_slippage_model_enabled() -> bool {
     true
 }
@@ -383,37 +797,471 @@ fn default_impact_weight_bps() -> f64 {
     8.0
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_price_reference() -> String {
+    "LastTrade".to_string()
+}
+
+fn default_price_rounding() -> String {
+    "Nearest".to_string()
+}
+
+fn default_quantity_rounding() -> String {
+    "Down".to_string()
+}
+
+fn default_close_dust_remainder() -> bool {
+    true
+}
+
+fn default_tp1_check_min_notional() -> bool {
+    true
+}
+
+fn default_keep_tuning_db_open() -> bool {
+    true
+}
+
+pub(crate) fn default_db_busy_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_tp1_source() -> String {
+    "VWAP".to_string()
+}
+
+fn default_tp2_source() -> String {
+    "ValueArea".to_string()
+}
+
+fn default_trail_after_tp1_pct() -> f64 {
+    0.3
+}
+
+fn default_tp2_r_multiple() -> f64 {
+    2.0
+}
+
+fn default_resting_remainder_ttl_ms() -> u64 {
+    60_000
+}
+
+fn default_unfilled_remainder() -> String {
+    "Reject".to_string()
+}
+
+fn default_round_stop_target_to_tick() -> bool {
+    true
+}
+
+/// Positions with notional up to `notional_cap` use this maintenance `rate` and `amount`
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct MaintenanceBracketConfig {
+    pub notional_cap: f64,
+    pub rate: f64,
+    pub amount: f64,
+}
+
+fn default_liquidation_formula() -> String {
+    "Simplified".to_string()
+}
+
+fn default_max_leverage() -> f64 {
+    125.0
+}
+
+fn default_stop_liquidation_check() -> String {
+    "Warn".to_string()
+}
+
+fn default_what_if_lookahead_bars() -> usize {
+    10
+}
+
+fn default_what_if_max_pending() -> usize {
+    1000
+}
+
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct LoggingConfig {
     pub trades_csv_path: String,
     pub trades_json_path: String,
     pub trades_db_path: String,
+    /// How long a trade database write waits on a lock before failing with SQLITE_BUSY
+    #[serde(default = "default_db_busy_timeout_ms")]
+    pub db_busy_timeout_ms: u64,
+    /// Strategy tuning logs keep one WAL connection of their own open instead of opening one
+    /// per write (both wait `db_busy_timeout_ms` on a lock)
+    #[serde(default = "default_keep_tuning_db_open")]
+    pub keep_tuning_db_open: bool,
+    /// Log closed trades from a background thread, up to N per SQLite transaction (0 = inline)
+    #[serde(default)]
+    pub trade_log_batch_size: usize,
+    /// Log per-symbol trade/depth message rates every N seconds (0 = off)
+    #[serde(default = "default_feed_metrics_interval_secs")]
+    pub feed_metrics_interval_secs: u64,
+    /// Persist completed bars, flow metrics and profile snapshots: "Off", "Jsonl" or "Sqlite"
+    #[serde(default = "default_market_sink")]
+    pub market_sink: String,
+    /// Empty = market_context.jsonl or market_context.db by `market_sink` (filled by `AppConfig::load`)
+    #[serde(default)]
+    pub market_sink_path: String,
+    /// Also append volume burst tuning results to this CSV (empty = off)
+    #[serde(default)]
+    pub tuning_csv_path: String,
+    /// Print the shutdown trade summary to stdout as one JSON line (for backtest harnesses)
+    #[serde(default)]
+    pub summary_json_stdout: bool,
+    /// Periodic top-N order book snapshots: "Off", "Jsonl" or "Sqlite"
+    #[serde(default = "default_market_sink")]
+    pub book_snapshot_sink: String,
+    /// Empty = book_snapshots.jsonl or book_snapshots.db by `book_snapshot_sink` (filled by `AppConfig::load`)
+    #[serde(default)]
+    pub book_snapshot_path: String,
+    /// Minimum event-time gap between snapshots of one symbol
+    #[serde(default = "default_book_snapshot_interval_ms")]
+    pub book_snapshot_interval_ms: u64,
+    /// Levels per side in each snapshot
+    #[serde(default = "default_book_snapshot_depth")]
+    pub book_snapshot_depth: usize,
+    /// Upper bounds (seconds) of the summary's holding-time histogram (empty = no histogram)
+    #[serde(default = "default_hold_time_buckets_secs")]
+    pub hold_time_buckets_secs: Vec<u64>,
+}
+
+fn default_book_snapshot_interval_ms() -> u64 {
+    1000
+}
+
+fn default_book_snapshot_depth() -> usize {
+    10
+}
+
+fn default_hold_time_buckets_secs() -> Vec<u64> {
+    vec![10, 30, 60, 300, 900, 3600]
+}
+
+fn default_feed_metrics_interval_secs() -> u64 {
+    60
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_market_sink() -> String {
+    "Off".to_string()
+}
+
+/// `{stem}.db` for the Sqlite sink, `{stem}.jsonl` otherwise
+fn default_sink_path(sink: &str, stem: &str) -> String {
+    let extension = if sink.eq_ignore_ascii_case("sqlite") { "db" } else { "jsonl" };
+    format!("{}.{}", stem, extension)
+}
+
+impl LoggingConfig {
+    /// Give sinks without a configured path one whose extension matches the sink type
+    pub fn fill_sink_paths(&mut self) {
+        if self.market_sink_path.is_empty() {
+            self.market_sink_path = default_sink_path(&self.market_sink, "market_context");
+        }
+        if self.book_snapshot_path.is_empty() {
+            self.book_snapshot_path = default_sink_path(&self.book_snapshot_sink, "book_snapshots");
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct DiscordConfig {
     pub enabled: bool,
+    /// Send a once-daily digest of the day's trades
+    #[serde(default)]
+    pub daily_digest: bool,
+    /// Local "HH:MM" the digest is sent at
+    #[serde(default = "default_daily_digest_time")]
+    pub daily_digest_time: String,
+    /// UTC offset of `daily_digest_time` in hours (9 = KST)
+    #[serde(default = "default_daily_digest_utc_offset_hours")]
+    pub daily_digest_utc_offset_hours: i32,
+    /// Notify on break-even/stop moves (liquidation and daily-limit alerts always send)
+    #[serde(default = "default_stop_move_alerts")]
+    pub stop_move_alerts: bool,
+    /// Add exposure/PnL grouped by quote asset and base-asset category to the hourly report
+    #[serde(default)]
+    pub exposure_breakdown: bool,
+    /// Base asset -> category for the exposure breakdown, e.g. `DOGE = "meme"` (uncategorised bases group alone)
+    #[serde(default)]
+    pub base_asset_categories: HashMap<String, String>,
+}
+
+fn default_daily_digest_time() -> String {
+    "09:00".to_string()
+}
+
+fn default_daily_digest_utc_offset_hours() -> i32 {
+    9
+}
+
+fn default_stop_move_alerts() -> bool {
+    true
 }
 
 impl DiscordConfig {
+    /// Parsed `daily_digest_time`
+    pub fn daily_digest_time(&self) -> Result<chrono::NaiveTime, String> {
+        chrono::NaiveTime::parse_from_str(&self.daily_digest_time, "%H:%M")
+            .map_err(|e| format!("invalid discord.daily_digest_time {:?}: {}", self.daily_digest_time, e))
+    }
+
     pub fn webhook_url(&self) -> Result<String, String> {
         std::env::var("DISCORD_WEBHOOK_URL")
             .map_err(|_| "DISCORD_WEBHOOK_URL not set in .env file".to_string())
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct BinanceConfig {
     pub api_url: String,
     pub max_time_offset_ms: i64,
     pub max_latency_ms: f64,
     pub ping_samples: usize,
+    /// Book feed: "Depth" (full @depth diff stream) or "BookTicker" (best bid/ask only)
+    #[serde(default = "default_book_stream")]
+    pub book_stream: String,
+    /// Drop aggTrades at or below this quantity before they reach bars and profiles (0 = zero-quantity only)
+    #[serde(default)]
+    pub min_trade_quantity: f64,
+    /// Drop trades more than this percent away from the symbol's recent median price (0 = off)
+    #[serde(default)]
+    pub max_trade_deviation_pct: f64,
+    /// Trade aggressor side: "Maker" (is_buyer_maker flag), "TickRule", or "Compare" (maker flag, log tick-rule disagreement)
+    #[serde(default = "default_trade_side_source")]
+    pub trade_side_source: String,
+    /// Re-measure RTT every N seconds while running (0 = startup check only)
+    #[serde(default)]
+    pub latency_sample_interval_secs: u64,
+    /// Wait between WebSocket reconnect attempts
+    #[serde(default = "default_reconnect_delay_ms")]
+    pub reconnect_delay_ms: u64,
+    /// Consecutive failed reconnects before a critical alert (0 = retry silently forever)
+    #[serde(default)]
+    pub max_reconnect_failures: u32,
+    /// Shut down (exit status 1) once `max_reconnect_failures` is reached so a supervisor can restart
+    #[serde(default)]
+    pub exit_on_reconnect_failure: bool,
+    /// Load symbols with unparsable exchange filters using zero/max fallbacks
+    /// instead of skipping them
+    #[serde(default)]
+    pub lenient_filter_parsing: bool,
+}
+
+fn default_reconnect_delay_ms() -> u64 {
+    5000
+}
+
+fn default_trade_side_source() -> String {
+    "Maker".to_string()
+}
+
+fn default_book_stream() -> String {
+    "Depth".to_string()
+}
+
+/// Shared REST client settings (Binance + Discord)
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct HttpConfig {
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: default_connect_timeout_ms(),
+            request_timeout_ms: default_request_timeout_ms(),
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+        }
+    }
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    3_000
+}
+
+fn default_request_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    90
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    4
+}
+
+/// Replay recorded market events (JSONL) instead of the live WebSocket
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct ReplayConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub path: String,
+    /// Playback speed relative to recorded wall-clock time (10.0 = 10x)
+    #[serde(default = "default_replay_speed")]
+    pub speed: f64,
+    /// Skip events before this RFC3339 timestamp
+    #[serde(default)]
+    pub seek_to: Option<String>,
+    #[serde(default = "default_replay_progress_interval_secs")]
+    pub progress_interval_secs: u64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::new(),
+            speed: default_replay_speed(),
+            seek_to: None,
+            progress_interval_secs: default_replay_progress_interval_secs(),
+        }
+    }
+}
+
+fn default_replay_speed() -> f64 {
+    1.0
+}
+
+fn default_replay_progress_interval_secs() -> u64 {
+    10
+}
+
+/// Record the full market event stream (JSONL) for later replay
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct RecordingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_recording_path")]
+    pub path: String,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_recording_path(),
+        }
+    }
+}
+
+fn default_recording_path() -> String {
+    "market_events.jsonl".to_string()
+}
+
+/// Ring buffer of recent events dumped on panic or on demand
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct DiagnosticsConfig {
+    /// Number of recent market events (and signals) kept; 0 = disabled
+    #[serde(default)]
+    pub ring_buffer_size: usize,
+    #[serde(default = "default_dump_path")]
+    pub dump_path: String,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            ring_buffer_size: 0,
+            dump_path: default_dump_path(),
+        }
+    }
+}
+
+fn default_dump_path() -> String {
+    "diagnostics_dump.jsonl".to_string()
+}
+
+/// HTTP control API (health/readiness probes, diagnostics)
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct ControlConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_control_bind_addr")]
+    pub bind_addr: String,
+    /// A symbol with no trade for this long fails readiness
+    #[serde(default = "default_stale_trade_secs")]
+    pub stale_trade_secs: u64,
+    /// Dead-man's switch: flatten and halt if no POST /heartbeat for this long (0 = off)
+    #[serde(default)]
+    pub heartbeat_timeout_secs: u64,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_addr: default_control_bind_addr(),
+            stale_trade_secs: default_stale_trade_secs(),
+            heartbeat_timeout_secs: 0,
+        }
+    }
+}
+
+fn default_control_bind_addr() -> String {
+    "127.0.0.1:9090".to_string()
+}
+
+fn default_stale_trade_secs() -> u64 {
+    60
+}
+
+/// Push counters/gauges over UDP in statsd format (Datadog, Telegraf)
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct StatsdConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_statsd_host")]
+    pub host: String,
+    #[serde(default = "default_statsd_prefix")]
+    pub prefix: String,
+    /// Append DogStatsD `|#key:value` tags (symbol, side, reason)
+    #[serde(default)]
+    pub tags: bool,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_statsd_host(),
+            prefix: default_statsd_prefix(),
+            tags: false,
+        }
+    }
+}
+
+fn default_statsd_host() -> String {
+    "127.0.0.1:8125".to_string()
+}
+
+fn default_statsd_prefix() -> String {
+    "rusto".to_string()
 }
 
 impl AppConfig {
+    /// JSON Schema for the config file (validate `config.toml` after TOML→JSON)
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(AppConfig)).unwrap_or_default()
+    }
+
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
-        let config: AppConfig = toml::from_str(&content)?;
+        let mut config: AppConfig = toml::from_str(&content)?;
+        config.general.duplicate_symbols = config.general.dedup_symbols();
+        config.logging.fill_sink_paths();
         config.validate()?;
         Ok(config)
     }
@@ -424,21 +1272,311 @@ impl AppConfig {
                 "At least one symbol must be configured (or enable auto_select_symbols)".into(),
             );
         }
+        if self.general.auto_select_symbols {
+            let top_n = self.general.top_n_symbols;
+            if top_n == 0 || top_n > MAX_TOP_N_SYMBOLS {
+                return Err(format!(
+                    "general.top_n_symbols must be between 1 and {}, got {}",
+                    MAX_TOP_N_SYMBOLS, top_n
+                ));
+            }
+            if self.general.pinned_symbols.len() > top_n {
+                return Err(format!(
+                    "general.pinned_symbols ({}) exceeds top_n_symbols ({})",
+                    self.general.pinned_symbols.len(),
+                    top_n
+                ));
+            }
+        }
         if self.risk.max_risk_per_trade <= 0.0 || self.risk.max_risk_per_trade > 0.1 {
             return Err("max_risk_per_trade must be between 0 and 0.1".into());
         }
         if self.risk.daily_loss_limit_pct <= 0.0 || self.risk.daily_loss_limit_pct > 0.5 {
             return Err("daily_loss_limit_pct must be between 0 and 0.5".into());
         }
+        if let Some((symbol, _)) = self
+            .simulator
+            .symbol_leverage
+            .iter()
+            .find(|(_, &leverage)| leverage < 1.0)
+        {
+            return Err(format!("simulator.symbol_leverage for {} must be >= 1", symbol));
+        }
+        if !(1.0..).contains(&self.simulator.max_leverage) {
+            return Err(format!(
+                "simulator.max_leverage must be >= 1 (got {})",
+                self.simulator.max_leverage
+            ));
+        }
+        if !(1.0..).contains(&self.simulator.leverage) {
+            return Err(format!("simulator.leverage must be >= 1 (got {})", self.simulator.leverage));
+        }
+        let mmr = self.simulator.maintenance_margin_rate;
+        if !(mmr > 0.0 && mmr < 1.0) {
+            return Err(format!("simulator.maintenance_margin_rate must be in (0, 1) (got {})", mmr));
+        }
+        if !matches!(
+            self.simulator.liquidation_formula.to_lowercase().as_str(),
+            "simplified" | "binance"
+        ) {
+            return Err(format!(
+                "simulator.liquidation_formula must be Simplified or Binance (got {})",
+                self.simulator.liquidation_formula
+            ));
+        }
+        let brackets = &self.simulator.maintenance_brackets;
+        if brackets
+            .iter()
+            .any(|b| b.notional_cap <= 0.0 || !(b.rate > 0.0 && b.rate < 1.0) || b.amount < 0.0)
+            || brackets.windows(2).any(|w| w[0].notional_cap >= w[1].notional_cap)
+        {
+            return Err(
+                "simulator.maintenance_brackets need ascending notional_cap > 0, rate in (0, 1) and amount >= 0"
+                    .into(),
+            );
+        }
+        if !(0.0..=0.01).contains(&self.simulator.taker_fee)
+            || !(-0.001..=0.01).contains(&self.simulator.maker_fee)
+        {
+            return Err(format!(
+                "simulator fees out of range: taker_fee must be in [0, 0.01], maker_fee in [-0.001, 0.01] (got {} / {})",
+                self.simulator.taker_fee, self.simulator.maker_fee
+            ));
+        }
+        if let Some((symbol, _)) = self
+            .range_bar
+            .symbol_pct
+            .iter()
+            .find(|(_, &pct)| pct <= 0.0)
+        {
+            return Err(format!("range_bar.symbol_pct for {} must be > 0", symbol));
+        }
+        if self.binance.exit_on_reconnect_failure && self.binance.max_reconnect_failures == 0 {
+            return Err("binance.exit_on_reconnect_failure needs max_reconnect_failures > 0".into());
+        }
+        if self.simulator.max_entry_latency_ms < 0.0 {
+            return Err("simulator.max_entry_latency_ms must be >= 0".into());
+        }
+        if self.simulator.max_entry_latency_ms > 0.0 && self.binance.latency_sample_interval_secs == 0 {
+            return Err("simulator.max_entry_latency_ms needs binance.latency_sample_interval_secs > 0".into());
+        }
+        if self.range_bar.footprint_price_dp > 12 {
+            return Err("range_bar.footprint_price_dp must be <= 12".into());
+        }
+        if self.simulator.imbalance_vol_reference_bps < 0.0 {
+            return Err("simulator.imbalance_vol_reference_bps must be >= 0".into());
+        }
+        if self.simulator.imbalance_vol_reference_bps > 0.0 && self.simulator.imbalance_vol_window_bars < 2 {
+            return Err("simulator.imbalance_vol_window_bars must be >= 2".into());
+        }
+        if self.discord.daily_digest {
+            self.discord.daily_digest_time()?;
+            if !(-12..=14).contains(&self.discord.daily_digest_utc_offset_hours) {
+                return Err("discord.daily_digest_utc_offset_hours must be in [-12, 14]".into());
+            }
+        }
+        if self.binance.max_trade_deviation_pct < 0.0 {
+            return Err("binance.max_trade_deviation_pct must be >= 0".into());
+        }
+        if !matches!(
+            self.binance.trade_side_source.to_lowercase().as_str(),
+            "maker" | "tickrule" | "compare"
+        ) {
+            return Err(format!(
+                "binance.trade_side_source must be Maker, TickRule or Compare (got {})",
+                self.binance.trade_side_source
+            ));
+        }
+        if self.binance.min_trade_quantity < 0.0 {
+            return Err("binance.min_trade_quantity must be >= 0".into());
+        }
         if self.volume_profile.value_area_pct <= 0.0 || self.volume_profile.value_area_pct > 1.0 {
             return Err("value_area_pct must be between 0 and 1".into());
         }
+        if let Some((symbol, _)) = self
+            .volume_profile
+            .symbol_value_area_pct
+            .iter()
+            .find(|(_, &pct)| pct <= 0.0 || pct > 1.0)
+        {
+            return Err(format!(
+                "volume_profile.symbol_value_area_pct for {} must be between 0 and 1",
+                symbol
+            ));
+        }
+        if self.order_flow.absorption_min_levels == 0 {
+            return Err("order_flow.absorption_min_levels must be >= 1".into());
+        }
+        if self.volume_profile.min_levels == 0 || self.volume_profile.min_total_volume < 0.0 {
+            return Err("volume_profile min_levels must be >= 1 and min_total_volume >= 0".into());
+        }
+        if self.volume_profile.vwap_half_life_secs < 0.0 {
+            return Err("vwap_half_life_secs must be >= 0".into());
+        }
         if self.risk.min_confidence_scale <= 0.0
             || self.risk.max_confidence_scale <= 0.0
             || self.risk.min_confidence_scale > self.risk.max_confidence_scale
         {
             return Err("confidence scale range is invalid".into());
         }
+        if self.simulator.trail_after_tp1_pct <= 0.0 || self.simulator.trail_after_tp1_pct >= 100.0 {
+            return Err("trail_after_tp1_pct must be in (0, 100)".into());
+        }
+        if self.simulator.tp2_r_multiple <= 0.0 {
+            return Err("tp2_r_multiple must be > 0".into());
+        }
+        if self.simulator.min_liquidation_buffer_ratio < 0.0 {
+            return Err("min_liquidation_buffer_ratio must be >= 0".into());
+        }
+        if self.control.enabled {
+            if self.control.bind_addr.parse::<std::net::SocketAddr>().is_err() {
+                return Err(format!("control.bind_addr is not a valid address: {}", self.control.bind_addr));
+            }
+            if self.control.stale_trade_secs == 0 {
+                return Err("control.stale_trade_secs must be > 0".into());
+            }
+        } else if self.control.heartbeat_timeout_secs > 0 {
+            return Err("control.heartbeat_timeout_secs requires control.enabled (POST /heartbeat)".into());
+        }
+        if self.statsd.enabled
+            && self
+                .statsd
+                .host
+                .rsplit_once(':')
+                .is_none_or(|(host, port)| host.is_empty() || port.parse::<u16>().is_err())
+        {
+            return Err(format!("statsd.host must be host:port, got {:?}", self.statsd.host));
+        }
+        if self.order_flow.absorption_min_volume_ratio < 0.0 {
+            return Err("absorption_min_volume_ratio must be >= 0".into());
+        }
+        if self.general.session_break_rearm_pct < 0.0 {
+            return Err("session_break_rearm_pct must be >= 0".into());
+        }
+        if self.strategy.advanced_stop_buffer_pct < 0.0 {
+            return Err("advanced_stop_buffer_pct must be >= 0".into());
+        }
+        if self.strategy.session_warmup_min_volume < 0.0 {
+            return Err("session_warmup_min_volume must be >= 0".into());
+        }
+        let candidates = &self.strategy.advanced_tuning_ratio_candidates;
+        if candidates.is_empty() || candidates.iter().any(|c| *c <= 0.0) {
+            return Err("advanced_tuning_ratio_candidates must be non-empty and > 0".into());
+        }
+        // Symbols match case-insensitively, as in the tuner's lookup
+        for (symbol, floor) in &self.strategy.advanced_tuning_ratio_floor_by_symbol {
+            let ceiling = self
+                .strategy
+                .advanced_tuning_ratio_ceiling_by_symbol
+                .iter()
+                .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+                .map(|(_, c)| *c);
+            if ceiling.is_some_and(|ceiling| *floor > ceiling) {
+                return Err(format!(
+                    "advanced_tuning_ratio floor for {} exceeds its ceiling",
+                    symbol
+                ));
+            }
+        }
+        let floors = std::iter::once(&self.strategy.min_confidence)
+            .chain(self.strategy.min_confidence_by_setup.values());
+        for floor in floors {
+            if !(0.0..=1.0).contains(floor) {
+                return Err(format!("strategy confidence floors must be in [0, 1] (got {})", floor));
+            }
+        }
+        if !matches!(
+            self.logging.market_sink.to_lowercase().as_str(),
+            "off" | "jsonl" | "sqlite"
+        ) {
+            return Err(format!(
+                "logging.market_sink must be Off, Jsonl or Sqlite (got {})",
+                self.logging.market_sink
+            ));
+        }
+        match self.logging.book_snapshot_sink.to_lowercase().as_str() {
+            "off" => {}
+            "jsonl" | "sqlite" => {
+                if self.logging.book_snapshot_interval_ms == 0 || self.logging.book_snapshot_depth == 0 {
+                    return Err("book_snapshot_interval_ms and book_snapshot_depth must be > 0".into());
+                }
+                if !self.logging.market_sink.eq_ignore_ascii_case("off")
+                    && self.logging.book_snapshot_path == self.logging.market_sink_path
+                {
+                    return Err("logging.book_snapshot_path must differ from market_sink_path".into());
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "logging.book_snapshot_sink must be Off, Jsonl or Sqlite (got {})",
+                    self.logging.book_snapshot_sink
+                ))
+            }
+        }
+        if self.risk.balance_floor < 0.0 || self.risk.balance_floor >= self.risk.initial_balance {
+            return Err("risk.balance_floor must be in [0, initial_balance)".into());
+        }
+        if self.risk.balance_floor_pct < 0.0 || self.risk.balance_floor_pct >= 100.0 {
+            return Err("risk.balance_floor_pct must be in [0, 100)".into());
+        }
+        if self.strategy.min_volume_rate_ratio < 0.0 {
+            return Err("strategy.min_volume_rate_ratio must be >= 0".into());
+        }
+        if self.order_flow.volume_rate_window_bars == 0 {
+            return Err("order_flow.volume_rate_window_bars must be >= 1".into());
+        }
+        if self.strategy.market_cvd_gate_threshold < 0.0 {
+            return Err("strategy.market_cvd_gate_threshold must be >= 0".into());
+        }
+        if !matches!(
+            self.strategy.aaa_target_mode.to_lowercase().as_str(),
+            "valuearea" | "structure" | "poc" | "rmultiple"
+        ) {
+            return Err(format!(
+                "strategy.aaa_target_mode must be ValueArea, Structure, POC or RMultiple (got {})",
+                self.strategy.aaa_target_mode
+            ));
+        }
+        let setup_lists = [
+            ("strategy.cvd_confirmation_setups", &self.strategy.cvd_confirmation_setups),
+            ("strategy.volume_burst_required_setups", &self.strategy.volume_burst_required_setups),
+            ("simulator.trail_after_tp1_setups", &self.simulator.trail_after_tp1_setups),
+        ];
+        let by_setup: Vec<String> = self.risk.max_positions_by_setup.keys().cloned().collect();
+        let confidence_by_setup: Vec<String> =
+            self.strategy.min_confidence_by_setup.keys().cloned().collect();
+        let setup_maps = [
+            ("risk.max_positions_by_setup", &by_setup),
+            ("strategy.min_confidence_by_setup", &confidence_by_setup),
+        ];
+        for (key, names) in setup_lists.into_iter().chain(setup_maps) {
+            if let Some(unknown) = names.iter().find(|name| SetupType::from_name(name).is_none()) {
+                return Err(format!(
+                    "{} has unknown setup {} (expected AAA, MomentumSqueeze, AbsorptionReversal or AdvancedOrderFlow)",
+                    key, unknown
+                ));
+            }
+        }
+        if self.strategy.aaa_target_r_multiple <= 0.0 {
+            return Err("strategy.aaa_target_r_multiple must be > 0".into());
+        }
+        if self.strategy.advanced_min_vwap_distance_pct < 0.0 {
+            return Err("strategy.advanced_min_vwap_distance_pct must be >= 0".into());
+        }
+        for tier in &self.risk.drawdown_tiers {
+            if tier.drawdown_pct <= 0.0 || tier.drawdown_pct >= 100.0 {
+                return Err("risk.drawdown_tiers drawdown_pct must be in (0, 100)".into());
+            }
+            if tier.size_scale <= 0.0 || tier.size_scale > 1.0 {
+                return Err("risk.drawdown_tiers size_scale must be in (0, 1]".into());
+            }
+        }
+        if self.risk.emergency_stop_value <= 0.0 {
+            return Err("emergency_stop_value must be > 0".into());
+        }
+        if self.risk.max_open_notional_ratio <= 0.0 {
+            return Err("max_open_notional_ratio must be > 0".into());
+        }
         if self.simulator.max_spread_bps <= 0.0 {
             return Err("max_spread_bps must be > 0".into());
         }
@@ -454,12 +1592,224 @@ impl AppConfig {
         if self.simulator.expectancy_lookback_trades == 0 {
             return Err("expectancy_lookback_trades must be > 0".into());
         }
+        if !(0.0..50.0).contains(&self.simulator.expectancy_trim_pct) {
+            return Err("expectancy_trim_pct must be in [0, 50)".into());
+        }
+        if self.simulator.expectancy_confidence_z < 0.0 {
+            return Err("expectancy_confidence_z must be >= 0".into());
+        }
+        if self.simulator.absorption_exit_min_burst_ratio < 0.0 {
+            return Err("absorption_exit_min_burst_ratio must be >= 0".into());
+        }
         if self.simulator.max_model_slippage_bps <= 0.0 {
             return Err("max_model_slippage_bps must be > 0".into());
         }
+        if !matches!(self.simulator.unfilled_remainder.to_lowercase().as_str(), "reject" | "rest") {
+            return Err(format!(
+                "simulator.unfilled_remainder must be Reject or Rest (got {})",
+                self.simulator.unfilled_remainder
+            ));
+        }
+        if self.simulator.max_model_slippage_ticks > 0
+            && self.simulator.min_model_slippage_ticks > self.simulator.max_model_slippage_ticks
+        {
+            return Err("min_model_slippage_ticks must be <= max_model_slippage_ticks".into());
+        }
         if self.simulator.impact_depth_levels == 0 {
             return Err("impact_depth_levels must be > 0".into());
         }
+        if self.http.connect_timeout_ms == 0 || self.http.request_timeout_ms == 0 {
+            return Err("http timeouts must be > 0".into());
+        }
+        if self.recording.enabled {
+            if self.recording.path.is_empty() {
+                return Err("recording.path must be set when recording is enabled".into());
+            }
+            if self.replay.enabled && self.replay.path == self.recording.path {
+                return Err("recording.path must differ from replay.path".into());
+            }
+        }
+        if self.replay.enabled {
+            if self.replay.path.is_empty() {
+                return Err("replay.path must be set when replay is enabled".into());
+            }
+            if self.replay.speed <= 0.0 {
+                return Err("replay.speed must be > 0".into());
+            }
+            if let Some(seek_to) = &self.replay.seek_to {
+                if chrono::DateTime::parse_from_rfc3339(seek_to).is_err() {
+                    return Err("replay.seek_to must be an RFC3339 timestamp".into());
+                }
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_duplicate_symbols_are_dropped_case_insensitively() {
+        let mut general: GeneralConfig = toml::from_str(
+            r#"
+            symbols = ["btcusdt", "ethusdt", "BTCUSDT", "ethusdt", "solusdt"]
+            log_level = "info"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(general.dedup_symbols(), vec!["BTCUSDT", "ethusdt"]);
+        assert_eq!(general.symbols, vec!["btcusdt", "ethusdt", "solusdt"]);
+    }
+
+    fn sample_config() -> Value {
+        let content = std::fs::read_to_string("config.toml").unwrap();
+        let toml: toml::Value = toml::from_str(&content).unwrap();
+        serde_json::to_value(toml).unwrap()
+    }
+
+    #[test]
+    fn test_schema_validates_sample_config() {
+        let validator = jsonschema::validator_for(&AppConfig::json_schema()).unwrap();
+        let config = sample_config();
+        assert!(validator.validate(&config).is_ok());
+
+        let mut bad = config.clone();
+        bad["risk"]["max_risk_per_trade"] = serde_json::json!(0.5);
+        let err = validator.validate(&bad).unwrap_err();
+        assert_eq!(err.instance_path.to_string(), "/risk/max_risk_per_trade");
+
+        let mut bad = config;
+        bad["risk"]["max_risk_per_trade"] = serde_json::json!("high");
+        assert!(!validator.is_valid(&bad));
+    }
+
+    #[test]
+    fn test_range_bar_symbol_pct_scales_with_price() {
+        let config: RangeBarConfig = toml::from_str(
+            r#"
+            ethusdt = 3.0
+            default = 10.0
+            default_pct = 0.05
+
+            [symbol_pct]
+            btcusdt = 0.05
+            ethusdt = 0.08
+            solusdt = 0.1
+            "#,
+        )
+        .unwrap();
+        let price = |p: i64| Decimal::from(p);
+
+        assert_eq!(config.range_for_with_price("btcusdt", price(60_000)), price(30));
+        assert_eq!(config.range_for_with_price("solusdt", price(150)), Decimal::new(15, 2));
+        // Absolute override wins over the percent override
+        assert_eq!(config.range_for_with_price("ethusdt", price(3_000)), price(3));
+        // Unlisted symbols use the global percent, then the default key
+        assert_eq!(config.range_for_with_price("xrpusdt", price(2_000)), price(1));
+        assert_eq!(config.range_for_with_price("btcusdt", Decimal::ZERO), price(10));
+        assert!(!config.symbol_ranges.contains_key("symbol_pct"));
+    }
+
+    #[test]
+    fn test_zero_leverage_fails_validation() {
+        let content = std::fs::read_to_string("config.toml").unwrap();
+        assert!(content.contains("\nleverage = 50.0"));
+        let path = std::env::temp_dir().join(format!("rusto-config-{}.toml", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+
+        std::fs::write(&path, content.replace("\nleverage = 50.0", "\nleverage = 0.0")).unwrap();
+        let err = AppConfig::load(&path).unwrap_err().to_string();
+        assert!(err.contains("simulator.leverage must be >= 1"), "{}", err);
+
+        let config: AppConfig = toml::from_str(&content).unwrap();
+        assert_eq!(config.validate(), Ok(()));
+        let mut bad = config.clone();
+        bad.simulator.maintenance_margin_rate = 0.0;
+        assert!(bad.validate().unwrap_err().contains("maintenance_margin_rate"));
+        let mut bad = config;
+        bad.simulator.taker_fee = -0.0004;
+        assert!(bad.validate().unwrap_err().contains("fees"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_top_n_symbols_is_honored_and_bounded() {
+        let content = std::fs::read_to_string("config.toml").unwrap();
+        let mut config: AppConfig = toml::from_str(&content).unwrap();
+        config.general.auto_select_symbols = true;
+        config.general.pinned_symbols.clear();
+        config.general.top_n_symbols = 5;
+        assert_eq!(config.validate(), Ok(()));
+
+        config.general.top_n_symbols = 0;
+        assert!(config.validate().unwrap_err().contains("top_n_symbols"));
+        config.general.top_n_symbols = MAX_TOP_N_SYMBOLS + 1;
+        assert!(config.validate().unwrap_err().contains("top_n_symbols"));
+
+        config.general.top_n_symbols = 1;
+        config.general.pinned_symbols = vec!["btcusdt".into(), "ethusdt".into()];
+        assert!(config.validate().unwrap_err().contains("pinned_symbols"));
+    }
+
+    #[test]
+    fn test_sink_path_defaults_follow_the_sink_type() {
+        let content = std::fs::read_to_string("config.toml").unwrap();
+        let mut config: AppConfig = toml::from_str(&content).unwrap();
+        config.logging.market_sink = "Sqlite".to_string();
+        config.logging.book_snapshot_sink = "Sqlite".to_string();
+        config.logging.fill_sink_paths();
+        assert_eq!(config.logging.market_sink_path, "market_context.db");
+        assert_eq!(config.logging.book_snapshot_path, "book_snapshots.db");
+
+        let mut config: AppConfig = toml::from_str(&content).unwrap();
+        config.logging.market_sink = "Jsonl".to_string();
+        config.logging.book_snapshot_sink = "Jsonl".to_string();
+        config.logging.fill_sink_paths();
+        assert_eq!(config.logging.market_sink_path, "market_context.jsonl");
+        assert_eq!(config.logging.book_snapshot_path, "book_snapshots.jsonl");
+
+        // An explicit path is kept
+        config.logging.market_sink_path = "context.sqlite".to_string();
+        config.logging.fill_sink_paths();
+        assert_eq!(config.logging.market_sink_path, "context.sqlite");
+    }
+
+    #[test]
+    fn test_tuning_ratio_bounds_are_validated_case_insensitively() {
+        let content = std::fs::read_to_string("config.toml").unwrap();
+        let mut config: AppConfig = toml::from_str(&content).unwrap();
+        assert_eq!(
+            config.strategy.advanced_tuning_ratio_candidates,
+            default_advanced_tuning_ratio_candidates()
+        );
+        config.strategy.advanced_tuning_ratio_floor_by_symbol = HashMap::from([("btcusdt".into(), 2.4)]);
+        config.strategy.advanced_tuning_ratio_ceiling_by_symbol = HashMap::from([("BTCUSDT".into(), 2.8)]);
+        assert_eq!(config.validate(), Ok(()));
+
+        config.strategy.advanced_tuning_ratio_ceiling_by_symbol = HashMap::from([("BTCUSDT".into(), 1.8)]);
+        assert!(config.validate().unwrap_err().contains("exceeds its ceiling"));
+    }
+
+    #[test]
+    fn test_setup_lists_are_case_insensitive_and_reject_unknown_names() {
+        let content = std::fs::read_to_string("config.toml").unwrap();
+        let mut config: AppConfig = toml::from_str(&content).unwrap();
+        config.strategy.cvd_confirmation_setups = vec!["aaa".into(), "AbsorptionReversal".into()];
+        config.risk.max_positions_by_setup = HashMap::from([("momentumSQUEEZE".into(), 2)]);
+        assert_eq!(config.validate(), Ok(()));
+
+        config.strategy.volume_burst_required_setups = vec!["Momentum".into()];
+        assert!(config.validate().unwrap_err().contains("volume_burst_required_setups"));
+        config.strategy.volume_burst_required_setups.clear();
+        config.risk.max_positions_by_setup = HashMap::from([("AAAA".into(), 1)]);
+        assert!(config.validate().unwrap_err().contains("max_positions_by_setup"));
+        config.risk.max_positions_by_setup.clear();
+        config.strategy.min_confidence_by_setup = HashMap::from([("advancedorderflow".into(), 0.8)]);
+        assert_eq!(config.validate(), Ok(()));
+        config.strategy.min_confidence_by_setup = HashMap::from([("AdvancedOrderFlw".into(), 0.8)]);
+        assert!(config.validate().unwrap_err().contains("min_confidence_by_setup"));
+    }
+}
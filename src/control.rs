@@ -0,0 +1,347 @@
+use crate::diagnostics::{dump_recorder, SharedRecorder};
+use crate::metrics::SharedFeedMetrics;
+use crate::types::ProcessingEvent;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+use tracing::{error, info};
+
+/// Shared handle to the health state
+pub type SharedHealth = Arc<Mutex<HealthState>>;
+
+/// Stream and sync status reported by the readiness probe
+pub struct HealthState {
+    ws_connected: bool,
+    exchange_synced: bool,
+    time_offset_ms: Option<i64>,
+    max_time_offset_ms: i64,
+    symbols: Vec<String>,
+    /// Wall-clock receipt time of the latest trade per symbol
+    last_trade: BTreeMap<String, DateTime<Utc>>,
+    /// Latest operator heartbeat (None = dead-man's switch not armed)
+    last_heartbeat: Option<DateTime<Utc>>,
+}
+
+impl HealthState {
+    pub fn new(symbols: Vec<String>, max_time_offset_ms: i64) -> Self {
+        Self {
+            ws_connected: false,
+            exchange_synced: false,
+            time_offset_ms: None,
+            max_time_offset_ms,
+            symbols,
+            last_trade: BTreeMap::new(),
+            last_heartbeat: None,
+        }
+    }
+
+    pub fn shared(symbols: Vec<String>, max_time_offset_ms: i64) -> SharedHealth {
+        Arc::new(Mutex::new(Self::new(symbols, max_time_offset_ms)))
+    }
+
+    pub fn set_ws_connected(&mut self, connected: bool) {
+        self.ws_connected = connected;
+    }
+
+    pub fn set_exchange_synced(&mut self, synced: bool) {
+        self.exchange_synced = synced;
+    }
+
+    pub fn set_time_offset(&mut self, offset_ms: i64) {
+        self.time_offset_ms = Some(offset_ms);
+    }
+
+    pub fn record_trade(&mut self, symbol: &str, at: DateTime<Utc>) {
+        match self.last_trade.get_mut(symbol) {
+            Some(last) => *last = at,
+            None => {
+                self.last_trade.insert(symbol.to_string(), at);
+            }
+        }
+    }
+
+    /// Record an operator heartbeat; the first call arms the dead-man's switch
+    pub fn record_heartbeat(&mut self, at: DateTime<Utc>) {
+        self.last_heartbeat = Some(at);
+    }
+
+    /// True once an armed heartbeat is older than `timeout`
+    pub fn heartbeat_expired(&self, now: DateTime<Utc>, timeout: Duration) -> bool {
+        self.last_heartbeat.is_some_and(|at| now - at > timeout)
+    }
+
+    /// Reasons the bot is not ready (empty = ready)
+    pub fn readiness_failures(&self, now: DateTime<Utc>, stale_after: Duration) -> Vec<String> {
+        let mut failures = Vec::new();
+        if !self.ws_connected {
+            failures.push("websocket disconnected".to_string());
+        }
+        if !self.exchange_synced {
+            failures.push("exchange info not synced".to_string());
+        }
+        match self.time_offset_ms {
+            None => failures.push("time offset unknown".to_string()),
+            Some(offset) if offset.abs() > self.max_time_offset_ms => failures.push(format!(
+                "time offset {}ms exceeds {}ms",
+                offset, self.max_time_offset_ms
+            )),
+            Some(_) => {}
+        }
+        for symbol in &self.symbols {
+            match self.last_trade.get(symbol) {
+                None => failures.push(format!("no trades for {}", symbol)),
+                Some(at) if now - *at > stale_after => failures.push(format!(
+                    "no trades for {} in {}s",
+                    symbol,
+                    (now - *at).num_seconds()
+                )),
+                Some(_) => {}
+            }
+        }
+        failures
+    }
+}
+
+/// State shared by the control API handlers
+#[derive(Clone)]
+pub struct ControlState {
+    pub health: SharedHealth,
+    /// A symbol without a trade for this long fails readiness
+    pub stale_after: Duration,
+    /// Diagnostics ring buffer and dump path (when enabled)
+    pub recorder: Option<(SharedRecorder, String)>,
+    /// Per-symbol feed counters and rates
+    pub metrics: SharedFeedMetrics,
+}
+
+/// Build the control API routes
+pub fn router(state: ControlState) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics))
+        .route("/heartbeat", post(heartbeat))
+        .route("/diagnostics/dump", post(diagnostics_dump))
+        .with_state(state)
+}
+
+/// Serve the control API until shutdown
+pub async fn serve(
+    listener: TcpListener,
+    state: ControlState,
+    mut shutdown: watch::Receiver<bool>,
+) -> std::io::Result<()> {
+    if let Ok(addr) = listener.local_addr() {
+        info!(addr = %addr, "Control API listening");
+    }
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await
+}
+
+/// Dead-man's switch: once heartbeats stop for `timeout`, ask the simulator
+/// to flatten every position and halt new entries, then exit
+pub async fn watch_heartbeat(
+    health: SharedHealth,
+    timeout: Duration,
+    processing_tx: mpsc::Sender<ProcessingEvent>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let poll = timeout
+        .to_std()
+        .map(|t| (t / 4).min(std::time::Duration::from_secs(1)))
+        .unwrap_or(std::time::Duration::from_secs(1));
+    let mut interval = tokio::time::interval(poll);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let expired = health
+                    .lock()
+                    .map(|h| h.heartbeat_expired(Utc::now(), timeout))
+                    .unwrap_or(false);
+                if expired {
+                    error!(
+                        timeout_secs = timeout.num_seconds(),
+                        "Heartbeat lost: flattening all positions and halting"
+                    );
+                    let reason = "dead-man's switch: heartbeat lost".to_string();
+                    let _ = processing_tx.send(ProcessingEvent::FlattenAndHalt { reason }).await;
+                    return;
+                }
+            }
+            _ = shutdown.changed() => {
+                if *shutdown.borrow() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz(State(state): State<ControlState>) -> (StatusCode, Json<serde_json::Value>) {
+    let failures = match state.health.lock() {
+        Ok(health) => health.readiness_failures(Utc::now(), state.stale_after),
+        Err(_) => vec!["health state poisoned".to_string()],
+    };
+    let status = if failures.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(json!({ "ready": failures.is_empty(), "failures": failures })),
+    )
+}
+
+async fn heartbeat(State(state): State<ControlState>) -> StatusCode {
+    match state.health.lock() {
+        Ok(mut health) => {
+            health.record_heartbeat(Utc::now());
+            StatusCode::NO_CONTENT
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+async fn metrics(State(state): State<ControlState>) -> (StatusCode, String) {
+    match state.metrics.lock() {
+        Ok(metrics) => (StatusCode::OK, metrics.render_prometheus()),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, String::new()),
+    }
+}
+
+async fn diagnostics_dump(State(state): State<ControlState>) -> (StatusCode, &'static str) {
+    match &state.recorder {
+        Some((recorder, path)) => {
+            dump_recorder(recorder, path);
+            (StatusCode::OK, "dumped")
+        }
+        None => (StatusCode::NOT_FOUND, "diagnostics ring buffer disabled"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::FeedMetrics;
+
+    async fn spawn_server(health: SharedHealth) -> (String, watch::Sender<bool>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let state = ControlState {
+            health,
+            stale_after: Duration::seconds(30),
+            recorder: None,
+            metrics: FeedMetrics::shared(&["btcusdt".to_string()], Utc::now()),
+        };
+        tokio::spawn(serve(listener, state, shutdown_rx));
+        (base, shutdown_tx)
+    }
+
+    #[tokio::test]
+    async fn test_health_and_readiness_probes() {
+        let health = HealthState::shared(vec!["btcusdt".to_string(), "ethusdt".to_string()], 1000);
+        {
+            let mut h = health.lock().unwrap();
+            h.set_ws_connected(true);
+            h.set_exchange_synced(true);
+            h.set_time_offset(-120);
+            h.record_trade("btcusdt", Utc::now());
+            h.record_trade("ethusdt", Utc::now());
+        }
+        let (base, shutdown_tx) = spawn_server(health.clone()).await;
+        let client = reqwest::Client::new();
+
+        let healthz = client.get(format!("{}/healthz", base)).send().await.unwrap();
+        assert_eq!(healthz.status(), 200);
+        let readyz = client.get(format!("{}/readyz", base)).send().await.unwrap();
+        assert_eq!(readyz.status(), 200);
+
+        // Stalled feed: ethusdt's last trade is older than the stale window
+        health
+            .lock()
+            .unwrap()
+            .record_trade("ethusdt", Utc::now() - Duration::seconds(120));
+        let readyz = client.get(format!("{}/readyz", base)).send().await.unwrap();
+        assert_eq!(readyz.status(), 503);
+        let body: serde_json::Value = readyz.json().await.unwrap();
+        assert_eq!(body["ready"], false);
+        assert!(body["failures"][0].as_str().unwrap().contains("ethusdt"));
+
+        // Liveness is independent of readiness
+        let healthz = client.get(format!("{}/healthz", base)).send().await.unwrap();
+        assert_eq!(healthz.status(), 200);
+
+        let metrics = client.get(format!("{}/metrics", base)).send().await.unwrap();
+        assert_eq!(metrics.status(), 200);
+        let text = metrics.text().await.unwrap();
+        assert!(text.contains("rusto_trades_total{symbol=\"btcusdt\"} 0"));
+
+        let _ = shutdown_tx.send(true);
+    }
+
+    #[test]
+    fn test_readiness_failures() {
+        let mut health = HealthState::new(vec!["btcusdt".to_string()], 500);
+        let now = Utc::now();
+        assert_eq!(health.readiness_failures(now, Duration::seconds(30)).len(), 4);
+
+        health.set_ws_connected(true);
+        health.set_exchange_synced(true);
+        health.set_time_offset(800);
+        health.record_trade("btcusdt", now);
+        assert_eq!(
+            health.readiness_failures(now, Duration::seconds(30)),
+            vec!["time offset 800ms exceeds 500ms".to_string()]
+        );
+
+        health.set_time_offset(200);
+        assert!(health.readiness_failures(now, Duration::seconds(30)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dead_mans_switch_fires_after_heartbeats_stop() {
+        let health = HealthState::shared(vec!["btcusdt".to_string()], 1000);
+        health.lock().unwrap().record_heartbeat(Utc::now());
+        let (base, shutdown_tx) = spawn_server(health.clone()).await;
+        let (processing_tx, mut processing_rx) = mpsc::channel(4);
+        let watchdog = tokio::spawn(watch_heartbeat(
+            health,
+            Duration::milliseconds(300),
+            processing_tx,
+            shutdown_tx.subscribe(),
+        ));
+        let client = reqwest::Client::new();
+
+        // Regular heartbeats keep the switch from firing
+        for _ in 0..4 {
+            let resp = client.post(format!("{}/heartbeat", base)).send().await.unwrap();
+            assert_eq!(resp.status(), 204);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(processing_rx.try_recv().is_err());
+
+        // Heartbeats stop: flatten-and-halt is requested once the timeout passes
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), processing_rx.recv())
+            .await
+            .unwrap();
+        assert!(matches!(event, Some(ProcessingEvent::FlattenAndHalt { .. })));
+        watchdog.await.unwrap();
+        let _ = shutdown_tx.send(true);
+    }
+}
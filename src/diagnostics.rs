@@ -0,0 +1,159 @@
+use crate::types::{MarketEvent, TradeSignal};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info};
+
+/// Shared handle to the event recorder
+pub type SharedRecorder = Arc<Mutex<EventRecorder>>;
+
+/// Bounded ring buffer of the most recent market events and signals,
+/// dumped to disk for post-mortem replay.
+pub struct EventRecorder {
+    capacity: usize,
+    market: VecDeque<MarketEvent>,
+    signals: VecDeque<TradeSignal>,
+}
+
+impl EventRecorder {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            market: VecDeque::with_capacity(capacity),
+            signals: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn shared(capacity: usize) -> SharedRecorder {
+        Arc::new(Mutex::new(Self::new(capacity)))
+    }
+
+    pub fn record_market(&mut self, event: &MarketEvent) {
+        if self.market.len() >= self.capacity {
+            self.market.pop_front();
+        }
+        self.market.push_back(event.clone());
+    }
+
+    pub fn record_signal(&mut self, signal: &TradeSignal) {
+        if self.signals.len() >= self.capacity {
+            self.signals.pop_front();
+        }
+        self.signals.push_back(signal.clone());
+    }
+
+    /// Write buffered market events to `path` as JSONL (replayable by `ReplaySource`)
+    /// and buffered signals to `<path stem>.signals.jsonl`. Returns the market event count.
+    pub fn dump(&self, path: &str) -> std::io::Result<usize> {
+        write_jsonl(path, self.market.iter())?;
+        write_jsonl(&signals_path(path), self.signals.iter())?;
+        Ok(self.market.len())
+    }
+}
+
+/// Sibling path for the signal dump: "dump.jsonl" -> "dump.signals.jsonl"
+pub fn signals_path(path: &str) -> String {
+    match path.strip_suffix(".jsonl") {
+        Some(stem) => format!("{}.signals.jsonl", stem),
+        None => format!("{}.signals.jsonl", path),
+    }
+}
+
+fn write_jsonl<'a, T: serde::Serialize + 'a>(
+    path: &str,
+    items: impl Iterator<Item = &'a T>,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for item in items {
+        serde_json::to_writer(&mut writer, item)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Dump the recorder, logging the outcome
+pub fn dump_recorder(recorder: &SharedRecorder, path: &str) {
+    let result = match recorder.lock() {
+        Ok(rec) => rec.dump(path),
+        Err(poisoned) => poisoned.into_inner().dump(path),
+    };
+    match result {
+        Ok(count) => info!(path = %path, events = count, "Diagnostics dump written"),
+        Err(e) => error!(path = %path, "Failed to write diagnostics dump: {}", e),
+    }
+}
+
+/// Dump the ring buffer when any thread panics (after the default hook runs)
+pub fn install_panic_hook(recorder: SharedRecorder, path: String) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        previous(panic_info);
+        // try_lock: the panicking thread may already hold the recorder
+        if let Ok(rec) = recorder.try_lock() {
+            match rec.dump(&path) {
+                Ok(count) => eprintln!("Diagnostics dump written to {} ({} events)", path, count),
+                Err(e) => eprintln!("Failed to write diagnostics dump to {}: {}", path, e),
+            }
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReplayConfig;
+    use crate::market_data::ReplaySource;
+    use crate::types::{NormalizedTrade, Side};
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use tokio::sync::{broadcast, watch};
+
+    fn trade(i: u64) -> MarketEvent {
+        MarketEvent::Trade(NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            price: Decimal::from(100 + i),
+            quantity: Decimal::ONE,
+            side: Side::Buy,
+            timestamp: Utc::now(),
+            trade_id: i,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_dump_keeps_last_k_events_and_replays() {
+        let mut recorder = EventRecorder::new(3);
+        for i in 0..5 {
+            recorder.record_market(&trade(i));
+        }
+
+        let path = std::env::temp_dir()
+            .join(format!("rusto-dump-{}.jsonl", uuid::Uuid::new_v4()))
+            .display()
+            .to_string();
+        assert_eq!(recorder.dump(&path).unwrap(), 3);
+
+        let (tx, mut rx) = broadcast::channel(16);
+        let config = ReplayConfig {
+            enabled: true,
+            path: path.clone(),
+            speed: 1000.0,
+            seek_to: None,
+            progress_interval_secs: 10,
+        };
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let stats = ReplaySource::new(&config, tx).run(shutdown_rx).await.unwrap();
+        assert_eq!(stats.events_sent, 3);
+
+        let ids: Vec<u64> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|e| match e {
+                MarketEvent::Trade(t) => t.trade_id,
+                other => panic!("unexpected event: {:?}", other),
+            })
+            .collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(signals_path(&path));
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Once-a-day summary of the trades closed since the previous digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyDigest {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub trades: u32,
+    pub wins: u32,
+    pub pnl: Decimal,
+    /// Symbol with the highest / lowest net PnL over the period
+    pub best_symbol: Option<(String, Decimal)>,
+    pub worst_symbol: Option<(String, Decimal)>,
+    /// Largest peak-to-trough fall of the closed-trade equity curve
+    pub max_drawdown: Decimal,
+    pub max_drawdown_pct: Decimal,
+    pub balance: Decimal,
+}
+
+impl DailyDigest {
+    /// Aggregate `closes` (symbol, pnl) in close order; `balance` is the balance after the last one
+    pub fn from_closes(
+        closes: &[(String, Decimal)],
+        balance: Decimal,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Self {
+        let pnl: Decimal = closes.iter().map(|(_, p)| *p).sum();
+        let wins = closes.iter().filter(|(_, p)| *p >= Decimal::ZERO).count() as u32;
+
+        let mut by_symbol: BTreeMap<&str, Decimal> = BTreeMap::new();
+        for (symbol, p) in closes {
+            *by_symbol.entry(symbol.as_str()).or_default() += *p;
+        }
+        let best_symbol = by_symbol
+            .iter()
+            .max_by_key(|(_, p)| **p)
+            .map(|(s, p)| (s.to_string(), *p));
+        let worst_symbol = by_symbol
+            .iter()
+            .min_by_key(|(_, p)| **p)
+            .map(|(s, p)| (s.to_string(), *p));
+
+        let mut equity = balance - pnl;
+        let mut peak = equity;
+        let mut max_drawdown = Decimal::ZERO;
+        let mut max_drawdown_pct = Decimal::ZERO;
+        for (_, p) in closes {
+            equity += *p;
+            peak = peak.max(equity);
+            let drawdown = peak - equity;
+            max_drawdown = max_drawdown.max(drawdown);
+            if peak > Decimal::ZERO {
+                max_drawdown_pct = max_drawdown_pct.max(drawdown / peak * Decimal::from(100));
+            }
+        }
+
+        Self {
+            period_start,
+            period_end,
+            trades: closes.len() as u32,
+            wins,
+            pnl,
+            best_symbol,
+            worst_symbol,
+            max_drawdown,
+            max_drawdown_pct,
+            balance,
+        }
+    }
+
+    pub fn win_rate(&self) -> Decimal {
+        if self.trades == 0 {
+            return Decimal::ZERO;
+        }
+        Decimal::from(self.wins) * Decimal::from(100) / Decimal::from(self.trades)
+    }
+}
+
+/// Next occurrence of `time` in the timezone `utc_offset_hours` after `now`
+pub fn next_digest_at(now: DateTime<Utc>, time: NaiveTime, utc_offset_hours: i32) -> DateTime<Utc> {
+    let offset = FixedOffset::east_opt(utc_offset_hours * 3600)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    let local_now = now.with_timezone(&offset);
+    let today = offset
+        .from_local_datetime(&local_now.date_naive().and_time(time))
+        .single()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or(now);
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_aggregates_period_closes() {
+        let closes: Vec<(String, Decimal)> = [
+            ("btcusdt", 50),
+            ("ethusdt", -30),
+            ("ethusdt", -40),
+            ("solusdt", 20),
+            ("btcusdt", 10),
+        ]
+        .iter()
+        .map(|(s, p)| (s.to_string(), Decimal::from(*p)))
+        .collect();
+        let end = Utc::now();
+        let digest =
+            DailyDigest::from_closes(&closes, Decimal::from(10_010), end - Duration::days(1), end);
+
+        assert_eq!(digest.trades, 5);
+        assert_eq!(digest.wins, 3);
+        assert_eq!(digest.win_rate(), Decimal::from(60));
+        assert_eq!(digest.pnl, Decimal::from(10));
+        assert_eq!(digest.best_symbol, Some(("btcusdt".to_string(), Decimal::from(60))));
+        assert_eq!(digest.worst_symbol, Some(("ethusdt".to_string(), Decimal::from(-70))));
+        // Started at 10_000, peaked at 10_050, troughed at 9_980
+        assert_eq!(digest.max_drawdown, Decimal::from(70));
+    }
+
+    #[test]
+    fn test_next_digest_at_kst_morning() {
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        // 23:30 UTC = 08:30 KST: due in 30 minutes
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 23, 30, 0).unwrap();
+        assert_eq!(
+            next_digest_at(now, nine, 9),
+            Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap()
+        );
+        // 01:00 UTC = 10:00 KST: tomorrow morning
+        let now = Utc.with_ymd_and_hms(2024, 3, 2, 1, 0, 0).unwrap();
+        assert_eq!(
+            next_digest_at(now, nine, 9),
+            Utc.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap()
+        );
+    }
+}
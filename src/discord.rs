@@ -1,5 +1,6 @@
 use crate::binance::NetworkStats;
-use crate::types::{ExecutionEvent, Position, Side, SymbolStats};
+use crate::digest::DailyDigest;
+use crate::types::{AssetExposure, ExecutionEvent, Position, SessionBreakKind, Side, SymbolStats, TradeSignal};
 use reqwest::Client;
 use rust_decimal::Decimal;
 use serde_json::json;
@@ -11,6 +12,8 @@ use tracing::{error, info};
 pub struct DiscordBot {
     webhook_url: String,
     client: Client,
+    /// Send routine break-even/stop-move updates
+    stop_move_alerts: bool,
 }
 
 impl DiscordBot {
@@ -18,6 +21,27 @@ impl DiscordBot {
         Self {
             webhook_url,
             client: Client::new(),
+            stop_move_alerts: true,
+        }
+    }
+
+    /// Use a shared, pre-configured HTTP client
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Enable or suppress stop-move notifications (liquidation and limit alerts always send)
+    pub fn with_stop_move_alerts(mut self, enabled: bool) -> Self {
+        self.stop_move_alerts = enabled;
+        self
+    }
+
+    /// Whether `event` should produce a Discord message
+    fn notifies(&self, event: &ExecutionEvent) -> bool {
+        match event {
+            ExecutionEvent::StopMoved { .. } => self.stop_move_alerts,
+            _ => true,
         }
     }
 
@@ -36,6 +60,10 @@ impl DiscordBot {
                 }
                 _ = shutdown.changed() => {
                     if *shutdown.borrow() {
+                        // Alerts queued just before shutdown (restart, feed down) still go out
+                        while let Ok(event) = execution_rx.try_recv() {
+                            self.handle_execution_event(event).await;
+                        }
                         info!("Discord bot shutting down");
                         return;
                     }
@@ -45,6 +73,9 @@ impl DiscordBot {
     }
 
     async fn handle_execution_event(&self, event: ExecutionEvent) {
+        if !self.notifies(&event) {
+            return;
+        }
         match event {
             ExecutionEvent::PositionOpened(position) => {
                 self.send_position_opened(&position).await;
@@ -64,8 +95,38 @@ impl DiscordBot {
             ExecutionEvent::DailyLimitReached { pnl } => {
                 self.send_daily_limit_reached(pnl).await;
             }
-            ExecutionEvent::HourlyReport { balance, daily_pnl, open_positions, ping_ms, total_trades, symbol_stats } => {
+            ExecutionEvent::BalanceFloorReached { balance, floor } => {
+                self.send_balance_floor_reached(balance, floor).await;
+            }
+            ExecutionEvent::FeedDown { failures, error } => {
+                self.send_feed_down(failures, &error).await;
+            }
+            ExecutionEvent::SignalAlert(signal) => {
+                self.send_signal_alert(&signal).await;
+            }
+            ExecutionEvent::SessionBreak { symbol, kind, level, price, timestamp } => {
+                self.send_session_break(&symbol, kind, level, price, timestamp).await;
+            }
+            ExecutionEvent::HourlyReport {
+                balance,
+                daily_pnl,
+                open_positions,
+                ping_ms,
+                total_trades,
+                symbol_stats,
+                exposure_by_quote,
+                exposure_by_category,
+            } => {
                 self.send_hourly_report(balance, daily_pnl, open_positions, ping_ms, total_trades, symbol_stats).await;
+                if !exposure_by_quote.is_empty() || !exposure_by_category.is_empty() {
+                    self.send_exposure_report(&exposure_by_quote, &exposure_by_category).await;
+                }
+            }
+            ExecutionEvent::DailyDigest(digest) => {
+                self.send_payload("📅 일일 요약", &daily_digest_payload(&digest)).await;
+            }
+            ExecutionEvent::SymbolsReselected { added, removed } => {
+                self.send_symbols_reselected(&added, &removed).await;
             }
         }
     }
@@ -261,6 +322,111 @@ impl DiscordBot {
         self.send_embed("일일 한도 도달", &message, 0xFF0000).await;
     }
 
+    async fn send_balance_floor_reached(&self, balance: Decimal, floor: Decimal) {
+        let message = format!(
+            "🛑 **잔고 하한 도달**\n\
+            **잔고**: ${:.2}\n\
+            **하한**: ${:.2}\n\
+            **상태**: 매매 중단 (재시작 필요)",
+            balance, floor
+        );
+
+        self.send_embed("잔고 하한 도달", &message, 0xFF0000).await;
+    }
+
+    async fn send_feed_down(&self, failures: u32, error: &str) {
+        let message = format!(
+            "🚨 **시세 연결 실패**\n\
+            **연속 실패**: {}회\n\
+            **마지막 오류**: {}",
+            failures, error
+        );
+
+        self.send_embed("시세 연결 실패", &message, 0xFF0000).await;
+    }
+
+    async fn send_symbols_reselected(&self, added: &[String], removed: &[String]) {
+        let list = |symbols: &[String]| {
+            if symbols.is_empty() {
+                "-".to_string()
+            } else {
+                symbols.iter().map(|s| s.to_uppercase()).collect::<Vec<_>>().join(", ")
+            }
+        };
+        let message = format!(
+            "🔄 **심볼 재선정 (KST 09:00)**\n\
+            **추가**: {}\n\
+            **제외**: {}\n\
+            **상태**: 재시작 후 적용",
+            list(added),
+            list(removed)
+        );
+
+        self.send_embed("심볼 재선정", &message, 0x3498DB).await;
+    }
+
+    async fn send_signal_alert(&self, signal: &TradeSignal) {
+        let (side_emoji, color) = match signal.side {
+            Side::Buy => ("🟢", 0x00FF00),
+            Side::Sell => ("🔴", 0xFF0000),
+        };
+
+        let message = format!(
+            "{} **매매 신호 (알림 전용)**\n\
+            **심볼**: {}\n\
+            **방향**: {:?}\n\
+            **전략**: {}\n\
+            **진입가**: ${}\n\
+            **손절가**: ${}\n\
+            **목표가**: ${}\n\
+            **신뢰도**: {:.2}\n\
+            **시간**: {}",
+            side_emoji,
+            signal.symbol.to_uppercase(),
+            signal.side,
+            signal.setup,
+            signal.entry_price,
+            signal.stop_loss,
+            signal.take_profit,
+            signal.confidence,
+            signal.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+
+        self.send_embed("📣 매매 신호", &message, color).await;
+    }
+
+    async fn send_session_break(
+        &self,
+        symbol: &str,
+        kind: SessionBreakKind,
+        level: Decimal,
+        price: Decimal,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) {
+        let (label, emoji, color) = match kind {
+            SessionBreakKind::NewSessionHigh => ("세션 신고가", "⬆️", 0x00FF00),
+            SessionBreakKind::NewSessionLow => ("세션 신저가", "⬇️", 0xFF0000),
+            SessionBreakKind::PriorVahBreak => ("전 세션 VAH 돌파", "⬆️", 0x00FF00),
+            SessionBreakKind::PriorValBreak => ("전 세션 VAL 이탈", "⬇️", 0xFF0000),
+        };
+
+        let message = format!(
+            "{} **{}**\n\
+            **심볼**: {}\n\
+            **기준 레벨**: ${}\n\
+            **현재가**: ${}\n\
+            **시간**: {}",
+            emoji,
+            label,
+            symbol.to_uppercase(),
+            level,
+            price,
+            timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+
+        self.send_embed("📍 세션 레벨 돌파", &message, color).await;
+    }
+
     async fn send_hourly_report(
         &self,
         balance: Decimal,
@@ -340,10 +506,10 @@ impl DiscordBot {
                 } else {
                     "  -".to_string()
                 };
-                let pf = if stats.total_loss_pnl != Decimal::ZERO {
-                    format!("{:.1}", stats.profit_factor())
-                } else {
-                    " - ".to_string()
+                let pf = match stats.profit_factor() {
+                    Some(pf) => format!("{:.1}", pf),
+                    None if stats.total_win_pnl > Decimal::ZERO => " ∞ ".to_string(),
+                    None => " - ".to_string(),
                 };
                 let pnl_sign = if stats.total_pnl >= Decimal::ZERO { "+" } else { "" };
                 message.push_str(&format!(
@@ -368,6 +534,36 @@ impl DiscordBot {
         self.send_embed("📊 정각 상태 보고", &message, color).await;
     }
 
+    /// Exposure/PnL breakdown by quote asset and base-asset category
+    async fn send_exposure_report(
+        &self,
+        by_quote: &BTreeMap<String, AssetExposure>,
+        by_category: &BTreeMap<String, AssetExposure>,
+    ) {
+        let mut message = String::new();
+        for (title, groups) in [("💱 **호가 자산별**", by_quote), ("🏷️ **자산 분류별**", by_category)] {
+            if groups.is_empty() {
+                continue;
+            }
+            message.push_str(&format!("{}\n```\n", title));
+            message.push_str("Group   |Open|Notional   |uPnL     |PnL\n");
+            message.push_str("--------|----|-----------|---------|---------\n");
+            for (group, e) in groups {
+                message.push_str(&format!(
+                    "{:<8}|{:>4}|${:<10.2}|${:<8.2}|${:.2}\n",
+                    group,
+                    e.open_positions,
+                    e.open_notional.round_dp(2),
+                    e.unrealized_pnl.round_dp(2),
+                    e.realized_pnl.round_dp(2),
+                ));
+            }
+            message.push_str("```\n");
+        }
+
+        self.send_embed("📊 노출 현황", &message, 0x3498DB).await;
+    }
+
     /// Send a warning message (e.g. auto-selection failure)
     pub async fn send_warning(&self, title: &str, description: &str) {
         self.send_embed(title, description, 0xFFAA00).await;
@@ -442,11 +638,14 @@ impl DiscordBot {
                 }
             }]
         });
+        self.send_payload(title, &payload).await;
+    }
 
+    async fn send_payload(&self, title: &str, payload: &serde_json::Value) {
         let response = match self
             .client
             .post(&self.webhook_url)
-            .json(&payload)
+            .json(payload)
             .send()
             .await
         {
@@ -470,3 +669,160 @@ impl DiscordBot {
         info!("Discord notification sent: {}", title);
     }
 }
+
+/// Daily digest embed: headline numbers as inline fields
+fn daily_digest_payload(digest: &DailyDigest) -> serde_json::Value {
+    let color = if digest.pnl >= Decimal::ZERO { 0x00FF00 } else { 0xFF4444 };
+    let symbol_field = |entry: &Option<(String, Decimal)>| match entry {
+        Some((symbol, pnl)) => format!("{} (${:.2})", symbol.to_uppercase(), pnl.round_dp(2)),
+        None => "-".to_string(),
+    };
+    json!({
+        "embeds": [{
+            "title": "📅 일일 요약",
+            "description": format!(
+                "{} ~ {}",
+                digest.period_start.format("%Y-%m-%d %H:%M UTC"),
+                digest.period_end.format("%Y-%m-%d %H:%M UTC")
+            ),
+            "color": color,
+            "fields": [
+                { "name": "거래 수", "value": format!("{}건", digest.trades), "inline": true },
+                { "name": "승률", "value": format!("{:.1}%", digest.win_rate().round_dp(1)), "inline": true },
+                { "name": "손익", "value": format!("${:.2}", digest.pnl.round_dp(2)), "inline": true },
+                { "name": "최고 심볼", "value": symbol_field(&digest.best_symbol), "inline": true },
+                { "name": "최저 심볼", "value": symbol_field(&digest.worst_symbol), "inline": true },
+                {
+                    "name": "최대 낙폭",
+                    "value": format!(
+                        "${:.2} ({:.2}%)",
+                        digest.max_drawdown.round_dp(2),
+                        digest.max_drawdown_pct.round_dp(2)
+                    ),
+                    "inline": true
+                },
+                { "name": "잔고", "value": format!("${:.2}", digest.balance.round_dp(2)), "inline": false },
+            ],
+            "timestamp": digest.period_end.to_rfc3339(),
+            "footer": {
+                "text": "Rusto Trading Bot"
+            }
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::position::test_support::open_position;
+    use crate::simulator::position::PositionManager;
+    use chrono::{Duration, Utc};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_daily_digest_embed_carries_period_stats() {
+        let closes: Vec<(String, Decimal)> = [("btcusdt", 120), ("ethusdt", -45), ("btcusdt", -15)]
+            .iter()
+            .map(|(s, p)| (s.to_string(), Decimal::from(*p)))
+            .collect();
+        let end = Utc::now();
+        let digest = DailyDigest::from_closes(&closes, Decimal::from(10_060), end - Duration::days(1), end);
+
+        let payload = daily_digest_payload(&digest);
+        let fields = payload["embeds"][0]["fields"].as_array().unwrap();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|f| f["name"] == name)
+                .and_then(|f| f["value"].as_str())
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(field("거래 수"), "3건");
+        assert_eq!(field("승률"), "33.3%");
+        assert_eq!(field("손익"), "$60.00");
+        assert_eq!(field("최고 심볼"), "BTCUSDT ($105.00)");
+        assert_eq!(field("최저 심볼"), "ETHUSDT ($-45.00)");
+        assert_eq!(field("최대 낙폭"), "$60.00 (0.59%)");
+        assert_eq!(payload["embeds"][0]["color"], 0x00FF00);
+    }
+
+    /// Local webhook that answers every POST with 204 and counts them
+    async fn counting_webhook() -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+        let posts = Arc::new(AtomicUsize::new(0));
+        let counter = posts.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Headers plus a Content-Length body
+                while let Ok(n) = stream.read(&mut buf).await {
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                let _ = stream
+                    .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                    .await;
+            }
+        });
+        (url, posts)
+    }
+
+    #[tokio::test]
+    async fn test_events_queued_before_shutdown_are_delivered() {
+        let (url, posts) = counting_webhook().await;
+        let bot = DiscordBot::new(url);
+        let (tx, rx) = mpsc::channel(8);
+        for failures in 1..=3 {
+            tx.send(ExecutionEvent::FeedDown {
+                failures,
+                error: "closed".to_string(),
+            })
+            .await
+            .unwrap();
+        }
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        shutdown_tx.send(true).unwrap();
+
+        tokio::time::timeout(std::time::Duration::from_secs(10), bot.run(rx, shutdown_rx))
+            .await
+            .unwrap();
+        assert_eq!(posts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_stop_move_alerts_can_be_suppressed() {
+        let bot = DiscordBot::new("http://localhost".to_string()).with_stop_move_alerts(false);
+        let stop_moved = ExecutionEvent::StopMoved {
+            position_id: "p1".to_string(),
+            new_stop: Decimal::from(100),
+        };
+        assert!(!bot.notifies(&stop_moved));
+        assert!(bot.notifies(&ExecutionEvent::DailyLimitReached {
+            pnl: Decimal::from(-500),
+        }));
+
+        let position = open_position(&mut PositionManager::new(), "btcusdt", 100);
+        assert!(bot.notifies(&ExecutionEvent::PositionLiquidated(position)));
+
+        assert!(DiscordBot::new("http://localhost".to_string()).notifies(&stop_moved));
+    }
+}
@@ -0,0 +1,344 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Boxed future returned by async `Exchange` methods (keeps the trait object-safe)
+pub type ExchangeFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A trading venue: symbol naming, trading rules and endpoints.
+/// The pipeline only talks to the venue through this, so a second exchange
+/// plugs in by implementing it.
+pub trait Exchange: Send + Sync {
+    /// Canonical (lowercase) form of a venue-native symbol
+    fn normalize_symbol(&self, raw: &str) -> String {
+        raw.to_lowercase()
+    }
+
+    /// Trading rules and filters for a symbol (any case)
+    fn symbol_info(&self, symbol: &str) -> Option<&SymbolRules>;
+
+    /// Market data stream URL for trades plus depth (or best bid/ask) of `symbols`
+    fn ws_url(&self, symbols: &[String], book_ticker: bool) -> String;
+
+    /// Venue clock, in epoch milliseconds
+    fn server_time(&self) -> ExchangeFuture<'_, Result<i64, ExchangeError>>;
+}
+
+/// Venue-neutral failure of an `Exchange` call
+#[derive(Debug, thiserror::Error)]
+#[error("{venue}: {message}")]
+pub struct ExchangeError {
+    pub venue: &'static str,
+    pub message: String,
+}
+
+/// Venue-neutral assets, trading rules and filters of one symbol
+#[derive(Debug, Clone)]
+pub struct SymbolRules {
+    pub base_asset: String,
+    pub quote_asset: String,
+    // Price filter
+    pub price_tick_size: Decimal,
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    // Lot size filter
+    pub quantity_step_size: Decimal,
+    pub min_quantity: Decimal,
+    pub max_quantity: Decimal,
+    // Min notional
+    pub min_notional: Decimal,
+    /// Highest leverage the venue allows on the symbol (None = not published)
+    pub max_leverage: Option<Decimal>,
+}
+
+/// How prices/quantities are snapped to the exchange tick/step grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Closest grid point (ties to even)
+    Nearest,
+    /// Toward zero: never exceeds the requested value
+    Down,
+    /// Away from zero: never falls short of the requested value
+    Up,
+}
+
+impl RoundingMode {
+    /// Parse a config value ("Nearest" | "Down" | "Up"), defaulting to Nearest
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "down" | "floor" => RoundingMode::Down,
+            "up" | "ceil" => RoundingMode::Up,
+            _ => RoundingMode::Nearest,
+        }
+    }
+
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::Nearest => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::Down => RoundingStrategy::ToNegativeInfinity,
+            RoundingMode::Up => RoundingStrategy::ToPositiveInfinity,
+        }
+    }
+
+    /// Snap `value` to a multiple of `step`
+    pub fn apply(self, value: Decimal, step: Decimal) -> Decimal {
+        let step = step.normalize();
+        // Power-of-ten ticks (0.1, 1e-8, ...) round at the tick's scale directly;
+        // value / step would need more than Decimal's 28 digits for large prices
+        if step.mantissa() == 1 {
+            return value.round_dp_with_strategy(step.scale(), self.strategy());
+        }
+        let snapped = value.checked_div(step).and_then(|steps| {
+            steps
+                .round_dp_with_strategy(0, self.strategy())
+                .checked_mul(step)
+        });
+        // Ratio out of range: the tick's decimal places are the finest grid we can honour
+        snapped.unwrap_or_else(|| value.round_dp_with_strategy(step.scale(), self.strategy()))
+    }
+}
+
+impl SymbolRules {
+    /// Validate and round price to comply with tick size
+    pub fn round_price(&self, price: Decimal) -> Result<Decimal, OrderValidationError> {
+        self.round_price_with(price, RoundingMode::Nearest)
+    }
+
+    /// Validate and round price to tick size using the given rounding mode
+    pub fn round_price_with(
+        &self,
+        price: Decimal,
+        mode: RoundingMode,
+    ) -> Result<Decimal, OrderValidationError> {
+        if price < self.min_price {
+            return Err(OrderValidationError::PriceTooLow {
+                price,
+                min: self.min_price,
+            });
+        }
+
+        if price > self.max_price {
+            return Err(OrderValidationError::PriceTooHigh {
+                price,
+                max: self.max_price,
+            });
+        }
+
+        Ok(mode.apply(price, self.price_tick_size))
+    }
+
+    /// Validate and round quantity to comply with step size
+    pub fn round_quantity(&self, quantity: Decimal) -> Result<Decimal, OrderValidationError> {
+        self.round_quantity_with(quantity, RoundingMode::Nearest)
+    }
+
+    /// Validate and round quantity to step size using the given rounding mode
+    pub fn round_quantity_with(
+        &self,
+        quantity: Decimal,
+        mode: RoundingMode,
+    ) -> Result<Decimal, OrderValidationError> {
+        if quantity < self.min_quantity {
+            return Err(OrderValidationError::QuantityTooLow {
+                quantity,
+                min: self.min_quantity,
+            });
+        }
+
+        if quantity > self.max_quantity {
+            return Err(OrderValidationError::QuantityTooHigh {
+                quantity,
+                max: self.max_quantity,
+            });
+        }
+
+        let rounded = mode.apply(quantity, self.quantity_step_size);
+        // Flooring can drop below the minimum even when the raw size was above it
+        if rounded < self.min_quantity {
+            return Err(OrderValidationError::QuantityTooLow {
+                quantity: rounded,
+                min: self.min_quantity,
+            });
+        }
+
+        Ok(rounded)
+    }
+
+    /// Validate notional value (price * quantity)
+    pub fn validate_notional(&self, price: Decimal, quantity: Decimal) -> Result<(), OrderValidationError> {
+        let notional = price * quantity;
+
+        if notional < self.min_notional {
+            return Err(OrderValidationError::NotionalTooLow {
+                notional,
+                min: self.min_notional,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Full order validation (price, quantity, and notional)
+    pub fn validate_order(
+        &self,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<(Decimal, Decimal), OrderValidationError> {
+        self.validate_order_with(price, quantity, RoundingMode::Nearest, RoundingMode::Nearest)
+    }
+
+    /// Full order validation with explicit price/quantity rounding modes
+    pub fn validate_order_with(
+        &self,
+        price: Decimal,
+        quantity: Decimal,
+        price_mode: RoundingMode,
+        quantity_mode: RoundingMode,
+    ) -> Result<(Decimal, Decimal), OrderValidationError> {
+        let rounded_price = self.round_price_with(price, price_mode)?;
+        let rounded_quantity = self.round_quantity_with(quantity, quantity_mode)?;
+        self.validate_notional(rounded_price, rounded_quantity)?;
+
+        Ok((rounded_price, rounded_quantity))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrderValidationError {
+    #[error("Price {price} is below minimum {min}")]
+    PriceTooLow { price: Decimal, min: Decimal },
+
+    #[error("Price {price} is above maximum {max}")]
+    PriceTooHigh { price: Decimal, max: Decimal },
+
+    #[error("Quantity {quantity} is below minimum {min}")]
+    QuantityTooLow { quantity: Decimal, min: Decimal },
+
+    #[error("Quantity {quantity} is above maximum {max}")]
+    QuantityTooHigh { quantity: Decimal, max: Decimal },
+
+    #[error("Notional value {notional} is below minimum {min}")]
+    NotionalTooLow { notional: Decimal, min: Decimal },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_price_rounding() {
+        let rules = SymbolRules {
+            base_asset: "BTC".to_string(),
+            quote_asset: "USDT".to_string(),
+            price_tick_size: Decimal::new(1, 1), // 0.1
+            min_price: Decimal::from(100),
+            max_price: Decimal::from(100000),
+            quantity_step_size: Decimal::new(1, 3), // 0.001
+            min_quantity: Decimal::new(1, 3),
+            max_quantity: Decimal::from(1000),
+            min_notional: Decimal::from(5),
+            max_leverage: None,
+        };
+
+        // Test price rounding (tick_size = 0.1)
+        // 50002.56 should round to 50002.6
+        let price = Decimal::new(5000256, 2); // 50002.56
+        let rounded = rules.round_price(price).unwrap();
+        assert_eq!(rounded, Decimal::new(500026, 1)); // Should round to 50002.6
+
+        // 50002.54 should round to 50002.5
+        let price2 = Decimal::new(5000254, 2); // 50002.54
+        let rounded2 = rules.round_price(price2).unwrap();
+        assert_eq!(rounded2, Decimal::new(500025, 1)); // Should round to 50002.5
+
+        // Test quantity rounding
+        let qty = Decimal::new(12345, 4); // 1.2345
+        let rounded_qty = rules.round_quantity(qty).unwrap();
+        assert_eq!(rounded_qty, Decimal::new(1234, 3)); // Should round to 1.234
+    }
+
+    #[test]
+    fn test_rounding_handles_extreme_tick_sizes() {
+        let big = Decimal::from_str("1000000000000000000000.12345678").unwrap();
+
+        // 1e21 / 1e-8 is past Decimal's range; must not overflow
+        let tiny = Decimal::new(1, 8);
+        assert_eq!(RoundingMode::Nearest.apply(big, tiny), big);
+        assert_eq!(
+            RoundingMode::Down.apply(big, Decimal::new(1, 6)),
+            Decimal::from_str("1000000000000000000000.123456").unwrap()
+        );
+        assert_eq!(
+            RoundingMode::Up.apply(big, Decimal::new(1, 6)),
+            Decimal::from_str("1000000000000000000000.123457").unwrap()
+        );
+        let precise = Decimal::from_str("12345678901234567.12345678951").unwrap();
+        assert_eq!(
+            RoundingMode::Nearest.apply(precise, Decimal::new(1, 9)),
+            Decimal::from_str("12345678901234567.123456790").unwrap()
+        );
+
+        // Large and non-power-of-ten ticks still snap to multiples
+        assert_eq!(
+            RoundingMode::Nearest.apply(big, Decimal::from(1000)),
+            Decimal::from_str("1000000000000000000000").unwrap()
+        );
+        let price = Decimal::from_str("123456789012345.37").unwrap();
+        assert_eq!(
+            RoundingMode::Down.apply(price, Decimal::new(25, 2)),
+            Decimal::from_str("123456789012345.25").unwrap()
+        );
+        assert_eq!(
+            RoundingMode::Up.apply(price, Decimal::from(50)),
+            Decimal::from_str("123456789012350").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let rules = SymbolRules {
+            base_asset: "BTC".to_string(),
+            quote_asset: "USDT".to_string(),
+            price_tick_size: Decimal::new(1, 1),
+            min_price: Decimal::from(100),
+            max_price: Decimal::from(100000),
+            quantity_step_size: Decimal::new(1, 3),
+            min_quantity: Decimal::new(1, 3),
+            max_quantity: Decimal::from(1000),
+            min_notional: Decimal::from(5),
+            max_leverage: None,
+        };
+
+        // 1.2347 floors to the step below in Down mode, rounds up in Nearest
+        let qty = Decimal::new(12347, 4);
+        assert_eq!(
+            rules.round_quantity_with(qty, RoundingMode::Down).unwrap(),
+            Decimal::new(1234, 3)
+        );
+        assert_eq!(
+            rules.round_quantity_with(qty, RoundingMode::Nearest).unwrap(),
+            Decimal::new(1235, 3)
+        );
+        assert_eq!(
+            rules.round_quantity_with(Decimal::new(12341, 4), RoundingMode::Up).unwrap(),
+            Decimal::new(1235, 3)
+        );
+
+        // Buy limit rounded down stays below the signal price
+        let price = Decimal::new(5000256, 2); // 50002.56
+        assert_eq!(
+            rules.round_price_with(price, RoundingMode::Down).unwrap(),
+            Decimal::new(500025, 1)
+        );
+
+        // Flooring below the minimum step is rejected instead of yielding zero
+        assert!(rules
+            .round_quantity_with(Decimal::new(15, 4), RoundingMode::Down)
+            .is_ok());
+        assert!(matches!(
+            rules.round_quantity_with(Decimal::new(9, 4), RoundingMode::Down),
+            Err(OrderValidationError::QuantityTooLow { .. })
+        ));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::config::HttpConfig;
+use reqwest::Client;
+use std::time::Duration;
+use tracing::warn;
+
+/// Build the shared REST client with connect/request timeouts and pooling.
+/// `reqwest::Client` is reference-counted, so clones share one connection pool.
+pub fn build_client(config: &HttpConfig) -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+        .timeout(Duration::from_millis(config.request_timeout_ms))
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .build()
+        .unwrap_or_else(|e| {
+            warn!("Failed to build HTTP client, using defaults: {}", e);
+            Client::new()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::ExchangeInfoManager;
+    use std::time::Instant;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_hanging_server_times_out() {
+        // Accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let config = HttpConfig {
+            request_timeout_ms: 200,
+            ..HttpConfig::default()
+        };
+        let mut manager =
+            ExchangeInfoManager::new(format!("http://{}", addr)).with_client(build_client(&config));
+
+        let started = Instant::now();
+        let result = manager.sync().await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
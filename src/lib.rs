@@ -1,11 +1,21 @@
 pub mod binance;
 pub mod config;
+pub mod control;
+pub mod diagnostics;
+pub mod digest;
 pub mod discord;
+pub mod exchange;
+pub mod http;
 pub mod market_data;
+pub mod market_sink;
+pub mod metrics;
 pub mod order_flow;
 pub mod range_bar;
 pub mod risk;
+pub mod session_alerts;
+pub mod signal_alert;
 pub mod simulator;
+pub mod statsd;
 pub mod strategy;
 pub mod types;
 pub mod volume_profile;
@@ -1,25 +1,41 @@
-use rusto::binance::{ExchangeInfoManager, TimeSyncChecker};
+use rusto::binance::{BinanceError, ExchangeInfoManager, TimeSyncChecker};
 use rusto::config::AppConfig;
+use rusto::control::{self, ControlState, HealthState};
 use rusto::discord::DiscordBot;
-use rusto::market_data::BinanceWebSocket;
+use rusto::diagnostics::{dump_recorder, install_panic_hook, EventRecorder};
+use rusto::digest::{next_digest_at, DailyDigest};
+use rusto::exchange::Exchange;
+use rusto::http;
+use rusto::market_data::{BinanceWebSocket, FeedExit, MarketRecorder, ReplaySource, TradeSideSource};
+use rusto::market_sink::MarketSink;
+use rusto::metrics::FeedMetrics;
 use rusto::order_flow::OrderFlowTracker;
 use rusto::range_bar::RangeBarBuilder;
 use rusto::risk::RiskManager;
+use rusto::session_alerts::SessionBreakTracker;
+use rusto::signal_alert;
 use rusto::simulator::trade_log::TradeLogger;
 use rusto::simulator::SimulatorEngine;
+use rusto::statsd::StatsdEmitter;
 use rusto::strategy::StrategyEngine;
-use rusto::types::{BotStats, ExecutionEvent, MarketEvent, ProcessingEvent};
+use rusto::types::{BotStats, ExecutionEvent, MarketEvent, ProcessingEvent, VolumeProfileSnapshot};
 use rusto::volume_profile::VolumeProfiler;
 use chrono::{Days, FixedOffset, Timelike};
 use std::sync::{Arc, Mutex};
 use tokio::sync::{broadcast, mpsc, watch};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables
     dotenvy::dotenv().ok();
 
+    // Emit the config JSON Schema (for editor/CI validation) and exit
+    if std::env::args().any(|arg| arg == "--print-schema") {
+        println!("{}", serde_json::to_string_pretty(&AppConfig::json_schema())?);
+        return Ok(());
+    }
+
     // Load config
     let config = AppConfig::load("config.toml").unwrap_or_else(|e| {
         eprintln!("Failed to load config: {}", e);
@@ -34,12 +50,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
 
     info!("Rusto - Order Flow Trading Bot starting...");
+    if !config.general.duplicate_symbols.is_empty() {
+        warn!(
+            duplicates = ?config.general.duplicate_symbols,
+            "Duplicate entries in general.symbols ignored"
+        );
+    }
     info!(
         "Config: auto_select_symbols={}, top_n_symbols={}, symbols={:?}",
         config.general.auto_select_symbols,
         config.general.top_n_symbols,
         config.general.symbols,
     );
+    if config.general.alert_mode {
+        info!("Mode: Alert-only (signals forwarded to notifications, no paper trading)");
+    }
     if config.general.auto_select_symbols {
         info!("Mode: Auto-select top {} symbols by volume", config.general.top_n_symbols);
     } else {
@@ -49,46 +74,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // === Binance Pre-flight Checks ===
     info!("Running Binance pre-flight checks...");
 
+    // Shared REST client (timeouts + pooled connections)
+    let http_client = http::build_client(&config.http);
+
     // 1. Time synchronization check
     let time_checker = TimeSyncChecker::new(
         config.binance.api_url.clone(),
         config.binance.max_time_offset_ms,
         config.binance.max_latency_ms,
         config.binance.ping_samples,
-    );
+    )
+    .with_client(http_client.clone());
 
-    let network_stats = match time_checker.check().await {
-        Ok(stats) => {
-            info!(
-                "✓ Time sync OK: offset={}ms, latency={:.2}ms (max: {:.2}ms)",
-                stats.time_offset_ms, stats.avg_latency_ms, stats.max_latency_ms
-            );
-            stats
-        }
-        Err(e) => {
-            error!("✗ Time sync failed: {}", e);
-            eprintln!("\n❌ Time synchronization check failed!");
-            eprintln!("   {}", e);
-            eprintln!("\n   Please ensure:");
-            eprintln!("   1. Your system clock is synchronized (use NTP)");
-            eprintln!("   2. Your network connection to Binance is stable");
-            eprintln!("   3. Check your system time with: date");
-            std::process::exit(1);
+    let mut attempt = 0;
+    let network_stats = loop {
+        match time_checker.check().await {
+            Ok(stats) => {
+                info!(
+                    "✓ Time sync OK: offset={}ms, latency={:.2}ms (max: {:.2}ms)",
+                    stats.time_offset_ms, stats.avg_latency_ms, stats.max_latency_ms
+                );
+                break stats;
+            }
+            Err(e) => {
+                if let Some(delay) = startup_retry_delay(&e, attempt) {
+                    warn!(attempt = attempt + 1, delay_secs = delay.as_secs(), "Time sync failed, retrying: {}", e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+                error!("✗ Time sync failed: {}", e);
+                eprintln!("\n❌ Time synchronization check failed!");
+                eprintln!("   {}", e);
+                eprintln!("\n   Please ensure:");
+                eprintln!("   1. Your system clock is synchronized (use NTP)");
+                eprintln!("   2. Your network connection to Binance is stable");
+                eprintln!("   3. Check your system time with: date");
+                std::process::exit(1);
+            }
         }
     };
 
     // 2. Exchange info sync (symbol filters)
-    let mut exchange_info = ExchangeInfoManager::new(config.binance.api_url.clone());
-
-    match exchange_info.sync().await {
-        Ok(_) => {
-            info!("✓ Exchange info synced: {} symbols loaded", exchange_info.symbols().len());
-        }
-        Err(e) => {
-            error!("✗ Exchange info sync failed: {}", e);
-            eprintln!("\n❌ Failed to fetch exchange information from Binance!");
-            eprintln!("   {}", e);
-            std::process::exit(1);
+    let mut exchange_info = ExchangeInfoManager::new(config.binance.api_url.clone())
+        .with_client(http_client.clone());
+    exchange_info.set_symbol_overrides(
+        &config.general.pinned_symbols,
+        &config.general.blacklisted_symbols,
+    );
+    exchange_info.set_lenient_filters(config.binance.lenient_filter_parsing);
+
+    let mut attempt = 0;
+    loop {
+        match exchange_info.sync().await {
+            Ok(_) => {
+                info!("✓ Exchange info synced: {} symbols loaded", exchange_info.symbols().len());
+                break;
+            }
+            Err(e) => {
+                if let Some(delay) = startup_retry_delay(&e, attempt) {
+                    warn!(attempt = attempt + 1, delay_secs = delay.as_secs(), "Exchange info sync failed, retrying: {}", e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+                error!("✗ Exchange info sync failed: {}", e);
+                eprintln!("\n❌ Failed to fetch exchange information from Binance!");
+                eprintln!("   {}", e);
+                std::process::exit(1);
+            }
         }
     }
 
@@ -96,14 +150,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // symbol_prices: map of symbol → last price (used for dynamic range calculation)
     let (symbols, symbol_prices): (Vec<String>, std::collections::HashMap<String, rust_decimal::Decimal>) =
         if config.general.auto_select_symbols {
-            let top_n = 10usize;
-            if config.general.top_n_symbols != top_n {
-                warn!(
-                    configured = config.general.top_n_symbols,
-                    forced = top_n,
-                    "Auto-select is forced to top 10 symbols for futures strategy"
-                );
-            }
+            let top_n = config.general.top_n_symbols;
 
             let kst = FixedOffset::east_opt(9 * 3600)
                 .unwrap_or_else(|| FixedOffset::east_opt(0).expect("UTC offset should be valid"));
@@ -113,7 +160,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "Selecting Binance Futures top symbols (KST snapshot)"
             );
 
-            match exchange_info.fetch_top_symbols(top_n).await {
+            let mut attempt = 0;
+            let top_result = loop {
+                match exchange_info.fetch_top_symbols(top_n).await {
+                    Err(e) => {
+                        if let Some(delay) = startup_retry_delay(&e, attempt) {
+                            warn!(
+                                attempt = attempt + 1,
+                                delay_secs = delay.as_secs(),
+                                "Auto symbol selection failed, retrying: {}",
+                                e
+                            );
+                            tokio::time::sleep(delay).await;
+                            attempt += 1;
+                            continue;
+                        }
+                        break Err(e);
+                    }
+                    result => break result,
+                }
+            };
+
+            match top_result {
                 Ok(top) if top.len() >= top_n => {
                     let syms: Vec<String> = top.iter().map(|(s, _)| s.clone()).collect();
                     let prices: std::collections::HashMap<String, rust_decimal::Decimal> =
@@ -148,7 +216,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         } else {
-            (config.general.symbols.clone(), std::collections::HashMap::new())
+            let symbols = config
+                .general
+                .symbols
+                .iter()
+                .map(|symbol| exchange_info.normalize_symbol(symbol))
+                .collect();
+            (symbols, std::collections::HashMap::new())
         };
 
     // Validate all symbols against exchange info
@@ -157,9 +231,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Some(info) => {
                 info!(
                     symbol = %symbol,
-                    tick_size = %info.price_tick_size,
-                    step_size = %info.quantity_step_size,
-                    min_notional = %info.min_notional,
+                    tick_size = %info.rules.price_tick_size,
+                    step_size = %info.rules.quantity_step_size,
+                    min_notional = %info.rules.min_notional,
                     "✓ Symbol validated"
                 );
             }
@@ -182,8 +256,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (execution_tx, execution_rx) = mpsc::channel::<ExecutionEvent>(1_000);
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
+    // Stream/sync status for the readiness probe (pre-flight checks passed above)
+    let health = HealthState::shared(symbols.clone(), config.binance.max_time_offset_ms);
+    if let Ok(mut h) = health.lock() {
+        h.set_exchange_synced(true);
+        h.set_time_offset(network_stats.time_offset_ms);
+        // Replay has no live connection to lose
+        h.set_ws_connected(config.replay.enabled);
+    }
+
+    // Per-symbol trade/depth message counters (logged periodically, served on /metrics)
+    let feed_metrics = FeedMetrics::shared(&symbols, chrono::Utc::now());
+    if config.logging.feed_metrics_interval_secs > 0 {
+        let metrics = feed_metrics.clone();
+        let interval_secs = config.logging.feed_metrics_interval_secs;
+        let mut shutdown = shutdown_rx.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if let Ok(mut m) = metrics.lock() {
+                            for (symbol, rates) in m.roll(chrono::Utc::now()) {
+                                info!(
+                                    symbol = %symbol,
+                                    trades_per_sec = format!("{:.2}", rates.trades_per_sec),
+                                    depth_per_sec = format!("{:.2}", rates.depth_per_sec),
+                                    "Feed rates"
+                                );
+                            }
+                        }
+                    }
+                    _ = shutdown.changed() => {
+                        if *shutdown.borrow() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     // Market data feed
-    let ws = BinanceWebSocket::new(symbols.clone(), market_tx.clone());
+    let mut ws = BinanceWebSocket::new(symbols.clone(), market_tx.clone());
+    let book_ticker = config.binance.book_stream.eq_ignore_ascii_case("bookticker");
+    ws.set_book_ticker_mode(book_ticker);
+    ws.set_stream_url(exchange_info.ws_url(&symbols, book_ticker));
+    ws.set_reconnect_policy(
+        std::time::Duration::from_millis(config.binance.reconnect_delay_ms),
+        config.binance.max_reconnect_failures,
+        config.binance.exit_on_reconnect_failure,
+        Some(execution_tx.clone()),
+    );
+    ws.set_health(health.clone());
+    ws.set_min_trade_quantity(
+        rust_decimal::Decimal::try_from(config.binance.min_trade_quantity).unwrap_or_default(),
+    );
+    ws.set_max_trade_deviation_pct(
+        rust_decimal::Decimal::try_from(config.binance.max_trade_deviation_pct).unwrap_or_default(),
+    );
+    ws.set_trade_side_source(
+        TradeSideSource::parse(&config.binance.trade_side_source).unwrap_or_default(),
+    );
     let ws_shutdown = shutdown_rx.clone();
 
     // Processing components
@@ -200,18 +335,170 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 info!(symbol = %symbol, range = %range, price = %price, "Range bar size set");
             }
             // Per-symbol VP tick size = exchange tick_size × multiplier
-            let vp_tick = sym_info.price_tick_size * rust_decimal::Decimal::from(config.volume_profile.tick_multiplier);
+            let vp_tick = sym_info.rules.price_tick_size * rust_decimal::Decimal::from(config.volume_profile.tick_multiplier);
             volume_profiler.set_tick_size(symbol, vp_tick);
             info!(symbol = %symbol, vp_tick = %vp_tick, "Volume profile tick size set");
         }
     }
     let mut order_flow_tracker = OrderFlowTracker::new(&config.order_flow);
+    let trade_logger = TradeLogger::new(
+        config.logging.trades_csv_path.clone(),
+        config.logging.trades_json_path.clone(),
+        config.logging.trades_db_path.clone(),
+    )
+    .with_summary_json(config.logging.summary_json_stdout)
+    .with_hold_time_buckets(config.logging.hold_time_buckets_secs.clone())
+    .with_busy_timeout(config.logging.db_busy_timeout_ms)
+    .with_background_writer(config.logging.trade_log_batch_size);
     let mut strategy_engine =
         StrategyEngine::new(
             config.strategy.clone(),
             config.risk.clone(),
             Some(config.logging.trades_db_path.clone()),
         );
+    strategy_engine.configure_tuning_db(config.logging.db_busy_timeout_ms, config.logging.keep_tuning_db_open);
+    strategy_engine.set_deterministic_ids(config.general.deterministic_ids);
+    if !config.logging.tuning_csv_path.is_empty() {
+        strategy_engine.set_tuning_csv_path(Some(config.logging.tuning_csv_path.clone()));
+    }
+    if config.strategy.advanced_restore_tuned_ratios {
+        let restored = strategy_engine.restore_tuned_ratios(&symbols);
+        info!(restored = restored, selected = symbols.len(), "Tuned volume burst ratios restored");
+    }
+
+    // Diagnostics ring buffer: dumped on panic or SIGUSR1
+    let recorder = if config.diagnostics.ring_buffer_size > 0 {
+        let recorder = EventRecorder::shared(config.diagnostics.ring_buffer_size);
+        install_panic_hook(recorder.clone(), config.diagnostics.dump_path.clone());
+        #[cfg(unix)]
+        {
+            let recorder = recorder.clone();
+            let dump_path = config.diagnostics.dump_path.clone();
+            tokio::spawn(async move {
+                use tokio::signal::unix::{signal, SignalKind};
+                let Ok(mut usr1) = signal(SignalKind::user_defined1()) else {
+                    warn!("Could not register SIGUSR1 handler for diagnostics dumps");
+                    return;
+                };
+                while usr1.recv().await.is_some() {
+                    dump_recorder(&recorder, &dump_path);
+                }
+            });
+        }
+        info!(
+            size = config.diagnostics.ring_buffer_size,
+            path = %config.diagnostics.dump_path,
+            "Diagnostics ring buffer enabled"
+        );
+        Some(recorder)
+    } else {
+        None
+    };
+    // Bar/flow/profile time-series sink for offline analysis
+    let mut market_sink = if config.logging.market_sink.eq_ignore_ascii_case("off") {
+        None
+    } else {
+        match MarketSink::open(&config.logging.market_sink, &config.logging.market_sink_path) {
+            Ok(sink) => {
+                info!(
+                    format = %config.logging.market_sink,
+                    path = %config.logging.market_sink_path,
+                    "Market context sink enabled"
+                );
+                Some(sink)
+            }
+            Err(e) => {
+                error!("✗ Failed to open market sink: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+    // Trade-driven profiles are written once per bar (the latest per symbol), not per trade
+    let mut unsaved_profiles: std::collections::HashMap<String, VolumeProfileSnapshot> =
+        std::collections::HashMap::new();
+
+    // Session high/low and prior value-area break alerts (context only, no trading)
+    let mut session_breaks = config
+        .general
+        .session_break_alerts
+        .then(|| SessionBreakTracker::new(config.general.session_break_rearm_pct));
+    let session_break_tx = execution_tx.clone();
+
+    let processing_recorder = recorder.clone();
+    let processing_health = health.clone();
+    let processing_metrics = feed_metrics.clone();
+
+    // Control API (health/readiness probes, on-demand diagnostics dump)
+    if config.control.enabled {
+        let listener = match tokio::net::TcpListener::bind(&config.control.bind_addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("✗ Failed to bind control API on {}: {}", config.control.bind_addr, e);
+                std::process::exit(1);
+            }
+        };
+        let state = ControlState {
+            health: health.clone(),
+            stale_after: chrono::Duration::seconds(config.control.stale_trade_secs as i64),
+            recorder: recorder
+                .clone()
+                .map(|r| (r, config.diagnostics.dump_path.clone())),
+            metrics: feed_metrics.clone(),
+        };
+        let control_shutdown = shutdown_rx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = control::serve(listener, state, control_shutdown).await {
+                error!("Control API stopped: {}", e);
+            }
+        });
+
+        // Dead-man's switch: armed now, so the operator has one timeout to start heartbeating
+        if config.control.heartbeat_timeout_secs > 0 {
+            if let Ok(mut h) = health.lock() {
+                h.record_heartbeat(chrono::Utc::now());
+            }
+            let timeout = chrono::Duration::seconds(config.control.heartbeat_timeout_secs as i64);
+            info!(timeout_secs = config.control.heartbeat_timeout_secs, "Dead-man's switch armed");
+            tokio::spawn(control::watch_heartbeat(
+                health.clone(),
+                timeout,
+                processing_tx.clone(),
+                shutdown_rx.clone(),
+            ));
+        }
+    }
+
+    // Periodic RTT sampling feeds the simulator's entry latency gate
+    if config.binance.latency_sample_interval_secs > 0 {
+        let latency_checker = time_checker.clone();
+        let latency_tx = processing_tx.clone();
+        let mut latency_shutdown = shutdown_rx.clone();
+        let period = std::time::Duration::from_secs(config.binance.latency_sample_interval_secs);
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                tokio::select! {
+                    _ = timer.tick() => {
+                        match latency_checker.sample_latency().await {
+                            Ok(rtt_ms) => {
+                                debug!(rtt_ms = rtt_ms, "Latency sample");
+                                let _ = latency_tx.send(ProcessingEvent::LatencySample { rtt_ms: Some(rtt_ms) }).await;
+                            }
+                            Err(e) => {
+                                warn!("Latency sample failed: {}", e);
+                                let _ = latency_tx.send(ProcessingEvent::LatencySample { rtt_ms: None }).await;
+                            }
+                        }
+                    }
+                    _ = latency_shutdown.changed() => {
+                        if *latency_shutdown.borrow() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    }
 
     let mut market_rx_processing = market_tx.subscribe();
     let processing_shutdown = shutdown_rx.clone();
@@ -221,26 +508,79 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let leverage = rust_decimal::Decimal::try_from(config.simulator.leverage)
         .unwrap_or(rust_decimal::Decimal::from(100));
     let risk_manager = RiskManager::new(&config.risk, leverage);
-    let trade_logger = TradeLogger::new(
-        config.logging.trades_csv_path.clone(),
-        config.logging.trades_json_path.clone(),
-        config.logging.trades_db_path.clone(),
-    );
     let mut simulator = SimulatorEngine::new(config.simulator.clone(), risk_manager, trade_logger);
     simulator.set_execution_channel(execution_tx.clone());
     simulator.set_exchange_info(exchange_info.clone());
+    if !config.logging.book_snapshot_sink.eq_ignore_ascii_case("off") {
+        match MarketSink::open(&config.logging.book_snapshot_sink, &config.logging.book_snapshot_path) {
+            Ok(sink) => {
+                info!(
+                    format = %config.logging.book_snapshot_sink,
+                    path = %config.logging.book_snapshot_path,
+                    interval_ms = config.logging.book_snapshot_interval_ms,
+                    depth = config.logging.book_snapshot_depth,
+                    "Order book snapshot sink enabled"
+                );
+                simulator.set_book_sink(
+                    sink,
+                    config.logging.book_snapshot_interval_ms,
+                    config.logging.book_snapshot_depth,
+                );
+            }
+            Err(e) => {
+                error!("✗ Failed to open book snapshot sink: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if config.statsd.enabled {
+        match StatsdEmitter::connect(&config.statsd.host, &config.statsd.prefix, config.statsd.tags) {
+            Ok(emitter) => {
+                info!(host = %config.statsd.host, prefix = %config.statsd.prefix, "statsd telemetry enabled");
+                simulator.set_statsd(emitter);
+            }
+            Err(e) => {
+                error!("✗ Failed to open statsd socket to {}: {}", config.statsd.host, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    simulator.set_deterministic_ids(config.general.deterministic_ids);
 
     // Shared state between simulator and hourly reporter
     let bot_stats = Arc::new(Mutex::new(BotStats::default()));
     simulator.set_bot_stats(bot_stats.clone());
+    simulator.set_daily_digest(config.discord.daily_digest);
+    simulator.set_exposure_breakdown(config.discord.exposure_breakdown);
+    simulator.set_base_asset_categories(&config.discord.base_asset_categories);
     let market_rx_simulator = market_tx.subscribe();
     let sim_shutdown = shutdown_rx.clone();
 
+    // Full market event recording (replayable via [replay])
+    let market_recorder_handle = if config.recording.enabled {
+        match MarketRecorder::open(&config.recording.path) {
+            Ok(market_recorder) => {
+                info!(path = %config.recording.path, "Recording market events");
+                Some(tokio::spawn(
+                    market_recorder.run(market_tx.subscribe(), shutdown_rx.clone()),
+                ))
+            }
+            Err(e) => {
+                error!("Market recording disabled: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     // Discord bot (optional)
     let discord_handle = if config.discord.enabled {
         match config.discord.webhook_url() {
             Ok(webhook_url) => {
-                let discord_bot = DiscordBot::new(webhook_url);
+                let discord_bot = DiscordBot::new(webhook_url)
+                    .with_client(http_client.clone())
+                    .with_stop_move_alerts(config.discord.stop_move_alerts);
                 let discord_shutdown = shutdown_rx.clone();
                 info!("Discord notifications enabled");
 
@@ -268,11 +608,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let hourly_stats = bot_stats.clone();
     let hourly_ping_url = format!("{}/fapi/v1/ping", config.binance.api_url);
     let hourly_shutdown = shutdown_rx.clone();
+    let hourly_client = http_client.clone();
+    let hourly_exposure_breakdown = config.discord.exposure_breakdown;
     let hourly_handle = tokio::spawn(async move {
-        let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(5))
-            .build()
-            .unwrap_or_default();
 
         // Wait until the next whole-hour boundary (:00)
         let now = chrono::Utc::now();
@@ -291,7 +629,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // Ping Binance with timeout
                     let ping_ms = {
                         let t = std::time::Instant::now();
-                        match http_client.get(&hourly_ping_url).send().await {
+                        match hourly_client
+                            .get(&hourly_ping_url)
+                            .timeout(std::time::Duration::from_secs(5))
+                            .send()
+                            .await
+                        {
                             Ok(_) => t.elapsed().as_secs_f64() * 1000.0,
                             Err(e) => {
                                 warn!("Hourly ping failed: {}", e);
@@ -304,6 +647,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         let s = hourly_stats.lock().unwrap();
                         (s.balance, s.daily_pnl, s.open_positions, s.total_trades, s.symbol_stats.clone())
                     };
+                    let (exposure_by_quote, exposure_by_category) = if hourly_exposure_breakdown {
+                        let s = hourly_stats.lock().unwrap();
+                        (s.exposure_by_quote.clone(), s.exposure_by_category.clone())
+                    } else {
+                        Default::default()
+                    };
+                    for (quote, e) in &exposure_by_quote {
+                        info!(
+                            quote = %quote,
+                            open_positions = e.open_positions,
+                            open_notional = %e.open_notional,
+                            unrealized_pnl = %e.unrealized_pnl,
+                            realized_pnl = %e.realized_pnl,
+                            "Hourly exposure by quote asset"
+                        );
+                    }
 
                     info!(
                         balance = %balance,
@@ -321,6 +680,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         ping_ms,
                         total_trades,
                         symbol_stats,
+                        exposure_by_quote,
+                        exposure_by_category,
                     }).await;
                 }
                 _ = shutdown.changed() => {
@@ -333,13 +694,77 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    // Spawn WebSocket task
-    let ws_handle = tokio::spawn(async move {
-        ws.run(ws_shutdown).await;
-    });
+    // Spawn daily digest task: drains the day's closes at the configured local time
+    let digest_handle = if config.discord.daily_digest {
+        let digest_time = config.discord.daily_digest_time().unwrap_or_default();
+        let digest_offset = config.discord.daily_digest_utc_offset_hours;
+        let digest_execution_tx = execution_tx.clone();
+        let digest_stats = bot_stats.clone();
+        let mut digest_shutdown = shutdown_rx.clone();
+        Some(tokio::spawn(async move {
+            let mut period_start = chrono::Utc::now();
+            loop {
+                let due = next_digest_at(chrono::Utc::now(), digest_time, digest_offset);
+                info!(next = %due, "Daily digest scheduled");
+                let wait = (due - chrono::Utc::now()).to_std().unwrap_or_default();
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {
+                        let (closes, balance) = {
+                            let mut s = digest_stats.lock().unwrap();
+                            (std::mem::take(&mut s.period_closes), s.balance)
+                        };
+                        let digest = DailyDigest::from_closes(&closes, balance, period_start, due);
+                        info!(trades = digest.trades, pnl = %digest.pnl, "Daily digest");
+                        let _ = digest_execution_tx.send(ExecutionEvent::DailyDigest(digest)).await;
+                        period_start = due;
+                    }
+                    _ = digest_shutdown.changed() => {
+                        if *digest_shutdown.borrow() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }))
+    } else {
+        None
+    };
+
+    // Spawn market data task: live WebSocket, or recorded events in replay mode.
+    // Resolves to true when the live feed gave up after its reconnect limit.
+    let ws_handle = if config.replay.enabled {
+        let replay = ReplaySource::new(&config.replay, market_tx.clone());
+        let replay_shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            match replay.run(ws_shutdown).await {
+                Ok(stats) => info!(
+                    sent = stats.events_sent,
+                    skipped = stats.events_skipped,
+                    "Replay complete; shutting down"
+                ),
+                Err(e) => error!("Replay failed: {}", e),
+            }
+            let _ = replay_shutdown_tx.send(true);
+            false
+        })
+    } else {
+        let feed_shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            let feed_down = ws.run(ws_shutdown).await == FeedExit::ReconnectLimit;
+            if feed_down {
+                error!("Market data feed down: shutting down for supervisor restart");
+                let _ = feed_shutdown_tx.send(true);
+            }
+            feed_down
+        })
+    };
 
-    // Spawn KST 09:00 reselection task (graceful shutdown so supervisor can restart with new top-10)
+    // Spawn KST 09:00 reselection task (graceful shutdown so supervisor can restart with new top-N)
     let reselection_exchange_info = exchange_info.clone();
+    let reselection_top_n = config.general.top_n_symbols;
+    let reselection_alert_tx = (config.general.auto_select_symbols && config.general.reselection_alerts)
+        .then(|| execution_tx.clone());
+    let reselection_current = symbols.clone();
     let reselection_shutdown_tx = shutdown_tx.clone();
     let reselection_shutdown = shutdown_rx.clone();
     let reselection_handle = tokio::spawn(async move {
@@ -369,12 +794,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             tokio::select! {
                 _ = tokio::time::sleep(tokio::time::Duration::from_secs(wait_secs)) => {
-                    match reselection_exchange_info.fetch_top_symbols(10).await {
+                    match reselection_exchange_info.fetch_top_symbols(reselection_top_n).await {
                         Ok(top) => {
                             info!(
                                 symbols = ?top.iter().map(|(s, _)| s.as_str()).collect::<Vec<_>>(),
                                 "KST 09:00 symbol reselection complete; triggering graceful restart to apply"
                             );
+                            let selected: Vec<String> = top.into_iter().map(|(s, _)| s).collect();
+                            if let (Some(tx), Some(event)) = (
+                                &reselection_alert_tx,
+                                ExecutionEvent::symbols_reselected(&reselection_current, &selected),
+                            ) {
+                                let _ = tx.send(event).await;
+                            }
                         }
                         Err(e) => {
                             warn!("KST 09:00 symbol reselection failed: {}", e);
@@ -400,44 +832,108 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         loop {
             tokio::select! {
                 Ok(event) = market_rx_processing.recv() => {
+                    if let Some(rec) = &processing_recorder {
+                        if let Ok(mut rec) = rec.lock() {
+                            rec.record_market(&event);
+                        }
+                    }
+                    if let Ok(mut m) = processing_metrics.lock() {
+                        m.record(&event);
+                    }
+                    let mut bars = Vec::new();
                     match event {
                         MarketEvent::Trade(ref trade) => {
+                            if let Ok(mut h) = processing_health.lock() {
+                                h.record_trade(&trade.symbol, chrono::Utc::now());
+                            }
+
+                            if let Some(tracker) = session_breaks.as_mut() {
+                                for alert in tracker.on_trade(&trade.symbol, trade.price, trade.timestamp) {
+                                    if let Err(e) = session_break_tx.try_send(alert) {
+                                        warn!("Failed to send session break alert: {}", e);
+                                    }
+                                }
+                            }
+
                             // 1. Update volume profile
-                            if let Some(vp) = volume_profiler.process_trade(trade) {
-                                strategy_engine.update_profile(vp.clone());
-                                let _ = processing_tx_clone.send(ProcessingEvent::VolumeProfile(vp)).await;
+                            if !volume_profiler.uses_footprints() {
+                                if let Some(vp) = volume_profiler.process_trade(trade) {
+                                    if let Some(tracker) = session_breaks.as_mut() {
+                                        tracker.on_profile(&vp);
+                                    }
+                                    if market_sink.is_some() {
+                                        unsaved_profiles.insert(vp.symbol.clone(), vp.clone());
+                                    }
+                                    strategy_engine.update_profile(vp.clone());
+                                    let _ = processing_tx_clone.send(ProcessingEvent::VolumeProfile(vp)).await;
+                                }
                             }
 
                             // 2. Build range bars
-                            if let Some(bar) = range_bar_builder.process_trade(trade) {
-                                // 3. Analyze order flow
-                                let flow = order_flow_tracker.analyze_bar(&bar);
-                                strategy_engine.update_flow(flow.clone());
-                                let _ = processing_tx_clone.send(ProcessingEvent::OrderFlow(flow)).await;
-
-                                // 4. Generate signals
-                                let signals = strategy_engine.process_bar(&bar);
-                                let _ = processing_tx_clone.send(ProcessingEvent::NewBar(bar)).await;
-
-                                for signal in signals {
-                                    info!(
-                                        symbol = %signal.symbol,
-                                        setup = %signal.setup,
-                                        side = ?signal.side,
-                                        entry = %signal.entry_price,
-                                        "Signal generated"
-                                    );
-                                    let _ = processing_tx_clone.send(ProcessingEvent::Signal(signal)).await;
+                            bars.extend(range_bar_builder.process_trade(trade));
+                        }
+                        MarketEvent::Reconnected { .. } => {
+                            // Bars spanning the gap are closed or dropped per range_bar.on_reconnect
+                            bars = range_bar_builder.on_reconnect();
+                        }
+                        MarketEvent::Depth(_) | MarketEvent::BookTicker(_) => {
+                            // Book updates handled by simulator directly
+                        }
+                    }
+
+                    for bar in bars {
+                        // Footprint mode: profile shares the bar's volume source
+                        if volume_profiler.uses_footprints() {
+                            if let Some(vp) = volume_profiler.process_bar(&bar) {
+                                if let Some(tracker) = session_breaks.as_mut() {
+                                    tracker.on_profile(&vp);
+                                }
+                                if let Some(sink) = market_sink.as_mut() {
+                                    sink.record_profile(&vp);
                                 }
+                                strategy_engine.update_profile(vp.clone());
+                                let _ = processing_tx_clone.send(ProcessingEvent::VolumeProfile(vp)).await;
                             }
                         }
-                        MarketEvent::Depth(_) => {
-                            // Depth handled by simulator directly
+
+                        // 3. Analyze order flow
+                        let flow = order_flow_tracker.analyze_bar(&bar);
+                        if let Some(sink) = market_sink.as_mut() {
+                            if let Some(vp) = unsaved_profiles.remove(&bar.symbol) {
+                                sink.record_profile(&vp);
+                            }
+                            sink.record_flow(bar.bar_index, &flow);
+                            sink.record_bar(&bar);
+                        }
+                        strategy_engine.update_flow(flow.clone());
+                        let _ = processing_tx_clone.send(ProcessingEvent::OrderFlow(flow)).await;
+
+                        // 4. Generate signals
+                        let signals = strategy_engine.process_bar(&bar);
+                        let _ = processing_tx_clone.send(ProcessingEvent::NewBar(bar)).await;
+
+                        for signal in signals {
+                            info!(
+                                symbol = %signal.symbol,
+                                setup = %signal.setup,
+                                side = ?signal.side,
+                                entry = %signal.entry_price,
+                                "Signal generated"
+                            );
+                            if let Some(rec) = &processing_recorder {
+                                if let Ok(mut rec) = rec.lock() {
+                                    rec.record_signal(&signal);
+                                }
+                            }
+                            let _ = processing_tx_clone.send(ProcessingEvent::Signal(signal)).await;
                         }
                     }
                 }
                 _ = shutdown.changed() => {
                     if *shutdown.borrow() {
+                        if let Some(sink) = market_sink.as_mut() {
+                            sink.flush();
+                        }
                         info!("Processing pipeline shutting down");
                         return;
                     }
@@ -446,39 +942,76 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    // Spawn simulator task
-    let sim_handle = tokio::spawn(async move {
-        simulator
-            .run(processing_rx, market_rx_simulator, sim_shutdown)
-            .await;
-    });
+    // Spawn simulator task (or the signal forwarder in alert mode)
+    let sim_handle = if config.general.alert_mode {
+        let alert_tx = execution_tx.clone();
+        drop(market_rx_simulator);
+        tokio::spawn(async move {
+            signal_alert::forward_signals(processing_rx, alert_tx, sim_shutdown).await;
+        })
+    } else {
+        tokio::spawn(async move {
+            simulator
+                .run(processing_rx, market_rx_simulator, sim_shutdown)
+                .await;
+        })
+    };
 
-    // Wait for Ctrl+C
+    // Wait for Ctrl+C (or an internal shutdown, e.g. end of replay)
     info!("Bot running. Press Ctrl+C to stop.");
-    tokio::signal::ctrl_c().await?;
+    let mut main_shutdown = shutdown_rx.clone();
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result?,
+        _ = main_shutdown.wait_for(|stop| *stop) => {}
+    }
     info!("Shutdown signal received...");
     let _ = shutdown_tx.send(true);
 
     // Wait for all tasks to complete
-    if let Some(discord_handle) = discord_handle {
-        let _ = tokio::join!(
+    let feed_result = if let Some(discord_handle) = discord_handle {
+        tokio::join!(
             ws_handle,
             processing_handle,
             sim_handle,
             discord_handle,
             hourly_handle,
             reselection_handle
-        );
+        )
+        .0
     } else {
-        let _ = tokio::join!(
+        tokio::join!(
             ws_handle,
             processing_handle,
             sim_handle,
             hourly_handle,
             reselection_handle
-        );
+        )
+        .0
+    };
+    if let Some(digest_handle) = digest_handle {
+        let _ = digest_handle.await;
+    }
+    if let Some(market_recorder_handle) = market_recorder_handle {
+        let _ = market_recorder_handle.await;
+    }
+
+    // A non-zero status lets a Restart=on-failure supervisor bring the feed back
+    if matches!(feed_result, Ok(true)) {
+        error!("Rusto shut down after losing the market data feed.");
+        std::process::exit(1);
     }
 
     info!("Rusto shut down cleanly.");
     Ok(())
 }
+
+/// Maximum retries for startup REST calls before giving up
+const STARTUP_MAX_RETRIES: u32 = 5;
+
+/// Backoff for a failed startup call, or None to abort (non-retryable or out of attempts)
+fn startup_retry_delay(err: &BinanceError, attempt: u32) -> Option<std::time::Duration> {
+    if attempt >= STARTUP_MAX_RETRIES {
+        return None;
+    }
+    err.retry_delay(attempt)
+}
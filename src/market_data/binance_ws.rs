@@ -1,41 +1,320 @@
-use crate::market_data::types::{BinanceAggTrade, BinanceCombinedStream, BinanceDepthUpdate};
-use crate::types::{DepthLevel, DepthUpdate, MarketEvent, NormalizedTrade, Side};
+use crate::market_data::types::{
+    BinanceAggTrade, BinanceBookTicker, BinanceCombinedStream, BinanceDepthUpdate,
+};
+use crate::control::SharedHealth;
+use crate::types::{
+    BookTicker, DepthLevel, DepthUpdate, ExecutionEvent, MarketEvent, NormalizedTrade, Side,
+};
 use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
 use rust_decimal::Decimal;
+use std::collections::{BTreeMap, VecDeque};
 use std::str::FromStr;
-use tokio::sync::broadcast;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::{broadcast, mpsc};
 use tokio_tungstenite::connect_async;
 use tracing::{error, info, warn};
 
 const BINANCE_FUTURES_WS: &str = "wss://fstream.binance.com/stream?streams=";
 
+/// Accepted prices per symbol the price band's median is taken over
+const PRICE_BAND_WINDOW: usize = 21;
+/// Consecutive out-of-band prints that are taken as a genuine gap and re-anchor the band
+const PRICE_BAND_REANCHOR_STREAK: u32 = 5;
+
+/// Why `BinanceWebSocket::run` returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedExit {
+    Shutdown,
+    /// Consecutive failed connects (or sessions with no data) hit the limit with exit enabled
+    ReconnectLimit,
+}
+
+/// Recent accepted prices for one symbol
+#[derive(Default)]
+struct PriceBand {
+    prices: VecDeque<Decimal>,
+    rejected_streak: u32,
+}
+
+impl PriceBand {
+    fn median(&self) -> Option<Decimal> {
+        let mut sorted: Vec<Decimal> = self.prices.iter().copied().collect();
+        sorted.sort();
+        sorted.get(sorted.len() / 2).copied()
+    }
+
+    fn accept(&mut self, price: Decimal) {
+        self.rejected_streak = 0;
+        self.prices.push_back(price);
+        if self.prices.len() > PRICE_BAND_WINDOW {
+            self.prices.pop_front();
+        }
+    }
+}
+
+/// Where a trade's aggressor side comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TradeSideSource {
+    /// Binance's `is_buyer_maker` flag
+    #[default]
+    Maker,
+    /// Tick rule: uptick = buy, downtick = sell, zero tick repeats the last direction
+    TickRule,
+    /// Maker flag, with the tick rule run alongside and disagreements counted
+    Compare,
+}
+
+impl TradeSideSource {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.to_lowercase().as_str() {
+            "maker" => Some(Self::Maker),
+            "tickrule" => Some(Self::TickRule),
+            "compare" => Some(Self::Compare),
+            _ => None,
+        }
+    }
+}
+
+/// Last price and tick direction for one symbol
+#[derive(Default)]
+struct TickState {
+    last_price: Option<Decimal>,
+    last_side: Option<Side>,
+}
+
+impl TickState {
+    /// Tick-rule side of a trade at `price` (None until a first price change is seen)
+    fn classify(&mut self, price: Decimal) -> Option<Side> {
+        if let Some(last) = self.last_price {
+            if price > last {
+                self.last_side = Some(Side::Buy);
+            } else if price < last {
+                self.last_side = Some(Side::Sell);
+            }
+        }
+        self.last_price = Some(price);
+        self.last_side
+    }
+}
+
 pub struct BinanceWebSocket {
     symbols: Vec<String>,
     tx: broadcast::Sender<MarketEvent>,
+    /// Subscribe to @bookTicker instead of the full @depth stream
+    book_ticker: bool,
+    /// Connection status reported to the readiness probe
+    health: Option<SharedHealth>,
+    /// Trades at or below this quantity are dropped as dust
+    min_trade_quantity: Decimal,
+    dust_trades: AtomicU64,
+    /// Max fractional distance from the recent median price (None = no price band)
+    max_trade_deviation: Option<Decimal>,
+    price_bands: Mutex<BTreeMap<String, PriceBand>>,
+    bad_prints: AtomicU64,
+    trade_side_source: TradeSideSource,
+    tick_states: Mutex<BTreeMap<String, TickState>>,
+    /// Trades classified by both the maker flag and the tick rule
+    side_checks: AtomicU64,
+    side_disagreements: AtomicU64,
+    /// Full stream URL override (None = Binance Futures combined stream)
+    stream_url: Option<String>,
+    reconnect_delay: std::time::Duration,
+    /// Consecutive failed connects or data-less sessions before alerting (0 = retry silently forever)
+    max_reconnect_failures: u32,
+    /// Stop instead of retrying once the limit is hit
+    exit_on_reconnect_limit: bool,
+    alert_tx: Option<mpsc::Sender<ExecutionEvent>>,
 }
 
 impl BinanceWebSocket {
     pub fn new(symbols: Vec<String>, tx: broadcast::Sender<MarketEvent>) -> Self {
-        Self { symbols, tx }
+        Self {
+            symbols,
+            tx,
+            book_ticker: false,
+            health: None,
+            min_trade_quantity: Decimal::ZERO,
+            dust_trades: AtomicU64::new(0),
+            max_trade_deviation: None,
+            price_bands: Mutex::new(BTreeMap::new()),
+            bad_prints: AtomicU64::new(0),
+            trade_side_source: TradeSideSource::Maker,
+            tick_states: Mutex::new(BTreeMap::new()),
+            side_checks: AtomicU64::new(0),
+            side_disagreements: AtomicU64::new(0),
+            stream_url: None,
+            reconnect_delay: std::time::Duration::from_secs(5),
+            max_reconnect_failures: 0,
+            exit_on_reconnect_limit: false,
+            alert_tx: None,
+        }
+    }
+
+    /// Connect to this URL instead of building the Binance stream URL
+    pub fn set_stream_url(&mut self, url: String) {
+        self.stream_url = Some(url);
+    }
+
+    /// Alert (and optionally stop) after `max_failures` consecutive failed connects
+    pub fn set_reconnect_policy(
+        &mut self,
+        delay: std::time::Duration,
+        max_failures: u32,
+        exit_on_limit: bool,
+        alert_tx: Option<mpsc::Sender<ExecutionEvent>>,
+    ) {
+        self.reconnect_delay = delay;
+        self.max_reconnect_failures = max_failures;
+        self.exit_on_reconnect_limit = exit_on_limit;
+        self.alert_tx = alert_tx;
+    }
+
+    /// Drop trades whose quantity is at or below `min_quantity` (zero-quantity trades are always dropped)
+    pub fn set_min_trade_quantity(&mut self, min_quantity: Decimal) {
+        self.min_trade_quantity = min_quantity.max(Decimal::ZERO);
+    }
+
+    /// Number of trades dropped by the dust filter since startup
+    pub fn dust_trades(&self) -> u64 {
+        self.dust_trades.load(Ordering::Relaxed)
+    }
+
+    /// Drop trades more than `pct` percent away from the symbol's recent median (0 = off)
+    pub fn set_max_trade_deviation_pct(&mut self, pct: Decimal) {
+        self.max_trade_deviation = (pct > Decimal::ZERO).then(|| pct / Decimal::from(100));
+    }
+
+    /// Number of trades dropped by the price band since startup
+    pub fn bad_prints(&self) -> u64 {
+        self.bad_prints.load(Ordering::Relaxed)
+    }
+
+    pub fn set_trade_side_source(&mut self, source: TradeSideSource) {
+        self.trade_side_source = source;
+    }
+
+    /// (trades compared, trades where the tick rule and maker flag disagree) since startup
+    pub fn side_disagreements(&self) -> (u64, u64) {
+        (
+            self.side_checks.load(Ordering::Relaxed),
+            self.side_disagreements.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Aggressor side of an accepted trade under the configured source
+    fn trade_side(&self, symbol: &str, price: Decimal, is_buyer_maker: bool) -> Side {
+        // is_buyer_maker=true means the buyer was the maker, so the aggressor is the seller
+        let maker_side = if is_buyer_maker { Side::Sell } else { Side::Buy };
+        if self.trade_side_source == TradeSideSource::Maker {
+            return maker_side;
+        }
+        let tick_side = match self.tick_states.lock() {
+            Ok(mut states) => states.entry(symbol.to_string()).or_default().classify(price),
+            Err(_) => None,
+        };
+        match (self.trade_side_source, tick_side) {
+            (TradeSideSource::TickRule, Some(side)) => side,
+            (TradeSideSource::Compare, Some(side)) => {
+                self.side_checks.fetch_add(1, Ordering::Relaxed);
+                if side != maker_side {
+                    self.side_disagreements.fetch_add(1, Ordering::Relaxed);
+                }
+                maker_side
+            }
+            // No price change seen yet: the maker flag is all there is
+            _ => maker_side,
+        }
+    }
+
+    /// Whether `price` sits within the band around the symbol's recent median.
+    /// A run of out-of-band prints is treated as a real gap and re-anchors the band.
+    fn within_price_band(&self, symbol: &str, price: Decimal) -> bool {
+        let Some(max_deviation) = self.max_trade_deviation else {
+            return true;
+        };
+        let Ok(mut bands) = self.price_bands.lock() else {
+            return true;
+        };
+        let band = bands.entry(symbol.to_string()).or_default();
+        let Some(median) = band.median() else {
+            band.accept(price);
+            return true;
+        };
+        let deviation = (price - median).abs() / median;
+        if deviation <= max_deviation {
+            band.accept(price);
+            return true;
+        }
+
+        band.rejected_streak += 1;
+        if band.rejected_streak >= PRICE_BAND_REANCHOR_STREAK {
+            warn!(
+                symbol = %symbol,
+                price = %price,
+                median = %median,
+                "Price band re-anchored after sustained move"
+            );
+            band.prices.clear();
+            band.accept(price);
+            return true;
+        }
+        self.bad_prints.fetch_add(1, Ordering::Relaxed);
+        warn!(
+            symbol = %symbol,
+            price = %price,
+            median = %median,
+            deviation_pct = %(deviation * Decimal::from(100)).round_dp(2),
+            "Trade dropped: price outside sanity band"
+        );
+        false
+    }
+
+    /// Use the lightweight best bid/ask stream in place of depth diffs
+    pub fn set_book_ticker_mode(&mut self, enabled: bool) {
+        self.book_ticker = enabled;
+    }
+
+    /// Report connect/disconnect to the control API's health state
+    pub fn set_health(&mut self, health: SharedHealth) {
+        self.health = Some(health);
+    }
+
+    fn set_connected(&self, connected: bool) {
+        if let Some(health) = &self.health {
+            if let Ok(mut h) = health.lock() {
+                h.set_ws_connected(connected);
+            }
+        }
+    }
+
+    /// Alert once `failures` reaches the limit; true when the feed should stop
+    async fn reconnect_limit_hit(&self, failures: u32, error: &str) -> bool {
+        if self.max_reconnect_failures == 0 || failures != self.max_reconnect_failures {
+            return false;
+        }
+        error!(failures, "WebSocket reconnect limit reached");
+        if let Some(tx) = &self.alert_tx {
+            let _ = tx
+                .send(ExecutionEvent::FeedDown {
+                    failures,
+                    error: error.to_string(),
+                })
+                .await;
+        }
+        self.exit_on_reconnect_limit
     }
 
     fn build_url(&self) -> String {
-        let streams: Vec<String> = self
-            .symbols
-            .iter()
-            .flat_map(|s| {
-                let lower = s.to_lowercase();
-                vec![
-                    format!("{}@aggTrade", lower),
-                    format!("{}@depth@100ms", lower),
-                ]
-            })
-            .collect();
-        format!("{}{}", BINANCE_FUTURES_WS, streams.join("/"))
-    }
-
-    pub async fn run(&self, mut shutdown: tokio::sync::watch::Receiver<bool>) {
+        self.stream_url
+            .clone()
+            .unwrap_or_else(|| stream_url(&self.symbols, self.book_ticker))
+    }
+
+    pub async fn run(&self, mut shutdown: tokio::sync::watch::Receiver<bool>) -> FeedExit {
+        let mut sessions: u64 = 0;
+        let mut failures: u32 = 0;
         loop {
             let url = self.build_url();
             info!("Connecting to Binance WebSocket: {}", url);
@@ -43,13 +322,25 @@ impl BinanceWebSocket {
             match connect_async(&url).await {
                 Ok((ws_stream, _response)) => {
                     info!("Connected to Binance WebSocket");
+                    self.set_connected(true);
+                    if sessions > 0 {
+                        let _ = self.tx.send(MarketEvent::Reconnected {
+                            timestamp: Utc::now(),
+                        });
+                    }
+                    sessions += 1;
                     let (_write, mut read) = ws_stream.split();
+                    // Only a session that delivers data clears the failure count, so a
+                    // connect-then-drop loop still reaches the alert threshold
+                    let mut received = false;
 
                     loop {
                         tokio::select! {
                             msg = read.next() => {
                                 match msg {
                                     Some(Ok(tungstenite::Message::Text(text))) => {
+                                        received = true;
+                                        failures = 0;
                                         self.handle_message(&text);
                                     }
                                     Some(Ok(tungstenite::Message::Ping(_))) => {}
@@ -71,24 +362,59 @@ impl BinanceWebSocket {
                             _ = shutdown.changed() => {
                                 if *shutdown.borrow() {
                                     info!("Shutdown signal received, closing WebSocket");
-                                    return;
+                                    self.set_connected(false);
+                                    return FeedExit::Shutdown;
                                 }
                             }
                         }
                     }
+                    self.set_connected(false);
+                    info!(
+                        dust_trades = self.dust_trades(),
+                        bad_prints = self.bad_prints(),
+                        "WebSocket session ended"
+                    );
+                    if self.trade_side_source == TradeSideSource::Compare {
+                        let (checked, disagreed) = self.side_disagreements();
+                        let rate = if checked > 0 { disagreed as f64 / checked as f64 * 100.0 } else { 0.0 };
+                        info!(
+                            checked,
+                            disagreed,
+                            disagreement_pct = format!("{:.2}", rate),
+                            "Tick rule vs maker flag"
+                        );
+                    }
+                    if !received {
+                        failures += 1;
+                        error!(failures, "WebSocket session ended before any data");
+                        if self.reconnect_limit_hit(failures, "session ended before any data").await {
+                            return FeedExit::ReconnectLimit;
+                        }
+                    }
                 }
                 Err(e) => {
-                    error!("Failed to connect to Binance WebSocket: {}", e);
+                    failures += 1;
+                    error!(failures, "Failed to connect to Binance WebSocket: {}", e);
+                    if self.reconnect_limit_hit(failures, &e.to_string()).await {
+                        return FeedExit::ReconnectLimit;
+                    }
                 }
             }
 
             // Check shutdown before reconnecting
             if *shutdown.borrow() {
-                return;
+                return FeedExit::Shutdown;
             }
 
-            warn!("Reconnecting in 5 seconds...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            warn!(delay_ms = self.reconnect_delay.as_millis() as u64, "Reconnecting...");
+            tokio::select! {
+                _ = tokio::time::sleep(self.reconnect_delay) => {}
+                _ = shutdown.changed() => {
+                    if *shutdown.borrow() {
+                        return FeedExit::Shutdown;
+                    }
+                }
+            }
         }
     }
 
@@ -103,6 +429,8 @@ impl BinanceWebSocket {
 
         if combined.stream.contains("aggTrade") {
             self.handle_agg_trade(&combined.data);
+        } else if combined.stream.contains("bookTicker") {
+            self.handle_book_ticker(&combined.data);
         } else if combined.stream.contains("depth") {
             self.handle_depth(&combined.data);
         }
@@ -125,18 +453,21 @@ impl BinanceWebSocket {
             Ok(q) => q,
             Err(_) => return,
         };
+        if quantity <= self.min_trade_quantity {
+            self.dust_trades.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let symbol = trade.symbol.to_lowercase();
+        if !self.within_price_band(&symbol, price) {
+            return;
+        }
 
-        // is_buyer_maker=true means the buyer was the maker, so the aggressor is the seller
-        let side = if trade.is_buyer_maker {
-            Side::Sell
-        } else {
-            Side::Buy
-        };
+        let side = self.trade_side(&symbol, price, trade.is_buyer_maker);
 
         let timestamp = millis_to_datetime(trade.trade_time);
 
         let normalized = NormalizedTrade {
-            symbol: trade.symbol.to_lowercase(),
+            symbol,
             price,
             quantity,
             side,
@@ -175,8 +506,246 @@ impl BinanceWebSocket {
 
         let _ = self.tx.send(MarketEvent::Depth(update));
     }
+
+    fn handle_book_ticker(&self, data: &serde_json::Value) {
+        let ticker: BinanceBookTicker = match serde_json::from_value(data.clone()) {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("Failed to parse bookTicker: {}", e);
+                return;
+            }
+        };
+
+        let parse = |raw: &str| Decimal::from_str(raw).ok();
+        let (Some(bid_price), Some(bid_qty), Some(ask_price), Some(ask_qty)) = (
+            parse(&ticker.bid_price),
+            parse(&ticker.bid_qty),
+            parse(&ticker.ask_price),
+            parse(&ticker.ask_qty),
+        ) else {
+            return;
+        };
+
+        let update = BookTicker {
+            symbol: ticker.symbol.to_lowercase(),
+            bid_price,
+            bid_qty,
+            ask_price,
+            ask_qty,
+            timestamp: millis_to_datetime(ticker.event_time),
+        };
+
+        let _ = self.tx.send(MarketEvent::BookTicker(update));
+    }
+}
+
+/// Combined-stream URL: aggTrade plus depth diffs (or bookTicker) per symbol
+pub fn stream_url(symbols: &[String], book_ticker: bool) -> String {
+    let mut seen = std::collections::HashSet::new();
+    let streams: Vec<String> = symbols
+        .iter()
+        .map(|s| s.to_lowercase())
+        .filter(|lower| seen.insert(lower.clone()))
+        .flat_map(|lower| {
+            let book_stream = if book_ticker {
+                format!("{}@bookTicker", lower)
+            } else {
+                format!("{}@depth@100ms", lower)
+            };
+            vec![format!("{}@aggTrade", lower), book_stream]
+        })
+        .collect();
+    format!("{}{}", BINANCE_FUTURES_WS, streams.join("/"))
 }
 
 fn millis_to_datetime(millis: u64) -> DateTime<Utc> {
     DateTime::from_timestamp_millis(millis as i64).unwrap_or_else(Utc::now)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_book_ticker_mode_subscribes_and_parses() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let mut ws = BinanceWebSocket::new(vec!["BTCUSDT".to_string()], tx);
+        assert!(ws.build_url().ends_with("btcusdt@aggTrade/btcusdt@depth@100ms"));
+
+        ws.set_book_ticker_mode(true);
+        assert!(ws.build_url().ends_with("btcusdt@aggTrade/btcusdt@bookTicker"));
+
+        ws.handle_message(
+            r#"{"stream":"btcusdt@bookTicker","data":{"e":"bookTicker","u":400900217,"E":1568014460893,"T":1568014460891,"s":"BTCUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}}"#,
+        );
+        match rx.try_recv().unwrap() {
+            MarketEvent::BookTicker(t) => {
+                assert_eq!(t.symbol, "btcusdt");
+                assert_eq!(t.bid_price, Decimal::from_str("25.3519").unwrap());
+                assert_eq!(t.ask_qty, Decimal::from_str("40.66").unwrap());
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_connection_alerts_after_max_attempts() {
+        let (tx, _rx) = broadcast::channel(16);
+        let (alert_tx, mut alert_rx) = mpsc::channel(4);
+        let mut ws = BinanceWebSocket::new(vec!["btcusdt".to_string()], tx);
+        // Nothing listens on port 1: every connect is refused
+        ws.set_stream_url("ws://127.0.0.1:1/stream".to_string());
+        ws.set_reconnect_policy(std::time::Duration::from_millis(1), 3, true, Some(alert_tx));
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        let exit = tokio::time::timeout(std::time::Duration::from_secs(10), ws.run(shutdown_rx))
+            .await
+            .unwrap();
+        assert_eq!(exit, FeedExit::ReconnectLimit);
+        match alert_rx.try_recv().unwrap() {
+            ExecutionEvent::FeedDown { failures, .. } => assert_eq!(failures, 3),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(alert_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_dropped_before_data_count_as_failures() {
+        // Accept the handshake, then hang up without sending anything
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                if let Ok(ws) = tokio_tungstenite::accept_async(stream).await {
+                    drop(ws);
+                }
+            }
+        });
+
+        let (tx, _rx) = broadcast::channel(16);
+        let (alert_tx, mut alert_rx) = mpsc::channel(4);
+        let mut ws = BinanceWebSocket::new(vec!["btcusdt".to_string()], tx);
+        ws.set_stream_url(format!("ws://{}/stream", addr));
+        ws.set_reconnect_policy(std::time::Duration::from_millis(1), 3, true, Some(alert_tx));
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        let exit = tokio::time::timeout(std::time::Duration::from_secs(10), ws.run(shutdown_rx))
+            .await
+            .unwrap();
+        assert_eq!(exit, FeedExit::ReconnectLimit);
+        match alert_rx.try_recv().unwrap() {
+            ExecutionEvent::FeedDown { failures, .. } => assert_eq!(failures, 3),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_symbols_get_one_stream_each() {
+        let symbols = ["btcusdt", "BTCUSDT", "ethusdt"].map(String::from);
+        assert!(stream_url(&symbols, true)
+            .ends_with("btcusdt@aggTrade/btcusdt@bookTicker/ethusdt@aggTrade/ethusdt@bookTicker"));
+    }
+
+    #[test]
+    fn test_dust_trades_are_dropped_and_counted() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let mut ws = BinanceWebSocket::new(vec!["BTCUSDT".to_string()], tx);
+        ws.set_min_trade_quantity(Decimal::from_str("0.001").unwrap());
+        let agg = |qty: &str| {
+            format!(
+                r#"{{"stream":"btcusdt@aggTrade","data":{{"e":"aggTrade","E":1568014460893,"s":"BTCUSDT","a":5933014,"p":"100.0","q":"{}","f":100,"l":105,"T":1568014460891,"m":false}}}}"#,
+                qty
+            )
+        };
+
+        ws.handle_message(&agg("0"));
+        ws.handle_message(&agg("0.001"));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(ws.dust_trades(), 2);
+
+        ws.handle_message(&agg("0.002"));
+        match rx.try_recv().unwrap() {
+            MarketEvent::Trade(t) => assert_eq!(t.quantity, Decimal::from_str("0.002").unwrap()),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert_eq!(ws.dust_trades(), 2);
+    }
+
+    #[test]
+    fn test_price_spike_is_dropped_by_sanity_band() {
+        let (tx, mut rx) = broadcast::channel(64);
+        let mut ws = BinanceWebSocket::new(vec!["BTCUSDT".to_string()], tx);
+        ws.set_max_trade_deviation_pct(Decimal::from(5));
+        let agg = |price: &str| {
+            format!(
+                r#"{{"stream":"btcusdt@aggTrade","data":{{"e":"aggTrade","E":1568014460893,"s":"BTCUSDT","a":5933014,"p":"{}","q":"1.0","f":100,"l":105,"T":1568014460891,"m":false}}}}"#,
+                price
+            )
+        };
+        let mut delivered = || {
+            let mut prices = Vec::new();
+            while let Ok(MarketEvent::Trade(t)) = rx.try_recv() {
+                prices.push(t.price);
+            }
+            prices
+        };
+
+        for price in ["100.0", "100.5", "99.8", "1000.0", "100.2"] {
+            ws.handle_message(&agg(price));
+        }
+        // The 10x print never reaches bars, profiles or the simulator's stop checks
+        assert_eq!(
+            delivered(),
+            ["100.0", "100.5", "99.8", "100.2"].map(|p| Decimal::from_str(p).unwrap())
+        );
+        assert_eq!(ws.bad_prints(), 1);
+
+        // A sustained move is a real gap, not a bad print
+        for _ in 0..PRICE_BAND_REANCHOR_STREAK {
+            ws.handle_message(&agg("110.0"));
+        }
+        ws.handle_message(&agg("110.5"));
+        assert_eq!(delivered().len(), 2);
+    }
+
+    #[test]
+    fn test_tick_rule_disagreements_are_counted() {
+        let (tx, mut rx) = broadcast::channel(64);
+        let mut ws = BinanceWebSocket::new(vec!["BTCUSDT".to_string()], tx);
+        ws.set_trade_side_source(TradeSideSource::Compare);
+        let agg = |price: &str, maker: bool| {
+            format!(
+                r#"{{"stream":"btcusdt@aggTrade","data":{{"e":"aggTrade","E":1568014460893,"s":"BTCUSDT","a":5933014,"p":"{}","q":"1.0","f":100,"l":105,"T":1568014460891,"m":{}}}}}"#,
+                price, maker
+            )
+        };
+
+        // First print has no tick direction; then up (buy), zero tick (buy), down (sell), up (buy)
+        for (price, maker) in [("100.0", true), ("100.5", false), ("100.5", true), ("100.2", true), ("100.4", true)] {
+            ws.handle_message(&agg(price, maker));
+        }
+        assert_eq!(ws.side_disagreements(), (4, 2));
+        // Compare mode keeps the maker flag's side
+        let sides: Vec<Side> = std::iter::from_fn(|| match rx.try_recv() {
+            Ok(MarketEvent::Trade(t)) => Some(t.side),
+            _ => None,
+        })
+        .collect();
+        assert_eq!(sides, [Side::Sell, Side::Buy, Side::Sell, Side::Sell, Side::Sell]);
+
+        // Tick rule mode emits the tick rule's side and counts nothing
+        let (tx, mut rx) = broadcast::channel(64);
+        let mut ws = BinanceWebSocket::new(vec!["BTCUSDT".to_string()], tx);
+        ws.set_trade_side_source(TradeSideSource::TickRule);
+        for (price, maker) in [("100.0", true), ("100.5", true), ("100.5", true), ("100.2", false)] {
+            ws.handle_message(&agg(price, maker));
+        }
+        let sides: Vec<Side> = std::iter::from_fn(|| match rx.try_recv() {
+            Ok(MarketEvent::Trade(t)) => Some(t.side),
+            _ => None,
+        })
+        .collect();
+        assert_eq!(sides, [Side::Sell, Side::Buy, Side::Buy, Side::Sell]);
+        assert_eq!(ws.side_disagreements(), (0, 0));
+    }
+}
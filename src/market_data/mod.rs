@@ -1,4 +1,8 @@
 pub mod binance_ws;
+pub mod recorder;
+pub mod replay;
 pub mod types;
 
-pub use binance_ws::BinanceWebSocket;
+pub use binance_ws::{BinanceWebSocket, FeedExit, TradeSideSource};
+pub use recorder::MarketRecorder;
+pub use replay::ReplaySource;
@@ -0,0 +1,180 @@
+use crate::types::MarketEvent;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, warn};
+
+/// Appends every market event (trades, depth, book tickers) from the market channel
+/// to a JSONL file in arrival order, so `ReplaySource` can rebuild the exact book
+/// state the simulator saw
+pub struct MarketRecorder {
+    path: String,
+    writer: BufWriter<File>,
+    events_written: usize,
+}
+
+impl MarketRecorder {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("open {}: {}", path, e))?;
+        Ok(Self {
+            path: path.to_string(),
+            writer: BufWriter::new(file),
+            events_written: 0,
+        })
+    }
+
+    pub fn record(&mut self, event: &MarketEvent) {
+        let written = serde_json::to_string(event)
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(self.writer, "{}", line).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => self.events_written += 1,
+            Err(e) => error!(path = %self.path, "Failed to record market event: {}", e),
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            error!(path = %self.path, "Failed to flush market recording: {}", e);
+        }
+    }
+
+    /// Record until shutdown or the channel closes. A lagging receiver leaves a gap
+    /// in the recording, which is logged with the number of events lost.
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        mut shutdown: tokio::sync::watch::Receiver<bool>,
+    ) -> usize {
+        info!(path = %self.path, "Market recorder started");
+        loop {
+            tokio::select! {
+                received = rx.recv() => match received {
+                    Ok(event) => self.record(&event),
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(path = %self.path, missed, "Market recorder lagged; recording has a gap");
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown.changed() => {
+                    if *shutdown.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+        // Drain what was already published before shutdown
+        while let Ok(event) = rx.try_recv() {
+            self.record(&event);
+        }
+        self.flush();
+        info!(path = %self.path, events = self.events_written, "Market recorder stopped");
+        self.events_written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReplayConfig;
+    use crate::market_data::ReplaySource;
+    use crate::simulator::order_book::LocalOrderBook;
+    use crate::types::{DepthLevel, DepthUpdate, NormalizedTrade, Side};
+    use chrono::{Duration, Utc};
+    use rust_decimal::Decimal;
+    use std::collections::BTreeMap;
+
+    type BookState = (BTreeMap<Decimal, Decimal>, BTreeMap<Decimal, Decimal>);
+
+    /// Book state at every trade, i.e. wherever the simulator could evaluate a signal
+    fn books_at_trades(events: &[MarketEvent]) -> Vec<BookState> {
+        let mut book = LocalOrderBook::new("btcusdt".to_string(), 20);
+        let mut states = Vec::new();
+        for event in events {
+            match event {
+                MarketEvent::Depth(depth) => book.update(depth),
+                MarketEvent::Trade(_) => states.push((book.bids.clone(), book.asks.clone())),
+                _ => {}
+            }
+        }
+        states
+    }
+
+    fn session() -> Vec<MarketEvent> {
+        let start = Utc::now();
+        let level = |price: i64, quantity: i64| DepthLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        };
+        (0..30)
+            .map(|i| {
+                let timestamp = start + Duration::milliseconds(i);
+                if i % 3 == 0 {
+                    MarketEvent::Trade(NormalizedTrade {
+                        symbol: "btcusdt".to_string(),
+                        price: Decimal::from(100 + i % 5),
+                        quantity: Decimal::ONE,
+                        side: Side::Buy,
+                        timestamp,
+                        trade_id: i as u64,
+                    })
+                } else {
+                    // Levels appear, resize and get removed (quantity 0) as the session runs
+                    MarketEvent::Depth(DepthUpdate {
+                        symbol: "btcusdt".to_string(),
+                        bids: vec![level(99 - i % 4, i % 7), level(98, i)],
+                        asks: vec![level(101 + i % 3, (i + 1) % 5), level(103, i)],
+                        timestamp,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_recorded_session_replays_identical_books() {
+        let path = std::env::temp_dir()
+            .join(format!("rusto-record-{}.jsonl", uuid::Uuid::new_v4()))
+            .display()
+            .to_string();
+        let (tx, rx) = broadcast::channel(1024);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let recorder = tokio::spawn(MarketRecorder::open(&path).unwrap().run(rx, shutdown_rx));
+
+        let live = session();
+        for event in &live {
+            tx.send(event.clone()).unwrap();
+        }
+        shutdown_tx.send(true).unwrap();
+        assert_eq!(recorder.await.unwrap(), live.len());
+
+        let (replay_tx, mut replay_rx) = broadcast::channel(1024);
+        let config = ReplayConfig {
+            enabled: true,
+            path: path.clone(),
+            speed: 1000.0,
+            seek_to: None,
+            progress_interval_secs: 1,
+        };
+        let (_replay_shutdown_tx, replay_shutdown_rx) = tokio::sync::watch::channel(false);
+        let stats = ReplaySource::new(&config, replay_tx)
+            .run(replay_shutdown_rx)
+            .await
+            .unwrap();
+        assert_eq!(stats.events_sent, live.len());
+
+        let mut replayed = Vec::new();
+        while let Ok(event) = replay_rx.try_recv() {
+            replayed.push(event);
+        }
+        let live_books = books_at_trades(&live);
+        assert_eq!(live_books.len(), 10);
+        assert_eq!(books_at_trades(&replayed), live_books);
+        let _ = std::fs::remove_file(path);
+    }
+}
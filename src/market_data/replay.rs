@@ -0,0 +1,226 @@
+use crate::config::ReplayConfig;
+use crate::types::MarketEvent;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Summary of a finished replay run
+#[derive(Debug, Clone, Default)]
+pub struct ReplayStats {
+    pub events_sent: usize,
+    pub events_skipped: usize,
+    pub progress_reports: usize,
+}
+
+/// Publishes recorded market events (JSONL) onto the market channel,
+/// paced by their recorded timestamps scaled by `speed`.
+pub struct ReplaySource {
+    path: String,
+    speed: f64,
+    seek_to: Option<DateTime<Utc>>,
+    progress_interval: Duration,
+    tx: broadcast::Sender<MarketEvent>,
+}
+
+impl ReplaySource {
+    pub fn new(config: &ReplayConfig, tx: broadcast::Sender<MarketEvent>) -> Self {
+        let seek_to = config
+            .seek_to
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc));
+
+        Self {
+            path: config.path.clone(),
+            speed: config.speed,
+            seek_to,
+            progress_interval: Duration::from_secs(config.progress_interval_secs.max(1)),
+            tx,
+        }
+    }
+
+    /// Load all events from the JSONL file, skipping malformed lines
+    fn load_events(&self) -> Result<Vec<MarketEvent>, String> {
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read replay file {}: {}", self.path, e))?;
+
+        let mut events = Vec::new();
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<MarketEvent>(line) {
+                Ok(event) => events.push(event),
+                Err(e) => warn!(line = line_no + 1, "Skipping malformed replay event: {}", e),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Replay the file until the end or shutdown
+    pub async fn run(
+        &self,
+        mut shutdown: tokio::sync::watch::Receiver<bool>,
+    ) -> Result<ReplayStats, String> {
+        let events = self.load_events()?;
+        let mut stats = ReplayStats::default();
+
+        let start_index = match self.seek_to {
+            Some(seek_to) => events
+                .iter()
+                .position(|e| e.timestamp() >= seek_to)
+                .unwrap_or(events.len()),
+            None => 0,
+        };
+        stats.events_skipped = start_index;
+        let total = events.len() - start_index;
+
+        let Some(first) = events.get(start_index) else {
+            info!(path = %self.path, "Replay: no events to replay");
+            return Ok(stats);
+        };
+        let first_ts = first.timestamp();
+        info!(
+            path = %self.path,
+            events = total,
+            skipped = start_index,
+            speed = self.speed,
+            start = %first_ts,
+            "Replay started"
+        );
+
+        let wall_start = Instant::now();
+        let mut next_progress = wall_start + self.progress_interval;
+
+        for event in &events[start_index..] {
+            let recorded_offset = (event.timestamp() - first_ts)
+                .to_std()
+                .unwrap_or(Duration::ZERO);
+            let due = wall_start + recorded_offset.div_f64(self.speed);
+
+            tokio::select! {
+                _ = tokio::time::sleep_until(due) => {}
+                _ = shutdown.changed() => {
+                    if *shutdown.borrow() {
+                        info!(sent = stats.events_sent, "Replay interrupted by shutdown");
+                        return Ok(stats);
+                    }
+                }
+            }
+
+            let _ = self.tx.send(event.clone());
+            stats.events_sent += 1;
+
+            if Instant::now() >= next_progress {
+                stats.progress_reports += 1;
+                next_progress = Instant::now() + self.progress_interval;
+                info!(
+                    progress_pct = format!("{:.1}", stats.events_sent as f64 * 100.0 / total as f64),
+                    simulated_time = %event.timestamp(),
+                    sent = stats.events_sent,
+                    total = total,
+                    "Replay progress"
+                );
+            }
+        }
+
+        info!(
+            sent = stats.events_sent,
+            elapsed_secs = format!("{:.2}", wall_start.elapsed().as_secs_f64()),
+            "Replay finished"
+        );
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{NormalizedTrade, Side};
+    use rust_decimal::Decimal;
+    use std::io::Write;
+
+    /// Write `count` trades spaced `step_ms` apart and return the file path + first timestamp
+    fn write_recording(count: usize, step_ms: i64) -> (String, DateTime<Utc>) {
+        let path = std::env::temp_dir()
+            .join(format!("rusto-replay-{}.jsonl", uuid::Uuid::new_v4()))
+            .display()
+            .to_string();
+        let start = Utc::now();
+        let mut file = std::fs::File::create(&path).unwrap();
+        for i in 0..count {
+            let event = MarketEvent::Trade(NormalizedTrade {
+                symbol: "btcusdt".to_string(),
+                price: Decimal::from(100 + i as i64),
+                quantity: Decimal::ONE,
+                side: Side::Buy,
+                timestamp: start + chrono::Duration::milliseconds(i as i64 * step_ms),
+                trade_id: i as u64,
+            });
+            writeln!(file, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+        }
+        (path, start)
+    }
+
+    fn source(
+        path: &str,
+        speed: f64,
+        seek_to: Option<DateTime<Utc>>,
+    ) -> (ReplaySource, broadcast::Receiver<MarketEvent>) {
+        let (tx, rx) = broadcast::channel(1024);
+        let config = ReplayConfig {
+            enabled: true,
+            path: path.to_string(),
+            speed,
+            seek_to: seek_to.map(|t| t.to_rfc3339()),
+            progress_interval_secs: 1,
+        };
+        let mut replay = ReplaySource::new(&config, tx);
+        replay.progress_interval = Duration::from_millis(50);
+        (replay, rx)
+    }
+
+    #[tokio::test]
+    async fn test_replay_at_10x_takes_a_tenth_of_recorded_span() {
+        // 21 trades over 2s of recorded time
+        let (path, _) = write_recording(21, 100);
+        let (replay, mut rx) = source(&path, 10.0, None);
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        let started = std::time::Instant::now();
+        let stats = replay.run(shutdown_rx).await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(stats.events_sent, 21);
+        assert!(
+            elapsed >= Duration::from_millis(190),
+            "too fast: {:?}",
+            elapsed
+        );
+        assert!(
+            elapsed < Duration::from_millis(800),
+            "too slow: {:?}",
+            elapsed
+        );
+        assert!(stats.progress_reports >= 1);
+        assert!(matches!(rx.try_recv(), Ok(MarketEvent::Trade(_))));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_replay_seek_skips_earlier_events() {
+        let (path, start) = write_recording(10, 1);
+        let seek_to = start + chrono::Duration::milliseconds(4);
+        let (replay, mut rx) = source(&path, 1.0, Some(seek_to));
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        let stats = replay.run(shutdown_rx).await.unwrap();
+        assert_eq!(stats.events_skipped, 4);
+        assert_eq!(stats.events_sent, 6);
+        let first = rx.try_recv().unwrap();
+        assert_eq!(first.timestamp(), seek_to);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -50,6 +50,30 @@ pub struct BinanceDepthUpdate {
     pub asks: Vec<[String; 2]>,
 }
 
+/// Raw Binance best bid/ask stream message
+/// Stream: <symbol>@bookTicker
+#[derive(Debug, Deserialize)]
+pub struct BinanceBookTicker {
+    #[serde(rename = "e")]
+    pub event_type: String,
+    #[serde(rename = "u")]
+    pub update_id: u64,
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "b")]
+    pub bid_price: String,
+    #[serde(rename = "B")]
+    pub bid_qty: String,
+    #[serde(rename = "a")]
+    pub ask_price: String,
+    #[serde(rename = "A")]
+    pub ask_qty: String,
+}
+
 /// Combined stream wrapper
 #[derive(Debug, Deserialize)]
 pub struct BinanceCombinedStream {
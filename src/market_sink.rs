@@ -0,0 +1,386 @@
+use crate::types::{BookSnapshot, OrderFlowMetrics, RangeBar, VolumeProfileSnapshot};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use tracing::error;
+
+/// Most queued book snapshots written per SQLite transaction
+const BOOK_BATCH_SIZE: usize = 256;
+
+/// One persisted record; flow rows carry the index of the bar they were computed from
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SinkRecord<'a> {
+    Bar(&'a RangeBar),
+    Flow {
+        bar_index: u64,
+        #[serde(flatten)]
+        metrics: &'a OrderFlowMetrics,
+    },
+    Profile(&'a VolumeProfileSnapshot),
+    Book(&'a BookSnapshot),
+}
+
+enum Backend {
+    Jsonl(BufWriter<File>),
+    Sqlite(Connection),
+}
+
+/// Time-series sink for completed bars, flow metrics, profile and order book
+/// snapshots, so the market context around any trade can be rebuilt offline
+pub struct MarketSink {
+    backend: Backend,
+}
+
+impl MarketSink {
+    /// Open a sink; `format` is "Jsonl" or "Sqlite"
+    pub fn open(format: &str, path: &str) -> Result<Self, String> {
+        let backend = match format.to_lowercase().as_str() {
+            "jsonl" => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("open {}: {}", path, e))?;
+                Backend::Jsonl(BufWriter::new(file))
+            }
+            "sqlite" => {
+                let conn = Connection::open(path).map_err(|e| format!("open {}: {}", path, e))?;
+                conn.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS bars (
+                        symbol TEXT NOT NULL,
+                        bar_index INTEGER NOT NULL,
+                        open_time TEXT NOT NULL,
+                        close_time TEXT NOT NULL,
+                        data TEXT NOT NULL
+                    );
+                    CREATE TABLE IF NOT EXISTS flows (
+                        symbol TEXT NOT NULL,
+                        bar_index INTEGER NOT NULL,
+                        timestamp TEXT NOT NULL,
+                        data TEXT NOT NULL
+                    );
+                    CREATE TABLE IF NOT EXISTS profiles (
+                        symbol TEXT NOT NULL,
+                        timestamp TEXT NOT NULL,
+                        data TEXT NOT NULL
+                    );
+                    CREATE TABLE IF NOT EXISTS books (
+                        symbol TEXT NOT NULL,
+                        timestamp TEXT NOT NULL,
+                        best_bid TEXT,
+                        best_ask TEXT,
+                        data TEXT NOT NULL
+                    );",
+                )
+                .map_err(|e| format!("create sink tables: {}", e))?;
+                Backend::Sqlite(conn)
+            }
+            other => return Err(format!("unknown market sink format: {}", other)),
+        };
+        Ok(Self { backend })
+    }
+
+    pub fn record_bar(&mut self, bar: &RangeBar) {
+        let record = SinkRecord::Bar(bar);
+        match &mut self.backend {
+            Backend::Jsonl(writer) => {
+                Self::write_line(writer, &record);
+                // Bars are infrequent; flush so a crash loses at most the current bar
+                if let Err(e) = writer.flush() {
+                    error!("Failed to flush market sink: {}", e);
+                }
+            }
+            Backend::Sqlite(conn) => {
+                let result = conn.execute(
+                    "INSERT INTO bars (symbol, bar_index, open_time, close_time, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        bar.symbol,
+                        bar.bar_index as i64,
+                        bar.open_time.to_rfc3339(),
+                        bar.close_time.to_rfc3339(),
+                        serde_json::to_string(bar).unwrap_or_default(),
+                    ],
+                );
+                if let Err(e) = result {
+                    error!("Failed to insert bar into market sink: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn record_flow(&mut self, bar_index: u64, metrics: &OrderFlowMetrics) {
+        match &mut self.backend {
+            Backend::Jsonl(writer) => {
+                Self::write_line(writer, &SinkRecord::Flow { bar_index, metrics });
+            }
+            Backend::Sqlite(conn) => {
+                let result = conn.execute(
+                    "INSERT INTO flows (symbol, bar_index, timestamp, data) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        metrics.symbol,
+                        bar_index as i64,
+                        metrics.timestamp.to_rfc3339(),
+                        serde_json::to_string(metrics).unwrap_or_default(),
+                    ],
+                );
+                if let Err(e) = result {
+                    error!("Failed to insert flow into market sink: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn record_profile(&mut self, profile: &VolumeProfileSnapshot) {
+        match &mut self.backend {
+            Backend::Jsonl(writer) => Self::write_line(writer, &SinkRecord::Profile(profile)),
+            Backend::Sqlite(conn) => {
+                let result = conn.execute(
+                    "INSERT INTO profiles (symbol, timestamp, data) VALUES (?1, ?2, ?3)",
+                    params![
+                        profile.symbol,
+                        profile.timestamp.to_rfc3339(),
+                        serde_json::to_string(profile).unwrap_or_default(),
+                    ],
+                );
+                if let Err(e) = result {
+                    error!("Failed to insert profile into market sink: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Record book snapshots; SQLite writes them in one transaction
+    pub fn record_books(&mut self, books: &[BookSnapshot]) {
+        match &mut self.backend {
+            Backend::Jsonl(writer) => {
+                for book in books {
+                    Self::write_line(writer, &SinkRecord::Book(book));
+                }
+            }
+            Backend::Sqlite(conn) => {
+                let tx = match conn.transaction() {
+                    Ok(tx) => tx,
+                    Err(e) => {
+                        error!("Failed to start book snapshot transaction: {}", e);
+                        return;
+                    }
+                };
+                for book in books {
+                    let result = tx.execute(
+                        "INSERT INTO books (symbol, timestamp, best_bid, best_ask, data)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![
+                            book.symbol,
+                            book.timestamp.to_rfc3339(),
+                            book.bids.first().map(|l| l.price.to_string()),
+                            book.asks.first().map(|l| l.price.to_string()),
+                            serde_json::to_string(book).unwrap_or_default(),
+                        ],
+                    );
+                    if let Err(e) = result {
+                        error!(symbol = %book.symbol, "Failed to insert book snapshot into market sink: {}", e);
+                    }
+                }
+                if let Err(e) = tx.commit() {
+                    error!(snapshots = books.len(), "Failed to commit book snapshots: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let Backend::Jsonl(writer) = &mut self.backend {
+            if let Err(e) = writer.flush() {
+                error!("Failed to flush market sink: {}", e);
+            }
+        }
+    }
+
+    fn write_line(writer: &mut BufWriter<File>, record: &SinkRecord) {
+        match serde_json::to_string(record) {
+            Ok(json) => {
+                if let Err(e) = writeln!(writer, "{}", json) {
+                    error!("Failed to write market sink record: {}", e);
+                }
+            }
+            Err(e) => error!("Failed to serialize market sink record: {}", e),
+        }
+    }
+}
+
+/// Message for the background book snapshot writer
+enum BookMsg {
+    Snapshot(Box<BookSnapshot>),
+    /// Acknowledged once every earlier snapshot has been written
+    Flush(Sender<()>),
+}
+
+/// Dedicated thread that owns a sink and writes queued book snapshots in batches,
+/// so the simulator never waits on disk
+pub struct BookSnapshotWriter {
+    tx: Sender<BookMsg>,
+    _handle: JoinHandle<()>,
+}
+
+impl BookSnapshotWriter {
+    pub fn spawn(sink: MarketSink) -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel();
+        let handle = std::thread::Builder::new()
+            .name("book-snapshot-writer".to_string())
+            .spawn(move || run_book_writer(rx, sink))
+            .map_err(|e| format!("spawn book snapshot writer: {}", e))?;
+        Ok(Self { tx, _handle: handle })
+    }
+
+    /// Queue a snapshot for writing
+    pub fn record(&self, book: BookSnapshot) {
+        if self.tx.send(BookMsg::Snapshot(Box::new(book))).is_err() {
+            error!("Book snapshot writer stopped; snapshot dropped");
+        }
+    }
+
+    /// Block until every snapshot queued so far has been written
+    pub fn flush(&self) {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if self.tx.send(BookMsg::Flush(ack_tx)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+    }
+}
+
+fn run_book_writer(rx: Receiver<BookMsg>, mut sink: MarketSink) {
+    let mut batch: Vec<BookSnapshot> = Vec::with_capacity(BOOK_BATCH_SIZE);
+    while let Ok(first) = rx.recv() {
+        let mut acks = Vec::new();
+        let mut next = Some(first);
+        while let Some(msg) = next {
+            match msg {
+                BookMsg::Snapshot(book) => batch.push(*book),
+                BookMsg::Flush(ack) => {
+                    acks.push(ack);
+                    break;
+                }
+            }
+            if batch.len() >= BOOK_BATCH_SIZE {
+                break;
+            }
+            next = rx.try_recv().ok();
+        }
+        if !batch.is_empty() {
+            sink.record_books(&batch);
+            batch.clear();
+        }
+        if !acks.is_empty() {
+            sink.flush();
+        }
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+    sink.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OrderFlowConfig, RangeBarConfig};
+    use crate::order_flow::OrderFlowTracker;
+    use crate::range_bar::RangeBarBuilder;
+    use crate::types::{NormalizedTrade, Side};
+    use chrono::{Duration, Utc};
+    use rust_decimal::Decimal;
+
+    /// Run a zig-zag tape through the bar builder and flow tracker into the sink
+    fn run(sink: &mut MarketSink) -> usize {
+        let range: RangeBarConfig = toml::from_str("default = 5.0").unwrap();
+        let flow: OrderFlowConfig = toml::from_str(
+            r#"
+            absorption_delta_ratio = 3.0
+            max_price_delta_ticks = 2
+            large_volume_multiplier = 2.0
+            "#,
+        )
+        .unwrap();
+        let mut builder = RangeBarBuilder::new(range);
+        let mut tracker = OrderFlowTracker::new(&flow);
+        let start = Utc::now();
+        let mut bars = 0;
+        for i in 0..200i64 {
+            let trade = NormalizedTrade {
+                symbol: "btcusdt".to_string(),
+                price: Decimal::from(100 + (i % 14 - 7).abs()),
+                quantity: Decimal::ONE,
+                side: if i % 3 == 0 { Side::Sell } else { Side::Buy },
+                timestamp: start + Duration::seconds(i),
+                trade_id: i as u64,
+            };
+            if let Some(bar) = builder.process_trade(&trade) {
+                let metrics = tracker.analyze_bar(&bar);
+                sink.record_flow(bar.bar_index, &metrics);
+                sink.record_bar(&bar);
+                bars += 1;
+            }
+        }
+        sink.flush();
+        bars
+    }
+
+    #[test]
+    fn test_jsonl_sink_aligns_flow_rows_to_bars() {
+        let path = std::env::temp_dir().join(format!("rusto-sink-{}.jsonl", uuid::Uuid::new_v4()));
+        let path = path.display().to_string();
+        let mut sink = MarketSink::open("Jsonl", &path).unwrap();
+        let completed = run(&mut sink);
+        assert!(completed > 0);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let rows: Vec<serde_json::Value> =
+            contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let bar_indexes: Vec<u64> = rows
+            .iter()
+            .filter(|r| r["kind"] == "bar")
+            .map(|r| r["bar_index"].as_u64().unwrap())
+            .collect();
+        let flow_indexes: Vec<u64> = rows
+            .iter()
+            .filter(|r| r["kind"] == "flow")
+            .map(|r| r["bar_index"].as_u64().unwrap())
+            .collect();
+        assert_eq!(bar_indexes.len(), completed);
+        assert_eq!(flow_indexes, bar_indexes);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_sqlite_sink_row_counts() {
+        let path = std::env::temp_dir().join(format!("rusto-sink-{}.db", uuid::Uuid::new_v4()));
+        let path = path.display().to_string();
+        let mut sink = MarketSink::open("Sqlite", &path).unwrap();
+        let completed = run(&mut sink);
+
+        let conn = Connection::open(&path).unwrap();
+        let count = |table: &str| -> usize {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap() as usize
+        };
+        assert_eq!(count("bars"), completed);
+        assert_eq!(count("flows"), completed);
+        let unmatched: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM flows f LEFT JOIN bars b
+                 ON b.symbol = f.symbol AND b.bar_index = f.bar_index WHERE b.bar_index IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(unmatched, 0);
+        let _ = std::fs::remove_file(&path);
+    }
+}
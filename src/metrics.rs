@@ -0,0 +1,189 @@
+use crate::types::MarketEvent;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Shared handle to the feed metrics
+pub type SharedFeedMetrics = Arc<Mutex<FeedMetrics>>;
+
+/// Per-symbol message rates over the last completed window
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SymbolRates {
+    pub trades_per_sec: f64,
+    pub depth_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counts {
+    trades: u64,
+    depth: u64,
+}
+
+impl Counts {
+    fn bump(&mut self, is_trade: bool) {
+        if is_trade {
+            self.trades += 1;
+        } else {
+            self.depth += 1;
+        }
+    }
+}
+
+/// Per-symbol trade and depth/book message counters with windowed rates
+pub struct FeedMetrics {
+    window_start: DateTime<Utc>,
+    /// Counts in the current window
+    window: BTreeMap<String, Counts>,
+    /// Counts since startup
+    totals: BTreeMap<String, Counts>,
+    /// Rates over the last completed window
+    rates: BTreeMap<String, SymbolRates>,
+}
+
+impl FeedMetrics {
+    pub fn new(symbols: &[String], now: DateTime<Utc>) -> Self {
+        // Pre-register symbols so a dead feed reports zero instead of nothing
+        let zero: BTreeMap<String, Counts> =
+            symbols.iter().map(|s| (s.clone(), Counts::default())).collect();
+        Self {
+            window_start: now,
+            window: zero.clone(),
+            totals: zero,
+            rates: BTreeMap::new(),
+        }
+    }
+
+    pub fn shared(symbols: &[String], now: DateTime<Utc>) -> SharedFeedMetrics {
+        Arc::new(Mutex::new(Self::new(symbols, now)))
+    }
+
+    pub fn record(&mut self, event: &MarketEvent) {
+        let (symbol, is_trade) = match event {
+            MarketEvent::Trade(t) => (&t.symbol, true),
+            MarketEvent::Depth(d) => (&d.symbol, false),
+            MarketEvent::BookTicker(b) => (&b.symbol, false),
+            MarketEvent::Reconnected { .. } => return,
+        };
+        for counts in [&mut self.window, &mut self.totals] {
+            match counts.get_mut(symbol) {
+                Some(entry) => entry.bump(is_trade),
+                None => {
+                    let mut entry = Counts::default();
+                    entry.bump(is_trade);
+                    counts.insert(symbol.clone(), entry);
+                }
+            }
+        }
+    }
+
+    /// Close the current window: compute per-second rates and start a new one
+    pub fn roll(&mut self, now: DateTime<Utc>) -> &BTreeMap<String, SymbolRates> {
+        let elapsed = (now - self.window_start).num_milliseconds() as f64 / 1000.0;
+        if elapsed > 0.0 {
+            self.rates = self
+                .window
+                .iter()
+                .map(|(symbol, c)| {
+                    let rates = SymbolRates {
+                        trades_per_sec: c.trades as f64 / elapsed,
+                        depth_per_sec: c.depth as f64 / elapsed,
+                    };
+                    (symbol.clone(), rates)
+                })
+                .collect();
+            for counts in self.window.values_mut() {
+                *counts = Counts::default();
+            }
+            self.window_start = now;
+        }
+        &self.rates
+    }
+
+    pub fn rates(&self) -> &BTreeMap<String, SymbolRates> {
+        &self.rates
+    }
+
+    /// Prometheus text exposition of totals and last-window rates
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# TYPE rusto_trades_total counter");
+        for (symbol, c) in &self.totals {
+            let _ = writeln!(out, "rusto_trades_total{{symbol=\"{}\"}} {}", symbol, c.trades);
+        }
+        let _ = writeln!(out, "# TYPE rusto_depth_updates_total counter");
+        for (symbol, c) in &self.totals {
+            let _ = writeln!(out, "rusto_depth_updates_total{{symbol=\"{}\"}} {}", symbol, c.depth);
+        }
+        let _ = writeln!(out, "# TYPE rusto_trades_per_second gauge");
+        for (symbol, r) in &self.rates {
+            let _ = writeln!(out, "rusto_trades_per_second{{symbol=\"{}\"}} {}", symbol, r.trades_per_sec);
+        }
+        let _ = writeln!(out, "# TYPE rusto_depth_updates_per_second gauge");
+        for (symbol, r) in &self.rates {
+            let _ = writeln!(out, "rusto_depth_updates_per_second{{symbol=\"{}\"}} {}", symbol, r.depth_per_sec);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DepthUpdate, NormalizedTrade, Side};
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+
+    fn trade(symbol: &str) -> MarketEvent {
+        MarketEvent::Trade(NormalizedTrade {
+            symbol: symbol.to_string(),
+            price: Decimal::ONE,
+            quantity: Decimal::ONE,
+            side: Side::Buy,
+            timestamp: Utc::now(),
+            trade_id: 0,
+        })
+    }
+
+    fn depth(symbol: &str) -> MarketEvent {
+        MarketEvent::Depth(DepthUpdate {
+            symbol: symbol.to_string(),
+            bids: vec![],
+            asks: vec![],
+            timestamp: Utc::now(),
+        })
+    }
+
+    #[test]
+    fn test_per_symbol_rates() {
+        let start = Utc::now();
+        let symbols = vec!["btcusdt".to_string(), "ethusdt".to_string(), "dogeusdt".to_string()];
+        let mut metrics = FeedMetrics::new(&symbols, start);
+
+        // 10s window: btc 50 trades + 100 depth, eth 20 trades, doge silent
+        for _ in 0..50 {
+            metrics.record(&trade("btcusdt"));
+        }
+        for _ in 0..100 {
+            metrics.record(&depth("btcusdt"));
+        }
+        for _ in 0..20 {
+            metrics.record(&trade("ethusdt"));
+        }
+
+        let rates = metrics.roll(start + Duration::seconds(10)).clone();
+        assert_eq!(rates["btcusdt"], SymbolRates { trades_per_sec: 5.0, depth_per_sec: 10.0 });
+        assert_eq!(rates["ethusdt"], SymbolRates { trades_per_sec: 2.0, depth_per_sec: 0.0 });
+        assert_eq!(rates["dogeusdt"], SymbolRates::default());
+
+        // Next window starts from zero; totals keep counting
+        metrics.record(&trade("btcusdt"));
+        let rates = metrics.roll(start + Duration::seconds(15));
+        assert_eq!(rates["btcusdt"].trades_per_sec, 0.2);
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("rusto_trades_total{symbol=\"btcusdt\"} 51"));
+        assert!(text.contains("rusto_depth_updates_total{symbol=\"btcusdt\"} 100"));
+        assert!(text.contains("rusto_trades_per_second{symbol=\"btcusdt\"} 0.2"));
+    }
+}
@@ -12,12 +12,19 @@ pub struct OrderFlowTracker {
     large_volume_multiplier: Decimal,
     volume_baseline_bars: usize,
     volume_burst_multiplier: Decimal,
+    volume_rate_window_bars: usize,
+    /// Minimum level volume (× average bar volume) to qualify as absorption
+    absorption_min_volume_ratio: Decimal,
+    /// Bars with fewer footprint levels are never flagged as absorption
+    absorption_min_levels: usize,
     /// Per-symbol cumulative volume delta
     cvd: BTreeMap<String, Decimal>,
     /// Recent bar deltas for average calculation
     recent_deltas: BTreeMap<String, Vec<Decimal>>,
     /// Recent bar volumes for per-symbol burst detection
     recent_volumes: BTreeMap<String, Vec<Decimal>>,
+    /// Durations (ms) of the bars in `recent_volumes`, for volume rates
+    recent_durations: BTreeMap<String, Vec<i64>>,
     /// CVD history for 1-minute tracking (timestamp, cvd_value)
     cvd_history: BTreeMap<String, Vec<(DateTime<Utc>, Decimal)>>,
 }
@@ -33,9 +40,14 @@ impl OrderFlowTracker {
             volume_baseline_bars: config.volume_baseline_bars.max(5),
             volume_burst_multiplier: Decimal::try_from(config.volume_burst_multiplier)
                 .unwrap_or(Decimal::new(18, 1)),
+            volume_rate_window_bars: config.volume_rate_window_bars.max(1),
+            absorption_min_volume_ratio: Decimal::try_from(config.absorption_min_volume_ratio)
+                .unwrap_or(Decimal::ZERO),
+            absorption_min_levels: config.absorption_min_levels,
             cvd: BTreeMap::new(),
             recent_deltas: BTreeMap::new(),
             recent_volumes: BTreeMap::new(),
+            recent_durations: BTreeMap::new(),
             cvd_history: BTreeMap::new(),
         }
     }
@@ -137,22 +149,31 @@ impl OrderFlowTracker {
         if volumes.len() > self.volume_baseline_bars {
             volumes.remove(0);
         }
+        let durations = self
+            .recent_durations
+            .entry(bar.symbol.clone())
+            .or_default();
+        durations.push((bar.close_time - bar.open_time).num_milliseconds().max(0));
+        if durations.len() > self.volume_baseline_bars {
+            durations.remove(0);
+        }
 
         // Absorption detection from footprint
         let (absorption_detected, absorption_side) = self.detect_absorption(bar);
 
         // Imbalance ratio
         let imbalance_ratio = if bar.sell_volume > Decimal::ZERO {
-            bar.buy_volume / bar.sell_volume
+            Some(bar.buy_volume / bar.sell_volume)
         } else if bar.buy_volume > Decimal::ZERO {
-            Decimal::from(999)
+            None
         } else {
-            Decimal::ONE
+            Some(Decimal::ONE)
         };
 
         // Get CVD 1-minute change
         let (cvd_1min_change, cvd_rapid_drop, cvd_rapid_rise) = self.get_cvd_1min_change(&bar.symbol, bar.close_time);
         let (avg_bar_volume, volume_burst_ratio, volume_burst) = self.get_volume_burst_metrics(&bar.symbol, bar.volume);
+        let volume_rate_ratio = self.get_volume_rate_ratio(&bar.symbol);
 
         if absorption_detected {
             info!(
@@ -181,10 +202,33 @@ impl OrderFlowTracker {
             avg_bar_volume,
             volume_burst_ratio,
             volume_burst,
+            volume_rate_ratio,
             timestamp: Utc::now(),
         }
     }
 
+    /// Volume per second over the last `volume_rate_window_bars` bars relative to the
+    /// whole baseline window. Range bars close on price, not time, so a quiet market
+    /// shows up as long bars rather than small ones.
+    fn get_volume_rate_ratio(&self, symbol: &str) -> Option<Decimal> {
+        let (volumes, durations) = (self.recent_volumes.get(symbol)?, self.recent_durations.get(symbol)?);
+        if volumes.len() < self.volume_rate_window_bars.max(5) || volumes.len() != durations.len() {
+            return None;
+        }
+        let rate = |volumes: &[Decimal], durations: &[i64]| {
+            let ms: i64 = durations.iter().sum();
+            (ms > 0).then(|| volumes.iter().copied().sum::<Decimal>() / Decimal::from(ms))
+        };
+        let recent_start = volumes.len() - self.volume_rate_window_bars;
+        match (
+            rate(&volumes[recent_start..], &durations[recent_start..]),
+            rate(volumes, durations),
+        ) {
+            (Some(recent), Some(baseline)) if baseline > Decimal::ZERO => Some(recent / baseline),
+            _ => None,
+        }
+    }
+
     fn get_volume_burst_metrics(
         &self,
         symbol: &str,
@@ -209,14 +253,28 @@ impl OrderFlowTracker {
     /// Sell absorption: high bid_volume (aggressive sellers) but price didn't fall
     /// Buy absorption: high ask_volume (aggressive buyers) but price didn't rise
     fn detect_absorption(&self, bar: &RangeBar) -> (bool, Option<Side>) {
+        // One bucket holding the whole bar says nothing about a level holding
+        if bar.footprint.len() < self.absorption_min_levels {
+            return (false, None);
+        }
+
         // Price movement relative to range
         let price_delta = bar.close - bar.open;
         let price_delta_abs = price_delta.abs();
 
+        // Absolute volume floor: a lopsided but thin level is noise
+        let min_level_volume = match self.recent_volumes.get(&bar.symbol) {
+            Some(v) if !v.is_empty() => {
+                let avg = v.iter().copied().sum::<Decimal>() / Decimal::from(v.len() as u64);
+                avg * self.absorption_min_volume_ratio
+            }
+            _ => bar.volume * self.absorption_min_volume_ratio,
+        };
+
         // Check each footprint level for absorption patterns
         for level in bar.footprint.values() {
             let total = level.bid_volume + level.ask_volume;
-            if total == Decimal::ZERO {
+            if total == Decimal::ZERO || total < min_level_volume {
                 continue;
             }
 
@@ -256,3 +314,148 @@ impl OrderFlowTracker {
         bar.volume > avg_volume * self.large_volume_multiplier
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FootprintLevel;
+
+    fn tracker(min_volume_ratio: f64) -> OrderFlowTracker {
+        let mut config: OrderFlowConfig = toml::from_str(
+            r#"
+            absorption_delta_ratio = 3.0
+            max_price_delta_ticks = 2
+            large_volume_multiplier = 2.0
+            "#,
+        )
+        .unwrap();
+        config.absorption_min_volume_ratio = min_volume_ratio;
+        OrderFlowTracker::new(&config)
+    }
+
+    /// Flat bar of `volume` whose footprint has one lopsided level (bid, ask)
+    fn bar(volume: i64, bid: Decimal, ask: Decimal) -> RangeBar {
+        let mut footprint = BTreeMap::new();
+        footprint.insert(
+            "100.0".to_string(),
+            FootprintLevel {
+                bid_volume: bid,
+                ask_volume: ask,
+            },
+        );
+        RangeBar {
+            symbol: "btcusdt".to_string(),
+            open: Decimal::from(100),
+            high: Decimal::from(101),
+            low: Decimal::from(99),
+            close: Decimal::from(100),
+            volume: Decimal::from(volume),
+            buy_volume: Decimal::from(volume / 2),
+            sell_volume: Decimal::from(volume / 2),
+            open_time: Utc::now(),
+            close_time: Utc::now(),
+            footprint,
+            bar_index: 0,
+        }
+    }
+
+    #[test]
+    fn test_volume_rate_ratio_compares_recent_to_baseline() {
+        let mut flow = tracker(0.0);
+        let timed = |secs: i64| {
+            let mut b = bar(100, Decimal::ONE, Decimal::ONE);
+            b.close_time = b.open_time + Duration::seconds(secs);
+            b
+        };
+        // Baseline: 100 volume every 10s; then five bars taking 40s each
+        for _ in 0..15 {
+            flow.analyze_bar(&timed(10));
+        }
+        assert_eq!(flow.analyze_bar(&timed(10)).volume_rate_ratio, Some(Decimal::ONE));
+        let mut quiet = None;
+        for _ in 0..5 {
+            quiet = flow.analyze_bar(&timed(40)).volume_rate_ratio;
+        }
+        let quiet = quiet.unwrap();
+        // Recent 500 / 200s vs baseline 2100 / 360s
+        let expected = Decimal::new(25, 1) / (Decimal::from(2100) / Decimal::from(360));
+        assert_eq!(quiet.round_dp(6), expected.round_dp(6));
+        assert!(quiet < Decimal::ONE);
+    }
+
+    #[test]
+    fn test_imbalance_ratio_is_unbounded_without_sells() {
+        let mut flow = tracker(0.0);
+        assert_eq!(flow.analyze_bar(&bar(100, Decimal::ONE, Decimal::ONE)).imbalance_ratio, Some(Decimal::ONE));
+
+        let buys_only = RangeBar {
+            buy_volume: Decimal::from(100),
+            sell_volume: Decimal::ZERO,
+            ..bar(100, Decimal::ONE, Decimal::ONE)
+        };
+        assert_eq!(flow.analyze_bar(&buys_only).imbalance_ratio, None);
+
+        let idle = RangeBar {
+            buy_volume: Decimal::ZERO,
+            sell_volume: Decimal::ZERO,
+            ..bar(0, Decimal::ONE, Decimal::ONE)
+        };
+        assert_eq!(flow.analyze_bar(&idle).imbalance_ratio, Some(Decimal::ONE));
+    }
+
+    #[test]
+    fn test_absorption_needs_minimum_distinct_levels() {
+        let mut config: OrderFlowConfig = toml::from_str(
+            r#"
+            absorption_delta_ratio = 3.0
+            max_price_delta_ticks = 2
+            large_volume_multiplier = 2.0
+            absorption_min_levels = 3
+            "#,
+        )
+        .unwrap();
+        let mut flow = OrderFlowTracker::new(&config);
+
+        // Coarse bucketing: the whole bar lands in one lopsided level
+        let single = bar(100, Decimal::from(40), Decimal::from(10));
+        assert!(!flow.analyze_bar(&single).absorption_detected);
+
+        let mut multi = single.clone();
+        for key in ["99.9", "100.1"] {
+            multi.footprint.insert(
+                key.to_string(),
+                FootprintLevel {
+                    bid_volume: Decimal::from(5),
+                    ask_volume: Decimal::from(5),
+                },
+            );
+        }
+        let metrics = flow.analyze_bar(&multi);
+        assert!(metrics.absorption_detected);
+        assert_eq!(metrics.absorption_side, Some(Side::Sell));
+
+        config.absorption_min_levels = 1;
+        assert!(OrderFlowTracker::new(&config).analyze_bar(&single).absorption_detected);
+    }
+
+    #[test]
+    fn test_absorption_requires_minimum_level_volume() {
+        let thin = || bar(100, Decimal::TWO, Decimal::new(5, 1));
+        let heavy = || bar(100, Decimal::from(40), Decimal::from(10));
+
+        // Ratio-only (default): the thin 2 vs 0.5 level qualifies
+        let mut ratio_only = tracker(0.0);
+        assert!(ratio_only.analyze_bar(&thin()).absorption_detected);
+
+        // Level must hold at least 25% of the average bar volume (100)
+        let mut flow = tracker(0.25);
+        for _ in 0..5 {
+            flow.analyze_bar(&bar(100, Decimal::ONE, Decimal::ONE));
+        }
+        assert!(!flow.analyze_bar(&thin()).absorption_detected);
+
+        let metrics = flow.analyze_bar(&heavy());
+        assert!(metrics.absorption_detected);
+        assert_eq!(metrics.absorption_side, Some(Side::Sell));
+    }
+}
@@ -1,14 +1,36 @@
 use crate::config::RangeBarConfig;
 use crate::types::{FootprintLevel, NormalizedTrade, RangeBar, Side};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use std::collections::BTreeMap;
-use tracing::info;
+use tracing::{info, warn};
+
+/// What to do with in-progress bars when the feed reconnects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectBarMode {
+    /// Carry on building; the bar spans the gap
+    Keep,
+    /// Emit the bar as-is at its last pre-gap trade
+    Close,
+    /// Drop the bar; the first post-gap trade opens a fresh one
+    Discard,
+}
+
+impl ReconnectBarMode {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "close" => Self::Close,
+            "discard" => Self::Discard,
+            _ => Self::Keep,
+        }
+    }
+}
 
 /// Builds range bars from a stream of normalized trades.
 /// A new bar is completed when price moves `range_size` from the bar's open.
 pub struct RangeBarBuilder {
     config: RangeBarConfig,
+    reconnect_mode: ReconnectBarMode,
     /// Per-symbol state
     builders: BTreeMap<String, SymbolBarState>,
     /// Precomputed ranges override config for dynamically selected symbols
@@ -29,14 +51,15 @@ struct BuildingBar {
     volume: Decimal,
     buy_volume: Decimal,
     sell_volume: Decimal,
-    open_time: chrono::DateTime<Utc>,
+    open_time: DateTime<Utc>,
+    last_trade_time: DateTime<Utc>,
     footprint: BTreeMap<String, FootprintLevel>,
 }
 
 impl BuildingBar {
-    fn new(trade: &NormalizedTrade) -> Self {
+    fn new(trade: &NormalizedTrade, price_dp: u32) -> Self {
         let mut footprint = BTreeMap::new();
-        let key = price_key(trade.price);
+        let key = price_key(trade.price, price_dp);
         let mut level = FootprintLevel::default();
         match trade.side {
             Side::Buy => level.ask_volume = trade.quantity,
@@ -61,12 +84,14 @@ impl BuildingBar {
                 Decimal::ZERO
             },
             open_time: trade.timestamp,
+            last_trade_time: trade.timestamp,
             footprint,
         }
     }
 
-    fn update(&mut self, trade: &NormalizedTrade) {
+    fn update(&mut self, trade: &NormalizedTrade, price_dp: u32) {
         self.close = trade.price;
+        self.last_trade_time = trade.timestamp;
         if trade.price > self.high {
             self.high = trade.price;
         }
@@ -79,7 +104,7 @@ impl BuildingBar {
             Side::Sell => self.sell_volume += trade.quantity,
         }
 
-        let key = price_key(trade.price);
+        let key = price_key(trade.price, price_dp);
         let level = self.footprint.entry(key).or_default();
         match trade.side {
             Side::Buy => level.ask_volume += trade.quantity,
@@ -90,16 +115,34 @@ impl BuildingBar {
     fn range(&self) -> Decimal {
         self.high - self.low
     }
+
+    fn finish(self, symbol: &str, close_time: DateTime<Utc>, bar_index: u64) -> RangeBar {
+        RangeBar {
+            symbol: symbol.to_string(),
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            buy_volume: self.buy_volume,
+            sell_volume: self.sell_volume,
+            open_time: self.open_time,
+            close_time,
+            footprint: self.footprint,
+            bar_index,
+        }
+    }
 }
 
 /// Quantize price to a string key for footprint bucketing
-fn price_key(price: Decimal) -> String {
-    price.round_dp(1).to_string()
+fn price_key(price: Decimal, dp: u32) -> String {
+    price.round_dp(dp).to_string()
 }
 
 impl RangeBarBuilder {
     pub fn new(config: RangeBarConfig) -> Self {
         Self {
+            reconnect_mode: ReconnectBarMode::from_name(&config.on_reconnect),
             config,
             builders: BTreeMap::new(),
             precomputed_ranges: BTreeMap::new(),
@@ -115,6 +158,7 @@ impl RangeBarBuilder {
     pub fn process_trade(&mut self, trade: &NormalizedTrade) -> Option<RangeBar> {
         let precomputed = self.precomputed_ranges.get(&trade.symbol).copied();
         let config = &self.config;
+        let price_dp = config.footprint_price_dp;
         let state = self
             .builders
             .entry(trade.symbol.clone())
@@ -126,30 +170,18 @@ impl RangeBarBuilder {
 
         match &mut state.current {
             None => {
-                state.current = Some(BuildingBar::new(trade));
+                state.current = Some(BuildingBar::new(trade, price_dp));
                 None
             }
             Some(bar) => {
-                bar.update(trade);
+                bar.update(trade, price_dp);
 
                 if bar.range() >= state.range_size {
                     let completed = state.current.take().unwrap();
                     state.bar_count += 1;
 
-                    let range_bar = RangeBar {
-                        symbol: trade.symbol.clone(),
-                        open: completed.open,
-                        high: completed.high,
-                        low: completed.low,
-                        close: completed.close,
-                        volume: completed.volume,
-                        buy_volume: completed.buy_volume,
-                        sell_volume: completed.sell_volume,
-                        open_time: completed.open_time,
-                        close_time: trade.timestamp,
-                        footprint: completed.footprint,
-                        bar_index: state.bar_count,
-                    };
+                    let range_bar =
+                        completed.finish(&trade.symbol, trade.timestamp, state.bar_count);
 
                     info!(
                         symbol = %range_bar.symbol,
@@ -164,7 +196,7 @@ impl RangeBarBuilder {
                     );
 
                     // Start new bar with current trade
-                    state.current = Some(BuildingBar::new(trade));
+                    state.current = Some(BuildingBar::new(trade, price_dp));
 
                     Some(range_bar)
                 } else {
@@ -173,4 +205,94 @@ impl RangeBarBuilder {
             }
         }
     }
+
+    /// Apply the reconnect policy to every in-progress bar. Returns bars closed
+    /// as-is in `Close` mode, in symbol order.
+    pub fn on_reconnect(&mut self) -> Vec<RangeBar> {
+        if self.reconnect_mode == ReconnectBarMode::Keep {
+            return Vec::new();
+        }
+        let mut closed = Vec::new();
+        for (symbol, state) in self.builders.iter_mut() {
+            let Some(bar) = state.current.take() else {
+                continue;
+            };
+            match self.reconnect_mode {
+                ReconnectBarMode::Close => {
+                    state.bar_count += 1;
+                    let close_time = bar.last_trade_time;
+                    closed.push(bar.finish(symbol, close_time, state.bar_count));
+                }
+                ReconnectBarMode::Keep => state.current = Some(bar),
+                ReconnectBarMode::Discard => {
+                    warn!(
+                        symbol = %symbol,
+                        open = %bar.open,
+                        volume = %bar.volume,
+                        "Discarding in-progress range bar after reconnect"
+                    );
+                }
+            }
+        }
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(price: i64, secs: i64) -> NormalizedTrade {
+        NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+            side: Side::Buy,
+            timestamp: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+            trade_id: secs as u64,
+        }
+    }
+
+    fn builder(on_reconnect: &str) -> RangeBarBuilder {
+        let config: RangeBarConfig = toml::from_str(&format!(
+            "default = 10.0\non_reconnect = \"{}\"",
+            on_reconnect
+        ))
+        .unwrap();
+        RangeBarBuilder::new(config)
+    }
+
+    #[test]
+    fn test_discard_drops_bar_spanning_reconnect() {
+        let mut bars = builder("Discard");
+        assert!(bars.process_trade(&trade(100, 0)).is_none());
+        assert!(bars.process_trade(&trade(105, 1)).is_none());
+
+        assert!(bars.on_reconnect().is_empty());
+
+        // Post-gap trades start a fresh bar at 120 rather than completing the 100 bar
+        assert!(bars.process_trade(&trade(120, 60)).is_none());
+        assert!(bars.process_trade(&trade(125, 61)).is_none());
+        let bar = bars.process_trade(&trade(130, 62)).unwrap();
+        assert_eq!(bar.open, Decimal::from(120));
+        assert_eq!(bar.volume, Decimal::from(3));
+        assert_eq!(bar.bar_index, 1);
+    }
+
+    #[test]
+    fn test_close_emits_partial_bar_at_last_pre_gap_trade() {
+        let mut bars = builder("Close");
+        bars.process_trade(&trade(100, 0));
+        bars.process_trade(&trade(104, 5));
+
+        let closed = bars.on_reconnect();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].close, Decimal::from(104));
+        assert_eq!(closed[0].close_time, trade(0, 5).timestamp);
+
+        let mut kept = builder("Keep");
+        kept.process_trade(&trade(100, 0));
+        assert!(kept.on_reconnect().is_empty());
+        assert!(kept.process_trade(&trade(110, 60)).is_some());
+    }
 }
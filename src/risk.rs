@@ -1,10 +1,20 @@
 use crate::config::RiskConfig;
-use crate::types::{Position, SetupType, Side, TradeSignal};
+use crate::types::{Position, PositionStatus, SetupType, Side, TradeSignal};
 use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use std::collections::BTreeMap;
 use tracing::{info, warn};
 
+/// How the emergency stop distance is derived
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EmergencyStop {
+    Off,
+    /// Multiple of the entry-to-stop distance
+    StopMultiple(Decimal),
+    /// Percent from entry
+    Percent(Decimal),
+}
+
 /// Manages risk: position sizing, break-even stops, daily limits
 pub struct RiskManager {
     config: RiskConfig,
@@ -21,11 +31,35 @@ pub struct RiskManager {
     max_confidence_scale: Decimal,
     consecutive_loss_limit: u32,
     symbol_cooldown: Duration,
+    max_open_notional_ratio: Decimal,
+    liquidation_cooldown: Duration,
+    stop_min_hold: Duration,
+    emergency_stop: EmergencyStop,
+    /// (drawdown ratio, size scale), shallowest first
+    drawdown_tiers: Vec<(Decimal, Decimal)>,
+    /// Highest balance seen; drawdown is measured from here
+    peak_balance: Decimal,
+    /// Trading halts once balance drops below this (None = off)
+    balance_floor: Option<Decimal>,
+    /// Balance has fallen below the floor (alerted once)
+    floor_breached: bool,
+    /// Floor breach not yet picked up by the engine's alerting
+    floor_breach_pending: bool,
+    /// Account-wide entry pause after a liquidation
+    account_cooldown_until: Option<DateTime<Utc>>,
+    /// Notional (entry_price * quantity) of each open position
+    open_notional: BTreeMap<String, Decimal>, // position_id -> notional
     /// Currently open positions per symbol
     open_positions: BTreeMap<String, Vec<String>>, // symbol -> position_ids
+    /// Currently open positions per setup
+    open_by_setup: BTreeMap<String, usize>, // setup name -> count
     symbol_loss_streak: BTreeMap<String, u32>,
     symbol_cooldown_until: BTreeMap<String, DateTime<Utc>>,
     daily_halted: bool,
+    /// Set by an operator/safety halt; unlike the daily halt it survives the daily reset
+    halt_reason: Option<String>,
+    /// Entries opened since the last daily reset
+    daily_trades: u32,
     leverage: Decimal,
 }
 
@@ -54,22 +88,90 @@ impl RiskManager {
             consecutive_loss_limit: config.consecutive_loss_limit.max(1),
             symbol_cooldown: Duration::try_minutes(config.symbol_cooldown_minutes as i64)
                 .unwrap_or_else(|| Duration::minutes(30)),
+            max_open_notional_ratio: Decimal::try_from(config.max_open_notional_ratio)
+                .unwrap_or(Decimal::from(20)),
+            liquidation_cooldown: Duration::try_minutes(config.liquidation_cooldown_minutes as i64)
+                .unwrap_or_else(Duration::zero),
+            stop_min_hold: Duration::try_seconds(config.stop_min_hold_secs as i64)
+                .unwrap_or_else(Duration::zero),
+            emergency_stop: {
+                let value = Decimal::try_from(config.emergency_stop_value).unwrap_or(Decimal::TWO);
+                match config.emergency_stop_mode.to_lowercase().as_str() {
+                    "off" => EmergencyStop::Off,
+                    "percent" => EmergencyStop::Percent(value),
+                    _ => EmergencyStop::StopMultiple(value),
+                }
+            },
+            drawdown_tiers: {
+                let mut tiers: Vec<(Decimal, Decimal)> = config
+                    .drawdown_tiers
+                    .iter()
+                    .filter_map(|tier| {
+                        let drawdown = Decimal::try_from(tier.drawdown_pct).ok()? / Decimal::from(100);
+                        Some((drawdown, Decimal::try_from(tier.size_scale).ok()?))
+                    })
+                    .collect();
+                tiers.sort_by_key(|tier| tier.0);
+                tiers
+            },
+            peak_balance: balance,
+            balance_floor: {
+                let absolute = Decimal::try_from(config.balance_floor).unwrap_or(Decimal::ZERO);
+                let pct = Decimal::try_from(config.balance_floor_pct).unwrap_or(Decimal::ZERO);
+                let floor = absolute.max(balance * pct / Decimal::from(100));
+                (floor > Decimal::ZERO).then_some(floor)
+            },
+            floor_breached: false,
+            floor_breach_pending: false,
+            account_cooldown_until: None,
+            open_notional: BTreeMap::new(),
             open_positions: BTreeMap::new(),
+            open_by_setup: BTreeMap::new(),
             symbol_loss_streak: BTreeMap::new(),
             symbol_cooldown_until: BTreeMap::new(),
             daily_halted: false,
+            halt_reason: None,
+            daily_trades: 0,
             leverage,
         }
     }
 
     /// Check if a new trade is allowed
     pub fn can_trade(&self, signal: &TradeSignal) -> bool {
+        self.can_trade_at(signal, Utc::now())
+    }
+
+    /// Check if a new trade is allowed at the given time
+    pub fn can_trade_at(&self, signal: &TradeSignal, now: DateTime<Utc>) -> bool {
         if self.daily_halted {
             warn!("Trading halted: daily loss limit reached");
             return false;
         }
 
-        let now = Utc::now();
+        if let Some(reason) = &self.halt_reason {
+            warn!(reason = %reason, "Trading halted");
+            return false;
+        }
+
+        if self.config.max_daily_trades > 0 && self.daily_trades >= self.config.max_daily_trades {
+            warn!(
+                "Max daily trades reached: {}/{}",
+                self.daily_trades, self.config.max_daily_trades
+            );
+            return false;
+        }
+
+        if let Some(until) = self.account_cooldown_until {
+            if until > now {
+                warn!(
+                    symbol = %signal.symbol,
+                    cooldown_until = %until,
+                    "Account in cooldown after liquidation"
+                );
+                return false;
+            }
+        }
+
         if let Some(until) = self.symbol_cooldown_until.get(&signal.symbol) {
             if *until > now {
                 warn!(
@@ -91,6 +193,26 @@ impl RiskManager {
             return false;
         }
 
+        let setup = signal.setup.to_string();
+        let cap = self
+            .config
+            .max_positions_by_setup
+            .iter()
+            .find(|(name, _)| SetupType::from_name(name) == Some(signal.setup))
+            .map(|(_, &cap)| cap);
+        if let Some(cap) = cap {
+            let open = self.open_by_setup.get(&setup).copied().unwrap_or(0);
+            if open >= cap {
+                warn!(
+                    setup = %setup,
+                    open = open,
+                    cap = cap,
+                    "Max concurrent positions reached for setup"
+                );
+                return false;
+            }
+        }
+
         // Max one position per symbol
         if let Some(positions) = self.open_positions.get(&signal.symbol) {
             if !positions.is_empty() {
@@ -102,13 +224,40 @@ impl RiskManager {
         true
     }
 
+    /// Check that adding `notional` keeps total open notional within the cap.
+    /// The cap is `max_open_notional_ratio * balance`, never above what leverage allows.
+    pub fn can_add_notional(&self, symbol: &str, notional: Decimal) -> bool {
+        if self.balance <= Decimal::ZERO {
+            return false;
+        }
+
+        let ratio_cap = self.max_open_notional_ratio.min(self.leverage);
+        let total = self.open_notional() + notional;
+        let ratio = total / self.balance;
+        if ratio > ratio_cap {
+            warn!(
+                symbol = %symbol,
+                open_notional = %self.open_notional(),
+                new_notional = %notional,
+                ratio = %ratio.round_dp(2),
+                cap = %ratio_cap,
+                "Open notional cap reached"
+            );
+            return false;
+        }
+
+        true
+    }
+
     /// Calculate position size based on risk and leverage
     /// For leveraged trading:
     /// - risk_amount = balance * max_risk_per_trade (what we're willing to lose)
     /// - stop_distance = abs(entry - stop)
     /// - quantity = risk_amount / stop_distance
     /// - required_margin = (entry_price * quantity) / leverage
-    pub fn calculate_position_size(&self, signal: &TradeSignal) -> Decimal {
+    ///
+    /// `leverage` is the leverage the position will open with (per-symbol overrides apply).
+    pub fn calculate_position_size(&self, signal: &TradeSignal, leverage: Decimal) -> Decimal {
         let stop_distance = (signal.entry_price - signal.stop_loss).abs();
         if stop_distance == Decimal::ZERO {
             return Decimal::ZERO;
@@ -127,9 +276,11 @@ impl RiskManager {
                 .min(self.max_confidence_scale);
             quantity *= scale;
         }
+        let drawdown_scale = self.drawdown_size_scale();
+        quantity *= drawdown_scale;
 
         // Calculate required margin for this position
-        let required_margin = (signal.entry_price * quantity) / self.leverage;
+        let required_margin = (signal.entry_price * quantity) / leverage;
 
         // Ensure we have enough balance for the margin
         if required_margin > self.balance {
@@ -140,14 +291,15 @@ impl RiskManager {
                 "Insufficient balance for position, reducing size"
             );
             // Reduce quantity to fit available balance
-            let adjusted_quantity = (self.balance * self.leverage) / signal.entry_price;
+            let adjusted_quantity = (self.balance * leverage) / signal.entry_price;
             info!(
                 symbol = %signal.symbol,
                 risk_amount = %risk_amount,
                 stop_distance = %stop_distance,
                 quantity = %adjusted_quantity,
                 required_margin = %self.balance,
-                leverage = %self.leverage,
+                leverage = %leverage,
+                drawdown_scale = %drawdown_scale,
                 "Position size calculated (adjusted)"
             );
             return adjusted_quantity;
@@ -159,7 +311,8 @@ impl RiskManager {
             stop_distance = %stop_distance,
             quantity = %quantity,
             required_margin = %required_margin,
-            leverage = %self.leverage,
+            leverage = %leverage,
+            drawdown_scale = %drawdown_scale,
             "Position size calculated"
         );
 
@@ -168,20 +321,54 @@ impl RiskManager {
 
     /// Register a new open position
     pub fn register_position(&mut self, position: &Position) {
+        self.daily_trades += 1;
         self.open_positions
             .entry(position.symbol.clone())
             .or_insert_with(Vec::new)
             .push(position.id.clone());
+        *self
+            .open_by_setup
+            .entry(position.setup.to_string())
+            .or_insert(0) += 1;
+        self.open_notional
+            .insert(position.id.clone(), position.entry_price * position.quantity);
+    }
+
+    /// Refresh an open position's notional after its size changed
+    pub fn resize_position(&mut self, position: &Position) {
+        if let Some(notional) = self.open_notional.get_mut(&position.id) {
+            *notional = position.entry_price * position.quantity;
+        }
     }
 
     /// Close a position and update PnL
     pub fn close_position(&mut self, position: &Position) {
         if let Some(positions) = self.open_positions.get_mut(&position.symbol) {
+            let before = positions.len();
             positions.retain(|id| id != &position.id);
+            if positions.len() < before {
+                if let Some(count) = self.open_by_setup.get_mut(&position.setup.to_string()) {
+                    *count = count.saturating_sub(1);
+                }
+            }
         }
+        self.open_notional.remove(&position.id);
 
         self.daily_pnl += position.pnl;
         self.balance += position.pnl;
+        self.peak_balance = self.peak_balance.max(self.balance);
+
+        if position.status == PositionStatus::Liquidated
+            && self.liquidation_cooldown > Duration::zero()
+        {
+            let until = position.exit_time.unwrap_or_else(Utc::now) + self.liquidation_cooldown;
+            self.account_cooldown_until = Some(until);
+            warn!(
+                symbol = %position.symbol,
+                cooldown_until = %until,
+                "Liquidation cooldown activated: pausing all entries"
+            );
+        }
 
         if position.pnl < Decimal::ZERO {
             let streak = self
@@ -222,6 +409,34 @@ impl RiskManager {
             );
             self.daily_halted = true;
         }
+
+        if let Some(floor) = self.balance_floor {
+            // Alert once per breach even when already halted for another reason
+            if self.balance < floor && !self.floor_breached {
+                self.floor_breached = true;
+                self.floor_breach_pending = true;
+                if self.halt_reason.is_none() {
+                    self.halt(&format!("balance {} below floor {}", self.balance.round_dp(2), floor));
+                }
+            }
+        }
+    }
+
+    /// In mark-to-market mode, halt once realized plus open unrealized PnL breaches the daily limit
+    pub fn mark_to_market(&mut self, unrealized_pnl: Decimal) {
+        if !self.config.daily_limit_mark_to_market || self.daily_halted {
+            return;
+        }
+        let equity_pnl = self.daily_pnl + unrealized_pnl;
+        if equity_pnl < -self.daily_limit {
+            warn!(
+                daily_pnl = %self.daily_pnl,
+                unrealized_pnl = %unrealized_pnl,
+                limit = %self.daily_limit,
+                "Daily loss limit reached on mark-to-market! Halting trading."
+            );
+            self.daily_halted = true;
+        }
     }
 
     /// Check if stop should be moved to break-even
@@ -263,6 +478,43 @@ impl RiskManager {
         rr >= self.break_even_trigger_rr
     }
 
+    /// Fraction of balance currently below the peak balance
+    pub fn drawdown(&self) -> Decimal {
+        if self.peak_balance <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        ((self.peak_balance - self.balance) / self.peak_balance).max(Decimal::ZERO)
+    }
+
+    /// Size multiplier from the deepest drawdown tier reached (1 when above all tiers)
+    pub fn drawdown_size_scale(&self) -> Decimal {
+        let drawdown = self.drawdown();
+        self.drawdown_tiers
+            .iter()
+            .rev()
+            .find(|(threshold, _)| drawdown >= *threshold)
+            .map(|(_, scale)| *scale)
+            .unwrap_or(Decimal::ONE)
+    }
+
+    /// Grace window after entry during which only the emergency stop is evaluated
+    pub fn stop_min_hold(&self) -> Duration {
+        self.stop_min_hold
+    }
+
+    /// Emergency stop price for a new entry (None when disabled)
+    pub fn emergency_stop_price(&self, side: Side, entry: Decimal, stop: Decimal) -> Option<Decimal> {
+        let distance = match self.emergency_stop {
+            EmergencyStop::Off => return None,
+            EmergencyStop::StopMultiple(multiple) => (entry - stop).abs() * multiple,
+            EmergencyStop::Percent(pct) => entry * pct / Decimal::from(100),
+        };
+        Some(match side {
+            Side::Buy => entry - distance,
+            Side::Sell => entry + distance,
+        })
+    }
+
     pub fn break_even_stop_price(&self, position: &Position) -> Decimal {
         match position.side {
             Side::Buy => position.entry_price + self.break_even_profit_lock_ticks,
@@ -274,22 +526,301 @@ impl RiskManager {
     pub fn reset_daily(&mut self) {
         self.daily_pnl = Decimal::ZERO;
         self.daily_halted = false;
+        self.daily_trades = 0;
         info!("Daily risk stats reset");
     }
 
+    /// Halt new entries until restart
+    pub fn halt(&mut self, reason: &str) {
+        warn!(reason = %reason, "Trading halted");
+        self.halt_reason = Some(reason.to_string());
+    }
+
+    /// Balance floor, once, the first time it was breached (for alerting)
+    pub fn take_floor_breach(&mut self) -> Option<Decimal> {
+        if !std::mem::take(&mut self.floor_breach_pending) {
+            return None;
+        }
+        self.balance_floor
+    }
+
     pub fn is_halted(&self) -> bool {
-        self.daily_halted
+        self.daily_halted || self.halt_reason.is_some()
     }
 
     pub fn balance(&self) -> Decimal {
         self.balance
     }
 
+    /// Sum of entry notional across open positions
+    pub fn open_notional(&self) -> Decimal {
+        self.open_notional.values().sum()
+    }
+
     pub fn daily_pnl(&self) -> Decimal {
         self.daily_pnl
     }
 
+    pub fn daily_trades(&self) -> u32 {
+        self.daily_trades
+    }
+
     pub fn initial_balance(&self) -> Decimal {
         Decimal::try_from(self.config.initial_balance).unwrap_or(Decimal::from(10000))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::position::PositionManager;
+    use crate::types::{ExitReason, MarginType};
+
+    fn risk_config(extra: &str) -> RiskConfig {
+        toml::from_str(&format!(
+            r#"
+            initial_balance = 10000.0
+            max_risk_per_trade = 0.01
+            daily_loss_limit_pct = 0.5
+            max_concurrent_positions = 10
+            break_even_ticks = 3
+            default_stop_ticks = 10
+            default_target_multiplier = 2.0
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    fn buy_signal(symbol: &str) -> TradeSignal {
+        TradeSignal::new(
+            symbol.to_string(),
+            Side::Buy,
+            SetupType::MomentumSqueeze,
+            Decimal::from(100),
+            Decimal::from(99),
+            Decimal::from(102),
+            Decimal::ONE,
+        )
+    }
+
+    fn open(
+        positions: &mut PositionManager,
+        symbol: &str,
+        quantity: Decimal,
+        leverage: Decimal,
+    ) -> Position {
+        let signal = buy_signal(symbol);
+        positions.open_position(
+            &signal,
+            quantity,
+            leverage,
+            MarginType::Isolated,
+            Decimal::new(4, 3),
+            Decimal::new(4, 4),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unrealized_losses_trip_daily_halt_in_mark_to_market_mode() {
+        let leverage = Decimal::from(10);
+        for mark_to_market in [false, true] {
+            let mut config = risk_config(&format!(
+                "daily_limit_mark_to_market = {}",
+                mark_to_market
+            ));
+            config.daily_loss_limit_pct = 0.03;
+            let mut risk = RiskManager::new(&config, leverage);
+            let mut positions = PositionManager::new();
+            for symbol in ["btcusdt", "ethusdt"] {
+                let position = open(&mut positions, symbol, Decimal::from(20), leverage);
+                risk.register_position(&position);
+            }
+
+            // -200 per position: one alone stays inside the 300 limit, both breach it
+            positions.mark_unrealized("btcusdt", Decimal::from(90));
+            risk.mark_to_market(positions.unrealized_pnl());
+            assert!(!risk.is_halted());
+            positions.mark_unrealized("ethusdt", Decimal::from(90));
+            assert_eq!(positions.unrealized_pnl(), Decimal::from(-400));
+            risk.mark_to_market(positions.unrealized_pnl());
+
+            assert_eq!(risk.daily_pnl(), Decimal::ZERO);
+            assert!(positions.closed_positions().is_empty());
+            assert_eq!(risk.is_halted(), mark_to_market);
+        }
+    }
+
+    #[test]
+    fn test_open_notional_cap_rejects_next_entry() {
+        let leverage = Decimal::from(10);
+        let mut risk = RiskManager::new(&risk_config("max_open_notional_ratio = 3.0"), leverage);
+        let mut positions = PositionManager::new();
+        // 100 qty @ 100 = 10_000 notional = 1x balance per position
+        let qty = Decimal::from(100);
+        let notional = Decimal::from(10_000);
+
+        for symbol in ["btcusdt", "ethusdt", "solusdt"] {
+            assert!(risk.can_add_notional(symbol, notional));
+            let pos = open(&mut positions, symbol, qty, leverage);
+            risk.register_position(&pos);
+        }
+        assert_eq!(risk.open_notional(), Decimal::from(30_000));
+        assert!(!risk.can_add_notional("xrpusdt", notional));
+
+        // Closing one frees room again
+        let first = positions.open_positions()[0].id.clone();
+        let closed = positions
+            .close_position(&first, Decimal::from(100), Decimal::ZERO, ExitReason::TakeProfit)
+            .unwrap();
+        risk.close_position(&closed);
+        assert!(risk.can_add_notional("xrpusdt", notional));
+    }
+
+    #[test]
+    fn test_open_notional_cap_clamped_to_leverage() {
+        // Configured cap of 20x is unreachable at 2x leverage
+        let leverage = Decimal::TWO;
+        let mut risk = RiskManager::new(&risk_config("max_open_notional_ratio = 20.0"), leverage);
+        let mut positions = PositionManager::new();
+        let qty = Decimal::from(100);
+        let notional = Decimal::from(10_000);
+
+        for symbol in ["btcusdt", "ethusdt"] {
+            assert!(risk.can_add_notional(symbol, notional));
+            let pos = open(&mut positions, symbol, qty, leverage);
+            risk.register_position(&pos);
+        }
+        assert!(!risk.can_add_notional("solusdt", notional));
+    }
+
+    #[test]
+    fn test_liquidation_pauses_all_entries_until_cooldown_elapses() {
+        let leverage = Decimal::from(10);
+        let mut risk = RiskManager::new(&risk_config("liquidation_cooldown_minutes = 15"), leverage);
+        let mut positions = PositionManager::new();
+        let pos = open(&mut positions, "btcusdt", Decimal::ONE, leverage);
+        risk.register_position(&pos);
+
+        let liquidated = positions.check_liquidations("btcusdt", Decimal::from(80), Decimal::ZERO);
+        assert_eq!(liquidated.len(), 1);
+        risk.close_position(&liquidated[0]);
+        let liquidated_at = liquidated[0].exit_time.unwrap();
+
+        // Other symbols are blocked too, not just the liquidated one
+        let signal = buy_signal("ethusdt");
+        assert!(!risk.can_trade_at(&signal, liquidated_at + Duration::minutes(1)));
+        assert!(!risk.can_trade_at(&signal, liquidated_at + Duration::minutes(14)));
+        assert!(risk.can_trade_at(&signal, liquidated_at + Duration::minutes(16)));
+        assert!(!risk.is_halted());
+    }
+
+    #[test]
+    fn test_daily_trade_cap_blocks_until_reset() {
+        let leverage = Decimal::from(10);
+        let mut risk = RiskManager::new(&risk_config("max_daily_trades = 2"), leverage);
+        let mut positions = PositionManager::new();
+
+        for symbol in ["btcusdt", "ethusdt"] {
+            assert!(risk.can_trade(&buy_signal(symbol)));
+            let pos = open(&mut positions, symbol, Decimal::ONE, leverage);
+            risk.register_position(&pos);
+            let closed = positions
+                .close_position(&pos.id, Decimal::from(100), Decimal::ZERO, ExitReason::TakeProfit)
+                .unwrap();
+            risk.close_position(&closed);
+        }
+        assert_eq!(risk.daily_trades(), 2);
+
+        // Flat and within the loss limit, but out of trades for the day
+        assert!(!risk.can_trade(&buy_signal("solusdt")));
+        assert!(!risk.is_halted());
+
+        risk.reset_daily();
+        assert!(risk.can_trade(&buy_signal("solusdt")));
+    }
+
+    #[test]
+    fn test_per_setup_cap_leaves_room_for_other_setups() {
+        let leverage = Decimal::from(10);
+        let mut risk = RiskManager::new(
+            &risk_config("max_positions_by_setup = { MomentumSqueeze = 2 }"),
+            leverage,
+        );
+        risk.max_concurrent = 4;
+        let mut positions = PositionManager::new();
+
+        for symbol in ["btcusdt", "ethusdt"] {
+            assert!(risk.can_trade(&buy_signal(symbol)));
+            let pos = open(&mut positions, symbol, Decimal::ONE, leverage);
+            risk.register_position(&pos);
+        }
+        assert!(!risk.can_trade(&buy_signal("solusdt")));
+
+        // Other setups fill the rest of the global cap
+        let mut aaa = buy_signal("solusdt");
+        aaa.setup = SetupType::AAA;
+        for symbol in ["solusdt", "xrpusdt"] {
+            aaa.symbol = symbol.to_string();
+            assert!(risk.can_trade(&aaa));
+            let mut pos = open(&mut positions, symbol, Decimal::ONE, leverage);
+            pos.setup = SetupType::AAA;
+            risk.register_position(&pos);
+        }
+        aaa.symbol = "bnbusdt".to_string();
+        assert!(!risk.can_trade(&aaa));
+
+        // Closing a MomentumSqueeze position frees its setup slot
+        let first = positions.open_positions()[0].id.clone();
+        let closed = positions
+            .close_position(&first, Decimal::from(100), Decimal::ZERO, ExitReason::TakeProfit)
+            .unwrap();
+        risk.close_position(&closed);
+        assert!(risk.can_trade(&buy_signal("adausdt")));
+    }
+
+    #[test]
+    fn test_drawdown_tiers_scale_size_and_recover() {
+        let leverage = Decimal::from(10);
+        let mut risk = RiskManager::new(
+            &risk_config(
+                r#"
+                drawdown_tiers = [
+                    { drawdown_pct = 10.0, size_scale = 0.5 },
+                    { drawdown_pct = 5.0, size_scale = 0.75 },
+                ]
+                "#,
+            ),
+            leverage,
+        );
+        let mut positions = PositionManager::new();
+        let mut settle = |risk: &mut RiskManager, exit: i64| {
+            let pos = open(&mut positions, "btcusdt", Decimal::from(100), leverage);
+            risk.register_position(&pos);
+            let closed = positions
+                .close_position(&pos.id, Decimal::from(exit), Decimal::ZERO, ExitReason::StopLoss)
+                .unwrap();
+            risk.close_position(&closed);
+        };
+        // 1% of balance over a 1-point stop
+        let signal = buy_signal("ethusdt");
+        assert_eq!(risk.calculate_position_size(&signal, leverage), Decimal::from(100));
+
+        // -6%: first tier
+        settle(&mut risk, 94);
+        assert_eq!(risk.balance(), Decimal::from(9_400));
+        assert_eq!(risk.calculate_position_size(&signal, leverage), Decimal::new(705, 1));
+
+        // -11%: deeper tier
+        settle(&mut risk, 95);
+        assert_eq!(risk.calculate_position_size(&signal, leverage), Decimal::new(445, 1));
+
+        // Back to the peak: full size again
+        settle(&mut risk, 111);
+        assert_eq!(risk.balance(), Decimal::from(10_000));
+        assert_eq!(risk.drawdown_size_scale(), Decimal::ONE);
+        assert_eq!(risk.calculate_position_size(&signal, leverage), Decimal::from(100));
+    }
+}
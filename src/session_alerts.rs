@@ -0,0 +1,184 @@
+use crate::types::{ExecutionEvent, SessionBreakKind, VolumeProfileSnapshot};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+struct SymbolLevels {
+    /// Latest profile snapshot (session extremes and value area)
+    last: Option<VolumeProfileSnapshot>,
+    prior_vah: Option<Decimal>,
+    prior_val: Option<Decimal>,
+    high_armed: bool,
+    low_armed: bool,
+    vah_broken: bool,
+    val_broken: bool,
+}
+
+/// Emits one alert per break of the session high/low and the prior session's VAH/VAL.
+/// After a session-extreme alert the side re-arms only once price pulls back
+/// `rearm_pct` from the extreme, so a grinding trend alerts once, not every tick.
+pub struct SessionBreakTracker {
+    rearm_ratio: Decimal,
+    symbols: BTreeMap<String, SymbolLevels>,
+}
+
+impl SessionBreakTracker {
+    pub fn new(rearm_pct: f64) -> Self {
+        Self {
+            rearm_ratio: Decimal::try_from(rearm_pct).unwrap_or(Decimal::ZERO) / Decimal::from(100),
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    /// Update reference levels; a later `session_start` means the session reset
+    pub fn on_profile(&mut self, snapshot: &VolumeProfileSnapshot) {
+        let levels = self.symbols.entry(snapshot.symbol.clone()).or_default();
+        match &levels.last {
+            Some(last) if snapshot.session_start > last.session_start => {
+                levels.prior_vah = Some(last.vah);
+                levels.prior_val = Some(last.val);
+                levels.vah_broken = false;
+                levels.val_broken = false;
+                levels.high_armed = true;
+                levels.low_armed = true;
+            }
+            Some(_) => {}
+            None => {
+                levels.high_armed = true;
+                levels.low_armed = true;
+            }
+        }
+        levels.last = Some(snapshot.clone());
+    }
+
+    /// Check a trade price against the levels known before it printed
+    pub fn on_trade(&mut self, symbol: &str, price: Decimal, at: DateTime<Utc>) -> Vec<ExecutionEvent> {
+        let Some(levels) = self.symbols.get_mut(symbol) else {
+            return Vec::new();
+        };
+        let Some(last) = &levels.last else {
+            return Vec::new();
+        };
+        let (high, low) = (last.session_high, last.session_low);
+        let mut breaks = Vec::new();
+
+        if price > high {
+            if levels.high_armed {
+                levels.high_armed = false;
+                breaks.push((SessionBreakKind::NewSessionHigh, high));
+            }
+        } else if price <= high * (Decimal::ONE - self.rearm_ratio) {
+            levels.high_armed = true;
+        }
+        if price < low {
+            if levels.low_armed {
+                levels.low_armed = false;
+                breaks.push((SessionBreakKind::NewSessionLow, low));
+            }
+        } else if price >= low * (Decimal::ONE + self.rearm_ratio) {
+            levels.low_armed = true;
+        }
+        if let Some(vah) = levels.prior_vah {
+            if !levels.vah_broken && price > vah {
+                levels.vah_broken = true;
+                breaks.push((SessionBreakKind::PriorVahBreak, vah));
+            }
+        }
+        if let Some(val) = levels.prior_val {
+            if !levels.val_broken && price < val {
+                levels.val_broken = true;
+                breaks.push((SessionBreakKind::PriorValBreak, val));
+            }
+        }
+
+        breaks
+            .into_iter()
+            .map(|(kind, level)| ExecutionEvent::SessionBreak {
+                symbol: symbol.to_string(),
+                kind,
+                level,
+                price,
+                timestamp: at,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(high: i64, low: i64, vah: i64, val: i64) -> VolumeProfileSnapshot {
+        VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from((vah + val) / 2),
+            vah: Decimal::from(vah),
+            val: Decimal::from(val),
+            total_volume: Decimal::from(1000),
+            session_high: Decimal::from(high),
+            session_low: Decimal::from(low),
+            session_start: DateTime::UNIX_EPOCH,
+            vwap: Decimal::from((vah + val) / 2),
+            hvn: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Feed a trade, then the snapshot the profiler would emit after it
+    fn tick(tracker: &mut SessionBreakTracker, price: i64, high: i64, low: i64) -> Vec<ExecutionEvent> {
+        let events = tracker.on_trade("btcusdt", Decimal::from(price), Utc::now());
+        tracker.on_profile(&snapshot(high, low, 105, 95));
+        events
+    }
+
+    #[test]
+    fn test_new_session_high_alerts_once() {
+        let mut tracker = SessionBreakTracker::new(0.5);
+        tracker.on_profile(&snapshot(110, 90, 105, 95));
+
+        let events = tick(&mut tracker, 111, 111, 90);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            ExecutionEvent::SessionBreak { kind: SessionBreakKind::NewSessionHigh, level, .. }
+                if *level == Decimal::from(110)
+        ));
+
+        // Grinding higher keeps making session highs but does not re-alert
+        for price in 112..120 {
+            assert!(tick(&mut tracker, price, price, 90).is_empty());
+        }
+
+        // A pullback establishes 119 as the extreme; breaking it alerts again
+        assert!(tick(&mut tracker, 118, 119, 90).is_empty());
+        assert_eq!(tick(&mut tracker, 120, 120, 90).len(), 1);
+    }
+
+    #[test]
+    fn test_prior_value_area_break_after_session_reset() {
+        let mut tracker = SessionBreakTracker::new(0.5);
+        tracker.on_profile(&snapshot(110, 90, 105, 95));
+        // New session: range collapses around 100
+        let mut next = snapshot(101, 99, 100, 100);
+        next.session_start += chrono::Duration::hours(24);
+        tracker.on_profile(&next);
+
+        let events = tracker.on_trade("btcusdt", Decimal::from(106), Utc::now());
+        let kinds: Vec<SessionBreakKind> = events
+            .iter()
+            .filter_map(|e| match e {
+                ExecutionEvent::SessionBreak { kind, .. } => Some(*kind),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![SessionBreakKind::NewSessionHigh, SessionBreakKind::PriorVahBreak]
+        );
+        // Prior VAH break is reported once per session
+        assert!(tracker
+            .on_trade("btcusdt", Decimal::from(107), Utc::now())
+            .is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::types::{ExecutionEvent, ProcessingEvent};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Alert-mode replacement for the simulator task: forwards every strategy
+/// signal to the notification layer without opening positions.
+pub async fn forward_signals(
+    mut processing_rx: mpsc::Receiver<ProcessingEvent>,
+    execution_tx: mpsc::Sender<ExecutionEvent>,
+    mut shutdown: tokio::sync::watch::Receiver<bool>,
+) {
+    info!("Signal alert forwarder started (simulator bypassed)");
+
+    loop {
+        tokio::select! {
+            Some(event) = processing_rx.recv() => {
+                if let ProcessingEvent::Signal(signal) = event {
+                    if let Err(e) = execution_tx.try_send(ExecutionEvent::SignalAlert(signal)) {
+                        warn!("Failed to forward signal alert: {}", e);
+                    }
+                }
+            }
+            _ = shutdown.changed() => {
+                if *shutdown.borrow() {
+                    info!("Signal alert forwarder shutting down");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SetupType, Side, TradeSignal};
+    use rust_decimal::Decimal;
+    use tokio::sync::watch;
+
+    #[tokio::test]
+    async fn test_signals_forwarded_as_alerts() {
+        let (processing_tx, processing_rx) = mpsc::channel(10);
+        let (execution_tx, mut execution_rx) = mpsc::channel(10);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn(forward_signals(processing_rx, execution_tx, shutdown_rx));
+
+        let signal = TradeSignal::new(
+            "btcusdt".to_string(),
+            Side::Buy,
+            SetupType::AAA,
+            Decimal::from(100),
+            Decimal::from(99),
+            Decimal::from(103),
+            Decimal::new(7, 1),
+        );
+        processing_tx
+            .send(ProcessingEvent::Signal(signal.clone()))
+            .await
+            .unwrap();
+
+        match execution_rx.recv().await {
+            Some(ExecutionEvent::SignalAlert(alert)) => assert_eq!(alert.id, signal.id),
+            other => panic!("expected SignalAlert, got {:?}", other),
+        }
+
+        let _ = shutdown_tx.send(true);
+        handle.await.unwrap();
+        // Nothing besides the alert was emitted (no position lifecycle events)
+        assert!(execution_rx.try_recv().is_err());
+    }
+}
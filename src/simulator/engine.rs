@@ -1,27 +1,92 @@
-use crate::binance::ExchangeInfoManager;
 use crate::config::SimulatorConfig;
+use crate::exchange::{Exchange, RoundingMode};
+use crate::market_sink::{BookSnapshotWriter, MarketSink};
 use crate::risk::RiskManager;
 use crate::simulator::order_book::LocalOrderBook;
-use crate::simulator::position::PositionManager;
+use crate::simulator::position::{
+    LiquidationModel, MaintenanceBracket, PositionManager, StopLiquidationCheck,
+};
 use crate::simulator::trade_log::TradeLogger;
+use crate::statsd::StatsdEmitter;
+use crate::strategy::lookahead_outcome;
 use crate::types::{
-    BotStats, DepthUpdate, ExecutionEvent, ExitReason, MarginType, MarketEvent, NormalizedTrade,
-    ProcessingEvent, SymbolStats, TradeSignal,
+    AssetExposure, BookTicker, BotStats, DepthUpdate, ExecutionEvent, ExitReason, MarginType, MarketEvent, NormalizedTrade,
+    ProcessingEvent, RangeBar, SetupType, SymbolStats, TradeSignal,
 };
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
-use tracing::{info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::types::VolumeProfileSnapshot;
-use chrono::Timelike;
+use chrono::{DateTime, Duration, Timelike, Utc};
+
+/// Bounds on the volatility multiplier applied to the depth-imbalance edge
+const MIN_IMBALANCE_VOL_SCALE: Decimal = Decimal::from_parts(5, 0, 0, false, 1);
+const MAX_IMBALANCE_VOL_SCALE: Decimal = Decimal::from_parts(3, 0, 0, false, 0);
 
 #[derive(Default, Clone)]
 struct HourlyPerformance {
     pnls: Vec<Decimal>,
 }
 
+/// Reference price that entry/stop/target are anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PriceReference {
+    LastTrade,
+    Microprice,
+}
+
+/// Profile level used for the AdvancedOrderFlow TP1 partial close
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tp1Source {
+    Vwap,
+    Poc,
+}
+
+/// Target used for the AdvancedOrderFlow TP2 full close
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tp2Source {
+    /// VAH for longs, VAL for shorts
+    ValueArea,
+    /// Entry ± risk × tp2_r_multiple
+    RMultiple,
+}
+
+/// Statistic used for per-hour expectancy
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExpectancyEstimator {
+    Mean,
+    Median,
+    /// Mean after dropping this fraction of samples from each tail
+    TrimmedMean(Decimal),
+}
+
+/// A rejected signal waiting for enough bars to evaluate its hypothetical outcome
+struct PendingWhatIf {
+    signal: TradeSignal,
+    bars: Vec<RangeBar>,
+}
+
+/// A signal held until the book imbalance confirms it (entry confirmation mode)
+struct PendingEntry {
+    signal: TradeSignal,
+    /// Event time the window closes; set by the first market event after the signal
+    deadline: Option<DateTime<Utc>>,
+}
+
+/// Entry size the book could not fill, resting as a limit order for an open position
+struct RestingEntry {
+    symbol: String,
+    side: crate::types::Side,
+    limit_price: Decimal,
+    quantity: Decimal,
+    /// Set from the first trade seen after resting (event time)
+    deadline: Option<DateTime<Utc>>,
+}
+
 /// Paper trading execution engine with leverage support
 pub struct SimulatorEngine {
     config: SimulatorConfig,
@@ -29,29 +94,98 @@ pub struct SimulatorEngine {
     position_manager: PositionManager,
     trade_logger: TradeLogger,
     order_books: BTreeMap<String, LocalOrderBook>,
+    /// Depth updates coalesced since the book was last rebuilt (aggregation mode)
+    pending_depth: BTreeMap<String, DepthUpdate>,
+    /// Event time the book was last rebuilt from pending depth, per symbol
+    last_depth_apply: BTreeMap<String, DateTime<Utc>>,
+    depth_aggregation: Option<Duration>,
+    /// Event time of the last full exit evaluation, per symbol (throttle mode)
+    last_exit_check: BTreeMap<String, DateTime<Utc>>,
+    exit_check_interval: Option<Duration>,
+    /// Rejected signals awaiting their what-if lookahead
+    pending_what_ifs: VecDeque<PendingWhatIf>,
+    /// How long an entry waits for the book imbalance to hold (None = enter immediately)
+    entry_confirmation: Option<Duration>,
+    /// Signals waiting out their confirmation window, per symbol
+    pending_entries: BTreeMap<String, PendingEntry>,
+    /// Unfilled entry remainders resting on the book, per position id
+    resting_entries: BTreeMap<String, RestingEntry>,
+    /// Last trade price per symbol (used to flatten positions)
+    last_prices: BTreeMap<String, Decimal>,
+    /// Periodic top-N book snapshots, written off the simulator thread (None = off)
+    book_sink: Option<BookSnapshotWriter>,
+    book_snapshot_interval: Duration,
+    book_snapshot_depth: usize,
+    /// Push telemetry (signals, fills, rejections, latency) over UDP (None = off)
+    statsd: Option<StatsdEmitter>,
+    /// Event time of the last book snapshot, per symbol
+    last_book_snapshot: BTreeMap<String, DateTime<Utc>>,
+    /// Event time of the last trade per symbol (profile staleness clock)
+    last_trade_times: BTreeMap<String, DateTime<Utc>>,
     fee_rate: Decimal,
+    /// Fee on resting-limit fills (take-profit exits, resting entry remainders):
+    /// the maker fee when `maker_fee_on_limit_fills`, else `fee_rate`
+    limit_fee_rate: Decimal,
     execution_tx: Option<mpsc::Sender<ExecutionEvent>>,
     leverage: Decimal,
+    /// Per-symbol leverage overrides keyed by lowercase symbol
+    symbol_leverage: BTreeMap<String, Decimal>,
+    max_leverage: Decimal,
     margin_type: MarginType,
     maintenance_margin_rate: Decimal,
-    exchange_info: Option<Arc<ExchangeInfoManager>>,
+    exchange_info: Option<Arc<dyn Exchange>>,
     latest_profiles: BTreeMap<String, VolumeProfileSnapshot>,
     require_orderbook_for_entry: bool,
+    /// Startup grace during which missing books are counted as warmup
+    orderbook_warmup: Duration,
+    /// Event time of the first market event (start of the book warmup)
+    started_at: Option<DateTime<Utc>>,
+    /// Event time of the latest market event (warmup clock, so replays measure data time)
+    last_event_at: Option<DateTime<Utc>>,
+    /// Signals skipped for a not-yet-received book during warmup
+    book_warmup_skips: u64,
+    /// Sampled RTT above which new entries pause (None = no latency gate)
+    max_entry_latency_ms: Option<f64>,
+    /// Last sampled RTT exceeded the limit
+    latency_paused: bool,
     max_spread_bps: Decimal,
     min_depth_imbalance_ratio: Decimal,
+    /// Realized vol at which the imbalance threshold is unscaled (None = flat threshold)
+    imbalance_vol_reference_bps: Option<Decimal>,
+    /// Recent bars per symbol for realized volatility
+    recent_bars: BTreeMap<String, VecDeque<RangeBar>>,
+    min_liquidation_buffer_ratio: Decimal,
     expectancy_filter_enabled: bool,
     expectancy_min_trades_per_hour: usize,
     expectancy_min_avg_pnl: Decimal,
     expectancy_lookback_trades: usize,
+    expectancy_estimator: ExpectancyEstimator,
+    expectancy_confidence_z: f64,
     slippage_model_enabled: bool,
     max_model_slippage_bps: Decimal,
     impact_depth_levels: usize,
     impact_weight_bps: Decimal,
+    price_reference: PriceReference,
+    tp1_source: Tp1Source,
+    tp2_source: Tp2Source,
+    tp2_r_multiple: Decimal,
+    /// Setups that trail the remainder after TP1 instead of targeting TP2
+    trail_after_tp1_setups: Vec<SetupType>,
+    /// Trailing distance as a fraction of price
+    trail_after_tp1_ratio: Decimal,
+    price_rounding: RoundingMode,
+    quantity_rounding: RoundingMode,
     hourly_performance: BTreeMap<(String, u32), HourlyPerformance>,
     /// Per-symbol trading statistics
     symbol_stats: BTreeMap<String, SymbolStats>,
     /// Shared state read by the hourly reporter task
     bot_stats: Option<Arc<Mutex<BotStats>>>,
+    /// Queue closes in `BotStats.period_closes` (only drained by the daily digest)
+    collect_period_closes: bool,
+    /// Keep the quote-asset/category exposure breakdown in `BotStats` up to date
+    exposure_breakdown: bool,
+    /// Uppercase base asset -> reporting category (e.g. DOGE -> "meme")
+    base_asset_categories: BTreeMap<String, String>,
 }
 
 impl SimulatorEngine {
@@ -61,18 +195,44 @@ impl SimulatorEngine {
         trade_logger: TradeLogger,
     ) -> Self {
         let fee_rate = Decimal::try_from(config.taker_fee).unwrap_or_else(|_| Decimal::new(4, 4));
+        let limit_fee_rate = if config.maker_fee_on_limit_fills {
+            Decimal::try_from(config.maker_fee).unwrap_or(fee_rate)
+        } else {
+            fee_rate
+        };
         let leverage = Decimal::try_from(config.leverage).unwrap_or(Decimal::from(100));
+        let symbol_leverage: BTreeMap<String, Decimal> = config
+            .symbol_leverage
+            .iter()
+            .filter_map(|(symbol, &lev)| Some((symbol.to_lowercase(), Decimal::try_from(lev).ok()?)))
+            .collect();
+        let max_leverage = Decimal::try_from(config.max_leverage).unwrap_or(Decimal::from(125));
         let maintenance_margin_rate = Decimal::try_from(config.maintenance_margin_rate)
             .unwrap_or_else(|_| Decimal::new(4, 3)); // 0.004
         let max_spread_bps = Decimal::try_from(config.max_spread_bps).unwrap_or(Decimal::new(4, 0));
         let min_depth_imbalance_ratio =
             Decimal::try_from(config.min_depth_imbalance_ratio).unwrap_or(Decimal::new(105, 2));
+        let imbalance_vol_reference_bps = Decimal::try_from(config.imbalance_vol_reference_bps)
+            .ok()
+            .filter(|reference| *reference > Decimal::ZERO);
+        let min_liquidation_buffer_ratio =
+            Decimal::try_from(config.min_liquidation_buffer_ratio).unwrap_or(Decimal::ZERO);
         let require_orderbook_for_entry = config.require_orderbook_for_entry;
+        let orderbook_warmup = Duration::seconds(config.orderbook_warmup_secs as i64);
+        let max_entry_latency_ms = Some(config.max_entry_latency_ms).filter(|ms| *ms > 0.0);
         let expectancy_filter_enabled = config.expectancy_filter_enabled;
         let expectancy_min_trades_per_hour = config.expectancy_min_trades_per_hour;
         let expectancy_min_avg_pnl =
             Decimal::try_from(config.expectancy_min_avg_pnl).unwrap_or(Decimal::ZERO);
         let expectancy_lookback_trades = config.expectancy_lookback_trades;
+        let expectancy_estimator = match config.expectancy_estimator.to_lowercase().as_str() {
+            "median" => ExpectancyEstimator::Median,
+            "trimmedmean" => ExpectancyEstimator::TrimmedMean(
+                Decimal::try_from(config.expectancy_trim_pct).unwrap_or(Decimal::TEN) / Decimal::from(100),
+            ),
+            _ => ExpectancyEstimator::Mean,
+        };
+        let expectancy_confidence_z = config.expectancy_confidence_z;
         let slippage_model_enabled = config.slippage_model_enabled;
         let max_model_slippage_bps =
             Decimal::try_from(config.max_model_slippage_bps).unwrap_or(Decimal::new(6, 0));
@@ -83,34 +243,122 @@ impl SimulatorEngine {
             "cross" => MarginType::Cross,
             _ => MarginType::Isolated,
         };
+        let price_reference = match config.price_reference.to_lowercase().as_str() {
+            "microprice" => PriceReference::Microprice,
+            _ => PriceReference::LastTrade,
+        };
+        let tp1_source = match config.tp1_source.to_lowercase().as_str() {
+            "poc" => Tp1Source::Poc,
+            _ => Tp1Source::Vwap,
+        };
+        let tp2_source = match config.tp2_source.to_lowercase().as_str() {
+            "rmultiple" => Tp2Source::RMultiple,
+            _ => Tp2Source::ValueArea,
+        };
+        let tp2_r_multiple = Decimal::try_from(config.tp2_r_multiple).unwrap_or(Decimal::TWO);
+        let trail_after_tp1_setups: Vec<SetupType> = config
+            .trail_after_tp1_setups
+            .iter()
+            .filter_map(|name| SetupType::from_name(name))
+            .collect();
+        let trail_after_tp1_ratio = Decimal::try_from(config.trail_after_tp1_pct)
+            .unwrap_or(Decimal::new(3, 1))
+            / Decimal::from(100);
+        let price_rounding = RoundingMode::from_name(&config.price_rounding);
+        let quantity_rounding = RoundingMode::from_name(&config.quantity_rounding);
+        let depth_aggregation = (config.depth_aggregation_ms > 0)
+            .then(|| Duration::milliseconds(config.depth_aggregation_ms as i64));
+        let exit_check_interval = (config.exit_check_interval_ms > 0)
+            .then(|| Duration::milliseconds(config.exit_check_interval_ms as i64));
+        let entry_confirmation = (config.entry_confirmation_ms > 0)
+            .then(|| Duration::milliseconds(config.entry_confirmation_ms as i64));
+
+        let mut position_manager = PositionManager::new();
+        position_manager
+            .set_stop_liquidation_check(StopLiquidationCheck::from_name(&config.stop_liquidation_check));
+        if config.liquidation_formula.eq_ignore_ascii_case("binance") {
+            let brackets = config
+                .maintenance_brackets
+                .iter()
+                .filter_map(|b| {
+                    Some(MaintenanceBracket {
+                        notional_cap: Decimal::try_from(b.notional_cap).ok()?,
+                        rate: Decimal::try_from(b.rate).ok()?,
+                        amount: Decimal::try_from(b.amount).ok()?,
+                    })
+                })
+                .collect();
+            position_manager.set_liquidation_model(LiquidationModel::Binance(brackets));
+        }
 
         Self {
             config,
             risk_manager,
-            position_manager: PositionManager::new(),
+            position_manager,
             trade_logger,
             order_books: BTreeMap::new(),
+            pending_depth: BTreeMap::new(),
+            last_depth_apply: BTreeMap::new(),
+            depth_aggregation,
+            last_exit_check: BTreeMap::new(),
+            exit_check_interval,
+            pending_what_ifs: VecDeque::new(),
+            entry_confirmation,
+            pending_entries: BTreeMap::new(),
+            resting_entries: BTreeMap::new(),
+            last_prices: BTreeMap::new(),
+            last_trade_times: BTreeMap::new(),
+            book_sink: None,
+            book_snapshot_interval: Duration::zero(),
+            book_snapshot_depth: 0,
+            statsd: None,
+            last_book_snapshot: BTreeMap::new(),
             fee_rate,
+            limit_fee_rate,
             execution_tx: None,
             leverage,
+            symbol_leverage,
+            max_leverage,
             margin_type,
             maintenance_margin_rate,
             exchange_info: None,
             latest_profiles: BTreeMap::new(),
             require_orderbook_for_entry,
+            orderbook_warmup,
+            started_at: None,
+            last_event_at: None,
+            book_warmup_skips: 0,
+            max_entry_latency_ms,
+            latency_paused: false,
             max_spread_bps,
             min_depth_imbalance_ratio,
+            imbalance_vol_reference_bps,
+            recent_bars: BTreeMap::new(),
+            min_liquidation_buffer_ratio,
             expectancy_filter_enabled,
             expectancy_min_trades_per_hour,
             expectancy_min_avg_pnl,
             expectancy_lookback_trades,
+            expectancy_estimator,
+            expectancy_confidence_z,
             slippage_model_enabled,
             max_model_slippage_bps,
             impact_depth_levels,
             impact_weight_bps,
+            price_reference,
+            tp1_source,
+            tp2_source,
+            tp2_r_multiple,
+            trail_after_tp1_setups,
+            trail_after_tp1_ratio,
+            price_rounding,
+            quantity_rounding,
             hourly_performance: BTreeMap::new(),
             symbol_stats: BTreeMap::new(),
             bot_stats: None,
+            collect_period_closes: false,
+            exposure_breakdown: false,
+            base_asset_categories: BTreeMap::new(),
         }
     }
 
@@ -118,14 +366,105 @@ impl SimulatorEngine {
         self.execution_tx = Some(tx);
     }
 
-    pub fn set_exchange_info(&mut self, exchange_info: Arc<ExchangeInfoManager>) {
+    pub fn set_exchange_info(&mut self, exchange_info: Arc<dyn Exchange>) {
         self.exchange_info = Some(exchange_info);
     }
 
+    /// Write the top `depth` book levels per symbol to `sink` at most every `interval_ms`
+    pub fn set_book_sink(&mut self, sink: MarketSink, interval_ms: u64, depth: usize) {
+        match BookSnapshotWriter::spawn(sink) {
+            Ok(writer) => self.book_sink = Some(writer),
+            Err(e) => {
+                error!("Book snapshots disabled: {}", e);
+                return;
+            }
+        }
+        self.book_snapshot_interval = Duration::milliseconds(interval_ms as i64);
+        self.book_snapshot_depth = depth;
+    }
+
+    pub fn set_statsd(&mut self, emitter: StatsdEmitter) {
+        self.statsd = Some(emitter);
+    }
+
+    /// Sequential position ids for reproducible replays
+    pub fn set_deterministic_ids(&mut self, enabled: bool) {
+        self.position_manager.set_deterministic_ids(enabled);
+    }
+
     pub fn set_bot_stats(&mut self, stats: Arc<Mutex<BotStats>>) {
         self.bot_stats = Some(stats);
     }
 
+    /// Queue each close for the daily digest; leave off when no digest drains them
+    pub fn set_daily_digest(&mut self, enabled: bool) {
+        self.collect_period_closes = enabled;
+    }
+
+    /// Publish the exposure breakdown with every stats sync (off = never computed)
+    pub fn set_exposure_breakdown(&mut self, enabled: bool) {
+        self.exposure_breakdown = enabled;
+    }
+
+    /// Group base assets into reporting categories for the exposure breakdown
+    pub fn set_base_asset_categories(&mut self, categories: &HashMap<String, String>) {
+        self.base_asset_categories = categories
+            .iter()
+            .map(|(base, category)| (base.to_uppercase(), category.clone()))
+            .collect();
+    }
+
+    /// (base, quote) assets of a symbol, from exchange info or a known quote suffix
+    fn symbol_assets(&self, symbol: &str) -> (String, String) {
+        if let Some(info) = self
+            .exchange_info
+            .as_ref()
+            .and_then(|exchange| exchange.symbol_info(symbol))
+        {
+            return (info.base_asset.to_uppercase(), info.quote_asset.to_uppercase());
+        }
+        let upper = symbol.to_uppercase();
+        for quote in ["USDT", "USDC", "FDUSD", "BUSD", "BTC"] {
+            if let Some(base) = upper.strip_suffix(quote).filter(|b| !b.is_empty()) {
+                return (base.to_string(), quote.to_string());
+            }
+        }
+        (upper, "UNKNOWN".to_string())
+    }
+
+    /// Open exposure and PnL grouped by quote asset and by base-asset category
+    pub fn asset_exposure(
+        &self,
+    ) -> (BTreeMap<String, AssetExposure>, BTreeMap<String, AssetExposure>) {
+        let mut by_quote: BTreeMap<String, AssetExposure> = BTreeMap::new();
+        let mut by_category: BTreeMap<String, AssetExposure> = BTreeMap::new();
+        let groups = |symbol: &str| {
+            let (base, quote) = self.symbol_assets(symbol);
+            let category = self.base_asset_categories.get(&base).cloned().unwrap_or(base);
+            (quote, category)
+        };
+
+        for pos in self.position_manager.open_positions() {
+            let (quote, category) = groups(&pos.symbol);
+            let mark = self.last_prices.get(&pos.symbol).copied().unwrap_or(pos.entry_price);
+            let unrealized = pos.calculate_unrealized_pnl(mark);
+            for exposure in [
+                by_quote.entry(quote).or_default(),
+                by_category.entry(category).or_default(),
+            ] {
+                exposure.open_positions += 1;
+                exposure.open_notional += pos.entry_price * pos.quantity;
+                exposure.unrealized_pnl += unrealized;
+            }
+        }
+        for (symbol, stats) in &self.symbol_stats {
+            let (quote, category) = groups(symbol);
+            by_quote.entry(quote).or_default().realized_pnl += stats.total_pnl;
+            by_category.entry(category).or_default().realized_pnl += stats.total_pnl;
+        }
+        (by_quote, by_category)
+    }
+
     /// Main loop: consume processing events and market events
     pub async fn run(
         &mut self,
@@ -175,42 +514,208 @@ impl SimulatorEngine {
                     ss.entry(pos.symbol.clone()).or_default().open_positions += 1;
                 }
                 s.symbol_stats = ss;
+                if self.exposure_breakdown {
+                    (s.exposure_by_quote, s.exposure_by_category) = self.asset_exposure();
+                }
             }
         }
     }
 
-    fn handle_processing_event(&mut self, event: ProcessingEvent) {
+    pub fn handle_processing_event(&mut self, event: ProcessingEvent) {
         match event {
             ProcessingEvent::Signal(signal) => {
-                self.execute_signal(signal);
+                if self.entry_confirmation.is_some() && self.is_maker_entry(&signal) {
+                    self.hold_for_confirmation(signal);
+                } else {
+                    self.execute_signal(signal);
+                }
             }
             ProcessingEvent::VolumeProfile(profile) => {
                 self.latest_profiles.insert(profile.symbol.clone(), profile);
             }
-            _ => {
-                // Other events (bars, flow) handled by processing task
+            ProcessingEvent::NewBar(bar) => {
+                if let Some(statsd) = &self.statsd {
+                    statsd.count("bars", 1, &[("symbol", &bar.symbol)]);
+                }
+                self.resolve_what_ifs(&bar);
+                self.record_bar(bar);
+            }
+            ProcessingEvent::OrderFlow(flow) => {
+                if self.config.exit_on_opposite_absorption {
+                    self.exit_on_opposite_absorption(&flow);
+                }
+            }
+            ProcessingEvent::FlattenAndHalt { reason } => {
+                self.flatten_and_halt(&reason);
+            }
+            ProcessingEvent::LatencySample { rtt_ms } => {
+                self.record_latency(rtt_ms);
             }
         }
     }
 
-    fn handle_market_event(&mut self, event: MarketEvent) {
+    pub fn handle_market_event(&mut self, event: MarketEvent) {
+        if self.started_at.is_none() {
+            self.started_at = match &event {
+                MarketEvent::Trade(t) => Some(t.timestamp),
+                MarketEvent::Depth(d) => Some(d.timestamp),
+                MarketEvent::BookTicker(b) => Some(b.timestamp),
+                MarketEvent::Reconnected { .. } => None,
+            };
+        }
+        self.last_event_at = Some(event.timestamp());
         match event {
             MarketEvent::Trade(trade) => {
                 self.on_trade(&trade);
+                self.confirm_pending_entry(&trade.symbol, trade.timestamp);
             }
             MarketEvent::Depth(depth) => {
                 self.on_depth(&depth);
+                self.confirm_pending_entry(&depth.symbol, depth.timestamp);
+            }
+            MarketEvent::BookTicker(ticker) => {
+                self.on_book_ticker(&ticker);
+                self.confirm_pending_entry(&ticker.symbol, ticker.timestamp);
+            }
+            MarketEvent::Reconnected { .. } => {
+                // Bar state is handled by the processing task
+            }
+        }
+    }
+
+    /// Pause entries while sampled RTT is above the limit, resume once it recovers.
+    /// A failed sample (exchange unreachable) counts as over the limit.
+    fn record_latency(&mut self, rtt_ms: Option<f64>) {
+        if let (Some(statsd), Some(rtt_ms)) = (&self.statsd, rtt_ms) {
+            statsd.timing("latency", rtt_ms, &[]);
+        }
+        let Some(limit) = self.max_entry_latency_ms else {
+            return;
+        };
+        let paused = rtt_ms.is_none_or(|rtt| rtt > limit);
+        if paused && !self.latency_paused {
+            warn!(rtt_ms = ?rtt_ms, limit_ms = limit, "Latency above limit: new entries paused");
+        } else if !paused && self.latency_paused {
+            info!(rtt_ms = ?rtt_ms, limit_ms = limit, "Latency recovered: entries resumed");
+        }
+        self.latency_paused = paused;
+    }
+
+    /// Signals skipped during the startup book warmup
+    pub fn book_warmup_skips(&self) -> u64 {
+        self.book_warmup_skips
+    }
+
+    /// Within the startup grace a missing book means "not received yet", not bad
+    /// conditions: skip the signal under its own reason instead of execution_quality
+    fn skip_for_book_warmup(&mut self, signal: &TradeSignal) -> bool {
+        if !self.require_orderbook_for_entry
+            || self.orderbook_warmup <= Duration::zero()
+            || self.order_books.contains_key(&signal.symbol)
+        {
+            return false;
+        }
+        // No market event yet means the warmup has not started
+        if let (Some(started), Some(now)) = (self.started_at, self.last_event_at) {
+            if now - started >= self.orderbook_warmup {
+                return false;
+            }
+        }
+        self.book_warmup_skips += 1;
+        warn!(
+            symbol = %signal.symbol,
+            skipped = self.book_warmup_skips,
+            "Signal skipped: order book not received yet (startup warmup)"
+        );
+        self.reject_signal(signal.clone(), "book_warmup");
+        true
+    }
+
+    /// A maker entry is priced inside the book: a buy below the best ask or a sell above
+    /// the best bid would rest rather than cross. Without a book it counts as marketable.
+    fn is_maker_entry(&self, signal: &TradeSignal) -> bool {
+        let Some(book) = self.order_books.get(&signal.symbol) else {
+            return false;
+        };
+        match signal.side {
+            crate::types::Side::Buy => book.best_ask().is_some_and(|ask| signal.entry_price < ask),
+            crate::types::Side::Sell => book.best_bid().is_some_and(|bid| signal.entry_price > bid),
+        }
+    }
+
+    /// Park a maker entry until the book imbalance has held for the confirmation window
+    fn hold_for_confirmation(&mut self, signal: TradeSignal) {
+        if self.skip_for_book_warmup(&signal) {
+            return;
+        }
+        if !self.passes_execution_quality_filters(&signal) {
+            self.reject_signal(signal, "execution_quality");
+            return;
+        }
+        info!(
+            symbol = %signal.symbol,
+            side = ?signal.side,
+            "Entry waiting for book confirmation"
+        );
+        let symbol = signal.symbol.clone();
+        let pending = PendingEntry {
+            signal,
+            deadline: None,
+        };
+        if let Some(replaced) = self.pending_entries.insert(symbol, pending) {
+            self.reject_signal(replaced.signal, "confirmation_superseded");
+        }
+    }
+
+    /// Enter once the window closes with the imbalance intact; cancel if it reverses
+    fn confirm_pending_entry(&mut self, symbol: &str, now: DateTime<Utc>) {
+        let window = self.entry_confirmation.unwrap_or_else(Duration::zero);
+        let (side, deadline) = match self.pending_entries.get_mut(symbol) {
+            Some(pending) => (
+                pending.signal.side,
+                *pending.deadline.get_or_insert(now + window),
+            ),
+            None => return,
+        };
+
+        let holds = self
+            .order_books
+            .get(symbol)
+            .is_some_and(|book| self.imbalance_favors(symbol, book, side));
+        if !holds {
+            if let Some(pending) = self.pending_entries.remove(symbol) {
+                info!(symbol = %symbol, "Entry canceled: book imbalance reversed");
+                self.reject_signal(pending.signal, "confirmation_reversed");
+            }
+        } else if now >= deadline {
+            if let Some(pending) = self.pending_entries.remove(symbol) {
+                self.execute_signal(pending.signal);
             }
         }
     }
 
     fn execute_signal(&mut self, signal: TradeSignal) {
+        if let Some(statsd) = &self.statsd {
+            let side = format!("{:?}", signal.side);
+            statsd.count("signals", 1, &[("symbol", &signal.symbol), ("side", &side)]);
+        }
+        if self.skip_for_book_warmup(&signal) {
+            return;
+        }
+        if self.latency_paused {
+            warn!(symbol = %signal.symbol, "Signal rejected: entries paused for high latency");
+            self.reject_signal(signal, "latency");
+            return;
+        }
         if !self.passes_execution_quality_filters(&signal) {
+            self.reject_signal(signal, "execution_quality");
             return;
         }
         if !self.passes_expectancy_filter(&signal) {
+            self.reject_signal(signal, "expectancy");
             return;
         }
+        let signal = self.anchor_to_reference_price(signal);
 
         if !self.risk_manager.can_trade(&signal) {
             warn!(
@@ -218,20 +723,28 @@ impl SimulatorEngine {
                 setup = %signal.setup,
                 "Signal rejected by risk manager"
             );
+            self.reject_signal(signal, "risk_manager");
             return;
         }
 
-        let quantity = self.risk_manager.calculate_position_size(&signal);
+        let leverage = self.leverage_for(&signal.symbol);
+        let quantity = self.risk_manager.calculate_position_size(&signal, leverage);
         if quantity <= Decimal::ZERO {
             warn!("Position size is zero, skipping");
+            self.reject_signal(signal, "zero_size");
             return;
         }
 
         // Validate and adjust order parameters using exchange info
         let (validated_entry, validated_quantity) =
-            if let Some(ref exchange_info) = self.exchange_info {
-                if let Some(symbol_info) = exchange_info.get_symbol_info(&signal.symbol) {
-                    match symbol_info.validate_order(signal.entry_price, quantity) {
+            if let Some(exchange_info) = self.exchange_info.clone() {
+                if let Some(symbol_info) = exchange_info.symbol_info(&signal.symbol) {
+                    match symbol_info.validate_order_with(
+                        signal.entry_price,
+                        quantity,
+                        self.price_rounding,
+                        self.quantity_rounding,
+                    ) {
                         Ok((rounded_price, rounded_qty)) => {
                             if rounded_price != signal.entry_price || rounded_qty != quantity {
                                 info!(
@@ -253,6 +766,7 @@ impl SimulatorEngine {
                                 error = ?e,
                                 "Order validation failed"
                             );
+                            self.reject_signal(signal, "order_validation");
                             return;
                         }
                     }
@@ -268,43 +782,154 @@ impl SimulatorEngine {
                 (signal.entry_price, quantity)
             };
 
+        if !self
+            .risk_manager
+            .can_add_notional(&signal.symbol, validated_entry * validated_quantity)
+        {
+            self.reject_signal(signal, "notional_cap");
+            return;
+        }
+        if !self.passes_liquidation_buffer(&signal, validated_entry, validated_quantity, leverage) {
+            self.reject_signal(signal, "liquidation_buffer");
+            return;
+        }
+
         // Create modified signal with validated values
         let mut validated_signal = signal.clone();
         validated_signal.entry_price = validated_entry;
+        // An R-multiple TP2 needs no profile: set it up front so it holds even when
+        // stage_targets finds no fresh profile
+        if validated_signal.setup == crate::types::SetupType::AdvancedOrderFlow
+            && self.tp2_source == Tp2Source::RMultiple
+        {
+            let reward = (validated_entry - validated_signal.stop_loss).abs() * self.tp2_r_multiple;
+            validated_signal.take_profit = match validated_signal.side {
+                crate::types::Side::Buy => validated_entry + reward,
+                crate::types::Side::Sell => validated_entry - reward,
+            };
+        }
+        if self.config.round_stop_target_to_tick {
+            self.round_stop_target(&mut validated_signal);
+        }
         if !self.passes_slippage_model(
             &validated_signal.symbol,
             validated_signal.side,
             validated_entry,
             validated_quantity,
         ) {
+            self.reject_signal(validated_signal, "slippage");
             return;
         }
 
-        let mut position = self.position_manager.open_position(
+        let mut validated_quantity = validated_quantity;
+        let mut remainder = None;
+        if self.config.book_walk_fills {
+            if let Some(book) = self.order_books.get(&validated_signal.symbol) {
+                let Some(fill) = book.walk(validated_signal.side, validated_quantity) else {
+                    warn!(symbol = %validated_signal.symbol, "Signal rejected: no book liquidity to fill");
+                    self.reject_signal(validated_signal, "no_liquidity");
+                    return;
+                };
+                if fill.filled < validated_quantity {
+                    let unfilled = validated_quantity - fill.filled;
+                    // The remainder is a new order: it must meet the exchange minimums itself
+                    if self.config.unfilled_remainder.eq_ignore_ascii_case("rest") {
+                        remainder = self
+                            .exchange_valid_quantity(&validated_signal.symbol, unfilled, fill.worst_price)
+                            .map(|quantity| RestingEntry {
+                                symbol: validated_signal.symbol.clone(),
+                                side: validated_signal.side,
+                                limit_price: fill.worst_price,
+                                quantity,
+                                deadline: None,
+                            });
+                    }
+                    info!(
+                        symbol = %validated_signal.symbol,
+                        requested = %validated_quantity,
+                        filled = %fill.filled,
+                        average_price = %fill.average_price,
+                        unfilled = %unfilled,
+                        resting = remainder.is_some(),
+                        "Book too thin: partial entry fill"
+                    );
+                }
+                validated_signal.entry_price = fill.average_price;
+                validated_quantity = fill.filled;
+            }
+        }
+
+        let mut position = match self.position_manager.open_position(
             &validated_signal,
             validated_quantity,
-            self.leverage,
+            leverage,
             self.margin_type,
             self.maintenance_margin_rate,
             self.fee_rate,
+        ) {
+            Ok(position) => position,
+            Err(e) => {
+                warn!(
+                    symbol = %validated_signal.symbol,
+                    error = %e,
+                    "Signal rejected: stop beyond liquidation"
+                );
+                self.reject_signal(validated_signal, "stop_beyond_liquidation");
+                return;
+            }
+        };
+
+        // Market entry: taker fee, even when exits are charged the maker rate
+        position.entry_fee_rate = Some(self.fee_rate);
+        self.position_manager
+            .set_entry_fee_rate(&position.id, self.fee_rate);
+        if let Some(statsd) = &self.statsd {
+            statsd.count("fills", 1, &[("symbol", &position.symbol)]);
+            statsd.gauge(
+                "open_positions",
+                self.position_manager.open_positions().len() as f64,
+                &[],
+            );
+        }
+
+        position.emergency_stop = self.risk_manager.emergency_stop_price(
+            position.side,
+            position.entry_price,
+            position.stop_loss,
         );
+        self.position_manager
+            .set_emergency_stop(&position.id, position.emergency_stop);
 
-        // For AdvancedOrderFlow strategy, set TP1/TP2 from volume profile
+        // For AdvancedOrderFlow strategy, set TP1/TP2 from the configured sources
         if position.setup == crate::types::SetupType::AdvancedOrderFlow {
-            if let Some(profile) = self.latest_profiles.get(&position.symbol) {
-                position.tp1_price = Some(profile.vwap);
-                position.tp2_price = Some(profile.vah);
+            if let Some((tp1, tp2)) = self.stage_targets(&position) {
+                position.tp1_price = Some(tp1);
+                position.tp2_price = Some(tp2);
+                self.position_manager
+                    .set_stage_targets(&position.id, position.tp1_price, position.tp2_price);
 
                 info!(
                     position_id = %position.id,
-                    tp1_vwap = %profile.vwap,
-                    tp2_vah = %profile.vah,
-                    "AdvancedOrderFlow: TP1/TP2 set from profile"
+                    tp1 = %tp1,
+                    tp1_source = ?self.tp1_source,
+                    tp2 = %tp2,
+                    tp2_source = ?self.tp2_source,
+                    "AdvancedOrderFlow: TP1/TP2 set"
                 );
             }
         }
 
+        let (entry_spread, entry_depth_imbalance) = self.book_conditions(&position.symbol);
+        position.entry_spread = entry_spread;
+        position.entry_depth_imbalance = entry_depth_imbalance;
+        self.position_manager
+            .record_entry_book(&position.id, entry_spread, entry_depth_imbalance);
+
         self.risk_manager.register_position(&position);
+        if let Some(remainder) = remainder {
+            self.resting_entries.insert(position.id.clone(), remainder);
+        }
+        self.trade_logger.log_open(&position);
         self.trade_logger.log_entry(&position);
 
         info!(
@@ -328,6 +953,203 @@ impl SimulatorEngine {
         }
     }
 
+    /// TP1/TP2 prices for a multi-stage position from the latest profile
+    fn stage_targets(&self, position: &crate::types::Position) -> Option<(Decimal, Decimal)> {
+        let profile = self.latest_profiles.get(&position.symbol)?;
+        if self.config.max_profile_age_secs > 0 {
+            let now = self
+                .last_trade_times
+                .get(&position.symbol)
+                .copied()
+                .unwrap_or_else(Utc::now);
+            let age = now - profile.timestamp;
+            if age > Duration::seconds(self.config.max_profile_age_secs as i64) {
+                warn!(
+                    symbol = %position.symbol,
+                    profile_age_secs = age.num_seconds(),
+                    "Stale volume profile: keeping the signal's take-profit"
+                );
+                return None;
+            }
+        }
+        let tp1 = match self.tp1_source {
+            Tp1Source::Vwap => profile.vwap,
+            Tp1Source::Poc => profile.poc,
+        };
+        let tp2 = match (self.tp2_source, position.side) {
+            (Tp2Source::ValueArea, crate::types::Side::Buy) => profile.vah,
+            (Tp2Source::ValueArea, crate::types::Side::Sell) => profile.val,
+            // Already set on the signal at entry
+            (Tp2Source::RMultiple, _) => position.take_profit,
+        };
+        if self.config.round_stop_target_to_tick {
+            let round = |price| self.round_to_tick(&position.symbol, price, RoundingMode::Nearest);
+            return Some((round(tp1), round(tp2)));
+        }
+        Some((tp1, tp2))
+    }
+
+    /// Log a rejected signal and queue it for what-if evaluation
+    fn reject_signal(&mut self, signal: TradeSignal, reason: &str) {
+        if let Some(statsd) = &self.statsd {
+            statsd.count("rejections", 1, &[("symbol", &signal.symbol), ("reason", reason)]);
+        }
+        self.trade_logger.log_rejected_signal(&signal, reason);
+        if self.config.what_if_lookahead_bars > 0 {
+            // Symbols that stop printing bars never resolve theirs
+            while self.pending_what_ifs.len() >= self.config.what_if_max_pending.max(1) {
+                if let Some(dropped) = self.pending_what_ifs.pop_front() {
+                    debug!(
+                        signal_id = %dropped.signal.id,
+                        symbol = %dropped.signal.symbol,
+                        "What-if queue full, dropping oldest rejected signal"
+                    );
+                }
+            }
+            self.pending_what_ifs.push_back(PendingWhatIf {
+                signal,
+                bars: Vec::new(),
+            });
+        }
+    }
+
+    /// Feed a completed bar to pending what-ifs and log those with a full lookahead
+    fn resolve_what_ifs(&mut self, bar: &RangeBar) {
+        let lookahead = self.config.what_if_lookahead_bars;
+        for pending in &mut self.pending_what_ifs {
+            if pending.signal.symbol == bar.symbol {
+                pending.bars.push(bar.clone());
+            }
+        }
+
+        let (ready, waiting): (VecDeque<_>, VecDeque<_>) = std::mem::take(&mut self.pending_what_ifs)
+            .into_iter()
+            .partition(|p| p.bars.len() >= lookahead);
+        self.pending_what_ifs = waiting;
+
+        for pending in ready {
+            let signal = &pending.signal;
+            let won = lookahead_outcome(
+                &pending.bars,
+                signal.side,
+                signal.entry_price,
+                signal.stop_loss,
+                signal.take_profit,
+            );
+            info!(
+                signal_id = %signal.id,
+                symbol = %signal.symbol,
+                setup = %signal.setup,
+                won = won,
+                "What-if outcome for rejected signal"
+            );
+            self.trade_logger
+                .log_what_if_outcome(&signal.id, won, pending.bars.len());
+        }
+    }
+
+    /// Current spread and top-N depth imbalance for a symbol's book
+    /// (imbalance None also when the book has bids but no asks)
+    fn book_conditions(&self, symbol: &str) -> (Option<Decimal>, Option<Decimal>) {
+        match self.order_books.get(symbol) {
+            Some(book) => (
+                book.spread(),
+                book.top_depth_imbalance(self.impact_depth_levels),
+            ),
+            None => (None, None),
+        }
+    }
+
+    /// Stamp exit-time book conditions on a closed position
+    fn record_exit_book(&mut self, position: &mut crate::types::Position) {
+        let (spread, depth_imbalance) = self.book_conditions(&position.symbol);
+        position.exit_spread = spread;
+        position.exit_depth_imbalance = depth_imbalance;
+        self.position_manager
+            .record_exit_book(&position.id, spread, depth_imbalance);
+    }
+
+    /// Re-anchor entry/stop/target onto the book microprice (when configured),
+    /// keeping the strategy's stop and target distances intact.
+    fn anchor_to_reference_price(&self, mut signal: TradeSignal) -> TradeSignal {
+        if self.price_reference != PriceReference::Microprice {
+            return signal;
+        }
+        let Some(microprice) = self
+            .order_books
+            .get(&signal.symbol)
+            .and_then(|b| b.microprice())
+        else {
+            return signal;
+        };
+
+        let shift = microprice - signal.entry_price;
+        info!(
+            symbol = %signal.symbol,
+            last_trade = %signal.entry_price,
+            microprice = %microprice,
+            "Anchoring stop/target to book microprice"
+        );
+        signal.entry_price = microprice;
+        signal.stop_loss += shift;
+        signal.take_profit += shift;
+        signal
+    }
+
+    /// Leverage for a new position on `symbol`: the per-symbol override if configured,
+    /// else the global default, never above the symbol's exchange maximum
+    /// (`max_leverage` when the exchange rules don't publish one)
+    fn leverage_for(&self, symbol: &str) -> Decimal {
+        let cap = self
+            .exchange_info
+            .as_ref()
+            .and_then(|exchange| exchange.symbol_info(symbol))
+            .and_then(|rules| rules.max_leverage)
+            .unwrap_or(self.max_leverage);
+        self.symbol_leverage
+            .get(&symbol.to_lowercase())
+            .copied()
+            .unwrap_or(self.leverage)
+            .min(cap)
+    }
+
+    /// Reject entries whose liquidation price sits too close to (or inside) the stop,
+    /// i.e. the leverage is too high for the intended risk.
+    fn passes_liquidation_buffer(
+        &self,
+        signal: &TradeSignal,
+        entry: Decimal,
+        quantity: Decimal,
+        leverage: Decimal,
+    ) -> bool {
+        if self.min_liquidation_buffer_ratio <= Decimal::ZERO {
+            return true;
+        }
+        let liquidation = self.position_manager.liquidation_price(
+            signal.side,
+            entry,
+            quantity,
+            leverage,
+            self.maintenance_margin_rate,
+            self.fee_rate,
+        );
+        let stop_distance = (entry - signal.stop_loss).abs();
+        let liquidation_distance = (entry - liquidation).abs();
+        if liquidation_distance >= stop_distance * self.min_liquidation_buffer_ratio {
+            return true;
+        }
+
+        warn!(
+            symbol = %signal.symbol,
+            entry = %entry,
+            stop = %signal.stop_loss,
+            liquidation = %liquidation,
+            leverage = %leverage,
+            "Signal rejected: liquidation price too close to stop"
+        );
+        false
+    }
+
     fn passes_execution_quality_filters(&self, signal: &TradeSignal) -> bool {
         let book = match self.order_books.get(&signal.symbol) {
             Some(b) => b,
@@ -341,19 +1163,18 @@ impl SimulatorEngine {
             None => return true,
         };
 
-        let Some(spread) = book.spread() else {
-            if self.require_orderbook_for_entry {
-                warn!(symbol = %signal.symbol, "Signal rejected: missing spread data");
-                return false;
-            }
-            return true;
-        };
-        let Some(mid) = book.mid_price() else {
-            if self.require_orderbook_for_entry {
-                warn!(symbol = %signal.symbol, "Signal rejected: missing mid price");
-                return false;
+        // A one-sided or crossed book has no reliable price: treated like a missing book
+        let (Some(spread), Some(mid)) = (book.spread(), book.mid_price()) else {
+            if !self.require_orderbook_for_entry {
+                return true;
             }
-            return true;
+            warn!(
+                symbol = %signal.symbol,
+                best_bid = ?book.best_bid(),
+                best_ask = ?book.best_ask(),
+                "Signal rejected: one-sided or crossed order book"
+            );
+            return false;
         };
         if mid <= Decimal::ZERO {
             return false;
@@ -371,24 +1192,14 @@ impl SimulatorEngine {
         }
 
         let (bid_vol, ask_vol, ratio) = book.depth_imbalance();
-        let side_ok = match signal.side {
-            crate::types::Side::Buy => ratio >= self.min_depth_imbalance_ratio,
-            crate::types::Side::Sell => {
-                if bid_vol <= Decimal::ZERO {
-                    false
-                } else {
-                    (ask_vol / bid_vol) >= self.min_depth_imbalance_ratio
-                }
-            }
-        };
-        if !side_ok {
+        if !self.imbalance_favors(&signal.symbol, book, signal.side) {
             warn!(
                 symbol = %signal.symbol,
                 side = ?signal.side,
                 bid_vol = %bid_vol,
                 ask_vol = %ask_vol,
-                ratio = %ratio,
-                min_depth_imbalance_ratio = %self.min_depth_imbalance_ratio,
+                ratio = ?ratio,
+                min_depth_imbalance_ratio = %self.required_imbalance_ratio(&signal.symbol),
                 "Signal rejected: insufficient depth imbalance"
             );
             return false;
@@ -397,6 +1208,62 @@ impl SimulatorEngine {
         true
     }
 
+    /// Whether resting depth leans toward `side` by at least the required imbalance ratio
+    fn imbalance_favors(&self, symbol: &str, book: &LocalOrderBook, side: crate::types::Side) -> bool {
+        let required = self.required_imbalance_ratio(symbol);
+        let (bid_vol, ask_vol, ratio) = book.depth_imbalance();
+        match side {
+            crate::types::Side::Buy => ratio.is_none_or(|r| r >= required),
+            crate::types::Side::Sell => {
+                if bid_vol <= Decimal::ZERO {
+                    false
+                } else {
+                    (ask_vol / bid_vol) >= required
+                }
+            }
+        }
+    }
+
+    /// `min_depth_imbalance_ratio` with its edge over 1.0 scaled by realized volatility
+    /// relative to the reference: noisier, thinner books in fast markets must lean harder.
+    fn required_imbalance_ratio(&self, symbol: &str) -> Decimal {
+        let base = self.min_depth_imbalance_ratio;
+        let Some(reference) = self.imbalance_vol_reference_bps else {
+            return base;
+        };
+        let Some(vol) = self.realized_vol_bps(symbol) else {
+            return base;
+        };
+        let scale = (vol / reference).clamp(MIN_IMBALANCE_VOL_SCALE, MAX_IMBALANCE_VOL_SCALE);
+        Decimal::ONE + (base - Decimal::ONE) * scale
+    }
+
+    /// Realized volatility as bar range traversed per minute, in bps of price.
+    /// Range bars have a fixed size, so this rises with how quickly they print.
+    fn realized_vol_bps(&self, symbol: &str) -> Option<Decimal> {
+        let bars = self.recent_bars.get(symbol)?;
+        let (first, last) = (bars.front()?, bars.back()?);
+        if bars.len() < 2 || last.close <= Decimal::ZERO {
+            return None;
+        }
+        let elapsed_ms = (last.close_time - first.open_time).num_milliseconds().max(1);
+        let traversed: Decimal = bars.iter().map(|b| b.high - b.low).sum();
+        let range_bps = traversed / last.close * Decimal::from(10_000);
+        Some(range_bps * Decimal::from(60_000) / Decimal::from(elapsed_ms))
+    }
+
+    fn record_bar(&mut self, bar: RangeBar) {
+        if self.imbalance_vol_reference_bps.is_none() {
+            return;
+        }
+        let window = self.config.imbalance_vol_window_bars.max(2);
+        let bars = self.recent_bars.entry(bar.symbol.clone()).or_default();
+        bars.push_back(bar);
+        while bars.len() > window {
+            bars.pop_front();
+        }
+    }
+
     fn passes_expectancy_filter(&self, signal: &TradeSignal) -> bool {
         if !self.expectancy_filter_enabled {
             return true;
@@ -411,13 +1278,18 @@ impl SimulatorEngine {
             return true;
         }
 
-        let avg =
-            stats.pnls.iter().copied().sum::<Decimal>() / Decimal::from(stats.pnls.len() as u64);
-        if avg < self.expectancy_min_avg_pnl {
+        let estimate = self.expectancy_estimate(&stats.pnls);
+        // With a confidence bound, a noisy hour is only rejected when even its optimistic
+        // bound stays below the threshold
+        let margin = Self::standard_error(&stats.pnls) * self.expectancy_confidence_z;
+        let bound = estimate + Decimal::try_from(margin).unwrap_or(Decimal::ZERO);
+        if bound < self.expectancy_min_avg_pnl {
             warn!(
                 symbol = %signal.symbol,
                 utc_hour = hour,
-                avg_pnl = %avg,
+                estimator = ?self.expectancy_estimator,
+                avg_pnl = %estimate,
+                upper_bound = %bound,
                 min_avg_pnl = %self.expectancy_min_avg_pnl,
                 samples = stats.pnls.len(),
                 "Signal rejected: UTC-hour expectancy below threshold"
@@ -427,15 +1299,54 @@ impl SimulatorEngine {
         true
     }
 
-    fn passes_slippage_model(
-        &self,
-        symbol: &str,
-        side: crate::types::Side,
-        entry_price: Decimal,
-        quantity: Decimal,
-    ) -> bool {
-        if !self.slippage_model_enabled {
-            return true;
+    fn expectancy_estimate(&self, pnls: &[Decimal]) -> Decimal {
+        let mean = |values: &[Decimal]| {
+            values.iter().copied().sum::<Decimal>() / Decimal::from(values.len().max(1) as u64)
+        };
+        let mut sorted = pnls.to_vec();
+        sorted.sort();
+        match self.expectancy_estimator {
+            ExpectancyEstimator::Mean => mean(pnls),
+            ExpectancyEstimator::Median => {
+                let mid = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[mid - 1] + sorted[mid]) / Decimal::TWO
+                } else {
+                    sorted[mid]
+                }
+            }
+            ExpectancyEstimator::TrimmedMean(ratio) => {
+                let trim = (Decimal::from(sorted.len() as u64) * ratio)
+                    .floor()
+                    .to_usize()
+                    .unwrap_or(0)
+                    .min((sorted.len() - 1) / 2);
+                mean(&sorted[trim..sorted.len() - trim])
+            }
+        }
+    }
+
+    /// Standard error of the mean PnL
+    fn standard_error(pnls: &[Decimal]) -> f64 {
+        let values: Vec<f64> = pnls.iter().filter_map(|p| p.to_f64()).collect();
+        if values.len() < 2 {
+            return 0.0;
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (variance / n).sqrt()
+    }
+
+    fn passes_slippage_model(
+        &self,
+        symbol: &str,
+        side: crate::types::Side,
+        entry_price: Decimal,
+        quantity: Decimal,
+    ) -> bool {
+        if !self.slippage_model_enabled {
+            return true;
         }
         let Some(book) = self.order_books.get(symbol) else {
             return true;
@@ -444,9 +1355,10 @@ impl SimulatorEngine {
             return false;
         }
 
+        // One-sided or crossed book: no reliable price to model, so skip unless a book is required
         let mid = match book.mid_price() {
             Some(v) if v > Decimal::ZERO => v,
-            _ => return true,
+            _ => return !self.require_orderbook_for_entry,
         };
         let spread = book.spread().unwrap_or(Decimal::ZERO);
         let half_spread_bps = (spread / mid) * Decimal::from(5_000);
@@ -459,16 +1371,36 @@ impl SimulatorEngine {
         }
         let impact_ratio = quantity / top_depth;
         let impact_bps = impact_ratio * self.impact_weight_bps;
-        let total_slippage_bps = half_spread_bps + impact_bps;
+        let mut total_slippage_bps = half_spread_bps + impact_bps;
+
+        // Fills move in whole ticks: on low-priced symbols one tick can be many bps
+        let tick_bps = self
+            .exchange_info
+            .as_ref()
+            .and_then(|info| info.symbol_info(symbol))
+            .map(|info| info.price_tick_size / mid * Decimal::from(10_000))
+            .filter(|bps| *bps > Decimal::ZERO);
+        let mut max_slippage_bps = self.max_model_slippage_bps;
+        if let Some(tick_bps) = tick_bps {
+            let min_ticks = self.config.min_model_slippage_ticks;
+            if min_ticks > 0 {
+                total_slippage_bps = total_slippage_bps.max(Decimal::from(min_ticks) * tick_bps);
+            }
+            let max_ticks = self.config.max_model_slippage_ticks;
+            if max_ticks > 0 {
+                max_slippage_bps = max_slippage_bps.min(Decimal::from(max_ticks) * tick_bps);
+            }
+        }
 
-        if total_slippage_bps > self.max_model_slippage_bps {
+        if total_slippage_bps > max_slippage_bps {
             warn!(
                 symbol = %symbol,
                 side = ?side,
                 quantity = %quantity,
                 top_depth = %top_depth,
                 estimated_slippage_bps = %total_slippage_bps,
-                max_model_slippage_bps = %self.max_model_slippage_bps,
+                max_slippage_bps = %max_slippage_bps,
+                tick_bps = ?tick_bps,
                 "Signal rejected: estimated slippage too high"
             );
             return false;
@@ -476,6 +1408,149 @@ impl SimulatorEngine {
         true
     }
 
+    /// Round stop and target to the symbol's tick size so they could rest as real orders.
+    /// The stop rounds away from entry (down for longs, up for shorts) so the real risk never
+    /// shrinks; the target rounds to the nearest tick.
+    fn round_stop_target(&self, signal: &mut TradeSignal) {
+        let stop_mode = match signal.side {
+            crate::types::Side::Buy => RoundingMode::Down,
+            crate::types::Side::Sell => RoundingMode::Up,
+        };
+        signal.stop_loss = self.round_to_tick(&signal.symbol, signal.stop_loss, stop_mode);
+        signal.take_profit = self.round_to_tick(&signal.symbol, signal.take_profit, RoundingMode::Nearest);
+    }
+
+    /// Snap `price` to the symbol's tick; left as-is without symbol rules or outside its filters
+    fn round_to_tick(&self, symbol: &str, price: Decimal, mode: RoundingMode) -> Decimal {
+        self.exchange_info
+            .as_ref()
+            .and_then(|info| info.symbol_info(symbol))
+            .and_then(|info| info.round_price_with(price, mode).ok())
+            .unwrap_or(price)
+    }
+
+    /// Book a closed position: risk, logs, stats and the close notification
+    fn settle_closed(&mut self, position: &crate::types::Position) {
+        self.risk_manager.close_position(position);
+        self.alert_balance_floor();
+        self.trade_logger.log_trade(position);
+        self.record_hourly_expectancy(position);
+        self.record_symbol_close(position);
+
+        info!(
+            id = %position.id,
+            symbol = %position.symbol,
+            pnl = %position.pnl,
+            exit_price = %position.exit_price.unwrap_or_default(),
+            "Position closed"
+        );
+
+        // Send execution event
+        if let Some(tx) = &self.execution_tx {
+            let _ = tx.try_send(ExecutionEvent::PositionClosed(position.clone()));
+        }
+    }
+
+    /// Alert once when a close pushed the balance below the risk floor and drop queued entries
+    fn alert_balance_floor(&mut self) {
+        let Some(floor) = self.risk_manager.take_floor_breach() else {
+            return;
+        };
+        self.pending_entries.clear();
+        self.resting_entries.clear();
+        let balance = self.risk_manager.balance();
+        warn!(balance = %balance, floor = %floor, "Balance floor reached: trading halted");
+        if let Some(tx) = &self.execution_tx {
+            let _ = tx.try_send(ExecutionEvent::BalanceFloorReached { balance, floor });
+        }
+    }
+
+    /// Close every open position at its symbol's last trade price and halt new entries
+    fn flatten_and_halt(&mut self, reason: &str) {
+        self.risk_manager.halt(reason);
+        self.pending_entries.clear();
+        self.resting_entries.clear();
+
+        let open: Vec<(String, Decimal)> = self
+            .position_manager
+            .open_positions()
+            .iter()
+            .map(|p| {
+                let price = self.last_prices.get(&p.symbol).copied().unwrap_or(p.entry_price);
+                (p.id.clone(), price)
+            })
+            .collect();
+        for (id, price) in open {
+            if let Some(mut position) =
+                self.position_manager
+                    .close_position(&id, price, self.fee_rate, ExitReason::Flatten)
+            {
+                self.record_exit_book(&mut position);
+                self.settle_closed(&position);
+            }
+        }
+        self.sync_bot_stats();
+    }
+
+    /// Close positions the latest bar's absorption argues against: buying absorbed
+    /// without price rising suggests a top for longs, and selling absorbed a bottom for shorts
+    fn exit_on_opposite_absorption(&mut self, flow: &crate::types::OrderFlowMetrics) {
+        let Some(absorbed) = flow.absorption_side.filter(|_| flow.absorption_detected) else {
+            return;
+        };
+        let min_burst = Decimal::try_from(self.config.absorption_exit_min_burst_ratio)
+            .unwrap_or(Decimal::ZERO);
+        if flow.volume_burst_ratio < min_burst {
+            return;
+        }
+
+        let against: Vec<(String, Decimal)> = self
+            .position_manager
+            .open_positions_for(&flow.symbol)
+            .iter()
+            .filter(|p| p.side == absorbed)
+            .map(|p| {
+                let price = self.last_prices.get(&p.symbol).copied().unwrap_or(p.entry_price);
+                (p.id.clone(), price)
+            })
+            .collect();
+        for (id, price) in against {
+            if let Some(mut position) = self.position_manager.close_position(
+                &id,
+                price,
+                self.fee_rate,
+                ExitReason::OppositeAbsorption,
+            ) {
+                info!(
+                    id = %position.id,
+                    symbol = %position.symbol,
+                    absorbed = ?absorbed,
+                    burst_ratio = %flow.volume_burst_ratio,
+                    "Exiting on opposite absorption"
+                );
+                self.record_exit_book(&mut position);
+                self.settle_closed(&position);
+            }
+        }
+        self.sync_bot_stats();
+    }
+
+    /// Per-symbol stats, plus the close queued for the next daily digest
+    fn record_symbol_close(&mut self, position: &crate::types::Position) {
+        self.symbol_stats
+            .entry(position.symbol.clone())
+            .or_default()
+            .record_close(position.pnl);
+        if !self.collect_period_closes {
+            return;
+        }
+        if let Some(stats) = &self.bot_stats {
+            if let Ok(mut s) = stats.lock() {
+                s.period_closes.push((position.symbol.clone(), position.pnl));
+            }
+        }
+    }
+
     fn record_hourly_expectancy(&mut self, position: &crate::types::Position) {
         let hour = position.entry_time.hour();
         let key = (position.symbol.clone(), hour);
@@ -487,23 +1562,112 @@ impl SimulatorEngine {
         }
     }
 
+    /// Fill resting entry remainders this trade printed through, up to its size.
+    /// Fills and what stays resting must meet the exchange minimums; a remainder that
+    /// would not is canceled. Remainders of positions that closed or took TP1, or past
+    /// `resting_remainder_ttl_ms`, are dropped.
+    fn fill_resting_entries(&mut self, trade: &NormalizedTrade) {
+        let ttl = Duration::milliseconds(self.config.resting_remainder_ttl_ms as i64);
+        let ids: Vec<String> = self
+            .resting_entries
+            .iter()
+            .filter(|(_, r)| r.symbol == trade.symbol)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut traded = trade.quantity;
+        for id in ids {
+            let Some(resting) = self.resting_entries.get_mut(&id) else {
+                continue;
+            };
+            let deadline = *resting.deadline.get_or_insert(trade.timestamp + ttl);
+            if self.config.resting_remainder_ttl_ms > 0 && trade.timestamp >= deadline {
+                info!(id = %id, unfilled = %resting.quantity, "Resting entry remainder expired");
+                self.resting_entries.remove(&id);
+                continue;
+            }
+            let crosses = match resting.side {
+                crate::types::Side::Buy => trade.price <= resting.limit_price,
+                crate::types::Side::Sell => trade.price >= resting.limit_price,
+            };
+            if !crosses || traded <= Decimal::ZERO {
+                continue;
+            }
+            let (resting_quantity, price) = (resting.quantity, resting.limit_price);
+            let Some(quantity) =
+                self.exchange_valid_quantity(&trade.symbol, resting_quantity.min(traded), price)
+            else {
+                continue;
+            };
+            let left = resting_quantity - quantity;
+            let keep_resting = left > Decimal::ZERO
+                && self.exchange_valid_quantity(&trade.symbol, left, price) == Some(left);
+            if keep_resting {
+                if let Some(resting) = self.resting_entries.get_mut(&id) {
+                    resting.quantity = left;
+                }
+            } else {
+                if left > Decimal::ZERO {
+                    info!(id = %id, unfilled = %left, "Resting remainder below exchange minimum: canceled");
+                }
+                self.resting_entries.remove(&id);
+            }
+            match self.position_manager.add_fill(
+                &id,
+                quantity,
+                price,
+                self.limit_fee_rate,
+                self.maintenance_margin_rate,
+                self.fee_rate,
+            ) {
+                Some(position) => {
+                    traded -= quantity;
+                    self.risk_manager.resize_position(&position);
+                    info!(
+                        id = %position.id,
+                        symbol = %position.symbol,
+                        filled = %quantity,
+                        price = %price,
+                        qty = %position.quantity,
+                        entry = %position.entry_price,
+                        "Resting entry remainder filled"
+                    );
+                }
+                None => {
+                    self.resting_entries.remove(&id);
+                }
+            }
+        }
+    }
+
     fn on_trade(&mut self, trade: &NormalizedTrade) {
-        // Keep shared stats up to date for the hourly reporter task
-        self.sync_bot_stats();
+        self.last_prices.insert(trade.symbol.clone(), trade.price);
+        self.last_trade_times.insert(trade.symbol.clone(), trade.timestamp);
+        if !self.resting_entries.is_empty() {
+            self.fill_resting_entries(trade);
+        }
         // Update per-position MFE/MAE before checking exits
         self.position_manager
             .update_excursions(&trade.symbol, trade.price, trade.timestamp);
+        self.position_manager.mark_unrealized(&trade.symbol, trade.price);
+        self.risk_manager
+            .mark_to_market(self.position_manager.unrealized_pnl());
+        if !self.exit_check_due(trade) {
+            return;
+        }
+        // Keep shared stats up to date for the hourly reporter task
+        self.sync_bot_stats();
 
         // First, check for liquidations (highest priority)
-        let liquidated = self.check_liquidations(&trade.symbol, trade.price);
+        let mut liquidated = self.check_liquidations(&trade.symbol, trade.price);
+        for position in liquidated.iter_mut() {
+            self.record_exit_book(position);
+        }
         for position in &liquidated {
             self.risk_manager.close_position(position);
+            self.alert_balance_floor();
             self.trade_logger.log_trade(position);
             self.record_hourly_expectancy(position);
-            self.symbol_stats
-                .entry(position.symbol.clone())
-                .or_default()
-                .record_close(position.pnl);
+            self.record_symbol_close(position);
 
             warn!(
                 id = %position.id,
@@ -524,31 +1688,22 @@ impl SimulatorEngine {
         self.check_multi_stage_exits(&trade.symbol, trade.price, trade.timestamp);
 
         // Then check normal exits (stop loss / take profit)
-        let closed = self
+        let mut closed = self
             .position_manager
-            .check_exits(&trade.symbol, trade.price, self.fee_rate);
-
-        for position in &closed {
-            self.risk_manager.close_position(position);
-            self.trade_logger.log_trade(position);
-            self.record_hourly_expectancy(position);
-            self.symbol_stats
-                .entry(position.symbol.clone())
-                .or_default()
-                .record_close(position.pnl);
-
-            info!(
-                id = %position.id,
-                symbol = %position.symbol,
-                pnl = %position.pnl,
-                exit_price = %position.exit_price.unwrap_or_default(),
-                "Position closed"
+            .check_exits(
+                &trade.symbol,
+                trade.price,
+                trade.timestamp,
+                self.risk_manager.stop_min_hold(),
+                self.fee_rate,
+                self.limit_fee_rate,
             );
+        for position in closed.iter_mut() {
+            self.record_exit_book(position);
+        }
 
-            // Send execution event
-            if let Some(tx) = &self.execution_tx {
-                let _ = tx.try_send(ExecutionEvent::PositionClosed(position.clone()));
-            }
+        for position in &closed {
+            self.settle_closed(position);
         }
 
         // Check break-even moves
@@ -594,7 +1749,53 @@ impl SimulatorEngine {
         }
     }
 
+    /// Throttle mode: evaluate exits once the interval has passed, or at once when the
+    /// trade reaches a position's stop, target or liquidation price
+    fn exit_check_due(&mut self, trade: &NormalizedTrade) -> bool {
+        let Some(interval) = self.exit_check_interval else {
+            return true;
+        };
+        let due = self
+            .last_exit_check
+            .get(&trade.symbol)
+            .is_none_or(|last| trade.timestamp - *last >= interval)
+            || self
+                .position_manager
+                .any_exit_level_crossed(&trade.symbol, trade.price);
+        if due {
+            self.last_exit_check
+                .insert(trade.symbol.clone(), trade.timestamp);
+        }
+        due
+    }
+
     fn on_depth(&mut self, depth: &DepthUpdate) {
+        let Some(interval) = self.depth_aggregation else {
+            self.apply_depth(depth);
+            return;
+        };
+
+        match self.pending_depth.get_mut(&depth.symbol) {
+            Some(pending) => pending.merge(depth),
+            None => {
+                self.pending_depth.insert(depth.symbol.clone(), depth.clone());
+            }
+        }
+
+        let due = self
+            .last_depth_apply
+            .get(&depth.symbol)
+            .is_none_or(|last| depth.timestamp - *last >= interval);
+        if due {
+            if let Some(pending) = self.pending_depth.remove(&depth.symbol) {
+                self.apply_depth(&pending);
+            }
+            self.last_depth_apply
+                .insert(depth.symbol.clone(), depth.timestamp);
+        }
+    }
+
+    fn apply_depth(&mut self, depth: &DepthUpdate) {
         let book = self
             .order_books
             .entry(depth.symbol.clone())
@@ -602,6 +1803,36 @@ impl SimulatorEngine {
                 LocalOrderBook::new(depth.symbol.clone(), self.config.order_book_depth)
             });
         book.update(depth);
+        self.maybe_snapshot_book(&depth.symbol, depth.timestamp);
+    }
+
+    fn on_book_ticker(&mut self, ticker: &BookTicker) {
+        let book = self
+            .order_books
+            .entry(ticker.symbol.clone())
+            .or_insert_with(|| {
+                LocalOrderBook::new(ticker.symbol.clone(), self.config.order_book_depth)
+            });
+        book.apply_book_ticker(ticker);
+        self.maybe_snapshot_book(&ticker.symbol, ticker.timestamp);
+    }
+
+    /// Record the book once the snapshot interval has elapsed in event time
+    fn maybe_snapshot_book(&mut self, symbol: &str, at: DateTime<Utc>) {
+        let Some(sink) = self.book_sink.as_ref() else {
+            return;
+        };
+        let due = self
+            .last_book_snapshot
+            .get(symbol)
+            .is_none_or(|last| at - *last >= self.book_snapshot_interval);
+        if !due {
+            return;
+        }
+        if let Some(book) = self.order_books.get(symbol) {
+            sink.record(book.snapshot(self.book_snapshot_depth, at));
+            self.last_book_snapshot.insert(symbol.to_string(), at);
+        }
     }
 
     /// Check for liquidations based on current price
@@ -614,7 +1845,7 @@ impl SimulatorEngine {
             .check_liquidations(symbol, mark_price, self.fee_rate)
     }
 
-    /// Check multi-stage exits: TP1 (50% at VWAP), TP2 (100% at VAH), Soft Stop (10s timeout)
+    /// Check multi-stage exits: TP1 (50% at tp1_source), TP2 (100% at tp2_source), Soft Stop (10s timeout)
     fn check_multi_stage_exits(
         &mut self,
         symbol: &str,
@@ -659,7 +1890,7 @@ impl SimulatorEngine {
                 continue;
             }
 
-            // TP1: VWAP reached, close 50%
+            // TP1 reached, close 50%
             if !tp1_filled {
                 if let Some(tp1) = tp1_price {
                     let tp1_reached = match side {
@@ -668,18 +1899,35 @@ impl SimulatorEngine {
                     };
 
                     if tp1_reached {
-                        let half_qty = quantity / Decimal::TWO;
+                        let Some(half_qty) = self.tp1_close_quantity(symbol, quantity, tp1) else {
+                            if let Some(mut pos) = self.position_manager.close_position(
+                                &pos_id,
+                                tp1,
+                                self.limit_fee_rate,
+                                ExitReason::TakeProfit,
+                            ) {
+                                info!(
+                                    position_id = %pos_id,
+                                    tp1_price = %tp1,
+                                    quantity = %quantity,
+                                    "TP1 hit: split would leave a leg below exchange minimums, 100% closed"
+                                );
+                                self.record_exit_book(&mut pos);
+                                self.settle_closed(&pos);
+                            }
+                            continue;
+                        };
                         if let Some(partial_pnl) = self.position_manager.close_partial(
                             &pos_id,
                             half_qty,
                             tp1,
-                            self.fee_rate,
+                            self.limit_fee_rate,
                         ) {
                             info!(
                                 position_id = %pos_id,
                                 tp1_price = %tp1,
                                 partial_pnl = %partial_pnl,
-                                "TP1 hit: 50% closed at VWAP"
+                                "TP1 hit: 50% closed"
                             );
 
                             // Mark TP1 as filled and move stop to protected break-even
@@ -711,7 +1959,26 @@ impl SimulatorEngine {
                 }
             }
 
-            // TP2: VAH reached (or reverse flow), close 100%
+            // Trailing remainder: ratchet the stop behind price; the fill happens in check_exits
+            // (re-read so trailing starts on the same tick TP1 fills)
+            let trailing = self.trail_after_tp1_setups.contains(&setup)
+                && self
+                    .position_manager
+                    .open_positions()
+                    .iter()
+                    .any(|p| p.id == pos_id && p.tp1_filled);
+            if trailing {
+                let trail = match side {
+                    crate::types::Side::Buy => current_price * (Decimal::ONE - self.trail_after_tp1_ratio),
+                    crate::types::Side::Sell => current_price * (Decimal::ONE + self.trail_after_tp1_ratio),
+                };
+                if self.position_manager.ratchet_trailing_stop(&pos_id, trail) {
+                    debug!(position_id = %pos_id, trailing_stop = %trail, "Trailing stop moved");
+                }
+                continue;
+            }
+
+            // TP2 reached (or reverse flow), close 100%
             if tp1_filled {
                 if let Some(tp2) = tp2_price {
                     let tp2_reached = match side {
@@ -723,22 +1990,22 @@ impl SimulatorEngine {
                         if let Some(pos) = self.position_manager.close_position(
                             &pos_id,
                             tp2,
-                            self.fee_rate,
+                            self.limit_fee_rate,
                             ExitReason::TP2,
                         ) {
+                            let mut pos = pos;
+                            self.record_exit_book(&mut pos);
                             self.risk_manager.close_position(&pos);
+                            self.alert_balance_floor();
                             self.trade_logger.log_trade(&pos);
                             self.record_hourly_expectancy(&pos);
-                            self.symbol_stats
-                                .entry(pos.symbol.clone())
-                                .or_default()
-                                .record_close(pos.pnl);
+                            self.record_symbol_close(&pos);
 
                             info!(
                                 position_id = %pos_id,
                                 tp2_price = %tp2,
                                 total_pnl = %pos.pnl,
-                                "TP2 hit: 100% closed at VAH"
+                                "TP2 hit: 100% closed"
                             );
 
                             // Send TP2 execution event
@@ -776,13 +2043,13 @@ impl SimulatorEngine {
                         self.fee_rate,
                         ExitReason::SoftStop,
                     ) {
+                        let mut pos = pos;
+                        self.record_exit_book(&mut pos);
                         self.risk_manager.close_position(&pos);
+                        self.alert_balance_floor();
                         self.trade_logger.log_trade(&pos);
                         self.record_hourly_expectancy(&pos);
-                        self.symbol_stats
-                            .entry(pos.symbol.clone())
-                            .or_default()
-                            .record_close(pos.pnl);
+                        self.record_symbol_close(&pos);
 
                         warn!(
                             position_id = %pos_id,
@@ -802,7 +2069,60 @@ impl SimulatorEngine {
         }
     }
 
+    /// `quantity` rounded down to the exchange step, or None when that is below the
+    /// minimum quantity or notional at `price` (unchanged without exchange rules)
+    fn exchange_valid_quantity(&self, symbol: &str, quantity: Decimal, price: Decimal) -> Option<Decimal> {
+        let Some(rules) = self
+            .exchange_info
+            .as_ref()
+            .and_then(|exchange| exchange.symbol_info(symbol))
+        else {
+            return Some(quantity);
+        };
+        let quantity = rules.round_quantity_with(quantity, RoundingMode::Down).ok()?;
+        rules.validate_notional(price, quantity).ok()?;
+        Some(quantity)
+    }
+
+    /// TP1 close size: half the position on the exchange step grid, or None when
+    /// either half would be below the exchange minimum quantity or notional at `price`
+    fn tp1_close_quantity(&self, symbol: &str, quantity: Decimal, price: Decimal) -> Option<Decimal> {
+        let half = quantity / Decimal::TWO;
+        if !self.config.close_dust_remainder && !self.config.tp1_check_min_notional {
+            return Some(half);
+        }
+        let Some(info) = self
+            .exchange_info
+            .as_ref()
+            .and_then(|exchange| exchange.symbol_info(symbol))
+        else {
+            return Some(half);
+        };
+        let half = if info.quantity_step_size > Decimal::ZERO {
+            RoundingMode::Down.apply(half, info.quantity_step_size)
+        } else {
+            half
+        };
+        let remainder = quantity - half;
+        if self.config.close_dust_remainder
+            && (half <= Decimal::ZERO || half < info.min_quantity || remainder < info.min_quantity)
+        {
+            return None;
+        }
+        if self.config.tp1_check_min_notional
+            && (info.validate_notional(price, half).is_err()
+                || info.validate_notional(price, remainder).is_err())
+        {
+            return None;
+        }
+        Some(half)
+    }
+
     fn shutdown_summary(&mut self) {
+        if let Some(sink) = self.book_sink.as_ref() {
+            sink.flush();
+        }
+        self.trade_logger.flush();
         let finalized: Vec<_> = self
             .position_manager
             .finalized_positions()
@@ -820,3 +2140,1510 @@ impl SimulatorEngine {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::{ExchangeInfoManager, SymbolInfo};
+    use crate::exchange::SymbolRules;
+    use crate::config::RiskConfig;
+    use crate::types::{DepthLevel, SetupType, Side};
+
+    fn sim_config(extra: &str) -> SimulatorConfig {
+        toml::from_str(&format!(
+            r#"
+            slippage_ticks = 1
+            maker_fee = 0.0002
+            taker_fee = 0.0004
+            order_book_depth = 20
+            leverage = 50.0
+            margin_type = "Isolated"
+            maintenance_margin_rate = 0.004
+            max_spread_bps = 1000.0
+            min_depth_imbalance_ratio = 0.01
+            slippage_model_enabled = false
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    fn risk_config(extra: &str) -> RiskConfig {
+        toml::from_str(&format!(
+            r#"
+            initial_balance = 10000.0
+            max_risk_per_trade = 0.01
+            daily_loss_limit_pct = 0.03
+            max_concurrent_positions = 5
+            break_even_ticks = 3
+            default_stop_ticks = 10
+            default_target_multiplier = 2.0
+            confidence_sizing_enabled = false
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    fn engine(sim_extra: &str, risk_extra: &str) -> SimulatorEngine {
+        let dir = std::env::temp_dir();
+        let id = uuid::Uuid::new_v4();
+        let logger = TradeLogger::new(
+            dir.join(format!("rusto-test-{}.csv", id)).display().to_string(),
+            dir.join(format!("rusto-test-{}.json", id)).display().to_string(),
+            ":memory:".to_string(),
+        );
+        let config = sim_config(sim_extra);
+        let leverage = Decimal::try_from(config.leverage).unwrap();
+        let risk = RiskManager::new(&risk_config(risk_extra), leverage);
+        SimulatorEngine::new(config, risk, logger)
+    }
+
+    fn level(price: i64, quantity: i64) -> DepthLevel {
+        DepthLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        }
+    }
+
+    fn depth(symbol: &str, bids: Vec<DepthLevel>, asks: Vec<DepthLevel>) -> DepthUpdate {
+        DepthUpdate {
+            symbol: symbol.to_string(),
+            bids,
+            asks,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn trade(symbol: &str, price: i64) -> NormalizedTrade {
+        NormalizedTrade {
+            symbol: symbol.to_string(),
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+            side: Side::Buy,
+            timestamp: Utc::now(),
+            trade_id: 1,
+        }
+    }
+
+    fn signal(symbol: &str, side: Side, entry: i64, stop: i64, target: i64) -> TradeSignal {
+        TradeSignal::new(
+            symbol.to_string(),
+            side,
+            SetupType::MomentumSqueeze,
+            Decimal::from(entry),
+            Decimal::from(stop),
+            Decimal::from(target),
+            Decimal::ONE,
+        )
+    }
+
+    #[test]
+    fn test_microprice_reference_anchors_stop_and_target() {
+        let mut sim = engine(r#"price_reference = "Microprice""#, "");
+        // Thin bid, heavy ask → microprice leans toward the bid: (100*3 + 101*1) / 4
+        sim.on_depth(&depth("btcusdt", vec![level(100, 1)], vec![level(101, 3)]));
+        // Last trade printed well above the book
+        sim.execute_signal(signal("btcusdt", Side::Buy, 102, 101, 104));
+
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        let pos = open[0];
+        assert_eq!(pos.entry_price, Decimal::new(10025, 2));
+        assert_eq!(pos.stop_loss, Decimal::new(9925, 2));
+        assert_eq!(pos.take_profit, Decimal::new(10225, 2));
+    }
+
+    #[test]
+    fn test_last_trade_reference_keeps_signal_prices() {
+        let mut sim = engine("", "");
+        sim.on_depth(&depth("btcusdt", vec![level(100, 1)], vec![level(101, 3)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 102, 101, 104));
+
+        let pos = sim.position_manager.open_positions()[0].clone();
+        assert_eq!(pos.entry_price, Decimal::from(102));
+        assert_eq!(pos.stop_loss, Decimal::from(101));
+        assert_eq!(pos.take_profit, Decimal::from(104));
+    }
+
+    #[test]
+    fn test_book_conditions_recorded_at_entry_and_exit() {
+        let mut sim = engine("", "");
+        sim.on_depth(&depth("btcusdt", vec![level(100, 4)], vec![level(101, 2)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 101, 99, 104));
+
+        // Book widens and flips before the target prints
+        sim.on_depth(&depth(
+            "btcusdt",
+            vec![level(100, 0), level(99, 1)],
+            vec![level(101, 0), level(102, 3)],
+        ));
+        sim.on_trade(&trade("btcusdt", 104));
+
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed.len(), 1);
+        let pos = closed[0];
+        assert_eq!(pos.entry_spread, Some(Decimal::ONE));
+        assert_eq!(pos.entry_depth_imbalance, Some(Decimal::TWO));
+        assert_eq!(pos.exit_spread, Some(Decimal::from(3)));
+        assert_eq!(pos.exit_depth_imbalance, Some(Decimal::ONE / Decimal::from(3)));
+    }
+
+    #[test]
+    fn test_book_ticker_feeds_spread_filter() {
+        let mut sim = engine("", "");
+        sim.max_spread_bps = Decimal::from(50);
+        let ticker = |bid: i64, ask: i64| BookTicker {
+            symbol: "btcusdt".to_string(),
+            bid_price: Decimal::from(bid),
+            bid_qty: Decimal::from(5),
+            ask_price: Decimal::from(ask),
+            ask_qty: Decimal::from(5),
+            timestamp: Utc::now(),
+        };
+
+        // 2 / 101 ≈ 198 bps: too wide
+        sim.handle_market_event(MarketEvent::BookTicker(ticker(100, 102)));
+        let book = &sim.order_books["btcusdt"];
+        assert_eq!(book.best_bid(), Some(Decimal::from(100)));
+        assert_eq!(book.best_ask(), Some(Decimal::from(102)));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 101, 99, 105));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        // Next tick replaces the top of book: 0.1 / 1000.05 ≈ 1 bps
+        sim.handle_market_event(MarketEvent::BookTicker(BookTicker {
+            bid_price: Decimal::new(10000, 1),
+            ask_price: Decimal::new(10001, 1),
+            ..ticker(0, 0)
+        }));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1020));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+    }
+
+    #[test]
+    fn test_tp2_r_multiple_source_sets_position_target() {
+        let mut sim = engine("tp2_source = \"RMultiple\"\ntp2_r_multiple = 2.0", "");
+        sim.handle_processing_event(ProcessingEvent::VolumeProfile(VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from(1010),
+            vah: Decimal::from(1030),
+            val: Decimal::from(980),
+            total_volume: Decimal::from(500),
+            session_high: Decimal::from(1050),
+            session_low: Decimal::from(960),
+            session_start: Utc::now(),
+            vwap: Decimal::from(1005),
+            hvn: None,
+            timestamp: Utc::now(),
+        }));
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(TradeSignal {
+            setup: SetupType::AdvancedOrderFlow,
+            ..signal("btcusdt", Side::Buy, 1000, 990, 1030)
+        });
+
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        let risk = open[0].entry_price - open[0].stop_loss;
+        assert!(risk > Decimal::ZERO);
+        assert_eq!(open[0].tp2_price, Some(open[0].entry_price + risk * Decimal::TWO));
+
+        // Without a profile there are no stages, but the target is still 2R, not the signal's
+        let mut sim = engine("tp2_source = \"RMultiple\"\ntp2_r_multiple = 2.0", "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(TradeSignal {
+            setup: SetupType::AdvancedOrderFlow,
+            ..signal("btcusdt", Side::Buy, 1000, 990, 1030)
+        });
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].tp2_price, None);
+        let risk = open[0].entry_price - open[0].stop_loss;
+        assert_eq!(open[0].take_profit, open[0].entry_price + risk * Decimal::TWO);
+    }
+
+    #[test]
+    fn test_tp1_poc_source_fills_multi_stage_exit() {
+        let mut sim = engine(r#"tp1_source = "POC""#, "");
+        sim.handle_processing_event(ProcessingEvent::VolumeProfile(VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from(1010),
+            vah: Decimal::from(1030),
+            val: Decimal::from(980),
+            total_volume: Decimal::from(500),
+            session_high: Decimal::from(1050),
+            session_low: Decimal::from(960),
+            session_start: Utc::now(),
+            vwap: Decimal::from(1005),
+            hvn: None,
+            timestamp: Utc::now(),
+        }));
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(TradeSignal {
+            setup: SetupType::AdvancedOrderFlow,
+            ..signal("btcusdt", Side::Buy, 1000, 990, 1040)
+        });
+
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].tp1_price, Some(Decimal::from(1010)));
+        assert_eq!(open[0].tp2_price, Some(Decimal::from(1030)));
+        let full_qty = open[0].quantity;
+
+        // POC prints (VWAP already passed): half closes at TP1
+        sim.on_trade(&trade("btcusdt", 1010));
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert!(open[0].tp1_filled);
+        assert_eq!(open[0].quantity, full_qty / Decimal::TWO);
+
+        sim.on_trade(&trade("btcusdt", 1030));
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::TP2));
+    }
+
+    #[test]
+    fn test_tp1_closes_fully_instead_of_sub_minimum_split() {
+        let run = |min_quantity: i64, min_notional: i64, extra: &str| {
+            let mut sim = engine(&format!("tp1_source = \"POC\"\n{}", extra), "");
+            let info = SymbolRules {
+                base_asset: "BTC".to_string(),
+                quote_asset: "USDT".to_string(),
+                price_tick_size: Decimal::ONE,
+                min_price: Decimal::ONE,
+                max_price: Decimal::from(100_000),
+                quantity_step_size: Decimal::ONE,
+                min_quantity: Decimal::from(min_quantity),
+                max_quantity: Decimal::from(1000),
+                min_notional: Decimal::from(min_notional),
+                max_leverage: None,
+            };
+            sim.set_exchange_info(Arc::new(MockExchange {
+                symbols: BTreeMap::from([("btcusdt".to_string(), info)]),
+            }));
+            sim.handle_processing_event(ProcessingEvent::VolumeProfile(VolumeProfileSnapshot {
+                symbol: "btcusdt".to_string(),
+                poc: Decimal::from(1010),
+                vah: Decimal::from(1030),
+                val: Decimal::from(980),
+                total_volume: Decimal::from(500),
+                session_high: Decimal::from(1050),
+                session_low: Decimal::from(960),
+                session_start: Utc::now(),
+                vwap: Decimal::from(1005),
+                hvn: None,
+                timestamp: Utc::now(),
+            }));
+            sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+            sim.execute_signal(TradeSignal {
+                setup: SetupType::AdvancedOrderFlow,
+                ..signal("btcusdt", Side::Buy, 1000, 990, 1040)
+            });
+            let quantity = sim.position_manager.open_positions()[0].quantity;
+            sim.on_trade(&trade("btcusdt", 1010));
+            (sim, quantity)
+        };
+
+        // 1% of 10000 over a 10-wide stop = 10 units, split 5/5
+        let (sim, quantity) = run(1, 5, "");
+        assert_eq!(quantity, Decimal::TEN);
+        let open = sim.position_manager.open_positions();
+        assert!(open[0].tp1_filled);
+        assert_eq!(open[0].quantity, Decimal::from(5));
+
+        // A 5-unit half is below a 6-unit minimum: close everything at TP1
+        let (sim, _) = run(6, 5, "");
+        assert!(sim.position_manager.open_positions().is_empty());
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed[0].quantity, Decimal::TEN);
+        assert_eq!(closed[0].exit_price, Some(Decimal::from(1010)));
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::TakeProfit));
+
+        // Each 5-unit half is 5050 at TP1, below a 6000 minimum notional
+        let (sim, _) = run(1, 6000, "");
+        assert!(sim.position_manager.open_positions().is_empty());
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed[0].quantity, Decimal::TEN);
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::TakeProfit));
+
+        // With the check off the sub-minimum partial still goes through
+        let (sim, _) = run(1, 6000, "tp1_check_min_notional = false");
+        assert_eq!(sim.position_manager.open_positions()[0].quantity, Decimal::from(5));
+    }
+
+    #[test]
+    fn test_negative_maker_fee_rebates_take_profit_exit() {
+        let close_at_target = |maker_on_limit: bool| {
+            let mut config = sim_config(&format!("maker_fee_on_limit_fills = {}", maker_on_limit));
+            config.maker_fee = -0.0001;
+            let leverage = Decimal::try_from(config.leverage).unwrap();
+            let risk = RiskManager::new(&risk_config(""), leverage);
+            let logger = TradeLogger::new(String::new(), String::new(), ":memory:".to_string());
+            let mut sim = SimulatorEngine::new(config, risk, logger);
+            sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+            sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+            sim.on_trade(&trade("btcusdt", 1040));
+            sim.position_manager.closed_positions()[0].clone()
+        };
+
+        let taker = close_at_target(false);
+        let maker = close_at_target(true);
+        assert_eq!(maker.exit_reason, Some(ExitReason::TakeProfit));
+        let exit_notional = maker.exit_price.unwrap() * maker.quantity;
+        let raw = exit_notional - maker.entry_price * maker.quantity;
+        let entry_fee = maker.entry_price * maker.quantity * Decimal::new(4, 4);
+        // Taker entry fee paid, maker exit rebate received
+        assert_eq!(maker.pnl, raw - entry_fee + exit_notional * Decimal::new(1, 4));
+        assert!(maker.pnl > raw - entry_fee);
+        assert_eq!(maker.pnl - taker.pnl, exit_notional * Decimal::new(5, 4));
+    }
+
+    #[test]
+    fn test_stale_profile_keeps_signal_take_profit() {
+        let mut sim = engine("max_profile_age_secs = 300", "");
+        sim.handle_processing_event(ProcessingEvent::VolumeProfile(VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from(1010),
+            vah: Decimal::from(1030),
+            val: Decimal::from(980),
+            total_volume: Decimal::from(500),
+            session_high: Decimal::from(1050),
+            session_low: Decimal::from(960),
+            session_start: Utc::now() - chrono::Duration::hours(2),
+            vwap: Decimal::from(1005),
+            hvn: None,
+            timestamp: Utc::now() - chrono::Duration::hours(2),
+        }));
+        sim.on_trade(&trade("btcusdt", 1000));
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(TradeSignal {
+            setup: SetupType::AdvancedOrderFlow,
+            ..signal("btcusdt", Side::Buy, 1000, 990, 1040)
+        });
+
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].tp1_price, None);
+        assert_eq!(open[0].tp2_price, None);
+        assert_eq!(open[0].take_profit, Decimal::from(1040));
+    }
+
+    #[test]
+    fn test_book_snapshots_follow_interval_and_live_book() {
+        let path = std::env::temp_dir().join(format!("rusto-books-{}.jsonl", uuid::Uuid::new_v4()));
+        let path = path.display().to_string();
+        let mut sim = engine("", "");
+        sim.set_book_sink(MarketSink::open("Jsonl", &path).unwrap(), 1000, 2);
+
+        let start = Utc::now();
+        let mut expected = Vec::new();
+        for (i, ms) in [0i64, 300, 1100, 1500, 2200].iter().enumerate() {
+            let bid = 100 + i as i64;
+            // Each update steps the quote up a tick, clearing the previous level
+            let mut update = depth(
+                "btcusdt",
+                vec![level(bid - 1, 0), level(bid, 5)],
+                vec![level(bid + 1, 0), level(bid + 2, 5)],
+            );
+            update.timestamp = start + chrono::Duration::milliseconds(*ms);
+            sim.on_depth(&update);
+            let book = &sim.order_books["btcusdt"];
+            if [0, 1100, 2200].contains(ms) {
+                assert_eq!(book.best_ask(), Some(Decimal::from(bid + 2)));
+                expected.push((book.best_bid().unwrap(), book.best_ask().unwrap()));
+            }
+        }
+        sim.book_sink.as_ref().unwrap().flush();
+
+        let rows: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let recorded: Vec<(Decimal, Decimal)> = rows
+            .iter()
+            .map(|r| {
+                assert_eq!(r["kind"], "book");
+                let price = |side: &str| r[side][0]["price"].as_str().unwrap().parse().unwrap();
+                (price("bids"), price("asks"))
+            })
+            .collect();
+        assert_eq!(recorded, expected);
+        assert!(rows.iter().all(|r| r["bids"].as_array().unwrap().len() <= 2));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_trailing_remainder_after_tp1_beats_fixed_tp2() {
+        let mut sim = engine(
+            r#"
+            tp1_source = "POC"
+            trail_after_tp1_setups = ["AdvancedOrderFlow"]
+            trail_after_tp1_pct = 0.5
+            "#,
+            "",
+        );
+        sim.handle_processing_event(ProcessingEvent::VolumeProfile(VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from(1010),
+            vah: Decimal::from(1030),
+            val: Decimal::from(980),
+            total_volume: Decimal::from(500),
+            session_high: Decimal::from(1050),
+            session_low: Decimal::from(960),
+            session_start: Utc::now(),
+            vwap: Decimal::from(1005),
+            hvn: None,
+            timestamp: Utc::now(),
+        }));
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(TradeSignal {
+            setup: SetupType::AdvancedOrderFlow,
+            ..signal("btcusdt", Side::Buy, 1000, 990, 1040)
+        });
+
+        sim.on_trade(&trade("btcusdt", 1010));
+        assert!(sim.position_manager.open_positions()[0].tp1_filled);
+
+        // Runs through VAH (fixed TP2) and the signal target without closing
+        for price in [1030, 1045, 1060, 1080] {
+            sim.on_trade(&trade("btcusdt", price));
+        }
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].trailing_stop, Some(Decimal::new(10746, 1)));
+
+        // Pullback hits the trail: 1080 - 0.5%
+        sim.on_trade(&trade("btcusdt", 1070));
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::TrailingStop));
+        assert!(closed[0].exit_price.unwrap() > Decimal::from(1030));
+    }
+
+    #[test]
+    fn test_liquidation_buffer_rejects_overleveraged_entry() {
+        // 50x: liquidation ~1.5% from entry, inside the 2% stop
+        let mut sim = engine("min_liquidation_buffer_ratio = 1.0", "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        // 10x: liquidation ~9.5% away, well beyond the stop
+        let mut sim = engine("min_liquidation_buffer_ratio = 1.0", "");
+        sim.leverage = Decimal::from(10);
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert!(open[0].liquidation_price < Decimal::from(980));
+    }
+
+    #[test]
+    fn test_symbol_leverage_overrides_global_default() {
+        let mut sim = engine(
+            r#"
+            symbol_leverage = { ethusdt = 10.0, solusdt = 200.0 }
+            "#,
+            "",
+        );
+        for symbol in ["btcusdt", "ethusdt", "solusdt"] {
+            sim.on_depth(&depth(symbol, vec![level(999, 5)], vec![level(1001, 5)]));
+            sim.execute_signal(signal(symbol, Side::Buy, 1000, 990, 1040));
+        }
+        let open = sim.position_manager.open_positions();
+        let by_symbol = |symbol: &str| open.iter().find(|p| p.symbol == symbol).unwrap();
+
+        let btc = by_symbol("btcusdt");
+        let eth = by_symbol("ethusdt");
+        let sol = by_symbol("solusdt");
+        assert_eq!(btc.leverage, Decimal::from(50));
+        assert_eq!(eth.leverage, Decimal::from(10));
+        // Override above the default max_leverage is clamped
+        assert_eq!(sol.leverage, Decimal::from(125));
+
+        assert_eq!(btc.initial_margin, btc.entry_price * btc.quantity / Decimal::from(50));
+        assert_eq!(eth.initial_margin, eth.entry_price * eth.quantity / Decimal::from(10));
+        // Lower leverage puts liquidation further from entry
+        assert!(eth.liquidation_price < btc.liquidation_price);
+        assert!(btc.liquidation_price < sol.liquidation_price);
+    }
+
+    #[test]
+    fn test_stop_beyond_liquidation_is_rejected() {
+        // 50x long: liquidation ~984.8, above the 980 stop
+        let mut sim = engine(r#"stop_liquidation_check = "Reject""#, "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        let mut positions = PositionManager::new();
+        positions.set_stop_liquidation_check(StopLiquidationCheck::Reject);
+        let err = positions
+            .open_position(
+                &signal("btcusdt", Side::Buy, 1000, 980, 1040),
+                Decimal::ONE,
+                Decimal::from(50),
+                MarginType::Isolated,
+                Decimal::new(4, 3),
+                Decimal::new(4, 4),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Buy stop 980 is not between entry 1000 and liquidation 984.8000"
+        );
+        // A stop on the wrong side of entry is rejected too
+        let err = positions
+            .open_position(
+                &signal("btcusdt", Side::Buy, 1000, 1010, 1040),
+                Decimal::ONE,
+                Decimal::from(10),
+                MarginType::Isolated,
+                Decimal::new(4, 3),
+                Decimal::new(4, 4),
+            )
+            .unwrap_err();
+        assert!(matches!(err, crate::simulator::position::PositionError::StopBeyondLiquidation { .. }));
+
+        // Warn mode (the default) still opens the position
+        let mut sim = engine("", "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+    }
+
+    #[test]
+    fn test_statsd_emits_signal_fill_rejection_and_latency_packets() {
+        let listener = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+        let host = listener.local_addr().unwrap().to_string();
+        let mut sim = engine(r#"stop_liquidation_check = "Reject""#, "");
+        sim.set_statsd(StatsdEmitter::connect(&host, "rusto", true).unwrap());
+        sim.max_entry_latency_ms = Some(500.0);
+
+        sim.on_depth(&depth("ethusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("ethusdt", Side::Buy, 1000, 990, 1040));
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        sim.handle_processing_event(ProcessingEvent::LatencySample { rtt_ms: Some(42.5) });
+
+        let mut buf = [0u8; 512];
+        let packets: Vec<String> = (0..6)
+            .map(|_| {
+                let n = listener.recv(&mut buf).unwrap();
+                String::from_utf8_lossy(&buf[..n]).to_string()
+            })
+            .collect();
+        assert_eq!(
+            packets,
+            vec![
+                "rusto.signals:1|c|#symbol:ethusdt,side:Buy",
+                "rusto.fills:1|c|#symbol:ethusdt",
+                "rusto.open_positions:1|g",
+                "rusto.signals:1|c|#symbol:btcusdt,side:Buy",
+                "rusto.rejections:1|c|#symbol:btcusdt,reason:stop_beyond_liquidation",
+                "rusto.latency:42.5|ms",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binance_liquidation_formula_matches_documented_sample() {
+        // BTCUSDT brackets: <= 50k notional 0.4% / cum 0, <= 250k 0.5% / cum 50
+        let sim = engine(
+            r#"
+            liquidation_formula = "Binance"
+            maintenance_brackets = [
+                { notional_cap = 50000.0, rate = 0.004, amount = 0.0 },
+                { notional_cap = 250000.0, rate = 0.005, amount = 50.0 },
+            ]
+            "#,
+            "",
+        );
+        let liquidation = |side, quantity: i64| {
+            sim.position_manager.liquidation_price(
+                side,
+                Decimal::from(10_000),
+                Decimal::from(quantity),
+                Decimal::from(20),
+                Decimal::new(4, 3),
+                Decimal::new(4, 4),
+            )
+        };
+        let tick = Decimal::new(1, 1);
+
+        // 10 BTC at 10,000, 20x: WB = 5,000, second bracket
+        // long:  (5000 + 50 - 100000) / (10 * 0.005 - 10) = 9542.71
+        // short: (5000 + 50 + 100000) / (10 * 0.005 + 10) = 10452.74
+        assert!((liquidation(Side::Buy, 10) - Decimal::new(954271, 2)).abs() < tick);
+        assert!((liquidation(Side::Sell, 10) - Decimal::new(1045274, 2)).abs() < tick);
+        // 1 BTC falls in the first bracket: (500 - 10000) / (0.004 - 1) = 9538.15
+        assert!((liquidation(Side::Buy, 1) - Decimal::new(953815, 2)).abs() < tick);
+
+        // The simplified model ignores size and the maintenance amount
+        let simplified = engine("", "").position_manager.liquidation_price(
+            Side::Buy,
+            Decimal::from(10_000),
+            Decimal::from(10),
+            Decimal::from(20),
+            Decimal::new(4, 3),
+            Decimal::new(4, 4),
+        );
+        assert_eq!(simplified, Decimal::new(9548, 0));
+    }
+
+    #[test]
+    fn test_emergency_stop_fires_inside_min_hold_window() {
+        let mut sim = engine("", "stop_min_hold_secs = 60");
+        sim.leverage = Decimal::from(10);
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        // 2x the 20-point stop distance
+        assert_eq!(open[0].emergency_stop, Some(Decimal::from(960)));
+
+        // Mild move through the normal stop: suppressed by the min-hold window
+        sim.on_trade(&trade("btcusdt", 975));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        // Catastrophic move: the emergency stop ignores the window
+        sim.on_trade(&trade("btcusdt", 950));
+        assert!(sim.position_manager.open_positions().is_empty());
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::EmergencyStop));
+        assert_eq!(closed[0].exit_price, Some(Decimal::from(960)));
+    }
+
+    #[test]
+    fn test_armed_stop_wins_gap_through_emergency_stop() {
+        let mut sim = engine("", "");
+        sim.leverage = Decimal::from(10);
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(
+            sim.position_manager.open_positions()[0].emergency_stop,
+            Some(Decimal::from(960))
+        );
+
+        // Gap through both stops after the hold window: the normal stop fills
+        sim.on_trade(&trade("btcusdt", 950));
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::StopLoss));
+        assert_eq!(closed[0].exit_price, Some(Decimal::from(980)));
+    }
+
+    #[test]
+    fn test_period_closes_only_queued_for_daily_digest() {
+        for digest in [false, true] {
+            let stats = Arc::new(Mutex::new(BotStats::default()));
+            let mut sim = engine("", "");
+            sim.set_bot_stats(stats.clone());
+            sim.set_daily_digest(digest);
+            sim.leverage = Decimal::from(10);
+            sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+            sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+            sim.on_trade(&trade("btcusdt", 970));
+            assert_eq!(sim.position_manager.closed_positions().len(), 1);
+            assert_eq!(stats.lock().unwrap().period_closes.len(), usize::from(digest));
+        }
+    }
+
+    #[test]
+    fn test_entry_confirmation_cancels_on_reversal() {
+        let start = Utc::now();
+        let at = |ms: i64, bid_qty: i64, ask_qty: i64| {
+            let mut update = depth("btcusdt", vec![level(999, bid_qty)], vec![level(1001, ask_qty)]);
+            update.timestamp = start + Duration::milliseconds(ms);
+            MarketEvent::Depth(update)
+        };
+
+        // Imbalance flips to the ask side inside the window: no entry
+        let mut sim = engine("entry_confirmation_ms = 500", "");
+        sim.min_depth_imbalance_ratio = Decimal::new(15, 1);
+        sim.handle_market_event(at(0, 10, 5));
+        sim.handle_processing_event(ProcessingEvent::Signal(signal("btcusdt", Side::Buy, 1000, 980, 1040)));
+        sim.handle_market_event(at(100, 10, 5));
+        sim.handle_market_event(at(300, 1, 10));
+        sim.handle_market_event(at(700, 10, 5));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        // Imbalance persists through the window: filled once it closes
+        let mut sim = engine("entry_confirmation_ms = 500", "");
+        sim.min_depth_imbalance_ratio = Decimal::new(15, 1);
+        sim.handle_market_event(at(0, 10, 5));
+        sim.handle_processing_event(ProcessingEvent::Signal(signal("btcusdt", Side::Buy, 1000, 980, 1040)));
+        sim.handle_market_event(at(100, 10, 5));
+        sim.handle_market_event(at(300, 12, 5));
+        assert!(sim.position_manager.open_positions().is_empty());
+        sim.handle_market_event(at(700, 12, 4));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        // A marketable entry (buy at the ask) is a taker order: it fills without waiting
+        let mut sim = engine("entry_confirmation_ms = 500", "");
+        sim.min_depth_imbalance_ratio = Decimal::new(15, 1);
+        sim.handle_market_event(at(0, 10, 5));
+        sim.handle_processing_event(ProcessingEvent::Signal(signal("btcusdt", Side::Buy, 1001, 980, 1040)));
+        assert!(sim.pending_entries.is_empty());
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+    }
+
+    #[test]
+    fn test_imbalance_threshold_scales_with_realized_volatility() {
+        // Ten 10-point bars on ~1000: 1000 bps of range traversed
+        let feed_bars = |sim: &mut SimulatorEngine, minutes: i64| {
+            let start = Utc::now() - Duration::minutes(minutes);
+            let step = Duration::minutes(minutes) / 10;
+            for i in 0..10 {
+                sim.handle_processing_event(ProcessingEvent::NewBar(RangeBar {
+                    symbol: "btcusdt".to_string(),
+                    open: Decimal::from(995),
+                    high: Decimal::from(1005),
+                    low: Decimal::from(995),
+                    close: Decimal::from(1000),
+                    volume: Decimal::ONE,
+                    buy_volume: Decimal::ONE,
+                    sell_volume: Decimal::ZERO,
+                    open_time: start + step * i,
+                    close_time: start + step * (i + 1),
+                    footprint: BTreeMap::new(),
+                    bar_index: i as u64,
+                }));
+            }
+        };
+        let setup = |minutes: i64| {
+            let mut sim = engine("imbalance_vol_reference_bps = 100.0", "");
+            sim.min_depth_imbalance_ratio = Decimal::new(12, 1);
+            feed_bars(&mut sim, minutes);
+            // Bids lean 1.4x
+            sim.on_depth(&depth("btcusdt", vec![level(999, 7)], vec![level(1001, 5)]));
+            sim
+        };
+
+        // Calm: 1000 bps over 20 minutes = 50 bps/min -> edge halved, 1.1x required
+        let mut calm = setup(20);
+        assert_eq!(calm.required_imbalance_ratio("btcusdt"), Decimal::new(11, 1));
+        calm.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        assert_eq!(calm.position_manager.open_positions().len(), 1);
+
+        // Fast: same bars in 4 minutes = 250 bps/min -> edge x2.5, 1.5x required
+        let mut fast = setup(4);
+        assert_eq!(fast.required_imbalance_ratio("btcusdt"), Decimal::new(15, 1));
+        fast.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        assert!(fast.position_manager.open_positions().is_empty());
+    }
+
+    #[test]
+    fn test_expectancy_median_ignores_single_outlier_loss() {
+        let hour_with = |extra: &str| {
+            let mut sim = engine(&format!("expectancy_min_trades_per_hour = 5\n{}", extra), "");
+            let probe = signal("btcusdt", Side::Buy, 1000, 990, 1040);
+            let pnls = [10, 12, 8, 11, 9, -200].map(Decimal::from).to_vec();
+            sim.hourly_performance
+                .insert(("btcusdt".to_string(), probe.timestamp.hour()), HourlyPerformance { pnls });
+            (sim, probe)
+        };
+
+        // Mean is dragged to -25 by the one outlier
+        let (sim, probe) = hour_with("");
+        assert!(!sim.passes_expectancy_filter(&probe));
+
+        // Median and 20%-trimmed mean (both 9.5) see the hour as profitable
+        let (sim, probe) = hour_with(r#"expectancy_estimator = "Median""#);
+        assert!(sim.passes_expectancy_filter(&probe));
+        let (sim, probe) = hour_with("expectancy_estimator = \"TrimmedMean\"\nexpectancy_trim_pct = 20.0");
+        assert!(sim.passes_expectancy_filter(&probe));
+
+        // Mean with a 95% bound: too noisy to call the hour negative
+        let (sim, probe) = hour_with("expectancy_confidence_z = 1.96");
+        assert!(sim.passes_expectancy_filter(&probe));
+    }
+
+    #[test]
+    fn test_buy_absorption_closes_open_long_early() {
+        let flow = |absorbed: Side, burst: i64| crate::types::OrderFlowMetrics {
+            symbol: "btcusdt".to_string(),
+            cvd: Decimal::ZERO,
+            bar_delta: Decimal::ZERO,
+            absorption_detected: true,
+            absorption_side: Some(absorbed),
+            imbalance_ratio: Some(Decimal::ONE),
+            cvd_1min_change: Decimal::ZERO,
+            cvd_rapid_drop: false,
+            cvd_rapid_rise: false,
+            avg_bar_volume: Decimal::ONE,
+            volume_burst_ratio: Decimal::from(burst),
+            volume_burst: burst >= 2,
+            volume_rate_ratio: None,
+            timestamp: Utc::now(),
+        };
+        let mut sim = engine(
+            r#"
+            exit_on_opposite_absorption = true
+            absorption_exit_min_burst_ratio = 2.0
+            "#,
+            "",
+        );
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        sim.on_trade(&trade("btcusdt", 1015));
+
+        // Sell absorption supports the long; weak buy absorption is ignored
+        sim.handle_processing_event(ProcessingEvent::OrderFlow(flow(Side::Sell, 3)));
+        sim.handle_processing_event(ProcessingEvent::OrderFlow(flow(Side::Buy, 1)));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        sim.handle_processing_event(ProcessingEvent::OrderFlow(flow(Side::Buy, 3)));
+        assert!(sim.position_manager.open_positions().is_empty());
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::OppositeAbsorption));
+        assert_eq!(closed[0].exit_price, Some(Decimal::from(1015)));
+    }
+
+    #[test]
+    fn test_throttled_exit_checks_still_stop_out_inside_window() {
+        let mut sim = engine("exit_check_interval_ms = 1000", "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        let start = sim.position_manager.open_positions()[0].entry_time;
+        let at = |ms: i64, price: i64| NormalizedTrade {
+            timestamp: start + Duration::milliseconds(ms),
+            ..trade("btcusdt", price)
+        };
+
+        sim.on_trade(&at(0, 1000));
+        assert_eq!(sim.last_exit_check.get("btcusdt"), Some(&start));
+        // Inside the window and away from every level: skipped, but excursions still track
+        sim.on_trade(&at(100, 1020));
+        assert_eq!(sim.last_exit_check.get("btcusdt"), Some(&start));
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open[0].max_favorable_excursion_pct, Decimal::from(2));
+
+        // Stop crossed 200ms into the window: exits on that trade
+        sim.on_trade(&at(200, 989));
+        assert!(sim.position_manager.open_positions().is_empty());
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::StopLoss));
+    }
+
+    #[test]
+    fn test_crossed_book_skips_entry() {
+        let mut sim = engine("", "");
+        sim.on_depth(&depth("btcusdt", vec![level(1002, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        // One-sided after the asks are pulled
+        sim.on_depth(&depth("btcusdt", vec![level(1002, 0), level(999, 5)], vec![level(1001, 0)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        sim.on_depth(&depth("btcusdt", vec![], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+    }
+
+    #[test]
+    fn test_crossed_book_allows_entry_when_book_not_required() {
+        let mut sim = engine("require_orderbook_for_entry = false", "");
+        sim.on_depth(&depth("btcusdt", vec![level(1002, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 990, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        // The slippage model skips a book without a reliable mid the same way
+        let mut sim = engine("require_orderbook_for_entry = false", "");
+        sim.slippage_model_enabled = true;
+        sim.on_depth(&depth("ethusdt", vec![level(1002, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("ethusdt", Side::Buy, 1000, 990, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        sim.require_orderbook_for_entry = true;
+        sim.on_depth(&depth("solusdt", vec![level(1002, 5)], vec![level(1001, 5)]));
+        assert!(!sim.passes_slippage_model("solusdt", Side::Buy, Decimal::from(1000), Decimal::ONE));
+    }
+
+    #[test]
+    fn test_flatten_and_halt_closes_positions_and_blocks_entries() {
+        let mut sim = engine("", "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        sim.on_trade(&trade("btcusdt", 1005));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        sim.handle_processing_event(ProcessingEvent::FlattenAndHalt {
+            reason: "heartbeat lost".to_string(),
+        });
+        assert!(sim.position_manager.open_positions().is_empty());
+        let closed = sim.position_manager.closed_positions();
+        assert_eq!(closed[0].exit_reason, Some(ExitReason::Flatten));
+        assert_eq!(closed[0].exit_price, Some(Decimal::from(1005)));
+        assert!(sim.risk_manager.is_halted());
+
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+    }
+
+    #[test]
+    fn test_balance_floor_halts_trading_with_alert() {
+        let mut sim = engine("", "balance_floor_pct = 99.5");
+        let (tx, mut rx) = mpsc::channel(16);
+        sim.set_execution_channel(tx);
+        sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        // Stopped out for ~1% of balance, through the 99.5% floor
+        sim.on_trade(&trade("btcusdt", 975));
+        assert!(sim.position_manager.open_positions().is_empty());
+        assert!(sim.risk_manager.balance() < Decimal::from(9950));
+        assert!(sim.risk_manager.is_halted());
+        let mut alerts = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let ExecutionEvent::BalanceFloorReached { floor, .. } = event {
+                alerts.push(floor);
+            }
+        }
+        assert_eq!(alerts, [Decimal::from(9950)]);
+
+        // New signals are ignored from here on, even after a daily reset
+        sim.risk_manager.reset_daily();
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+    }
+
+    #[test]
+    fn test_balance_floor_alerts_when_already_halted() {
+        let mut sim = engine("", "balance_floor_pct = 99.5");
+        let (tx, mut rx) = mpsc::channel(16);
+        sim.set_execution_channel(tx);
+        sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        sim.risk_manager.halt("manual");
+
+        sim.on_trade(&trade("btcusdt", 975));
+        let floors: Vec<Decimal> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                ExecutionEvent::BalanceFloorReached { floor, .. } => Some(floor),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(floors, [Decimal::from(9950)]);
+    }
+
+    #[test]
+    fn test_stop_and_target_rounded_to_tick() {
+        let mut exchange_info = ExchangeInfoManager::new("http://localhost".to_string());
+        exchange_info.insert_symbol_info(SymbolInfo {
+            symbol: "BTCUSDT".to_string(),
+            status: "TRADING".to_string(),
+            rules: SymbolRules {
+                base_asset: "BTC".to_string(),
+                quote_asset: "USDT".to_string(),
+                price_tick_size: Decimal::new(5, 1),
+                min_price: Decimal::ONE,
+                max_price: Decimal::from(100_000),
+                quantity_step_size: Decimal::new(1, 3),
+                min_quantity: Decimal::new(1, 3),
+                max_quantity: Decimal::from(1000),
+                min_notional: Decimal::from(5),
+                max_leverage: None,
+            },
+        });
+        let exchange_info = Arc::new(exchange_info);
+        let open_one = |sig: TradeSignal, profile: Option<VolumeProfileSnapshot>| {
+            let mut sim = engine("", "");
+            sim.set_exchange_info(exchange_info.clone());
+            if let Some(profile) = profile {
+                sim.handle_processing_event(ProcessingEvent::VolumeProfile(profile));
+            }
+            sim.on_depth(&depth("btcusdt", vec![level(999, 5)], vec![level(1001, 5)]));
+            sim.execute_signal(sig);
+            let open = sim.position_manager.open_positions();
+            assert_eq!(open.len(), 1);
+            open[0].clone()
+        };
+
+        // Long stop rounds down (away from entry), target to the nearest tick
+        let mut sig = signal("btcusdt", Side::Buy, 1000, 980, 1040);
+        sig.stop_loss = Decimal::new(98043, 2); // 980.43
+        sig.take_profit = Decimal::new(104037, 2); // 1040.37
+        let long = open_one(sig, None);
+        assert_eq!(long.stop_loss, Decimal::from(980));
+        assert_eq!(long.take_profit, Decimal::new(10405, 1));
+
+        // Short stop rounds up
+        let mut sig = signal("btcusdt", Side::Sell, 1000, 1020, 960);
+        sig.stop_loss = Decimal::new(101907, 2); // 1019.07
+        let short = open_one(sig, None);
+        assert_eq!(short.stop_loss, Decimal::new(10195, 1));
+
+        // Multi-stage targets from the profile are tick-aligned too
+        let profile = VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from(1010),
+            vah: Decimal::new(10302, 1),
+            val: Decimal::from(980),
+            total_volume: Decimal::from(500),
+            session_high: Decimal::from(1050),
+            session_low: Decimal::from(960),
+            session_start: Utc::now(),
+            vwap: Decimal::new(10053, 1),
+            hvn: None,
+            timestamp: Utc::now(),
+        };
+        let staged = open_one(
+            TradeSignal {
+                setup: SetupType::AdvancedOrderFlow,
+                ..signal("btcusdt", Side::Buy, 1000, 990, 1040)
+            },
+            Some(profile),
+        );
+        assert_eq!(staged.tp1_price, Some(Decimal::new(10055, 1)));
+        assert_eq!(staged.tp2_price, Some(Decimal::from(1030)));
+    }
+
+    /// Venue stand-in with fixed symbol rules and no network
+    struct MockExchange {
+        symbols: BTreeMap<String, SymbolRules>,
+    }
+
+    impl Exchange for MockExchange {
+        fn symbol_info(&self, symbol: &str) -> Option<&SymbolRules> {
+            self.symbols.get(&self.normalize_symbol(symbol))
+        }
+
+        fn ws_url(&self, symbols: &[String], _book_ticker: bool) -> String {
+            format!("ws://mock/{}", symbols.join(","))
+        }
+
+        fn server_time(&self) -> crate::exchange::ExchangeFuture<'_, Result<i64, crate::exchange::ExchangeError>> {
+            Box::pin(async { Ok(0) })
+        }
+    }
+
+    #[test]
+    fn test_leverage_capped_by_exchange_rules_then_config() {
+        let rules = SymbolRules {
+            base_asset: "ETH".to_string(),
+            quote_asset: "USDT".to_string(),
+            price_tick_size: Decimal::ONE,
+            min_price: Decimal::ONE,
+            max_price: Decimal::from(100_000),
+            quantity_step_size: Decimal::new(1, 3),
+            min_quantity: Decimal::new(1, 3),
+            max_quantity: Decimal::from(1000),
+            min_notional: Decimal::from(5),
+            max_leverage: Some(Decimal::from(20)),
+        };
+        let mut sim = engine(
+            r#"
+            symbol_leverage = { solusdt = 200.0 }
+            max_leverage = 75.0
+            "#,
+            "",
+        );
+        sim.set_exchange_info(Arc::new(MockExchange {
+            symbols: BTreeMap::from([("ethusdt".to_string(), rules)]),
+        }));
+        for symbol in ["btcusdt", "ethusdt", "solusdt"] {
+            sim.on_depth(&depth(symbol, vec![level(999, 5)], vec![level(1001, 5)]));
+            sim.execute_signal(signal(symbol, Side::Buy, 1000, 990, 1040));
+        }
+        let open = sim.position_manager.open_positions();
+        let leverage = |symbol: &str| open.iter().find(|p| p.symbol == symbol).unwrap().leverage;
+
+        assert_eq!(leverage("btcusdt"), Decimal::from(50));
+        // The exchange's per-symbol cap wins over the global default
+        assert_eq!(leverage("ethusdt"), Decimal::from(20));
+        // No published cap: the config max_leverage applies
+        assert_eq!(leverage("solusdt"), Decimal::from(75));
+    }
+
+    #[test]
+    fn test_mock_exchange_supplies_symbol_rules() {
+        let info = SymbolRules {
+            base_asset: "ETH".to_string(),
+            quote_asset: "USDT".to_string(),
+            price_tick_size: Decimal::from(2),
+            min_price: Decimal::ONE,
+            max_price: Decimal::from(100_000),
+            quantity_step_size: Decimal::ONE,
+            min_quantity: Decimal::ONE,
+            max_quantity: Decimal::from(1000),
+            min_notional: Decimal::from(5),
+            max_leverage: None,
+        };
+        let exchange = MockExchange {
+            symbols: BTreeMap::from([("ethusdt".to_string(), info)]),
+        };
+        assert!(exchange.symbol_info("ETHUSDT").is_some());
+
+        let mut sim = engine("", "");
+        sim.set_exchange_info(Arc::new(exchange));
+        sim.on_depth(&depth("ethusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+        let mut sig = signal("ethusdt", Side::Buy, 1000, 980, 1040);
+        sig.stop_loss = Decimal::new(9813, 1); // 981.3
+        sim.execute_signal(sig);
+
+        // Stop (away from entry) and size snap to the mock's 2-unit tick and whole-unit step
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].stop_loss, Decimal::from(980));
+        assert_eq!(open[0].quantity.fract(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_missing_book_during_warmup_is_counted_separately() {
+        let mut sim = engine("orderbook_warmup_secs = 60", "");
+        // Replayed data: event time is far from the wall clock signals are stamped with
+        let start = Utc::now() - Duration::days(30);
+        let trade_at = |secs: i64| NormalizedTrade {
+            timestamp: start + Duration::seconds(secs),
+            ..trade("btcusdt", 1000)
+        };
+        sim.handle_market_event(MarketEvent::Trade(trade_at(0)));
+        sim.handle_market_event(MarketEvent::Trade(trade_at(10)));
+
+        sim.execute_signal(signal("ethusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.book_warmup_skips(), 1);
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        // A symbol whose book has arrived trades normally inside the grace
+        sim.handle_market_event(MarketEvent::Depth(DepthUpdate {
+            timestamp: start + Duration::seconds(20),
+            ..depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)])
+        }));
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.book_warmup_skips(), 1);
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        // After the grace (by event time) the requirement applies as a normal rejection
+        sim.handle_market_event(MarketEvent::Trade(trade_at(120)));
+        sim.execute_signal(signal("ethusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.book_warmup_skips(), 1);
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+    }
+
+    #[test]
+    fn test_high_sampled_latency_pauses_entries_until_recovery() {
+        let mut sim = engine("max_entry_latency_ms = 50.0", "");
+        sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+
+        sim.handle_processing_event(ProcessingEvent::LatencySample { rtt_ms: Some(120.0) });
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        sim.handle_processing_event(ProcessingEvent::LatencySample { rtt_ms: Some(55.0) });
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        sim.handle_processing_event(ProcessingEvent::LatencySample { rtt_ms: Some(20.0) });
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+
+        // An unreachable exchange pauses entries like a slow one
+        sim.handle_processing_event(ProcessingEvent::LatencySample { rtt_ms: None });
+        sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+        assert_eq!(sim.position_manager.open_positions().len(), 1);
+    }
+
+    #[test]
+    fn test_exposure_groups_by_quote_asset_and_category() {
+        let mut sim = engine("", "");
+        sim.set_base_asset_categories(&HashMap::from([
+            ("doge".to_string(), "meme".to_string()),
+            ("PEPE".to_string(), "meme".to_string()),
+        ]));
+        for symbol in ["btcusdt", "dogeusdt", "pepeusdt", "ethusdc"] {
+            sim.on_depth(&depth(symbol, vec![level(999, 50)], vec![level(1001, 50)]));
+            sim.execute_signal(signal(symbol, Side::Buy, 1000, 980, 1040));
+        }
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 4);
+        let notional = |quote: &str| {
+            open.iter()
+                .filter(|p| p.symbol.to_uppercase().ends_with(quote))
+                .map(|p| p.entry_price * p.quantity)
+                .sum::<Decimal>()
+        };
+
+        let (by_quote, by_category) = sim.asset_exposure();
+        assert_eq!(by_quote.len(), 2);
+        assert_eq!(by_quote["USDT"].open_positions, 3);
+        assert_eq!(by_quote["USDT"].open_notional, notional("USDT"));
+        assert_eq!(by_quote["USDC"].open_notional, notional("USDC"));
+        assert_eq!(by_category["meme"].open_positions, 2);
+        assert!(by_category.contains_key("BTC") && by_category.contains_key("ETH"));
+        let total: Decimal = by_category.values().map(|e| e.open_notional).sum();
+        assert_eq!(total, notional("USDT") + notional("USDC"));
+
+        // uPnL is marked at the last trade price
+        let btc_qty = open.iter().find(|p| p.symbol == "btcusdt").unwrap().quantity;
+        let btc_entry = open.iter().find(|p| p.symbol == "btcusdt").unwrap().entry_price;
+        sim.on_trade(&trade("btcusdt", 1010));
+        let (_, by_category) = sim.asset_exposure();
+        assert_eq!(by_category["BTC"].unrealized_pnl, (Decimal::from(1010) - btc_entry) * btc_qty);
+    }
+
+    #[test]
+    fn test_exposure_breakdown_only_published_when_enabled() {
+        for enabled in [false, true] {
+            let stats = Arc::new(Mutex::new(BotStats::default()));
+            let mut sim = engine("", "");
+            sim.set_bot_stats(stats.clone());
+            sim.set_exposure_breakdown(enabled);
+            sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 50)]));
+            sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+            sim.on_trade(&trade("btcusdt", 1005));
+            assert_eq!(stats.lock().unwrap().exposure_by_quote.contains_key("USDT"), enabled);
+        }
+    }
+
+    #[test]
+    fn test_thin_book_fills_partially_and_handles_remainder() {
+        let thin_asks = || vec![level(1001, 1), level(1002, 1)];
+        let open_long = |sim_extra: &str| {
+            let mut sim = engine(sim_extra, "");
+            sim.on_depth(&depth("btcusdt", vec![level(999, 50)], thin_asks()));
+            // 1% risk on a 20-wide stop sizes 5 units, more than the 2 resting asks
+            sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+            sim
+        };
+
+        let sim = open_long("book_walk_fills = true");
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].quantity, Decimal::TWO);
+        assert_eq!(open[0].entry_price, Decimal::new(10015, 1));
+        assert!(sim.resting_entries.is_empty());
+
+        let mut sim = open_long("book_walk_fills = true\nunfilled_remainder = \"Rest\"");
+        let id = sim.position_manager.open_positions()[0].id.clone();
+        assert_eq!(sim.resting_entries[&id].limit_price, Decimal::from(1002));
+        assert_eq!(sim.resting_entries[&id].quantity, Decimal::from(3));
+
+        // Prints above the limit leave it resting; one through it fills up to its size
+        sim.on_trade(&trade("btcusdt", 1003));
+        assert_eq!(sim.position_manager.open_positions()[0].quantity, Decimal::TWO);
+        let mut through = trade("btcusdt", 1002);
+        through.quantity = Decimal::ONE;
+        sim.on_trade(&through);
+        through.quantity = Decimal::from(10);
+        sim.on_trade(&through);
+        let open = sim.position_manager.open_positions();
+        assert_eq!(open[0].quantity, Decimal::from(5));
+        // (1001 + 1002 + 3 x 1002) / 5
+        assert_eq!(open[0].entry_price, Decimal::new(10018, 1));
+        assert!(sim.resting_entries.is_empty());
+    }
+
+    #[test]
+    fn test_resting_remainder_meets_exchange_minimums_and_expires() {
+        let open_long = |min_notional: i64| {
+            let mut sim = engine("book_walk_fills = true\nunfilled_remainder = \"Rest\"", "");
+            let rules = SymbolRules {
+                base_asset: "BTC".to_string(),
+                quote_asset: "USDT".to_string(),
+                price_tick_size: Decimal::ONE,
+                min_price: Decimal::ONE,
+                max_price: Decimal::from(100_000),
+                quantity_step_size: Decimal::ONE,
+                min_quantity: Decimal::ONE,
+                max_quantity: Decimal::from(1000),
+                min_notional: Decimal::from(min_notional),
+                max_leverage: None,
+            };
+            sim.set_exchange_info(Arc::new(MockExchange {
+                symbols: BTreeMap::from([("btcusdt".to_string(), rules)]),
+            }));
+            sim.on_depth(&depth("btcusdt", vec![level(999, 50)], vec![level(1001, 1), level(1002, 1)]));
+            sim.execute_signal(signal("btcusdt", Side::Buy, 1000, 980, 1040));
+            sim
+        };
+
+        // 3 unfilled at 1002 is worth 3006, below a 5000 minimum: nothing rests
+        let sim = open_long(5000);
+        assert!(sim.resting_entries.is_empty());
+
+        // A 1-unit print (1002 notional) is too small to fill against a 2000 minimum;
+        // a 2-unit fill would leave 1 unit below it, so the rest is canceled
+        let mut sim = open_long(2000);
+        let id = sim.position_manager.open_positions()[0].id.clone();
+        assert_eq!(sim.resting_entries[&id].quantity, Decimal::from(3));
+        let mut through = trade("btcusdt", 1002);
+        through.quantity = Decimal::ONE;
+        sim.on_trade(&through);
+        assert_eq!(sim.position_manager.open_positions()[0].quantity, Decimal::TWO);
+        through.quantity = Decimal::TWO;
+        sim.on_trade(&through);
+        assert_eq!(sim.position_manager.open_positions()[0].quantity, Decimal::from(4));
+        assert!(sim.resting_entries.is_empty());
+
+        // Past the TTL (60s after the first trade seen) the remainder is canceled unfilled
+        let mut sim = open_long(1000);
+        let first = trade("btcusdt", 1003);
+        sim.on_trade(&first);
+        let mut late = trade("btcusdt", 1002);
+        late.quantity = Decimal::from(10);
+        late.timestamp = first.timestamp + Duration::seconds(61);
+        sim.on_trade(&late);
+        assert_eq!(sim.position_manager.open_positions()[0].quantity, Decimal::TWO);
+        assert!(sim.resting_entries.is_empty());
+    }
+
+    #[test]
+    fn test_tick_floor_slippage_on_low_priced_symbol() {
+        let mut exchange_info = ExchangeInfoManager::new("http://localhost".to_string());
+        exchange_info.insert_symbol_info(SymbolInfo {
+            symbol: "DOGEUSDT".to_string(),
+            status: "TRADING".to_string(),
+            rules: SymbolRules {
+                base_asset: "DOGE".to_string(),
+                quote_asset: "USDT".to_string(),
+                price_tick_size: Decimal::new(1, 2), // 0.01 = 10 bps at 10.00
+                min_price: Decimal::new(1, 2),
+                max_price: Decimal::from(1000),
+                quantity_step_size: Decimal::ONE,
+                min_quantity: Decimal::ONE,
+                max_quantity: Decimal::from(10_000_000),
+                min_notional: Decimal::from(5),
+                max_leverage: None,
+            },
+        });
+        let exchange_info = Arc::new(exchange_info);
+        let price = |cents: i64| Decimal::new(cents, 2);
+        let open_long = |sim_extra: &str| {
+            let mut sim = engine(sim_extra, "");
+            sim.slippage_model_enabled = true;
+            sim.set_exchange_info(exchange_info.clone());
+            let deep = |cents: i64| DepthLevel {
+                price: price(cents),
+                quantity: Decimal::from(10_000_000),
+            };
+            // One-tick spread: the bps model sees ~5 bps of half-spread and no impact
+            sim.on_depth(&depth("dogeusdt", vec![deep(1000)], vec![deep(1001)]));
+            let mut sig = signal("dogeusdt", Side::Buy, 10, 10, 11);
+            sig.entry_price = price(1001);
+            sig.stop_loss = price(995);
+            sig.take_profit = price(1020);
+            sim.execute_signal(sig);
+            sim.position_manager.open_positions().len()
+        };
+
+        assert_eq!(open_long(""), 1);
+        // A one-tick floor is ~10 bps, above the 6 bps cap
+        assert_eq!(open_long("min_model_slippage_ticks = 1"), 0);
+    }
+
+    #[test]
+    fn test_rejected_signal_records_what_if_outcome() {
+        let mut sim = engine("what_if_lookahead_bars = 3", "");
+        let bar = |symbol: &str, low: i64, high: i64| RangeBar {
+            symbol: symbol.to_string(),
+            open: Decimal::from(low),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(high),
+            volume: Decimal::ONE,
+            buy_volume: Decimal::ONE,
+            sell_volume: Decimal::ZERO,
+            open_time: Utc::now(),
+            close_time: Utc::now(),
+            footprint: BTreeMap::new(),
+            bar_index: 0,
+        };
+
+        // No order book yet: both signals are rejected by the execution-quality filter
+        let long = signal("btcusdt", Side::Buy, 100, 95, 110);
+        let short = signal("btcusdt", Side::Sell, 100, 105, 90);
+        sim.handle_processing_event(ProcessingEvent::Signal(long.clone()));
+        sim.handle_processing_event(ProcessingEvent::Signal(short.clone()));
+        assert!(sim.position_manager.open_positions().is_empty());
+
+        sim.handle_processing_event(ProcessingEvent::NewBar(bar("btcusdt", 98, 104)));
+        sim.handle_processing_event(ProcessingEvent::NewBar(bar("ethusdt", 1, 1000)));
+        sim.handle_processing_event(ProcessingEvent::NewBar(bar("btcusdt", 99, 111)));
+        assert_eq!(sim.trade_logger.what_if_outcome(&long.id), None);
+
+        sim.handle_processing_event(ProcessingEvent::NewBar(bar("btcusdt", 100, 101)));
+        assert_eq!(sim.trade_logger.what_if_outcome(&long.id).as_deref(), Some("Win"));
+        assert_eq!(sim.trade_logger.what_if_outcome(&short.id).as_deref(), Some("Loss"));
+        assert!(sim.pending_what_ifs.is_empty());
+
+        // A full queue drops its oldest entry instead of growing
+        let mut sim = engine("what_if_lookahead_bars = 1\nwhat_if_max_pending = 2", "");
+        let quiet = signal("solusdt", Side::Buy, 100, 95, 110);
+        sim.handle_processing_event(ProcessingEvent::Signal(quiet.clone()));
+        sim.handle_processing_event(ProcessingEvent::Signal(long.clone()));
+        sim.handle_processing_event(ProcessingEvent::Signal(short.clone()));
+        assert_eq!(sim.pending_what_ifs.len(), 2);
+        assert!(sim.pending_what_ifs.iter().all(|p| p.signal.id != quiet.id));
+        sim.handle_processing_event(ProcessingEvent::NewBar(bar("btcusdt", 99, 111)));
+        assert!(sim.pending_what_ifs.is_empty());
+    }
+
+    #[test]
+    fn test_depth_aggregation_coalesces_updates() {
+        let mut sim = engine("depth_aggregation_ms = 500", "");
+        let start = Utc::now();
+        let update = |ms: i64, bid: i64, bid_qty: i64| DepthUpdate {
+            timestamp: start + Duration::milliseconds(ms),
+            ..depth("btcusdt", vec![level(bid, bid_qty)], vec![level(110, 1)])
+        };
+
+        // First update builds the book immediately
+        sim.handle_market_event(MarketEvent::Depth(update(0, 100, 1)));
+        assert_eq!(sim.order_books["btcusdt"].best_bid(), Some(Decimal::from(100)));
+
+        // Updates inside the window are held back
+        sim.handle_market_event(MarketEvent::Depth(update(100, 101, 2)));
+        sim.handle_market_event(MarketEvent::Depth(DepthUpdate {
+            bids: vec![level(101, 0), level(102, 3)],
+            ..update(200, 0, 0)
+        }));
+        sim.handle_market_event(MarketEvent::Depth(update(400, 100, 4)));
+        let book = &sim.order_books["btcusdt"];
+        assert_eq!(book.best_bid(), Some(Decimal::from(100)));
+        assert_eq!(book.bids[&Decimal::from(100)], Decimal::ONE);
+
+        // Window elapsed: the book reflects every coalesced change
+        sim.handle_market_event(MarketEvent::Depth(update(500, 99, 5)));
+        let book = &sim.order_books["btcusdt"];
+        assert_eq!(book.best_bid(), Some(Decimal::from(102)));
+        assert_eq!(book.bids.get(&Decimal::from(101)), None);
+        assert_eq!(book.bids[&Decimal::from(100)], Decimal::from(4));
+        assert_eq!(book.bids[&Decimal::from(99)], Decimal::from(5));
+        assert!(sim.pending_depth.is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_ids_identical_across_runs() {
+        let run = || {
+            let mut sim = engine("", "");
+            sim.set_deterministic_ids(true);
+            sim.on_depth(&depth("btcusdt", vec![level(100, 4)], vec![level(101, 2)]));
+            sim.on_depth(&depth("ethusdt", vec![level(100, 4)], vec![level(101, 2)]));
+            sim.execute_signal(signal("btcusdt", Side::Buy, 101, 99, 104));
+            sim.execute_signal(signal("ethusdt", Side::Buy, 101, 99, 104));
+            sim.on_trade(&trade("btcusdt", 104));
+            sim.on_trade(&trade("ethusdt", 98));
+            sim.position_manager
+                .finalized_positions()
+                .iter()
+                .map(|p| p.id.clone())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let first = run();
+        assert_eq!(first, "btcusdt-1\nethusdt-2");
+        assert_eq!(first, run());
+    }
+}
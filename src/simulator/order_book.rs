@@ -1,8 +1,18 @@
-use crate::types::DepthUpdate;
+use crate::types::{BookSnapshot, BookTicker, DepthLevel, DepthUpdate, Side};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use std::collections::BTreeMap;
 
-/// Local order book maintained from depth stream updates
+/// Result of taking liquidity from one side of the book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookFill {
+    pub filled: Decimal,
+    pub average_price: Decimal,
+    /// Price of the deepest level taken
+    pub worst_price: Decimal,
+}
+
+/// Local order book maintained from depth (or bookTicker) stream updates
 pub struct LocalOrderBook {
     pub symbol: String,
     /// Bids: price -> quantity (descending price order)
@@ -22,8 +32,14 @@ impl LocalOrderBook {
         }
     }
 
-    /// Apply a depth update
+    /// Apply a depth update. The first update into an empty book is treated as
+    /// a snapshot and bulk-loaded.
     pub fn update(&mut self, depth: &DepthUpdate) {
+        if self.bids.is_empty() && self.asks.is_empty() {
+            self.load_snapshot(&depth.bids, &depth.asks);
+            return;
+        }
+
         for level in &depth.bids {
             if level.quantity == Decimal::ZERO {
                 self.bids.remove(&level.price);
@@ -40,19 +56,55 @@ impl LocalOrderBook {
             }
         }
 
-        // Trim to max depth
-        while self.bids.len() > self.max_depth {
-            if let Some(&lowest_bid) = self.bids.keys().next() {
-                self.bids.remove(&lowest_bid);
+        // Trim to max depth: drop the lowest bids and highest asks
+        if self.bids.len() > self.max_depth {
+            if let Some(&cut) = self.bids.keys().nth(self.bids.len() - self.max_depth) {
+                self.bids = self.bids.split_off(&cut);
             }
         }
-        while self.asks.len() > self.max_depth {
-            if let Some(&highest_ask) = self.asks.keys().last() {
-                self.asks.remove(&highest_ask);
+        if self.asks.len() > self.max_depth {
+            if let Some(&cut) = self.asks.keys().nth(self.max_depth) {
+                self.asks.split_off(&cut);
             }
         }
     }
 
+    /// Replace the book with a full snapshot (e.g. a 1000-level REST response),
+    /// keeping only the `max_depth` levels nearest the touch on each side.
+    pub fn load_snapshot(&mut self, bids: &[DepthLevel], asks: &[DepthLevel]) {
+        fn nearest(
+            levels: &[DepthLevel],
+            max_depth: usize,
+            best_first: impl Fn(&Decimal, &Decimal) -> std::cmp::Ordering,
+        ) -> BTreeMap<Decimal, Decimal> {
+            let mut live: Vec<&DepthLevel> = levels
+                .iter()
+                .filter(|l| l.quantity > Decimal::ZERO)
+                .collect();
+            live.sort_unstable_by(|a, b| best_first(&a.price, &b.price));
+            live.into_iter()
+                .take(max_depth)
+                .map(|l| (l.price, l.quantity))
+                .collect()
+        }
+
+        self.bids = nearest(bids, self.max_depth, |a, b| b.cmp(a));
+        self.asks = nearest(asks, self.max_depth, |a, b| a.cmp(b));
+    }
+
+    /// Replace the book with a single best bid/ask level (bookTicker mode).
+    /// Spread, microprice and top-N depth then reflect the top of book only.
+    pub fn apply_book_ticker(&mut self, ticker: &BookTicker) {
+        self.bids.clear();
+        self.asks.clear();
+        if ticker.bid_qty > Decimal::ZERO {
+            self.bids.insert(ticker.bid_price, ticker.bid_qty);
+        }
+        if ticker.ask_qty > Decimal::ZERO {
+            self.asks.insert(ticker.ask_price, ticker.ask_qty);
+        }
+    }
+
     /// Best bid price
     pub fn best_bid(&self) -> Option<Decimal> {
         self.bids.keys().last().copied()
@@ -63,20 +115,49 @@ impl LocalOrderBook {
         self.asks.keys().next().copied()
     }
 
-    /// Mid price
+    /// Best bid and ask levels as (price, quantity), or None when the book is
+    /// one-sided or crossed/locked (best bid >= best ask) and has no reliable price
+    fn top_of_book(&self) -> Option<((Decimal, Decimal), (Decimal, Decimal))> {
+        let (&bid, &bid_qty) = self.bids.iter().next_back()?;
+        let (&ask, &ask_qty) = self.asks.iter().next()?;
+        (bid < ask).then_some(((bid, bid_qty), (ask, ask_qty)))
+    }
+
+    /// Mid price (None on a one-sided or crossed book)
     pub fn mid_price(&self) -> Option<Decimal> {
-        let bid = self.best_bid()?;
-        let ask = self.best_ask()?;
+        let ((bid, _), (ask, _)) = self.top_of_book()?;
         Some((bid + ask) / Decimal::TWO)
     }
 
-    /// Spread
+    /// Microprice: best bid/ask weighted by the opposite side's top-of-book size.
+    /// Leans toward the side with less resting liquidity (the likelier next print).
+    /// None on a one-sided or crossed book.
+    pub fn microprice(&self) -> Option<Decimal> {
+        let ((bid, bid_qty), (ask, ask_qty)) = self.top_of_book()?;
+        let total = bid_qty + ask_qty;
+        if total <= Decimal::ZERO {
+            return None;
+        }
+        Some((bid * ask_qty + ask * bid_qty) / total)
+    }
+
+    /// Spread (None on a one-sided or crossed book)
     pub fn spread(&self) -> Option<Decimal> {
-        let bid = self.best_bid()?;
-        let ask = self.best_ask()?;
+        let ((bid, _), (ask, _)) = self.top_of_book()?;
         Some(ask - bid)
     }
 
+    /// Copy of the top `levels` of each side, best prices first
+    pub fn snapshot(&self, levels: usize, timestamp: DateTime<Utc>) -> BookSnapshot {
+        let level = |(&price, &quantity): (&Decimal, &Decimal)| DepthLevel { price, quantity };
+        BookSnapshot {
+            symbol: self.symbol.clone(),
+            bids: self.bids.iter().rev().take(levels).map(level).collect(),
+            asks: self.asks.iter().take(levels).map(level).collect(),
+            timestamp,
+        }
+    }
+
     /// Calculate total bid volume in order book
     pub fn total_bid_volume(&self) -> Decimal {
         self.bids.values().sum()
@@ -92,25 +173,67 @@ impl LocalOrderBook {
         self.bids.iter().rev().take(levels).map(|(_, q)| *q).sum()
     }
 
+    /// Take up to `quantity` from the levels a `side` market order hits (asks for a buy),
+    /// best first. None when that side is empty.
+    pub fn walk(&self, side: Side, quantity: Decimal) -> Option<BookFill> {
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Decimal)>> = match side {
+            Side::Buy => Box::new(self.asks.iter()),
+            Side::Sell => Box::new(self.bids.iter().rev()),
+        };
+        let mut filled = Decimal::ZERO;
+        let mut cost = Decimal::ZERO;
+        let mut worst_price = None;
+        for (&price, &available) in levels {
+            if filled >= quantity {
+                break;
+            }
+            let take = available.min(quantity - filled);
+            filled += take;
+            cost += take * price;
+            worst_price = Some(price);
+        }
+        let worst_price = worst_price?;
+        Some(BookFill {
+            filled,
+            average_price: cost / filled,
+            worst_price,
+        })
+    }
+
     /// Sum of top-N ask depth quantities (best ask levels first).
     pub fn top_ask_depth(&self, levels: usize) -> Decimal {
         self.asks.iter().take(levels).map(|(_, q)| *q).sum()
     }
 
+    /// Bid/ask depth ratio over the top-N levels of each side (None = bids with no asks)
+    pub fn top_depth_imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid_vol = self.top_bid_depth(levels);
+        let ask_vol = self.top_ask_depth(levels);
+
+        if ask_vol > Decimal::ZERO {
+            Some(bid_vol / ask_vol)
+        } else if bid_vol > Decimal::ZERO {
+            None
+        } else {
+            Some(Decimal::ONE)
+        }
+    }
+
     /// Calculate bid/ask depth ratio
     /// Returns (bid_volume, ask_volume, ratio)
     /// ratio > 1.0 means more bids (buying pressure)
     /// ratio < 1.0 means more asks (selling pressure)
-    pub fn depth_imbalance(&self) -> (Decimal, Decimal, Decimal) {
+    /// ratio None means bids with no asks (unbounded)
+    pub fn depth_imbalance(&self) -> (Decimal, Decimal, Option<Decimal>) {
         let bid_vol = self.total_bid_volume();
         let ask_vol = self.total_ask_volume();
 
         let ratio = if ask_vol > Decimal::ZERO {
-            bid_vol / ask_vol
+            Some(bid_vol / ask_vol)
         } else if bid_vol > Decimal::ZERO {
-            Decimal::from(999)
+            None
         } else {
-            Decimal::ONE
+            Some(Decimal::ONE)
         };
 
         (bid_vol, ask_vol, ratio)
@@ -120,7 +243,7 @@ impl LocalOrderBook {
     /// This suggests absorption of sell orders
     pub fn has_strong_bid_imbalance(&self) -> bool {
         let (_, _, ratio) = self.depth_imbalance();
-        ratio >= Decimal::TWO
+        ratio.is_none_or(|r| r >= Decimal::TWO)
     }
 
     /// Check if there's strong ask-side imbalance (Ask >= 2x Bid)
@@ -133,3 +256,98 @@ impl LocalOrderBook {
         ask_vol / bid_vol >= Decimal::TWO
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(prices: impl Iterator<Item = i64>) -> Vec<DepthLevel> {
+        prices
+            .map(|p| DepthLevel {
+                price: Decimal::from(p),
+                quantity: Decimal::ONE,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot_keeps_nearest_levels() {
+        let mut book = LocalOrderBook::new("btcusdt".to_string(), 20);
+        // 1000 levels per side, unsorted: bids 1..=1000, asks 1001..=2000
+        let bids = levels((1..=1000).rev().step_by(2).chain((1..=1000).step_by(2)));
+        let asks = levels((1001..=2000).rev());
+        book.load_snapshot(&bids, &asks);
+
+        assert_eq!(book.bids.len(), 20);
+        assert_eq!(book.asks.len(), 20);
+        assert_eq!(book.best_bid(), Some(Decimal::from(1000)));
+        assert_eq!(book.bids.keys().next(), Some(&Decimal::from(981)));
+        assert_eq!(book.best_ask(), Some(Decimal::from(1001)));
+        assert_eq!(book.asks.keys().last(), Some(&Decimal::from(1020)));
+
+        // Incremental updates still trim to depth: a better bid pushes out the lowest
+        book.update(&DepthUpdate {
+            symbol: "btcusdt".to_string(),
+            bids: vec![DepthLevel {
+                price: Decimal::new(10005, 1),
+                quantity: Decimal::ONE,
+            }],
+            asks: vec![],
+            timestamp: chrono::Utc::now(),
+        });
+        assert_eq!(book.best_bid(), Some(Decimal::new(10005, 1)));
+        assert_eq!(book.bids.keys().next(), Some(&Decimal::from(982)));
+        assert_eq!(book.bids.len(), 20);
+    }
+
+    #[test]
+    fn test_crossed_or_one_sided_book_has_no_price() {
+        let mut book = LocalOrderBook::new("btcusdt".to_string(), 20);
+        let no_prices = |book: &LocalOrderBook| {
+            book.mid_price().is_none() && book.microprice().is_none() && book.spread().is_none()
+        };
+        assert!(no_prices(&book));
+
+        // One-sided: bids only
+        book.load_snapshot(&levels(98..=100), &[]);
+        assert!(no_prices(&book));
+
+        // Crossed: best bid 100 above best ask 99
+        book.load_snapshot(&levels(98..=100), &levels(99..=101));
+        assert!(no_prices(&book));
+
+        // Locked: best bid == best ask
+        book.load_snapshot(&levels(98..=100), &levels(100..=102));
+        assert!(no_prices(&book));
+
+        book.load_snapshot(&levels(98..=100), &levels(101..=103));
+        assert_eq!(book.mid_price(), Some(Decimal::new(1005, 1)));
+        assert_eq!(book.microprice(), Some(Decimal::new(1005, 1)));
+        assert_eq!(book.spread(), Some(Decimal::ONE));
+    }
+
+    #[test]
+    fn test_walk_averages_levels_and_stops_at_available_depth() {
+        let mut book = LocalOrderBook::new("btcusdt".to_string(), 20);
+        let level = |price: i64, quantity: i64| DepthLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        };
+        book.load_snapshot(&[level(99, 1), level(98, 3)], &[level(101, 2), level(102, 2)]);
+
+        // 2 @ 101 + 1 @ 102
+        let fill = book.walk(Side::Buy, Decimal::from(3)).unwrap();
+        assert_eq!(fill.filled, Decimal::from(3));
+        assert_eq!(fill.average_price, Decimal::from(304) / Decimal::from(3));
+        assert_eq!(fill.worst_price, Decimal::from(102));
+
+        // Only 4 resting on the bid side
+        let fill = book.walk(Side::Sell, Decimal::from(10)).unwrap();
+        assert_eq!(fill.filled, Decimal::from(4));
+        assert_eq!(fill.average_price, Decimal::new(9825, 2));
+        assert_eq!(fill.worst_price, Decimal::from(98));
+
+        book.asks.clear();
+        assert!(book.walk(Side::Buy, Decimal::ONE).is_none());
+    }
+}
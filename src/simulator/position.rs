@@ -1,6 +1,9 @@
-use crate::types::{ExitReason, MarginType, Position, PositionStatus, Side, TradeSignal};
-use chrono::{DateTime, Utc};
+use crate::types::{
+    ExitReason, MarginType, Position, PositionStatus, Side, TradeSignal, POSITION_SCHEMA_VERSION,
+};
+use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
+use tracing::warn;
 use uuid::Uuid;
 
 /// Calculate liquidation price for leveraged position
@@ -32,6 +35,48 @@ pub fn calculate_liquidation_price(
     }
 }
 
+/// Binance USDⓈ-M isolated-margin liquidation price (one-way mode):
+/// LP = (WB + cum - side * Q * EP) / (Q * MMR - side * Q), side = 1 long / -1 short
+/// where WB = isolated wallet balance, cum = the bracket's maintenance amount
+pub fn binance_isolated_liquidation_price(
+    side: Side,
+    entry_price: Decimal,
+    quantity: Decimal,
+    wallet_balance: Decimal,
+    maintenance_margin_rate: Decimal,
+    maintenance_amount: Decimal,
+) -> Decimal {
+    let side_sign = match side {
+        Side::Buy => Decimal::ONE,
+        Side::Sell => -Decimal::ONE,
+    };
+    let denominator = quantity * maintenance_margin_rate - side_sign * quantity;
+    if denominator.is_zero() {
+        return Decimal::ZERO;
+    }
+    let liquidation =
+        (wallet_balance + maintenance_amount - side_sign * quantity * entry_price) / denominator;
+    liquidation.max(Decimal::ZERO)
+}
+
+/// Maintenance tier: notional up to `notional_cap` uses `rate` less `amount`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaintenanceBracket {
+    pub notional_cap: Decimal,
+    pub rate: Decimal,
+    pub amount: Decimal,
+}
+
+/// How liquidation prices are computed
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiquidationModel {
+    /// Flat maintenance rate and round-trip fees (`calculate_liquidation_price`)
+    Simplified,
+    /// Binance isolated-margin formula; brackets ascending by notional
+    /// (empty = the flat maintenance rate with no maintenance amount)
+    Binance(Vec<MaintenanceBracket>),
+}
+
 /// Calculate initial margin required for position
 /// initial_margin = (entry_price * quantity) / leverage
 pub fn calculate_initial_margin(
@@ -52,19 +97,114 @@ pub fn calculate_maintenance_margin(
     entry_price * quantity * maintenance_margin_rate
 }
 
+/// What to do when a new position's stop is not between entry and its liquidation price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopLiquidationCheck {
+    Reject,
+    Warn,
+    Off,
+}
+
+impl StopLiquidationCheck {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "reject" => Self::Reject,
+            "off" => Self::Off,
+            _ => Self::Warn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PositionError {
+    /// The position would be liquidated before its stop could trigger
+    #[error("{side:?} stop {stop} is not between entry {entry} and liquidation {liquidation}")]
+    StopBeyondLiquidation {
+        side: Side,
+        entry: Decimal,
+        stop: Decimal,
+        liquidation: Decimal,
+    },
+}
+
 /// Manages simulated position lifecycle
 pub struct PositionManager {
     positions: Vec<Position>,
+    stop_liquidation_check: StopLiquidationCheck,
+    liquidation_model: LiquidationModel,
+    /// Use "{symbol}-{seq}" ids instead of random UUIDs
+    deterministic_ids: bool,
+    next_seq: u64,
 }
 
 impl PositionManager {
     pub fn new() -> Self {
         Self {
             positions: Vec::new(),
+            stop_liquidation_check: StopLiquidationCheck::Warn,
+            liquidation_model: LiquidationModel::Simplified,
+            deterministic_ids: false,
+            next_seq: 0,
+        }
+    }
+
+    pub fn set_deterministic_ids(&mut self, enabled: bool) {
+        self.deterministic_ids = enabled;
+    }
+
+    pub fn set_stop_liquidation_check(&mut self, check: StopLiquidationCheck) {
+        self.stop_liquidation_check = check;
+    }
+
+    pub fn set_liquidation_model(&mut self, model: LiquidationModel) {
+        self.liquidation_model = model;
+    }
+
+    /// Liquidation price of an isolated position under the configured model
+    pub fn liquidation_price(
+        &self,
+        side: Side,
+        entry_price: Decimal,
+        quantity: Decimal,
+        leverage: Decimal,
+        maintenance_margin_rate: Decimal,
+        taker_fee: Decimal,
+    ) -> Decimal {
+        let LiquidationModel::Binance(brackets) = &self.liquidation_model else {
+            return calculate_liquidation_price(
+                side,
+                entry_price,
+                leverage,
+                maintenance_margin_rate,
+                taker_fee,
+            );
+        };
+        let notional = entry_price * quantity;
+        let (rate, amount) = brackets
+            .iter()
+            .find(|b| notional <= b.notional_cap)
+            .or(brackets.last())
+            .map_or((maintenance_margin_rate, Decimal::ZERO), |b| (b.rate, b.amount));
+        binance_isolated_liquidation_price(
+            side,
+            entry_price,
+            quantity,
+            calculate_initial_margin(entry_price, quantity, leverage),
+            rate,
+            amount,
+        )
+    }
+
+    fn next_id(&mut self, symbol: &str) -> String {
+        if !self.deterministic_ids {
+            return Uuid::new_v4().to_string();
         }
+        self.next_seq += 1;
+        format!("{}-{}", symbol, self.next_seq)
     }
 
-    /// Open a new position from a trade signal with leverage
+    /// Open a new position from a trade signal with leverage.
+    /// Fails when the stop is not strictly between entry and liquidation (in `Reject` mode).
     pub fn open_position(
         &mut self,
         signal: &TradeSignal,
@@ -73,14 +213,32 @@ impl PositionManager {
         margin_type: MarginType,
         maintenance_margin_rate: Decimal,
         taker_fee: Decimal,
-    ) -> Position {
-        let liquidation_price = calculate_liquidation_price(
+    ) -> Result<Position, PositionError> {
+        let liquidation_price = self.liquidation_price(
             signal.side,
             signal.entry_price,
+            quantity,
             leverage,
             maintenance_margin_rate,
             taker_fee,
         );
+        let stop_in_range = match signal.side {
+            Side::Buy => signal.stop_loss < signal.entry_price && signal.stop_loss > liquidation_price,
+            Side::Sell => signal.stop_loss > signal.entry_price && signal.stop_loss < liquidation_price,
+        };
+        if !stop_in_range {
+            let error = PositionError::StopBeyondLiquidation {
+                side: signal.side,
+                entry: signal.entry_price,
+                stop: signal.stop_loss,
+                liquidation: liquidation_price,
+            };
+            match self.stop_liquidation_check {
+                StopLiquidationCheck::Reject => return Err(error),
+                StopLiquidationCheck::Warn => warn!(symbol = %signal.symbol, "{}", error),
+                StopLiquidationCheck::Off => {}
+            }
+        }
 
         let initial_margin = calculate_initial_margin(signal.entry_price, quantity, leverage);
 
@@ -88,13 +246,15 @@ impl PositionManager {
             calculate_maintenance_margin(signal.entry_price, quantity, maintenance_margin_rate);
 
         let position = Position {
-            id: Uuid::new_v4().to_string(),
+            schema_version: POSITION_SCHEMA_VERSION,
+            id: self.next_id(&signal.symbol),
             symbol: signal.symbol.clone(),
             side: signal.side,
             entry_price: signal.entry_price,
             quantity,
             stop_loss: signal.stop_loss,
             take_profit: signal.take_profit,
+            emergency_stop: None,
             setup: signal.setup,
             status: PositionStatus::Open,
             pnl: Decimal::ZERO,
@@ -112,15 +272,67 @@ impl PositionManager {
             tp1_filled: false,
             tp1_price: None,
             tp2_price: None,
+            trailing_stop: None,
             original_quantity: quantity,
             entry_features: signal.entry_features.clone(),
+            regime: signal.regime,
+            entry_fee_rate: None,
             max_favorable_excursion_pct: Decimal::ZERO,
             max_adverse_excursion_pct: Decimal::ZERO,
             time_to_mfe_secs: None,
             time_to_mae_secs: None,
+            entry_spread: None,
+            entry_depth_imbalance: None,
+            exit_spread: None,
+            exit_depth_imbalance: None,
         };
         self.positions.push(position.clone());
-        position
+        Ok(position)
+    }
+
+    /// Add a later entry fill: entry becomes the quantity-weighted average and margins and
+    /// liquidation are recomputed. None once the position has closed or taken TP1.
+    pub fn add_fill(
+        &mut self,
+        position_id: &str,
+        quantity: Decimal,
+        price: Decimal,
+        fill_fee_rate: Decimal,
+        maintenance_margin_rate: Decimal,
+        taker_fee: Decimal,
+    ) -> Option<Position> {
+        let idx = self
+            .positions
+            .iter()
+            .position(|p| p.id == position_id && p.status == PositionStatus::Open && !p.tp1_filled)?;
+
+        let pos = &mut self.positions[idx];
+        let total = pos.quantity + quantity;
+        // Notional-weighted fee rate over all entry fills
+        let prior_rate = pos.entry_fee_rate.unwrap_or(taker_fee);
+        pos.entry_fee_rate = Some(
+            (prior_rate * pos.entry_price * pos.quantity + fill_fee_rate * price * quantity)
+                / (pos.entry_price * pos.quantity + price * quantity),
+        );
+        pos.entry_price = (pos.entry_price * pos.quantity + price * quantity) / total;
+        pos.quantity = total;
+        pos.original_quantity = total;
+        pos.initial_margin = calculate_initial_margin(pos.entry_price, total, pos.leverage);
+        pos.maintenance_margin =
+            calculate_maintenance_margin(pos.entry_price, total, maintenance_margin_rate);
+        let (side, entry_price, leverage) = (pos.side, pos.entry_price, pos.leverage);
+        let liquidation_price = self.liquidation_price(
+            side,
+            entry_price,
+            total,
+            leverage,
+            maintenance_margin_rate,
+            taker_fee,
+        );
+
+        let pos = &mut self.positions[idx];
+        pos.liquidation_price = liquidation_price;
+        Some(pos.clone())
     }
 
     /// Close a partial position (e.g., 50% at TP1)
@@ -148,9 +360,10 @@ impl PositionManager {
             Side::Sell => (pos.entry_price - exit_price) * close_quantity,
         };
 
-        // Subtract fees for closed portion
-        let notional = pos.entry_price * close_quantity + exit_price * close_quantity;
-        let fees = notional * fee_rate;
+        // Subtract fees for closed portion (a negative rate is a rebate)
+        let entry_fee_rate = pos.entry_fee_rate.unwrap_or(fee_rate);
+        let fees = pos.entry_price * close_quantity * entry_fee_rate
+            + exit_price * close_quantity * fee_rate;
         let partial_pnl = raw_pnl - fees;
 
         // Update position: reduce quantity, accumulate PnL
@@ -178,9 +391,10 @@ impl PositionManager {
             Side::Sell => (pos.entry_price - exit_price) * pos.quantity,
         };
 
-        // Subtract fees (entry + exit)
-        let notional = pos.entry_price * pos.quantity + exit_price * pos.quantity;
-        let fees = notional * fee_rate;
+        // Subtract fees (entry + exit; a negative rate is a rebate)
+        let entry_fee_rate = pos.entry_fee_rate.unwrap_or(fee_rate);
+        let fees =
+            pos.entry_price * pos.quantity * entry_fee_rate + exit_price * pos.quantity * fee_rate;
         let net_pnl = raw_pnl - fees;
 
         pos.pnl += net_pnl; // Add to any existing partial PnL
@@ -192,6 +406,52 @@ impl PositionManager {
         Some(pos.clone())
     }
 
+    /// Set the multi-stage (TP1/TP2) targets on a stored position
+    pub fn set_stage_targets(
+        &mut self,
+        position_id: &str,
+        tp1_price: Option<Decimal>,
+        tp2_price: Option<Decimal>,
+    ) {
+        if let Some(pos) = self.positions.iter_mut().find(|p| p.id == position_id) {
+            pos.tp1_price = tp1_price;
+            pos.tp2_price = tp2_price;
+        }
+    }
+
+    /// Record the fee rate paid on the entry fill (charged at close instead of the exit rate)
+    pub fn set_entry_fee_rate(&mut self, position_id: &str, rate: Decimal) {
+        if let Some(pos) = self.positions.iter_mut().find(|p| p.id == position_id) {
+            pos.entry_fee_rate = Some(rate);
+        }
+    }
+
+    /// Record book spread/depth imbalance seen when the position was opened
+    pub fn record_entry_book(
+        &mut self,
+        position_id: &str,
+        spread: Option<Decimal>,
+        depth_imbalance: Option<Decimal>,
+    ) {
+        if let Some(pos) = self.positions.iter_mut().find(|p| p.id == position_id) {
+            pos.entry_spread = spread;
+            pos.entry_depth_imbalance = depth_imbalance;
+        }
+    }
+
+    /// Record book spread/depth imbalance seen when the position was closed
+    pub fn record_exit_book(
+        &mut self,
+        position_id: &str,
+        spread: Option<Decimal>,
+        depth_imbalance: Option<Decimal>,
+    ) {
+        if let Some(pos) = self.positions.iter_mut().find(|p| p.id == position_id) {
+            pos.exit_spread = spread;
+            pos.exit_depth_imbalance = depth_imbalance;
+        }
+    }
+
     /// Move stop to break-even for a position
     pub fn move_stop_to_break_even(&mut self, position_id: &str, stop_price: Decimal) -> bool {
         if let Some(pos) = self
@@ -223,6 +483,25 @@ impl PositionManager {
         }
     }
 
+    /// Move the trailing stop to `stop_price` if that is tighter (or start trailing)
+    pub fn ratchet_trailing_stop(&mut self, position_id: &str, stop_price: Decimal) -> bool {
+        let Some(pos) = self
+            .positions
+            .iter_mut()
+            .find(|p| p.id == position_id && p.status == PositionStatus::Open)
+        else {
+            return false;
+        };
+        let tighter = pos.trailing_stop.is_none_or(|current| match pos.side {
+            Side::Buy => stop_price > current,
+            Side::Sell => stop_price < current,
+        });
+        if tighter {
+            pos.trailing_stop = Some(stop_price);
+        }
+        tighter
+    }
+
     /// Get all open positions
     pub fn open_positions(&self) -> Vec<&Position> {
         self.positions
@@ -257,6 +536,30 @@ impl PositionManager {
             .collect()
     }
 
+    /// Whether `price` reaches any open position's stop, target, TP1/TP2 or liquidation price
+    pub fn any_exit_level_crossed(&self, symbol: &str, price: Decimal) -> bool {
+        self.positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Open && p.symbol == symbol)
+            .any(|p| {
+                let tp1 = p.tp1_price.filter(|_| !p.tp1_filled);
+                let (protective, targets) = (
+                    [Some(p.stop_loss), Some(p.liquidation_price), p.emergency_stop, p.trailing_stop],
+                    [Some(p.take_profit), tp1, p.tp2_price],
+                );
+                match p.side {
+                    Side::Buy => {
+                        protective.iter().flatten().any(|level| price <= *level)
+                            || targets.iter().flatten().any(|level| price >= *level)
+                    }
+                    Side::Sell => {
+                        protective.iter().flatten().any(|level| price >= *level)
+                            || targets.iter().flatten().any(|level| price <= *level)
+                    }
+                }
+            })
+    }
+
     /// Check if any position should be liquidated based on liquidation price
     pub fn check_liquidations(
         &mut self,
@@ -303,34 +606,70 @@ impl PositionManager {
         liquidated
     }
 
-    /// Check if any position should be stopped out or take profit hit
+    /// Set the emergency stop on a stored position
+    pub fn set_emergency_stop(&mut self, position_id: &str, emergency_stop: Option<Decimal>) {
+        if let Some(pos) = self.positions.iter_mut().find(|p| p.id == position_id) {
+            pos.emergency_stop = emergency_stop;
+        }
+    }
+
+    /// Check if any position should be stopped out or take profit hit.
+    /// Inside `stop_min_hold` of entry only the emergency stop (and target) apply.
+    /// Take-profit exits fill as resting limits and pay `limit_fee_rate`.
     pub fn check_exits(
         &mut self,
         symbol: &str,
         current_price: Decimal,
+        now: DateTime<Utc>,
+        stop_min_hold: Duration,
         fee_rate: Decimal,
+        limit_fee_rate: Decimal,
     ) -> Vec<Position> {
         let ids_to_close: Vec<(String, Decimal, ExitReason)> = self
             .positions
             .iter()
             .filter(|p| p.status == PositionStatus::Open && p.symbol == symbol)
-            .filter_map(|p| match p.side {
-                Side::Buy => {
-                    if current_price <= p.stop_loss {
-                        Some((p.id.clone(), p.stop_loss, ExitReason::StopLoss))
-                    } else if current_price >= p.take_profit {
-                        Some((p.id.clone(), p.take_profit, ExitReason::TakeProfit))
-                    } else {
-                        None
-                    }
+            .filter_map(|p| {
+                let emergency_hit = p.emergency_stop.is_some_and(|es| match p.side {
+                    Side::Buy => current_price <= es,
+                    Side::Sell => current_price >= es,
+                });
+                let stop_armed = now - p.entry_time >= stop_min_hold;
+                let stop_hit = stop_armed
+                    && match p.side {
+                        Side::Buy => current_price <= p.stop_loss,
+                        Side::Sell => current_price >= p.stop_loss,
+                    };
+                // Once armed, the (tighter) normal stop wins a gap through both
+                if emergency_hit && !stop_hit {
+                    return p
+                        .emergency_stop
+                        .map(|es| (p.id.clone(), es, ExitReason::EmergencyStop));
                 }
-                Side::Sell => {
-                    if current_price >= p.stop_loss {
-                        Some((p.id.clone(), p.stop_loss, ExitReason::StopLoss))
-                    } else if current_price <= p.take_profit {
-                        Some((p.id.clone(), p.take_profit, ExitReason::TakeProfit))
-                    } else {
-                        None
+                match p.side {
+                    Side::Buy => {
+                        if stop_armed && current_price <= p.stop_loss {
+                            Some((p.id.clone(), p.stop_loss, ExitReason::StopLoss))
+                        } else if let Some(trail) = p.trailing_stop {
+                            (current_price <= trail)
+                                .then(|| (p.id.clone(), trail, ExitReason::TrailingStop))
+                        } else if current_price >= p.take_profit {
+                            Some((p.id.clone(), p.take_profit, ExitReason::TakeProfit))
+                        } else {
+                            None
+                        }
+                    }
+                    Side::Sell => {
+                        if stop_armed && current_price >= p.stop_loss {
+                            Some((p.id.clone(), p.stop_loss, ExitReason::StopLoss))
+                        } else if let Some(trail) = p.trailing_stop {
+                            (current_price >= trail)
+                                .then(|| (p.id.clone(), trail, ExitReason::TrailingStop))
+                        } else if current_price <= p.take_profit {
+                            Some((p.id.clone(), p.take_profit, ExitReason::TakeProfit))
+                        } else {
+                            None
+                        }
                     }
                 }
             })
@@ -338,13 +677,41 @@ impl PositionManager {
 
         let mut closed = Vec::new();
         for (id, price, reason) in ids_to_close {
-            if let Some(pos) = self.close_position(&id, price, fee_rate, reason) {
+            let rate = if reason == ExitReason::TakeProfit {
+                limit_fee_rate
+            } else {
+                fee_rate
+            };
+            if let Some(pos) = self.close_position(&id, price, rate, reason) {
                 closed.push(pos);
             }
         }
         closed
     }
 
+    /// Re-mark open positions in `symbol` at `mark_price` (unrealized PnL before fees)
+    pub fn mark_unrealized(&mut self, symbol: &str, mark_price: Decimal) {
+        for pos in self
+            .positions
+            .iter_mut()
+            .filter(|p| p.status == PositionStatus::Open && p.symbol == symbol)
+        {
+            pos.unrealized_pnl = match pos.side {
+                Side::Buy => (mark_price - pos.entry_price) * pos.quantity,
+                Side::Sell => (pos.entry_price - mark_price) * pos.quantity,
+            };
+        }
+    }
+
+    /// Unrealized PnL summed over all open positions
+    pub fn unrealized_pnl(&self) -> Decimal {
+        self.positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Open)
+            .map(|p| p.unrealized_pnl)
+            .sum()
+    }
+
     pub fn update_excursions(&mut self, symbol: &str, mark_price: Decimal, now: DateTime<Utc>) {
         if mark_price <= Decimal::ZERO {
             return;
@@ -388,3 +755,43 @@ impl PositionManager {
         }
     }
 }
+
+/// Position fixtures shared by tests across modules
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::PositionManager;
+    use crate::types::{ExitReason, MarginType, Position, SetupType, Side, TradeSignal};
+    use rust_decimal::Decimal;
+
+    /// One-unit 10x isolated long on `symbol` at `entry` (stop 1 below, target 2 above)
+    pub(crate) fn open_position(positions: &mut PositionManager, symbol: &str, entry: i64) -> Position {
+        let signal = TradeSignal::new(
+            symbol.to_string(),
+            Side::Buy,
+            SetupType::MomentumSqueeze,
+            Decimal::from(entry),
+            Decimal::from(entry - 1),
+            Decimal::from(entry + 2),
+            Decimal::ONE,
+        );
+        positions
+            .open_position(
+                &signal,
+                Decimal::ONE,
+                Decimal::from(10),
+                MarginType::Isolated,
+                Decimal::new(4, 3),
+                Decimal::new(4, 4),
+            )
+            .unwrap()
+    }
+
+    /// The same long opened at `entry` and closed at `exit`
+    pub(crate) fn closed_position(symbol: &str, entry: i64, exit: i64) -> Position {
+        let mut positions = PositionManager::new();
+        let position = open_position(&mut positions, symbol, entry);
+        positions
+            .close_position(&position.id, Decimal::from(exit), Decimal::ZERO, ExitReason::TakeProfit)
+            .unwrap()
+    }
+}
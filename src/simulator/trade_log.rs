@@ -1,12 +1,17 @@
-use crate::types::Position;
+use crate::types::{Position, TradeSignal};
 use rusqlite::{params, Connection};
 use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
-use tracing::{error, info};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{error, info, warn};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PerformanceMetrics {
     pub total_trades: usize,
     pub winners: usize,
@@ -15,24 +20,100 @@ pub struct PerformanceMetrics {
     pub total_pnl: Decimal,
     pub gross_profit: Decimal,
     pub gross_loss_abs: Decimal,
+    /// Gross profit / gross loss; `None` when there were no losing trades
     pub profit_factor: Option<Decimal>,
     pub avg_win: Decimal,
     pub avg_loss: Decimal,
     pub max_drawdown_abs: Decimal,
     pub max_drawdown_pct: Decimal,
+    /// Entry-to-exit holding time of closed trades, in seconds
+    pub median_hold_secs: Decimal,
+    pub p90_hold_secs: Decimal,
 }
 
-/// Logs completed trades to CSV, JSON, and SQLite
-pub struct TradeLogger {
+impl PerformanceMetrics {
+    /// Profit factor for logs: "inf" when there were wins but no losses, "N/A" without either
+    pub fn profit_factor_display(&self) -> String {
+        match self.profit_factor {
+            Some(pf) => pf.round_dp(4).to_string(),
+            None if self.gross_profit > Decimal::ZERO => "inf".to_string(),
+            None => "N/A".to_string(),
+        }
+    }
+}
+
+/// Trade count, winners and net PnL for one setup or symbol
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SummaryBreakdown {
+    pub trades: usize,
+    pub winners: usize,
+    pub pnl: Decimal,
+}
+
+/// Trades held up to `max_secs` (None = longer than every configured bound)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HoldTimeBucket {
+    pub max_secs: Option<u64>,
+    pub trades: usize,
+}
+
+/// Machine-readable shutdown summary: overall metrics plus per-setup/per-symbol breakdown
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeSummary {
+    #[serde(flatten)]
+    pub metrics: PerformanceMetrics,
+    pub by_setup: BTreeMap<String, SummaryBreakdown>,
+    pub by_symbol: BTreeMap<String, SummaryBreakdown>,
+    pub hold_time_histogram: Vec<HoldTimeBucket>,
+}
+
+/// Message for the background trade writer
+enum WriterMsg {
+    Row(Row),
+    /// Acknowledged once every earlier message has been written
+    Flush(Sender<()>),
+}
+
+/// One database write, applied in the order it was logged
+enum Row {
+    Open(Box<Position>),
+    Entry(Box<Position>),
+    Rejected(Box<TradeSignal>, String),
+    WhatIf { signal_id: String, won: bool, bars: usize },
+    /// Also appended to the CSV and JSON files
+    Closed(Box<Position>),
+}
+
+/// Dedicated thread that owns the trade files and batches database writes
+struct TradeWriter {
+    tx: Sender<WriterMsg>,
+    handle: JoinHandle<()>,
+}
+
+/// CSV and JSON-lines trade files
+struct TradeFiles {
     csv_path: String,
     json_path: String,
     csv_initialized: bool,
+}
+
+/// Logs completed trades to CSV, JSON, and SQLite
+pub struct TradeLogger {
+    /// `None` once handed to the background writer
+    files: Option<TradeFiles>,
+    writer: Option<TradeWriter>,
+    /// Also print the shutdown summary to stdout as one JSON line
+    summary_json: bool,
+    /// Upper bounds (seconds) of the hold-time histogram buckets
+    hold_time_buckets_secs: Vec<u64>,
     db: Arc<Mutex<Connection>>,
 }
 
 impl TradeLogger {
     pub fn new(csv_path: String, json_path: String, db_path: String) -> Self {
-        let conn = Connection::open(&db_path).unwrap_or_else(|e| {
+        // Config default until `with_busy_timeout` overrides it
+        let busy_timeout = Duration::from_millis(crate::config::default_db_busy_timeout_ms());
+        let conn = open_database(&db_path, busy_timeout).unwrap_or_else(|e| {
             error!("Failed to open SQLite database: {}", e);
             panic!("Cannot continue without database");
         });
@@ -68,6 +149,11 @@ impl TradeLogger {
         Self::add_column_if_missing(&conn, "positions", "mae_pct", "REAL");
         Self::add_column_if_missing(&conn, "positions", "time_to_mfe_secs", "INTEGER");
         Self::add_column_if_missing(&conn, "positions", "time_to_mae_secs", "INTEGER");
+        Self::add_column_if_missing(&conn, "positions", "entry_spread", "REAL");
+        Self::add_column_if_missing(&conn, "positions", "entry_depth_imbalance", "REAL");
+        Self::add_column_if_missing(&conn, "positions", "exit_spread", "REAL");
+        Self::add_column_if_missing(&conn, "positions", "exit_depth_imbalance", "REAL");
+        Self::add_column_if_missing(&conn, "positions", "regime", "TEXT");
 
         // Create entry-feature table (one row per entry)
         if let Err(e) = conn.execute(
@@ -117,121 +203,172 @@ impl TradeLogger {
             error!("Failed to create performance_metrics table: {}", e);
             panic!("Cannot continue without metrics schema");
         }
+        Self::add_column_if_missing(&conn, "performance_metrics", "median_hold_secs", "REAL");
+        Self::add_column_if_missing(&conn, "performance_metrics", "p90_hold_secs", "REAL");
+
+        // Create rejected-signal table (what-if outcome filled in once resolved)
+        if let Err(e) = conn.execute(
+            "CREATE TABLE IF NOT EXISTS signals (
+                id TEXT PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                side TEXT NOT NULL,
+                setup TEXT NOT NULL,
+                entry_price REAL NOT NULL,
+                stop_loss REAL NOT NULL,
+                take_profit REAL NOT NULL,
+                signal_time TEXT NOT NULL,
+                status TEXT NOT NULL,
+                reject_reason TEXT,
+                what_if_outcome TEXT,
+                what_if_bars INTEGER,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        ) {
+            error!("Failed to create signals table: {}", e);
+            panic!("Cannot continue without signals schema");
+        }
 
         info!("SQLite database initialized at: {}", db_path);
 
         Self {
-            csv_path,
-            json_path,
-            csv_initialized: false,
+            files: Some(TradeFiles {
+                csv_path,
+                json_path,
+                csv_initialized: false,
+            }),
+            writer: None,
+            summary_json: false,
+            hold_time_buckets_secs: Vec::new(),
             db: Arc::new(Mutex::new(conn)),
         }
     }
 
-    /// Log a closed position
-    pub fn log_trade(&mut self, position: &Position) {
-        self.log_csv(position);
-        self.log_json(position);
-        self.log_sqlite(position);
+    pub fn with_summary_json(mut self, enabled: bool) -> Self {
+        self.summary_json = enabled;
+        self
     }
 
-    /// Log entry-time features for later strategy analysis.
-    pub fn log_entry(&self, position: &Position) {
-        let Some(features) = position.entry_features.as_ref() else {
-            return;
-        };
+    pub fn with_hold_time_buckets(mut self, mut bounds_secs: Vec<u64>) -> Self {
+        bounds_secs.sort_unstable();
+        bounds_secs.dedup();
+        self.hold_time_buckets_secs = bounds_secs;
+        self
+    }
 
-        let db = match self.db.lock() {
-            Ok(db) => db,
-            Err(e) => {
-                error!("Failed to acquire database lock for entry_features: {}", e);
-                return;
+    /// How long a write waits on a locked database before failing with SQLITE_BUSY
+    pub fn with_busy_timeout(self, timeout_ms: u64) -> Self {
+        match self.db.lock() {
+            Ok(conn) => {
+                if let Err(e) = conn.busy_timeout(Duration::from_millis(timeout_ms)) {
+                    warn!("Failed to set SQLite busy timeout: {}", e);
+                }
             }
+            Err(e) => error!("Failed to acquire database lock: {}", e),
+        }
+        self
+    }
+
+    /// Move closed-trade logging to a dedicated thread that writes up to
+    /// `batch_size` queued trades per SQLite transaction (0 = log inline)
+    pub fn with_background_writer(mut self, batch_size: usize) -> Self {
+        if batch_size == 0 || self.writer.is_some() {
+            return self;
+        }
+        let Some(files) = self.files.take() else {
+            return self;
         };
+        let (tx, rx) = mpsc::channel();
+        let db = Arc::clone(&self.db);
+        let spawned = std::thread::Builder::new()
+            .name("trade-writer".to_string())
+            .spawn(move || run_writer(rx, db, files, batch_size));
+        match spawned {
+            Ok(handle) => {
+                info!(batch_size, "Trade logging moved to background writer");
+                self.writer = Some(TradeWriter { tx, handle });
+            }
+            Err(e) => error!("Failed to start trade writer, logging inline: {}", e),
+        }
+        self
+    }
 
-        if let Err(e) = db.execute(
-            "INSERT INTO entry_features (
-                position_id, symbol, side, setup, entry_time, entry_price,
-                imbalance_ratio, cvd_1min_change, volume_burst_ratio, bar_range_pct,
-                zone_distance_pct, near_val, near_vah, near_hvn
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
-            ON CONFLICT(position_id) DO NOTHING",
-            params![
-                position.id,
-                position.symbol,
-                format!("{:?}", position.side),
-                format!("{}", position.setup),
-                position.entry_time.to_rfc3339(),
-                position.entry_price.to_string(),
-                features.imbalance_ratio.to_string(),
-                features.cvd_1min_change.to_string(),
-                features.volume_burst_ratio.to_string(),
-                features.bar_range_pct.to_string(),
-                features.zone_distance_pct.to_string(),
-                features.near_val as i32,
-                features.near_vah as i32,
-                features.near_hvn as i32,
-            ],
-        ) {
-            error!("Failed to insert entry_features into database: {}", e);
+    /// Log a closed position
+    pub fn log_trade(&mut self, position: &Position) {
+        if self.writer.is_none() {
+            if let Some(files) = self.files.as_mut() {
+                files.write(position);
+            }
         }
+        self.record(Row::Closed(Box::new(position.clone())));
     }
 
-    fn log_sqlite(&self, position: &Position) {
-        let db = match self.db.lock() {
-            Ok(db) => db,
-            Err(e) => {
-                error!("Failed to acquire database lock: {}", e);
-                return;
+    /// Queue a row on the background writer, or write it now when there is none
+    fn record(&self, row: Row) {
+        if let Some(writer) = &self.writer {
+            if writer.tx.send(WriterMsg::Row(row)).is_err() {
+                error!("Trade writer stopped; database row not logged");
             }
+            return;
+        }
+        match self.db.lock() {
+            Ok(db) => write_row(&db, &row),
+            Err(e) => error!("Failed to acquire database lock: {}", e),
+        }
+    }
+
+    /// Block until every trade queued so far has been written
+    pub fn flush(&self) {
+        let Some(writer) = &self.writer else {
+            return;
         };
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if writer.tx.send(WriterMsg::Flush(ack_tx)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+    }
 
-        let exit_price = position.exit_price.map(|p| p.to_string());
-        let exit_time = position.exit_time.map(|t| t.to_rfc3339());
-        let exit_reason = position.exit_reason.map(|r| r.to_string());
+    /// Insert a freshly opened position (status Open) so it is visible before it closes.
+    /// The close-time upsert in `log_trade` later fills in the exit fields on the same row.
+    /// A duplicate id keeps the existing row untouched.
+    pub fn log_open(&self, position: &Position) {
+        self.record(Row::Open(Box::new(position.clone())));
+    }
 
-        if let Err(e) = db.execute(
-            "INSERT INTO positions (
-                id, symbol, side, setup, entry_price, exit_price, quantity,
-                stop_loss, take_profit, pnl, status, entry_time, exit_time, break_even_moved,
-                exit_reason, mfe_pct, mae_pct, time_to_mfe_secs, time_to_mae_secs
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
-            ON CONFLICT(id) DO UPDATE SET
-                exit_price = excluded.exit_price,
-                pnl = excluded.pnl,
-                status = excluded.status,
-                exit_time = excluded.exit_time,
-                exit_reason = excluded.exit_reason,
-                mfe_pct = excluded.mfe_pct,
-                mae_pct = excluded.mae_pct,
-                time_to_mfe_secs = excluded.time_to_mfe_secs,
-                time_to_mae_secs = excluded.time_to_mae_secs",
-            params![
-                position.id,
-                position.symbol,
-                format!("{:?}", position.side),
-                format!("{}", position.setup),
-                position.entry_price.to_string(),
-                exit_price,
-                position.quantity.to_string(),
-                position.stop_loss.to_string(),
-                position.take_profit.to_string(),
-                position.pnl.to_string(),
-                format!("{:?}", position.status),
-                position.entry_time.to_rfc3339(),
-                exit_time,
-                position.break_even_moved as i32,
-                exit_reason,
-                position.max_favorable_excursion_pct.to_string(),
-                position.max_adverse_excursion_pct.to_string(),
-                position.time_to_mfe_secs,
-                position.time_to_mae_secs,
-            ],
-        ) {
-            error!("Failed to insert position into database: {}", e);
+    /// Log entry-time features for later strategy analysis.
+    pub fn log_entry(&self, position: &Position) {
+        if position.entry_features.is_some() {
+            self.record(Row::Entry(Box::new(position.clone())));
         }
     }
 
+    /// Log a signal rejected before entry, with the filter that rejected it.
+    pub fn log_rejected_signal(&self, signal: &TradeSignal, reason: &str) {
+        self.record(Row::Rejected(Box::new(signal.clone()), reason.to_string()));
+    }
+
+    /// Record whether a rejected signal would have won had it been taken.
+    pub fn log_what_if_outcome(&self, signal_id: &str, won: bool, bars: usize) {
+        self.record(Row::WhatIf {
+            signal_id: signal_id.to_string(),
+            won,
+            bars,
+        });
+    }
+
+    /// Hypothetical outcome ("Win"/"Loss") of a rejected signal, once resolved.
+    pub fn what_if_outcome(&self, signal_id: &str) -> Option<String> {
+        self.flush();
+        let db = self.db.lock().ok()?;
+        db.query_row(
+            "SELECT what_if_outcome FROM signals WHERE id = ?1",
+            params![signal_id],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten()
+    }
+
     fn add_column_if_missing(conn: &Connection, table: &str, column: &str, col_type: &str) {
         let sql = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, col_type);
         if let Err(e) = conn.execute(&sql, []) {
@@ -245,76 +382,6 @@ impl TradeLogger {
         }
     }
 
-    fn log_csv(&mut self, position: &Position) {
-        let file = if !self.csv_initialized {
-            self.csv_initialized = true;
-            match File::create(&self.csv_path) {
-                Ok(mut f) => {
-                    let _ = writeln!(
-                        f,
-                        "id,symbol,side,setup,entry_price,exit_price,quantity,pnl,entry_time,exit_time,break_even_moved"
-                    );
-                    Some(f)
-                }
-                Err(e) => {
-                    error!("Failed to create CSV file: {}", e);
-                    None
-                }
-            }
-        } else {
-            OpenOptions::new().append(true).open(&self.csv_path).ok()
-        };
-
-        if let Some(mut f) = file {
-            let exit_price = position
-                .exit_price
-                .map(|p| p.to_string())
-                .unwrap_or_default();
-            let exit_time = position
-                .exit_time
-                .map(|t| t.to_rfc3339())
-                .unwrap_or_default();
-
-            let _ = writeln!(
-                f,
-                "{},{},{:?},{},{},{},{},{},{},{}",
-                position.id,
-                position.symbol,
-                position.side,
-                position.setup,
-                position.entry_price,
-                exit_price,
-                position.quantity,
-                position.pnl,
-                position.entry_time.to_rfc3339(),
-                exit_time,
-            );
-        }
-    }
-
-    fn log_json(&self, position: &Position) {
-        let mut file = match OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.json_path)
-        {
-            Ok(f) => f,
-            Err(e) => {
-                error!("Failed to open JSON file: {}", e);
-                return;
-            }
-        };
-
-        match serde_json::to_string(position) {
-            Ok(json) => {
-                let _ = writeln!(file, "{}", json);
-            }
-            Err(e) => {
-                error!("Failed to serialize position: {}", e);
-            }
-        }
-    }
-
     pub fn calculate_metrics(
         &self,
         positions: &[Position],
@@ -356,13 +423,7 @@ impl TradeLogger {
         } else {
             Decimal::ZERO
         };
-        let profit_factor = if gross_loss_abs > Decimal::ZERO {
-            Some(gross_profit / gross_loss_abs)
-        } else if gross_profit > Decimal::ZERO {
-            Some(Decimal::from(999))
-        } else {
-            None
-        };
+        let profit_factor = (gross_loss_abs > Decimal::ZERO).then(|| gross_profit / gross_loss_abs);
 
         let mut equity = initial_balance;
         let mut peak = initial_balance;
@@ -386,6 +447,18 @@ impl TradeLogger {
             }
         }
 
+        let hold_secs = hold_times_secs(positions);
+        let median_hold_secs = match hold_secs.len() {
+            0 => Decimal::ZERO,
+            n if n % 2 == 1 => Decimal::from(hold_secs[n / 2]),
+            n => Decimal::from(hold_secs[n / 2 - 1] + hold_secs[n / 2]) / Decimal::TWO,
+        };
+        // Nearest-rank 90th percentile
+        let p90_hold_secs = match hold_secs.len() {
+            0 => Decimal::ZERO,
+            n => Decimal::from(hold_secs[(n * 9).div_ceil(10) - 1]),
+        };
+
         Some(PerformanceMetrics {
             total_trades,
             winners,
@@ -399,6 +472,60 @@ impl TradeLogger {
             avg_loss,
             max_drawdown_abs,
             max_drawdown_pct,
+            median_hold_secs,
+            p90_hold_secs,
+        })
+    }
+
+    /// Closed trades counted into the configured hold-time buckets
+    pub fn hold_time_histogram(&self, positions: &[Position]) -> Vec<HoldTimeBucket> {
+        if self.hold_time_buckets_secs.is_empty() {
+            return Vec::new();
+        }
+        let mut buckets: Vec<HoldTimeBucket> = self
+            .hold_time_buckets_secs
+            .iter()
+            .map(|&max| HoldTimeBucket {
+                max_secs: Some(max),
+                trades: 0,
+            })
+            .chain(std::iter::once(HoldTimeBucket {
+                max_secs: None,
+                trades: 0,
+            }))
+            .collect();
+        for secs in hold_times_secs(positions) {
+            let idx = self
+                .hold_time_buckets_secs
+                .iter()
+                .position(|&max| secs <= max as i64)
+                .unwrap_or(buckets.len() - 1);
+            buckets[idx].trades += 1;
+        }
+        buckets
+    }
+
+    pub fn summary(&self, positions: &[Position], initial_balance: Decimal) -> Option<TradeSummary> {
+        let metrics = self.calculate_metrics(positions, initial_balance)?;
+        let mut by_setup: BTreeMap<String, SummaryBreakdown> = BTreeMap::new();
+        let mut by_symbol: BTreeMap<String, SummaryBreakdown> = BTreeMap::new();
+        for p in positions {
+            for entry in [
+                by_setup.entry(p.setup.to_string()).or_default(),
+                by_symbol.entry(p.symbol.clone()).or_default(),
+            ] {
+                entry.trades += 1;
+                if p.pnl > Decimal::ZERO {
+                    entry.winners += 1;
+                }
+                entry.pnl += p.pnl;
+            }
+        }
+        Some(TradeSummary {
+            metrics,
+            by_setup,
+            by_symbol,
+            hold_time_histogram: self.hold_time_histogram(positions),
         })
     }
 
@@ -415,8 +542,8 @@ impl TradeLogger {
             "INSERT INTO performance_metrics (
                 total_trades, winners, losers, win_rate_pct, total_pnl,
                 gross_profit, gross_loss_abs, profit_factor, avg_win, avg_loss,
-                max_drawdown_abs, max_drawdown_pct
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                max_drawdown_abs, max_drawdown_pct, median_hold_secs, p90_hold_secs
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 m.total_trades as i64,
                 m.winners as i64,
@@ -430,6 +557,8 @@ impl TradeLogger {
                 m.avg_loss.to_string(),
                 m.max_drawdown_abs.to_string(),
                 m.max_drawdown_pct.to_string(),
+                m.median_hold_secs.to_string(),
+                m.p90_hold_secs.to_string(),
             ],
         ) {
             error!("Failed to insert performance metrics into database: {}", e);
@@ -438,11 +567,12 @@ impl TradeLogger {
 
     /// Print summary stats
     pub fn print_summary(&self, positions: &[Position], initial_balance: Decimal) {
-        let Some(m) = self.calculate_metrics(positions, initial_balance) else {
+        let Some(summary) = self.summary(positions, initial_balance) else {
             info!("No trades to summarize");
             return;
         };
-        self.log_metrics_sqlite(&m);
+        let m = &summary.metrics;
+        self.log_metrics_sqlite(m);
 
         info!("=== Trade Summary ===");
         info!("Total trades: {}", m.total_trades);
@@ -459,24 +589,691 @@ impl TradeLogger {
             m.avg_win.round_dp(4),
             m.avg_loss.round_dp(4)
         );
-        match m.profit_factor {
-            Some(v) => info!("Profit factor: {}", v.round_dp(4)),
-            None => info!("Profit factor: N/A"),
-        }
+        info!("Profit factor: {}", m.profit_factor_display());
         info!(
             "Max drawdown: {} ({:.2}%)",
             m.max_drawdown_abs.round_dp(4),
             m.max_drawdown_pct.round_dp(2)
         );
+        info!(
+            "Hold time: median {}s | p90 {}s",
+            m.median_hold_secs.round_dp(1),
+            m.p90_hold_secs
+        );
+        for bucket in &summary.hold_time_histogram {
+            match bucket.max_secs {
+                Some(max) => info!("  <= {}s: {}", max, bucket.trades),
+                None => info!("  longer: {}", bucket.trades),
+            }
+        }
         info!(
             "BACKTEST_METRICS wr_pct={} pf={} mdd_pct={} mdd_abs={} trades={} pnl={}",
             m.win_rate_pct.round_dp(4),
-            m.profit_factor.unwrap_or(Decimal::ZERO).round_dp(4),
+            m.profit_factor_display(),
             m.max_drawdown_pct.round_dp(4),
             m.max_drawdown_abs.round_dp(4),
             m.total_trades,
             m.total_pnl.round_dp(4)
         );
         info!("=====================");
+
+        if self.summary_json {
+            match serde_json::to_string(&summary) {
+                Ok(line) => println!("{}", line),
+                Err(e) => error!("Failed to serialize trade summary: {}", e),
+            }
+        }
+    }
+}
+
+impl Drop for TradeLogger {
+    /// Let the background writer drain its queue so no closed trade is lost
+    fn drop(&mut self) {
+        if let Some(TradeWriter { tx, handle }) = self.writer.take() {
+            drop(tx);
+            if handle.join().is_err() {
+                error!("Trade writer thread panicked");
+            }
+        }
+    }
+}
+
+/// Open a trade database connection in WAL mode with `busy_timeout`.
+/// WAL lets dashboards and other writers' connections read while the bot writes; the
+/// timeout waits out brief locks instead of failing with SQLITE_BUSY. In-memory
+/// databases stay in "memory" mode.
+pub fn open_database(path: &str, busy_timeout: Duration) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    match conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get::<_, String>(0)) {
+        Ok(mode) if !mode.eq_ignore_ascii_case("wal") && !mode.eq_ignore_ascii_case("memory") => {
+            warn!(journal_mode = %mode, "SQLite did not switch to WAL mode")
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to enable SQLite WAL mode: {}", e),
+    }
+    if let Err(e) = conn.busy_timeout(busy_timeout) {
+        warn!("Failed to set SQLite busy timeout: {}", e);
+    }
+    Ok(conn)
+}
+
+/// Apply one row, logging (not propagating) a failure so the rest of a batch still commits
+fn write_row(conn: &Connection, row: &Row) {
+    match row {
+        Row::Open(position) => match insert_open(conn, position) {
+            Ok(0) if is_same_open_row(conn, position) => {
+                warn!(position_id = %position.id, "Position id already in database; keeping existing row")
+            }
+            Ok(0) => error!(
+                position_id = %position.id,
+                "Position id belongs to another trade in the database (deterministic ids from an earlier run?); \
+                 open not recorded. Use a fresh database per replay"
+            ),
+            Ok(_) => {}
+            Err(e) => error!(position_id = %position.id, "Failed to insert open position into database: {}", e),
+        },
+        Row::Entry(position) => {
+            if let Err(e) = insert_entry_features(conn, position) {
+                error!(position_id = %position.id, "Failed to insert entry_features into database: {}", e);
+            }
+        }
+        Row::Rejected(signal, reason) => {
+            if let Err(e) = insert_rejected(conn, signal, reason) {
+                error!(signal_id = %signal.id, "Failed to insert rejected signal into database: {}", e);
+            }
+        }
+        Row::WhatIf { signal_id, won, bars } => {
+            if let Err(e) = conn.execute(
+                "UPDATE signals SET what_if_outcome = ?1, what_if_bars = ?2 WHERE id = ?3",
+                params![if *won { "Win" } else { "Loss" }, *bars as i64, signal_id],
+            ) {
+                error!(signal_id = %signal_id, "Failed to record what-if outcome: {}", e);
+            }
+        }
+        Row::Closed(position) => match upsert_closed(conn, position) {
+            Ok(0) => error!(
+                position_id = %position.id,
+                "Position id belongs to another trade in the database (deterministic ids from an earlier run?); \
+                 close not recorded. Use a fresh database per replay"
+            ),
+            Ok(_) => {}
+            Err(e) => error!(position_id = %position.id, "Failed to insert position into database: {}", e),
+        },
+    }
+}
+
+/// Whether the row stored under `position.id` is this position, still open
+fn is_same_open_row(conn: &Connection, position: &Position) -> bool {
+    conn.query_row(
+        "SELECT status = 'Open' AND entry_time = ?2 FROM positions WHERE id = ?1",
+        params![position.id, position.entry_time.to_rfc3339()],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Insert a freshly opened position; a duplicate id keeps the existing row (0 rows changed)
+fn insert_open(conn: &Connection, position: &Position) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO positions (
+            id, symbol, side, setup, entry_price, quantity, stop_loss, take_profit,
+            pnl, status, entry_time, break_even_moved, entry_spread, entry_depth_imbalance,
+            regime
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+        ON CONFLICT(id) DO NOTHING",
+        params![
+            position.id,
+            position.symbol,
+            format!("{:?}", position.side),
+            format!("{}", position.setup),
+            position.entry_price.to_string(),
+            position.quantity.to_string(),
+            position.stop_loss.to_string(),
+            position.take_profit.to_string(),
+            position.pnl.to_string(),
+            format!("{:?}", position.status),
+            position.entry_time.to_rfc3339(),
+            position.break_even_moved as i32,
+            position.entry_spread.map(|v| v.to_string()),
+            position.entry_depth_imbalance.map(|v| v.to_string()),
+            position.regime.map(|r| r.to_string()),
+        ],
+    )
+}
+
+fn insert_entry_features(conn: &Connection, position: &Position) -> rusqlite::Result<usize> {
+    let Some(features) = position.entry_features.as_ref() else {
+        return Ok(0);
+    };
+    conn.execute(
+        "INSERT INTO entry_features (
+            position_id, symbol, side, setup, entry_time, entry_price,
+            imbalance_ratio, cvd_1min_change, volume_burst_ratio, bar_range_pct,
+            zone_distance_pct, near_val, near_vah, near_hvn
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+        ON CONFLICT(position_id) DO NOTHING",
+        params![
+            position.id,
+            position.symbol,
+            format!("{:?}", position.side),
+            format!("{}", position.setup),
+            position.entry_time.to_rfc3339(),
+            position.entry_price.to_string(),
+            features.imbalance_ratio.map(|v| v.to_string()),
+            features.cvd_1min_change.to_string(),
+            features.volume_burst_ratio.to_string(),
+            features.bar_range_pct.to_string(),
+            features.zone_distance_pct.to_string(),
+            features.near_val as i32,
+            features.near_vah as i32,
+            features.near_hvn as i32,
+        ],
+    )
+}
+
+fn insert_rejected(conn: &Connection, signal: &TradeSignal, reason: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO signals (
+            id, symbol, side, setup, entry_price, stop_loss, take_profit,
+            signal_time, status, reject_reason
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'Rejected', ?9)
+        ON CONFLICT(id) DO NOTHING",
+        params![
+            signal.id,
+            signal.symbol,
+            format!("{:?}", signal.side),
+            format!("{}", signal.setup),
+            signal.entry_price.to_string(),
+            signal.stop_loss.to_string(),
+            signal.take_profit.to_string(),
+            signal.timestamp.to_rfc3339(),
+            reason,
+        ],
+    )
+}
+
+/// Upsert a closed position, filling the exit fields of its open row. A row under the
+/// same id that is already closed or was opened at another time is left untouched (0 rows).
+fn upsert_closed(conn: &Connection, position: &Position) -> rusqlite::Result<usize> {
+    let exit_price = position.exit_price.map(|p| p.to_string());
+    let exit_time = position.exit_time.map(|t| t.to_rfc3339());
+    let exit_reason = position.exit_reason.map(|r| r.to_string());
+
+    conn.execute(
+        "INSERT INTO positions (
+            id, symbol, side, setup, entry_price, exit_price, quantity,
+            stop_loss, take_profit, pnl, status, entry_time, exit_time, break_even_moved,
+            exit_reason, mfe_pct, mae_pct, time_to_mfe_secs, time_to_mae_secs,
+            entry_spread, entry_depth_imbalance, exit_spread, exit_depth_imbalance, regime
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19,
+            ?20, ?21, ?22, ?23, ?24)
+        ON CONFLICT(id) DO UPDATE SET
+            exit_price = excluded.exit_price,
+            stop_loss = excluded.stop_loss,
+            break_even_moved = excluded.break_even_moved,
+            pnl = excluded.pnl,
+            status = excluded.status,
+            exit_time = excluded.exit_time,
+            exit_reason = excluded.exit_reason,
+            mfe_pct = excluded.mfe_pct,
+            mae_pct = excluded.mae_pct,
+            time_to_mfe_secs = excluded.time_to_mfe_secs,
+            time_to_mae_secs = excluded.time_to_mae_secs,
+            exit_spread = excluded.exit_spread,
+            exit_depth_imbalance = excluded.exit_depth_imbalance
+        WHERE positions.status = 'Open' AND positions.entry_time = excluded.entry_time",
+        params![
+            position.id,
+            position.symbol,
+            format!("{:?}", position.side),
+            format!("{}", position.setup),
+            position.entry_price.to_string(),
+            exit_price,
+            position.quantity.to_string(),
+            position.stop_loss.to_string(),
+            position.take_profit.to_string(),
+            position.pnl.to_string(),
+            format!("{:?}", position.status),
+            position.entry_time.to_rfc3339(),
+            exit_time,
+            position.break_even_moved as i32,
+            exit_reason,
+            position.max_favorable_excursion_pct.to_string(),
+            position.max_adverse_excursion_pct.to_string(),
+            position.time_to_mfe_secs,
+            position.time_to_mae_secs,
+            position.entry_spread.map(|v| v.to_string()),
+            position.entry_depth_imbalance.map(|v| v.to_string()),
+            position.exit_spread.map(|v| v.to_string()),
+            position.exit_depth_imbalance.map(|v| v.to_string()),
+            position.regime.map(|r| r.to_string()),
+        ],
+    )
+}
+
+impl TradeFiles {
+    fn write(&mut self, position: &Position) {
+        self.log_csv(position);
+        self.log_json(position);
+    }
+
+    fn log_csv(&mut self, position: &Position) {
+        let file = if !self.csv_initialized {
+            self.csv_initialized = true;
+            match File::create(&self.csv_path) {
+                Ok(mut f) => {
+                    let _ = writeln!(
+                        f,
+                        "id,symbol,side,setup,entry_price,exit_price,quantity,pnl,entry_time,exit_time,break_even_moved,entry_spread,entry_depth_imbalance,exit_spread,exit_depth_imbalance"
+                    );
+                    Some(f)
+                }
+                Err(e) => {
+                    error!("Failed to create CSV file: {}", e);
+                    None
+                }
+            }
+        } else {
+            OpenOptions::new().append(true).open(&self.csv_path).ok()
+        };
+
+        if let Some(mut f) = file {
+            let exit_price = position
+                .exit_price
+                .map(|p| p.to_string())
+                .unwrap_or_default();
+            let exit_time = position
+                .exit_time
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default();
+            let opt = |v: Option<Decimal>| v.map(|d| d.to_string()).unwrap_or_default();
+
+            let _ = writeln!(
+                f,
+                "{},{},{:?},{},{},{},{},{},{},{},{},{},{},{},{}",
+                position.id,
+                position.symbol,
+                position.side,
+                position.setup,
+                position.entry_price,
+                exit_price,
+                position.quantity,
+                position.pnl,
+                position.entry_time.to_rfc3339(),
+                exit_time,
+                position.break_even_moved,
+                opt(position.entry_spread),
+                opt(position.entry_depth_imbalance),
+                opt(position.exit_spread),
+                opt(position.exit_depth_imbalance),
+            );
+        }
+    }
+
+    fn log_json(&self, position: &Position) {
+        let mut file = match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.json_path)
+        {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Failed to open JSON file: {}", e);
+                return;
+            }
+        };
+
+        match serde_json::to_string(position) {
+            Ok(json) => {
+                let _ = writeln!(file, "{}", json);
+            }
+            Err(e) => {
+                error!("Failed to serialize position: {}", e);
+            }
+        }
+    }
+}
+
+/// Drain the queue in batches: one SQLite transaction per batch, files appended in order
+fn run_writer(
+    rx: Receiver<WriterMsg>,
+    db: Arc<Mutex<Connection>>,
+    mut files: TradeFiles,
+    batch_size: usize,
+) {
+    let mut batch: Vec<Row> = Vec::with_capacity(batch_size);
+    while let Ok(first) = rx.recv() {
+        let mut acks = Vec::new();
+        let mut next = Some(first);
+        while let Some(msg) = next {
+            match msg {
+                WriterMsg::Row(row) => batch.push(row),
+                WriterMsg::Flush(ack) => {
+                    acks.push(ack);
+                    break;
+                }
+            }
+            if batch.len() >= batch_size {
+                break;
+            }
+            next = rx.try_recv().ok();
+        }
+
+        for row in &batch {
+            if let Row::Closed(position) = row {
+                files.write(position);
+            }
+        }
+        if !batch.is_empty() {
+            if let Err(e) = write_batch(&db, &batch) {
+                error!(rows = batch.len(), "Failed to write trade batch to database: {}", e);
+            }
+        }
+        batch.clear();
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+}
+
+/// Write a batch in one transaction; a failing row is logged and skipped, the rest commit
+fn write_batch(db: &Mutex<Connection>, batch: &[Row]) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for row in batch {
+        write_row(&tx, row);
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Sorted entry-to-exit durations of closed positions, in seconds
+fn hold_times_secs(positions: &[Position]) -> Vec<i64> {
+    let mut secs: Vec<i64> = positions
+        .iter()
+        .filter_map(|p| Some((p.exit_time? - p.entry_time).num_seconds()))
+        .collect();
+    secs.sort_unstable();
+    secs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::position::test_support::{closed_position, open_position};
+    use crate::simulator::position::PositionManager;
+    use crate::types::{ExitReason, SetupType};
+
+    fn status_row(logger: &TradeLogger, id: &str) -> Option<(String, Option<f64>, Option<String>)> {
+        let db = logger.db.lock().unwrap();
+        db.query_row(
+            "SELECT status, exit_price, exit_reason FROM positions WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn test_open_row_updated_in_place_on_close() {
+        let dir = std::env::temp_dir();
+        let id = uuid::Uuid::new_v4();
+        let mut logger = TradeLogger::new(
+            dir.join(format!("rusto-log-{}.csv", id))
+                .display()
+                .to_string(),
+            dir.join(format!("rusto-log-{}.json", id))
+                .display()
+                .to_string(),
+            ":memory:".to_string(),
+        );
+        let mut positions = PositionManager::new();
+        let position = open_position(&mut positions, "btcusdt", 100);
+
+        logger.log_open(&position);
+        assert_eq!(
+            status_row(&logger, &position.id),
+            Some(("Open".to_string(), None, None))
+        );
+        // Logging the same open position again does not add a row
+        logger.log_open(&position);
+
+        let closed = positions
+            .close_position(
+                &position.id,
+                Decimal::from(102),
+                Decimal::ZERO,
+                ExitReason::TakeProfit,
+            )
+            .unwrap();
+        logger.log_trade(&closed);
+        let (status, exit_price, exit_reason) = status_row(&logger, &position.id).unwrap();
+        assert_eq!(status, "Closed");
+        assert_eq!(exit_price, Some(102.0));
+        assert_eq!(exit_reason, Some(ExitReason::TakeProfit.to_string()));
+
+        // A later run reusing the id (deterministic ids) must not overwrite the closed trade
+        let mut rerun = closed.clone();
+        rerun.entry_time += chrono::Duration::minutes(5);
+        rerun.exit_price = Some(Decimal::from(99));
+        logger.log_open(&rerun);
+        logger.log_trade(&rerun);
+        assert_eq!(status_row(&logger, &position.id).unwrap().1, Some(102.0));
+
+        let rows: i64 = logger
+            .db
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM positions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+        let _ = std::fs::remove_file(dir.join(format!("rusto-log-{}.csv", id)));
+        let _ = std::fs::remove_file(dir.join(format!("rusto-log-{}.json", id)));
+    }
+
+    #[test]
+    fn test_background_writer_does_not_block_on_busy_database() {
+        let dir = std::env::temp_dir();
+        let id = uuid::Uuid::new_v4();
+        let csv_path = dir.join(format!("rusto-batch-{}.csv", id));
+        let json_path = dir.join(format!("rusto-batch-{}.json", id));
+        let mut logger = TradeLogger::new(
+            csv_path.display().to_string(),
+            json_path.display().to_string(),
+            ":memory:".to_string(),
+        )
+        .with_background_writer(16);
+        let mut positions = PositionManager::new();
+
+        // Hold the database lock for the whole burst: inline logging would deadlock here
+        let db = Arc::clone(&logger.db);
+        let guard = db.lock().unwrap();
+        for _ in 0..100 {
+            let position = open_position(&mut positions, "btcusdt", 100);
+            logger.log_open(&position);
+            let closed = positions
+                .close_position(&position.id, Decimal::from(102), Decimal::ZERO, ExitReason::TakeProfit)
+                .unwrap();
+            logger.log_trade(&closed);
+        }
+        drop(guard);
+        logger.flush();
+
+        let rows: i64 = logger
+            .db
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM positions WHERE status = 'Closed' AND exit_price = 102",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(rows, 100);
+        // Each open row was written before its close upsert, so no close left a stale Open row
+        let open_rows: i64 = logger
+            .db
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM positions WHERE status = 'Open'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(open_rows, 0);
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert_eq!(csv.lines().count(), 101);
+        let json = std::fs::read_to_string(&json_path).unwrap();
+        assert_eq!(json.lines().count(), 100);
+        let _ = std::fs::remove_file(csv_path);
+        let _ = std::fs::remove_file(json_path);
+    }
+
+    #[test]
+    fn test_failing_row_is_skipped_and_the_batch_commits() {
+        let logger = TradeLogger::new(String::new(), String::new(), ":memory:".to_string());
+        let mut positions = PositionManager::new();
+        let position = open_position(&mut positions, "btcusdt", 100);
+        let mut with_features = position.clone();
+        with_features.entry_features = Some(crate::types::EntryFeatures {
+            imbalance_ratio: None,
+            cvd_1min_change: Decimal::ZERO,
+            volume_burst_ratio: Decimal::ONE,
+            bar_range_pct: Decimal::ZERO,
+            zone_distance_pct: Decimal::ZERO,
+            near_val: false,
+            near_vah: false,
+            near_hvn: false,
+        });
+        let closed = positions
+            .close_position(&position.id, Decimal::from(102), Decimal::ZERO, ExitReason::TakeProfit)
+            .unwrap();
+
+        // The entry_features insert fails; the open and close around it still land
+        logger.db.lock().unwrap().execute("DROP TABLE entry_features", []).unwrap();
+        let batch = vec![
+            Row::Open(Box::new(position.clone())),
+            Row::Entry(Box::new(with_features)),
+            Row::Closed(Box::new(closed)),
+        ];
+        assert_eq!(write_batch(&logger.db, &batch), Ok(()));
+        let (status, exit_price, _) = status_row(&logger, &position.id).unwrap();
+        assert_eq!(status, "Closed");
+        assert_eq!(exit_price, Some(102.0));
+    }
+
+    #[test]
+    fn test_concurrent_reader_during_writes_is_never_busy() {
+        let path = std::env::temp_dir().join(format!("rusto-wal-{}.db", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+        let logger = TradeLogger::new(String::new(), String::new(), path.clone());
+        let mode: String = logger
+            .db
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        // Dashboard-style reader with no busy timeout of its own
+        let reader_path = path.clone();
+        let reader = std::thread::spawn(move || {
+            let conn = Connection::open(&reader_path).unwrap();
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+            let mut last = 0;
+            while last < 100 && std::time::Instant::now() < deadline {
+                last = conn
+                    .query_row("SELECT COUNT(*) FROM positions", [], |row| row.get::<_, i64>(0))
+                    .unwrap();
+            }
+            last
+        });
+
+        let mut positions = PositionManager::new();
+        for _ in 0..100 {
+            let position = open_position(&mut positions, "btcusdt", 100);
+            logger.log_open(&position);
+        }
+        // A lost write fails here instead of hanging the reader
+        assert_eq!(reader.join().unwrap(), 100);
+        drop(logger);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+        }
+    }
+
+    #[test]
+    fn test_summary_json_matches_metrics() {
+        let logger = TradeLogger::new(String::new(), String::new(), ":memory:".to_string());
+        let mut closed = Vec::new();
+        for (symbol, setup, exit) in [
+            ("btcusdt", SetupType::AAA, 102),
+            ("btcusdt", SetupType::MomentumSqueeze, 99),
+            ("ethusdt", SetupType::AAA, 101),
+        ] {
+            let mut position = closed_position(symbol, 100, exit);
+            position.setup = setup;
+            closed.push(position);
+        }
+
+        let summary = logger.summary(&closed, Decimal::from(10_000)).unwrap();
+        let line = serde_json::to_string(&summary).unwrap();
+        assert!(!line.contains('\n'));
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let m = &summary.metrics;
+        assert_eq!(parsed["total_trades"], 3);
+        assert_eq!(parsed["winners"], 2);
+        assert_eq!(parsed["total_pnl"], m.total_pnl.to_string());
+        assert_eq!(parsed["win_rate_pct"], m.win_rate_pct.to_string());
+        assert_eq!(parsed["max_drawdown_pct"], m.max_drawdown_pct.to_string());
+        assert_eq!(parsed["by_setup"]["AAA"]["trades"], 2);
+        assert_eq!(parsed["by_setup"]["MomentumSqueeze"]["winners"], 0);
+        assert_eq!(parsed["by_symbol"]["btcusdt"]["trades"], 2);
+        assert_eq!(parsed["by_symbol"]["ethusdt"]["pnl"], closed[2].pnl.to_string());
+    }
+
+    #[test]
+    fn test_profit_factor_without_losses_is_unbounded_not_999() {
+        let logger = TradeLogger::new(String::new(), String::new(), ":memory:".to_string());
+        let closed = vec![closed_position("btcusdt", 100, 102)];
+
+        let m = logger.calculate_metrics(&closed, Decimal::from(10_000)).unwrap();
+        assert_eq!(m.profit_factor, None);
+        assert_eq!(m.profit_factor_display(), "inf");
+        let json: serde_json::Value = serde_json::to_value(&m).unwrap();
+        assert!(json["profit_factor"].is_null());
+
+        logger.log_metrics_sqlite(&m);
+        let stored: Option<f64> = logger
+            .db
+            .lock()
+            .unwrap()
+            .query_row("SELECT profit_factor FROM performance_metrics", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, None);
+    }
+
+    #[test]
+    fn test_hold_time_median_p90_and_histogram() {
+        let logger = TradeLogger::new(String::new(), String::new(), ":memory:".to_string())
+            .with_hold_time_buckets(vec![60, 10, 300]);
+        let template = closed_position("btcusdt", 100, 102);
+        let closed: Vec<Position> = [5, 8, 12, 20, 45, 70, 90, 240, 600, 1800]
+            .iter()
+            .map(|&secs| {
+                let mut p = template.clone();
+                p.exit_time = Some(p.entry_time + chrono::Duration::seconds(secs));
+                p
+            })
+            .collect();
+
+        let m = logger.calculate_metrics(&closed, Decimal::from(10_000)).unwrap();
+        // (45 + 70) / 2
+        assert_eq!(m.median_hold_secs, Decimal::new(575, 1));
+        // Nearest rank: 9th of 10
+        assert_eq!(m.p90_hold_secs, Decimal::from(600));
+
+        let counts: Vec<(Option<u64>, usize)> = logger
+            .hold_time_histogram(&closed)
+            .iter()
+            .map(|b| (b.max_secs, b.trades))
+            .collect();
+        assert_eq!(counts, vec![(Some(10), 2), (Some(60), 3), (Some(300), 3), (None, 2)]);
     }
 }
@@ -0,0 +1,93 @@
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+use tracing::debug;
+
+/// Fire-and-forget statsd emitter (push alternative to the Prometheus endpoint)
+pub struct StatsdEmitter {
+    socket: UdpSocket,
+    prefix: String,
+    /// Append DogStatsD-style `|#key:value` tags (Datadog, Telegraf)
+    tags: bool,
+}
+
+impl StatsdEmitter {
+    /// Bind an ephemeral local port and connect it to `host` (`host:port`)
+    pub fn connect(host: &str, prefix: &str, tags: bool) -> io::Result<Self> {
+        let target = host
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "statsd host did not resolve"))?;
+        let local = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(target)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            prefix: prefix.trim_end_matches('.').to_string(),
+            tags,
+        })
+    }
+
+    pub fn count(&self, name: &str, value: i64, tags: &[(&str, &str)]) {
+        self.send(&self.format(name, &value.to_string(), "c", tags));
+    }
+
+    pub fn gauge(&self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.send(&self.format(name, &value.to_string(), "g", tags));
+    }
+
+    pub fn timing(&self, name: &str, ms: f64, tags: &[(&str, &str)]) {
+        self.send(&self.format(name, &ms.to_string(), "ms", tags));
+    }
+
+    /// `prefix.name:value|type[|#key:value,...]`
+    fn format(&self, name: &str, value: &str, kind: &str, tags: &[(&str, &str)]) -> String {
+        let mut line = if self.prefix.is_empty() {
+            format!("{}:{}|{}", name, value, kind)
+        } else {
+            format!("{}.{}:{}|{}", self.prefix, name, value, kind)
+        };
+        if self.tags && !tags.is_empty() {
+            let joined: Vec<String> = tags.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+            line.push_str("|#");
+            line.push_str(&joined.join(","));
+        }
+        line
+    }
+
+    /// UDP is lossy by design: a dropped packet must never stall the trading loop
+    fn send(&self, line: &str) {
+        if let Err(e) = self.socket.send(line.as_bytes()) {
+            debug!(error = %e, "statsd send failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn recv(listener: &UdpSocket) -> String {
+        let mut buf = [0u8; 512];
+        let n = listener.recv(&mut buf).unwrap();
+        String::from_utf8_lossy(&buf[..n]).to_string()
+    }
+
+    #[test]
+    fn test_packets_use_statsd_line_format() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let host = listener.local_addr().unwrap().to_string();
+
+        let tagged = StatsdEmitter::connect(&host, "rusto.", true).unwrap();
+        tagged.count("signals", 1, &[("symbol", "btcusdt"), ("side", "Long")]);
+        assert_eq!(recv(&listener), "rusto.signals:1|c|#symbol:btcusdt,side:Long");
+        tagged.timing("latency", 12.5, &[]);
+        assert_eq!(recv(&listener), "rusto.latency:12.5|ms");
+
+        let plain = StatsdEmitter::connect(&host, "rusto", false).unwrap();
+        plain.gauge("open_positions", 3.0, &[("symbol", "ethusdt")]);
+        assert_eq!(recv(&listener), "rusto.open_positions:3|g");
+    }
+}
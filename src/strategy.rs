@@ -1,12 +1,17 @@
 use crate::config::{RiskConfig, StrategyConfig};
+use crate::simulator::trade_log::open_database;
 use crate::types::{
-    EntryFeatures, OrderFlowMetrics, RangeBar, SetupType, Side, TradeSignal, VolumeProfileSnapshot,
+    EntryFeatures, MarketRegime, OrderFlowMetrics, RangeBar, SetupType, Side, TradeSignal,
+    VolumeProfileSnapshot,
 };
+use chrono::Utc;
 use rusqlite::{params, Connection};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use std::collections::BTreeMap;
-use tracing::{info, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::Write;
+use tracing::{debug, info, warn};
 
 #[derive(Clone)]
 struct AdvancedSample {
@@ -15,14 +20,6 @@ struct AdvancedSample {
     profile: VolumeProfileSnapshot,
 }
 
-#[derive(Clone, Copy, Debug)]
-enum MarketRegime {
-    TrendHighVol,
-    TrendLowVol,
-    ChopHighVol,
-    ChopLowVol,
-}
-
 #[derive(Clone, Copy)]
 struct AdvancedDynamicParams {
     cooldown_bars: usize,
@@ -37,6 +34,11 @@ pub struct StrategyEngine {
     config: StrategyConfig,
     risk_config: RiskConfig,
     tuning_db_path: Option<String>,
+    /// Tuning logs' own long-lived connection to `tuning_db_path` (None = open one per write)
+    tuning_db: Option<Connection>,
+    /// How long tuning writes wait on a database locked by the trade writer
+    tuning_busy_timeout: std::time::Duration,
+    tuning_csv_path: Option<String>,
     /// Latest volume profile per symbol
     profiles: BTreeMap<String, VolumeProfileSnapshot>,
     /// Recent bars per symbol
@@ -51,6 +53,11 @@ pub struct StrategyEngine {
     last_burst_tune_bar: BTreeMap<String, u64>,
     /// Last bar index where AdvancedOrderFlow signal was emitted (per symbol)
     last_advanced_signal_bar: BTreeMap<String, u64>,
+    /// Bars left to skip after the symbol's last session reset
+    session_warmup_remaining: BTreeMap<String, usize>,
+    /// Use "{symbol}-{bar_index}-{seq}" signal ids instead of random UUIDs
+    deterministic_ids: bool,
+    signal_seq: u64,
 }
 
 impl StrategyEngine {
@@ -62,11 +69,20 @@ impl StrategyEngine {
         if let Some(path) = tuning_db_path.as_deref() {
             Self::ensure_tuning_log_table(path);
         }
+        if !config.advanced_auto_tune_volume_burst {
+            info!(
+                ratio = config.advanced_min_volume_burst_ratio,
+                "Volume burst auto-tuning off; using the configured ratio for all symbols"
+            );
+        }
 
         Self {
             config,
             risk_config,
             tuning_db_path,
+            tuning_db: None,
+            tuning_busy_timeout: std::time::Duration::from_millis(crate::config::default_db_busy_timeout_ms()),
+            tuning_csv_path: None,
             profiles: BTreeMap::new(),
             recent_bars: BTreeMap::new(),
             latest_flow: BTreeMap::new(),
@@ -74,10 +90,54 @@ impl StrategyEngine {
             tuned_volume_burst_ratio: BTreeMap::new(),
             last_burst_tune_bar: BTreeMap::new(),
             last_advanced_signal_bar: BTreeMap::new(),
+            session_warmup_remaining: BTreeMap::new(),
+            deterministic_ids: false,
+            signal_seq: 0,
         }
     }
 
+    /// Sequential signal ids for reproducible replays
+    pub fn set_deterministic_ids(&mut self, enabled: bool) {
+        self.deterministic_ids = enabled;
+    }
+
+    /// Wait up to `busy_timeout_ms` on a locked database, and with `keep_open` write tuning
+    /// logs through one WAL connection of their own instead of opening one per insert.
+    /// Never shares the trade logger's connection, so tuning never waits on its writer's lock.
+    pub fn configure_tuning_db(&mut self, busy_timeout_ms: u64, keep_open: bool) {
+        self.tuning_busy_timeout = std::time::Duration::from_millis(busy_timeout_ms);
+        self.tuning_db = None;
+        if !keep_open {
+            return;
+        }
+        let Some(path) = self.tuning_db_path.as_deref() else {
+            return;
+        };
+        match open_database(path, self.tuning_busy_timeout) {
+            Ok(conn) => {
+                if let Err(e) = Self::create_tuning_log_table(&conn) {
+                    warn!(error = %e, "Failed to create tuning log table");
+                }
+                self.tuning_db = Some(conn);
+            }
+            Err(e) => warn!(db_path = %path, error = %e, "Failed to open SQLite for tuning logs"),
+        }
+    }
+
+    /// Append tuning results to a CSV alongside the SQLite log
+    pub fn set_tuning_csv_path(&mut self, path: Option<String>) {
+        self.tuning_csv_path = path;
+    }
+
+    /// A later `session_start` means the profiler started a new session
     pub fn update_profile(&mut self, profile: VolumeProfileSnapshot) {
+        if let Some(last) = self.profiles.get(&profile.symbol) {
+            let reset = profile.session_start > last.session_start;
+            if reset && self.config.session_warmup_bars > 0 {
+                self.session_warmup_remaining
+                    .insert(profile.symbol.clone(), self.config.session_warmup_bars);
+            }
+        }
         self.profiles.insert(profile.symbol.clone(), profile);
     }
 
@@ -95,23 +155,13 @@ impl StrategyEngine {
         if bars.len() > 100 {
             bars.drain(..bars.len() - 100);
         }
-        if let (Some(flow), Some(profile)) = (
-            self.latest_flow.get(&bar.symbol).cloned(),
-            self.profiles.get(&bar.symbol).cloned(),
-        ) {
-            let samples = self
-                .advanced_samples
-                .entry(bar.symbol.clone())
-                .or_insert_with(Vec::new);
-            samples.push(AdvancedSample {
-                bar: bar.clone(),
-                flow,
-                profile,
-            });
-            if samples.len() > 400 {
-                samples.drain(..samples.len() - 400);
-            }
-            self.maybe_tune_volume_burst_ratio(&bar.symbol, bar.bar_index);
+        // Samples only feed the tuner's rolling backtest
+        if self.config.advanced_auto_tune_volume_burst {
+            self.record_advanced_sample(bar);
+        }
+
+        if self.in_session_warmup(&bar.symbol) {
+            return Vec::new();
         }
 
         let mut signals = Vec::new();
@@ -143,12 +193,180 @@ impl StrategyEngine {
             }
         }
 
+        self.apply_confidence_floor(&mut signals);
+        self.apply_volume_burst_requirement(&mut signals);
+        self.apply_market_cvd_gate(&mut signals);
+        self.apply_volume_rate_gate(&mut signals);
+
+        if self.config.annotate_regime && !signals.is_empty() {
+            let regime = self.detect_regime(&bar.symbol);
+            for signal in &mut signals {
+                signal.regime = Some(regime);
+            }
+        }
+
+        if self.deterministic_ids {
+            for signal in &mut signals {
+                self.signal_seq += 1;
+                signal.id = format!("{}-{}-{}", bar.symbol, bar.bar_index, self.signal_seq);
+            }
+        }
+
         signals
     }
 
+    /// Counts down the post-reset bars; the profile must also hold
+    /// `session_warmup_min_volume` before its levels are traded
+    fn in_session_warmup(&mut self, symbol: &str) -> bool {
+        if let Some(remaining) = self.session_warmup_remaining.get_mut(symbol) {
+            if *remaining > 0 {
+                *remaining -= 1;
+                debug!(symbol = %symbol, remaining = *remaining, "Skipping signals: session warmup");
+                return true;
+            }
+        }
+        let min_volume =
+            Decimal::try_from(self.config.session_warmup_min_volume).unwrap_or(Decimal::ZERO);
+        self.profiles
+            .get(symbol)
+            .is_some_and(|profile| profile.total_volume < min_volume)
+    }
+
+    /// CVD confirmation for absorption setups listed in `cvd_confirmation_setups`:
+    /// a long needs the CVD rapid drop it is fading, a short the rapid rise
+    fn cvd_confirms(&self, setup: SetupType, flow: &OrderFlowMetrics, side: Side) -> bool {
+        if !setup.listed_in(&self.config.cvd_confirmation_setups) {
+            return true;
+        }
+        match side {
+            Side::Buy => flow.cvd_rapid_drop,
+            Side::Sell => flow.cvd_rapid_rise,
+        }
+    }
+
+    /// Drop signals from `volume_burst_required_setups` unless the symbol's
+    /// latest bar volume is bursting against its baseline
+    fn apply_volume_burst_requirement(&self, signals: &mut Vec<TradeSignal>) {
+        signals.retain(|signal| {
+            if !signal.setup.listed_in(&self.config.volume_burst_required_setups) {
+                return true;
+            }
+            let bursting = self
+                .latest_flow
+                .get(&signal.symbol)
+                .is_some_and(|flow| flow.volume_burst);
+            if !bursting {
+                debug!(
+                    symbol = %signal.symbol,
+                    setup = %signal.setup,
+                    "Signal dropped: no volume burst"
+                );
+            }
+            bursting
+        });
+    }
+
+    /// Market-wide breadth proxy: each symbol's 1-minute CVD change in units of its
+    /// average bar volume (so large-volume symbols don't dominate), summed
+    fn aggregate_cvd_change(&self) -> Decimal {
+        self.latest_flow
+            .values()
+            .filter(|flow| flow.avg_bar_volume > Decimal::ZERO)
+            .map(|flow| flow.cvd_1min_change / flow.avg_bar_volume)
+            .sum()
+    }
+
+    /// With `market_cvd_gate`, drop longs unless aggregate CVD is rising and shorts unless falling
+    fn apply_market_cvd_gate(&self, signals: &mut Vec<TradeSignal>) {
+        if !self.config.market_cvd_gate || signals.is_empty() {
+            return;
+        }
+        let aggregate = self.aggregate_cvd_change();
+        let threshold =
+            Decimal::try_from(self.config.market_cvd_gate_threshold).unwrap_or(Decimal::ZERO);
+        signals.retain(|signal| {
+            let keep = match signal.side {
+                Side::Buy => aggregate > threshold,
+                Side::Sell => aggregate < -threshold,
+            };
+            if !keep {
+                debug!(
+                    symbol = %signal.symbol,
+                    setup = %signal.setup,
+                    side = ?signal.side,
+                    aggregate_cvd = %aggregate.round_dp(2),
+                    "Signal dropped: against market-wide CVD"
+                );
+            }
+            keep
+        });
+    }
+
+    /// With `min_volume_rate_ratio`, drop entries while the symbol trades slower than its baseline
+    fn apply_volume_rate_gate(&self, signals: &mut Vec<TradeSignal>) {
+        let Ok(min_ratio) = Decimal::try_from(self.config.min_volume_rate_ratio) else {
+            return;
+        };
+        if min_ratio <= Decimal::ZERO {
+            return;
+        }
+        signals.retain(|signal| {
+            // No baseline yet: nothing to compare against
+            let Some(ratio) = self
+                .latest_flow
+                .get(&signal.symbol)
+                .and_then(|flow| flow.volume_rate_ratio)
+            else {
+                return true;
+            };
+            let keep = ratio >= min_ratio;
+            if !keep {
+                debug!(
+                    symbol = %signal.symbol,
+                    setup = %signal.setup,
+                    volume_rate_ratio = %ratio.round_dp(2),
+                    min_ratio = %min_ratio,
+                    "Signal dropped: volume rate below baseline"
+                );
+            }
+            keep
+        });
+    }
+
+    /// Drop signals whose confidence is below their setup's floor
+    fn apply_confidence_floor(&self, signals: &mut Vec<TradeSignal>) {
+        signals.retain(|signal| {
+            let floor = self.confidence_floor(signal.setup);
+            let keep = signal.confidence >= floor;
+            if !keep {
+                debug!(
+                    symbol = %signal.symbol,
+                    setup = %signal.setup,
+                    confidence = %signal.confidence,
+                    floor = %floor,
+                    "Signal dropped: below confidence floor"
+                );
+            }
+            keep
+        });
+    }
+
+    /// Minimum confidence for a setup: per-setup override, else the global floor
+    fn confidence_floor(&self, setup: SetupType) -> Decimal {
+        let floor = self
+            .config
+            .min_confidence_by_setup
+            .iter()
+            .find(|(name, _)| SetupType::from_name(name) == Some(setup))
+            .map(|(_, floor)| *floor)
+            .unwrap_or(self.config.min_confidence);
+        Decimal::try_from(floor).unwrap_or(Decimal::ZERO)
+    }
+
     /// AAA (Absorption At Area):
     /// Price near VAL + sell absorption → Long (target: VAH)
     /// Price near VAH + buy absorption → Short (target: VAL)
+    /// Target can be switched to POC or an R-multiple via `aaa_target_mode`.
     fn check_aaa(&self, bar: &RangeBar) -> Option<TradeSignal> {
         let profile = self.profiles.get(&bar.symbol)?;
         let flow = self.latest_flow.get(&bar.symbol)?;
@@ -163,10 +381,11 @@ impl StrategyEngine {
         // Near VAL + sell absorption → Long
         if flow.absorption_side == Some(Side::Sell)
             && (bar.close - profile.val).abs() <= distance_threshold
+            && self.cvd_confirms(SetupType::AAA, flow, Side::Buy)
         {
             let entry = bar.close;
             let stop = entry - stop_distance;
-            let target = profile.vah; // Target the VAH
+            let target = self.aaa_target(Side::Buy, entry, stop, profile);
             let confidence = Decimal::try_from(0.7).unwrap_or(Decimal::ONE);
 
             info!(
@@ -193,10 +412,11 @@ impl StrategyEngine {
         // Near VAH + buy absorption → Short
         if flow.absorption_side == Some(Side::Buy)
             && (bar.close - profile.vah).abs() <= distance_threshold
+            && self.cvd_confirms(SetupType::AAA, flow, Side::Sell)
         {
             let entry = bar.close;
             let stop = entry + stop_distance;
-            let target = profile.val; // Target the VAL
+            let target = self.aaa_target(Side::Sell, entry, stop, profile);
 
             let confidence = Decimal::try_from(0.7).unwrap_or(Decimal::ONE);
 
@@ -224,6 +444,44 @@ impl StrategyEngine {
         None
     }
 
+    /// AdvancedOrderFlow stop: a flat 0.4% from entry, or (mode "BarExtreme")
+    /// `advanced_stop_buffer_pct` beyond the signal bar's low/high, where the setup is invalidated
+    fn advanced_stop(&self, side: Side, entry: Decimal, bar: &RangeBar) -> Decimal {
+        match self.config.advanced_stop_mode.to_lowercase().as_str() {
+            "barextreme" => {
+                let buffer = entry
+                    * Decimal::try_from(self.config.advanced_stop_buffer_pct).unwrap_or(Decimal::ZERO)
+                    / Decimal::from(100);
+                match side {
+                    Side::Buy => bar.low - buffer,
+                    Side::Sell => bar.high + buffer,
+                }
+            }
+            _ => match side {
+                Side::Buy => entry * Decimal::new(996, 3),  // -0.4%
+                Side::Sell => entry * Decimal::new(1004, 3), // +0.4%
+            },
+        }
+    }
+
+    /// Resolve the AAA target price for the configured target mode
+    fn aaa_target(
+        &self,
+        side: Side,
+        entry: Decimal,
+        stop: Decimal,
+        profile: &VolumeProfileSnapshot,
+    ) -> Decimal {
+        match self.config.aaa_target_mode.to_lowercase().as_str() {
+            "poc" => profile.poc,
+            "rmultiple" => r_multiple_target(side, entry, stop, self.config.aaa_target_r_multiple),
+            _ => match side {
+                Side::Buy => profile.vah,
+                Side::Sell => profile.val,
+            },
+        }
+    }
+
     /// Momentum Squeeze: breakout of session high/low + delta confirmation
     fn check_momentum_squeeze(&self, bar: &RangeBar) -> Option<TradeSignal> {
         let profile = self.profiles.get(&bar.symbol)?;
@@ -305,7 +563,17 @@ impl StrategyEngine {
         let target_mult =
             Decimal::try_from(self.risk_config.default_target_multiplier).unwrap_or(Decimal::TWO);
 
-        match flow.absorption_side? {
+        let absorbed = flow.absorption_side?;
+        // Sell absorbed → Long, buy absorbed → Short
+        let side = match absorbed {
+            Side::Sell => Side::Buy,
+            Side::Buy => Side::Sell,
+        };
+        if !self.cvd_confirms(SetupType::AbsorptionReversal, flow, side) {
+            return None;
+        }
+
+        match absorbed {
             Side::Sell => {
                 // Sell absorbed → price should go up → Long
                 let entry = bar.close;
@@ -415,7 +683,7 @@ impl StrategyEngine {
                     near_hvn,
                 };
                 let entry = bar.close;
-                let stop = entry * Decimal::new(996, 3); // -0.4%
+                let stop = self.advanced_stop(Side::Buy, entry, bar);
                 let tp2 = profile.vah;
 
                 info!(
@@ -467,7 +735,7 @@ impl StrategyEngine {
                     near_hvn,
                 };
                 let entry = bar.close;
-                let stop = entry * Decimal::new(1004, 3); // +0.4%
+                let stop = self.advanced_stop(Side::Sell, entry, bar);
                 let tp2 = profile.val;
 
                 info!(
@@ -523,14 +791,40 @@ impl StrategyEngine {
         (min_dist / price) * Decimal::from(100)
     }
 
+    fn record_advanced_sample(&mut self, bar: &RangeBar) {
+        let (Some(flow), Some(profile)) = (
+            self.latest_flow.get(&bar.symbol).cloned(),
+            self.profiles.get(&bar.symbol).cloned(),
+        ) else {
+            return;
+        };
+        let samples = self
+            .advanced_samples
+            .entry(bar.symbol.clone())
+            .or_insert_with(Vec::new);
+        samples.push(AdvancedSample {
+            bar: bar.clone(),
+            flow,
+            profile,
+        });
+        if samples.len() > 400 {
+            samples.drain(..samples.len() - 400);
+        }
+        self.maybe_tune_volume_burst_ratio(&bar.symbol, bar.bar_index);
+    }
+
     fn min_volume_burst_ratio_for(&self, symbol: &str) -> Decimal {
+        let configured = || {
+            Decimal::try_from(self.config.advanced_min_volume_burst_ratio)
+                .unwrap_or(Decimal::new(18, 1))
+        };
+        if !self.config.advanced_auto_tune_volume_burst {
+            return configured();
+        }
         self.tuned_volume_burst_ratio
             .get(symbol)
             .copied()
-            .unwrap_or_else(|| {
-                Decimal::try_from(self.config.advanced_min_volume_burst_ratio)
-                    .unwrap_or(Decimal::new(18, 1))
-            })
+            .unwrap_or_else(configured)
     }
 
     fn dynamic_advanced_params(&self, symbol: &str) -> (AdvancedDynamicParams, MarketRegime) {
@@ -653,19 +947,25 @@ impl StrategyEngine {
             .hvn
             .map_or(false, |hvn| (bar.close - hvn).abs() <= zone_threshold);
         let reversal_ok_long = !self.config.advanced_require_reversal_bar || bar.close > bar.open;
-        let sell_to_buy_ratio = if flow.imbalance_ratio > Decimal::ZERO {
-            Decimal::ONE / flow.imbalance_ratio
-        } else {
-            Decimal::from(999)
+        // No buys at all is unbounded sell pressure; no sells (None) is none
+        let sells_dominate = match flow.imbalance_ratio {
+            Some(ratio) if ratio > Decimal::ZERO => Decimal::ONE / ratio >= min_imbalance,
+            Some(_) => true,
+            None => false,
         };
+        // TP1 sits at VWAP, so it must be far enough away to be worth taking
+        let min_vwap_distance = bar.close
+            * Decimal::try_from(self.config.advanced_min_vwap_distance_pct).unwrap_or(Decimal::ZERO)
+            / Decimal::from(100);
+        let vwap_room = |room: Decimal| room > Decimal::ZERO && room >= min_vwap_distance;
 
         if (near_val || near_hvn)
             && flow.cvd_rapid_drop
             && flow.absorption_detected
             && flow.absorption_side == Some(Side::Sell)
-            && sell_to_buy_ratio >= min_imbalance
+            && sells_dominate
             && reversal_ok_long
-            && profile.vwap > bar.close
+            && vwap_room(profile.vwap - bar.close)
             && profile.vah > profile.vwap
         {
             return Some(Side::Buy);
@@ -678,9 +978,9 @@ impl StrategyEngine {
             && flow.cvd_rapid_rise
             && flow.absorption_detected
             && flow.absorption_side == Some(Side::Buy)
-            && flow.imbalance_ratio >= min_imbalance
+            && flow.imbalance_ratio.is_none_or(|ratio| ratio >= min_imbalance)
             && reversal_ok_short
-            && profile.vwap < bar.close
+            && vwap_room(bar.close - profile.vwap)
             && profile.val < profile.vwap
         {
             return Some(Side::Sell);
@@ -715,6 +1015,14 @@ impl StrategyEngine {
                 expectancy_pct,
                 changed,
             );
+            self.log_tuning_result_csv(
+                symbol,
+                best_ratio,
+                trades,
+                win_rate,
+                expectancy_pct,
+                changed,
+            );
             if changed {
                 info!(
                     symbol = %symbol,
@@ -731,8 +1039,65 @@ impl StrategyEngine {
             .insert(symbol.to_string(), current_bar_index);
     }
 
+    /// Seed tuned ratios from each symbol's latest tuning log row, so symbols that stay
+    /// selected across a restart keep their tuning. Rows outside the symbol's current
+    /// floor/ceiling are ignored. Returns how many symbols were restored.
+    pub fn restore_tuned_ratios(&mut self, symbols: &[String]) -> usize {
+        if !self.config.advanced_auto_tune_volume_burst {
+            return 0;
+        }
+        let Some(latest) = self.with_tuning_conn(|conn| {
+            symbols
+                .iter()
+                .map(|symbol| {
+                    conn.query_row(
+                        "SELECT tuned_ratio FROM volume_burst_tuning_logs
+                         WHERE symbol = ?1 ORDER BY id DESC LIMIT 1",
+                        params![symbol],
+                        |row| row.get::<_, f64>(0),
+                    )
+                    .ok()
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return 0;
+        };
+
+        let mut restored = 0;
+        for (symbol, latest) in symbols.iter().zip(latest) {
+            let Some(ratio) = latest.and_then(|r| Decimal::try_from(r).ok()) else {
+                continue;
+            };
+            if !self.volume_burst_candidates(symbol).contains(&ratio) {
+                warn!(symbol = %symbol, ratio = %ratio, "Stored tuned ratio outside current tuning range, not restored");
+                continue;
+            }
+            info!(symbol = %symbol, tuned_ratio = %ratio, "Restored tuned volume burst ratio");
+            self.tuned_volume_burst_ratio.insert(symbol.clone(), ratio);
+            restored += 1;
+        }
+        restored
+    }
+
+    /// Run `f` on the long-lived tuning connection if open, otherwise on a fresh one for
+    /// `tuning_db_path`
+    fn with_tuning_conn<T>(&self, f: impl FnOnce(&Connection) -> T) -> Option<T> {
+        if let Some(conn) = &self.tuning_db {
+            return Some(f(conn));
+        }
+        let path = self.tuning_db_path.as_deref()?;
+        match open_database(path, self.tuning_busy_timeout) {
+            Ok(conn) => Some(f(&conn)),
+            Err(e) => {
+                warn!(db_path = %path, error = %e, "Failed to open SQLite for tuning logs");
+                None
+            }
+        }
+    }
+
     fn ensure_tuning_log_table(path: &str) {
-        let conn = match Connection::open(path) {
+        let busy_timeout = std::time::Duration::from_millis(crate::config::default_db_busy_timeout_ms());
+        let conn = match open_database(path, busy_timeout) {
             Ok(c) => c,
             Err(e) => {
                 warn!(db_path = %path, error = %e, "Failed to open SQLite for tuning logs");
@@ -740,7 +1105,13 @@ impl StrategyEngine {
             }
         };
 
-        if let Err(e) = conn.execute(
+        if let Err(e) = Self::create_tuning_log_table(&conn) {
+            warn!(db_path = %path, error = %e, "Failed to create tuning log table");
+        }
+    }
+
+    fn create_tuning_log_table(conn: &Connection) -> rusqlite::Result<usize> {
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS volume_burst_tuning_logs (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 symbol TEXT NOT NULL,
@@ -756,9 +1127,7 @@ impl StrategyEngine {
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP
             )",
             [],
-        ) {
-            warn!(db_path = %path, error = %e, "Failed to create tuning log table");
-        }
+        )
     }
 
     fn log_tuning_result_sqlite(
@@ -770,37 +1139,71 @@ impl StrategyEngine {
         expectancy_pct: Decimal,
         changed: bool,
     ) {
-        let Some(path) = self.tuning_db_path.as_deref() else {
+        let result = self.with_tuning_conn(|conn| {
+            conn.execute(
+                "INSERT INTO volume_burst_tuning_logs (
+                    symbol, tuned_ratio, trades, win_rate_pct, expectancy_pct,
+                    lookback_bars, lookahead_bars, stop_pct, target_pct, changed
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    symbol,
+                    tuned_ratio.to_string(),
+                    trades as i64,
+                    win_rate_pct.to_string(),
+                    expectancy_pct.to_string(),
+                    self.config.advanced_tuning_lookback_bars as i64,
+                    self.config.advanced_tuning_lookahead_bars as i64,
+                    self.config.advanced_tuning_stop_pct.to_string(),
+                    self.config.advanced_tuning_target_pct.to_string(),
+                    if changed { 1 } else { 0 },
+                ],
+            )
+        });
+        if let Some(Err(e)) = result {
+            warn!(error = %e, "Failed to insert tuning log row");
+        }
+    }
+
+    fn log_tuning_result_csv(
+        &self,
+        symbol: &str,
+        tuned_ratio: Decimal,
+        trades: usize,
+        win_rate_pct: Decimal,
+        expectancy_pct: Decimal,
+        changed: bool,
+    ) {
+        let Some(path) = self.tuning_csv_path.as_deref() else {
             return;
         };
 
-        let conn = match Connection::open(path) {
-            Ok(c) => c,
+        let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(f) => f,
             Err(e) => {
-                warn!(db_path = %path, error = %e, "Failed to open SQLite for tuning insert");
+                warn!(csv_path = %path, error = %e, "Failed to open tuning CSV");
                 return;
             }
         };
 
-        if let Err(e) = conn.execute(
-            "INSERT INTO volume_burst_tuning_logs (
-                symbol, tuned_ratio, trades, win_rate_pct, expectancy_pct,
-                lookback_bars, lookahead_bars, stop_pct, target_pct, changed
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![
-                symbol,
-                tuned_ratio.to_string(),
-                trades as i64,
-                win_rate_pct.to_string(),
-                expectancy_pct.to_string(),
-                self.config.advanced_tuning_lookback_bars as i64,
-                self.config.advanced_tuning_lookahead_bars as i64,
-                self.config.advanced_tuning_stop_pct.to_string(),
-                self.config.advanced_tuning_target_pct.to_string(),
-                if changed { 1 } else { 0 },
-            ],
+        // Header only for a fresh file so restarts keep appending to one table
+        if file.metadata().map(|m| m.len() == 0).unwrap_or(false) {
+            let _ = writeln!(
+                file,
+                "timestamp,symbol,tuned_ratio,trades,win_rate_pct,expectancy_pct,changed"
+            );
+        }
+        if let Err(e) = writeln!(
+            file,
+            "{},{},{},{},{},{},{}",
+            Utc::now().to_rfc3339(),
+            symbol,
+            tuned_ratio,
+            trades,
+            win_rate_pct,
+            expectancy_pct,
+            changed,
         ) {
-            warn!(db_path = %path, error = %e, "Failed to insert tuning log row");
+            warn!(csv_path = %path, error = %e, "Failed to append tuning CSV row");
         }
     }
 
@@ -837,12 +1240,11 @@ impl StrategyEngine {
             return None;
         }
 
-        let candidates = [1.2, 1.4, 1.6, 1.8, 2.1, 2.4, 2.8, 3.2];
+        let candidates = self.volume_burst_candidates(symbol);
         let mut best: Option<(Decimal, usize, Decimal, Decimal)> = None;
         let min_trades = self.config.advanced_tuning_min_trades.max(3);
 
-        for candidate in candidates {
-            let candidate_dec = Decimal::try_from(candidate).ok()?;
+        for candidate_dec in candidates {
             let mut trades = 0usize;
             let mut wins = 0usize;
 
@@ -911,6 +1313,25 @@ impl StrategyEngine {
         best
     }
 
+    /// Configured candidate ratios, narrowed to the symbol's floor/ceiling
+    fn volume_burst_candidates(&self, symbol: &str) -> Vec<Decimal> {
+        let bound = |bounds: &HashMap<String, f64>| {
+            bounds
+                .iter()
+                .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+                .and_then(|(_, v)| Decimal::try_from(*v).ok())
+        };
+        let floor = bound(&self.config.advanced_tuning_ratio_floor_by_symbol);
+        let ceiling = bound(&self.config.advanced_tuning_ratio_ceiling_by_symbol);
+
+        self.config
+            .advanced_tuning_ratio_candidates
+            .iter()
+            .filter_map(|c| Decimal::try_from(*c).ok())
+            .filter(|c| floor.is_none_or(|f| *c >= f) && ceiling.is_none_or(|max| *c <= max))
+            .collect()
+    }
+
     fn evaluate_lookahead_outcome(
         &self,
         samples: &[AdvancedSample],
@@ -936,42 +1357,615 @@ impl StrategyEngine {
         };
 
         let end = (idx + lookahead).min(samples.len().saturating_sub(1));
-        for sample in samples.iter().take(end + 1).skip(idx + 1) {
-            let bar = &sample.bar;
-            match side {
-                Side::Buy => {
-                    let hit_stop = bar.low <= stop;
-                    let hit_target = bar.high >= target;
-                    if hit_stop && hit_target {
-                        return false;
-                    }
-                    if hit_stop {
-                        return false;
-                    }
-                    if hit_target {
-                        return true;
-                    }
-                }
-                Side::Sell => {
-                    let hit_stop = bar.high >= stop;
-                    let hit_target = bar.low <= target;
-                    if hit_stop && hit_target {
-                        return false;
-                    }
-                    if hit_stop {
-                        return false;
-                    }
-                    if hit_target {
-                        return true;
-                    }
-                }
+        let bars = samples
+            .iter()
+            .take(end + 1)
+            .skip(idx + 1)
+            .map(|sample| &sample.bar);
+        lookahead_outcome(bars, side, entry, stop, target)
+    }
+}
+
+/// Walk the bars following an entry and report whether the trade would have won:
+/// the first bar touching stop or target decides (both touched counts as a loss),
+/// otherwise the last close must be on the right side of the entry.
+pub(crate) fn lookahead_outcome<'a>(
+    bars: impl IntoIterator<Item = &'a RangeBar>,
+    side: Side,
+    entry: Decimal,
+    stop: Decimal,
+    target: Decimal,
+) -> bool {
+    let mut final_close = entry;
+    for bar in bars {
+        let (hit_stop, hit_target) = match side {
+            Side::Buy => (bar.low <= stop, bar.high >= target),
+            Side::Sell => (bar.high >= stop, bar.low <= target),
+        };
+        if hit_stop {
+            return false;
+        }
+        if hit_target {
+            return true;
+        }
+        final_close = bar.close;
+    }
+
+    match side {
+        Side::Buy => final_close > entry,
+        Side::Sell => final_close < entry,
+    }
+}
+
+/// Target `r` stop-distances from entry in the trade's direction
+fn r_multiple_target(side: Side, entry: Decimal, stop: Decimal, r: f64) -> Decimal {
+    let r = Decimal::try_from(r).unwrap_or(Decimal::TWO);
+    let risk = (entry - stop).abs();
+    match side {
+        Side::Buy => entry + risk * r,
+        Side::Sell => entry - risk * r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(target_mode: &str) -> StrategyEngine {
+        let config: StrategyConfig = toml::from_str(&format!(
+            r#"
+            enabled_setups = ["AAA"]
+            aaa_poc_distance_ticks = 5
+            momentum_lookback_bars = 20
+            min_delta_confirmation = 1.5
+            aaa_target_mode = "{}"
+            aaa_target_r_multiple = 3.0
+            "#,
+            target_mode
+        ))
+        .unwrap();
+        let risk_config: RiskConfig = toml::from_str(
+            r#"
+            initial_balance = 10000.0
+            max_risk_per_trade = 0.01
+            daily_loss_limit_pct = 0.03
+            max_concurrent_positions = 5
+            break_even_ticks = 3
+            default_stop_ticks = 10
+            default_target_multiplier = 2.0
+            "#,
+        )
+        .unwrap();
+        let mut engine = StrategyEngine::new(config, risk_config, None);
+        engine.update_profile(VolumeProfileSnapshot {
+            symbol: "btcusdt".to_string(),
+            poc: Decimal::from(105),
+            vah: Decimal::from(110),
+            val: Decimal::from(100),
+            total_volume: Decimal::from(1000),
+            session_high: Decimal::from(112),
+            session_low: Decimal::from(98),
+            session_start: Utc::now(),
+            vwap: Decimal::from(105),
+            hvn: None,
+            timestamp: Utc::now(),
+        });
+        engine.update_flow(OrderFlowMetrics {
+            symbol: "btcusdt".to_string(),
+            cvd: Decimal::ZERO,
+            bar_delta: Decimal::from(-20),
+            absorption_detected: true,
+            absorption_side: Some(Side::Sell),
+            imbalance_ratio: Some(Decimal::ONE),
+            cvd_1min_change: Decimal::ZERO,
+            cvd_rapid_drop: false,
+            cvd_rapid_rise: false,
+            avg_bar_volume: Decimal::from(50),
+            volume_burst_ratio: Decimal::ONE,
+            volume_burst: false,
+            volume_rate_ratio: None,
+            timestamp: Utc::now(),
+        });
+        engine
+    }
+
+    fn bar_at_val() -> RangeBar {
+        RangeBar {
+            symbol: "btcusdt".to_string(),
+            open: Decimal::from(102),
+            high: Decimal::from(102),
+            low: Decimal::from(100),
+            close: Decimal::from(101),
+            volume: Decimal::from(50),
+            buy_volume: Decimal::from(15),
+            sell_volume: Decimal::from(35),
+            open_time: Utc::now(),
+            close_time: Utc::now(),
+            footprint: BTreeMap::new(),
+            bar_index: 1,
+        }
+    }
+
+    #[test]
+    fn test_aaa_target_modes() {
+        let bar = bar_at_val();
+
+        let value_area = strategy("ValueArea").check_aaa(&bar).unwrap();
+        assert_eq!(value_area.side, Side::Buy);
+        assert_eq!(value_area.take_profit, Decimal::from(110));
+
+        let poc = strategy("POC").check_aaa(&bar).unwrap();
+        assert_eq!(poc.take_profit, Decimal::from(105));
+
+        // Entry 101, stop 91 (10 ticks) → 3R target at 131
+        let r_multiple = strategy("RMultiple").check_aaa(&bar).unwrap();
+        assert_eq!(r_multiple.stop_loss, Decimal::from(91));
+        assert_eq!(r_multiple.take_profit, Decimal::from(131));
+    }
+
+    #[test]
+    fn test_cvd_confirmation_suppresses_unconfirmed_absorption() {
+        let bar = bar_at_val();
+        let mut engine = strategy("ValueArea");
+        assert!(engine.check_aaa(&bar).is_some());
+        assert!(engine.check_absorption_reversal(&bar).is_some());
+
+        engine.config.cvd_confirmation_setups =
+            vec!["aaa".to_string(), "ABSORPTIONREVERSAL".to_string()];
+        assert!(engine.check_aaa(&bar).is_none());
+        assert!(engine.check_absorption_reversal(&bar).is_none());
+
+        // A CVD rapid rise does not confirm a long; a rapid drop does
+        let mut flow = engine.latest_flow["btcusdt"].clone();
+        flow.cvd_rapid_rise = true;
+        engine.update_flow(flow.clone());
+        assert!(engine.check_aaa(&bar).is_none());
+        flow.cvd_rapid_rise = false;
+        flow.cvd_rapid_drop = true;
+        engine.update_flow(flow);
+        assert_eq!(engine.check_aaa(&bar).unwrap().side, Side::Buy);
+        assert_eq!(engine.check_absorption_reversal(&bar).unwrap().side, Side::Buy);
+    }
+
+    #[test]
+    fn test_confidence_floor_drops_low_conviction_signals() {
+        let mut engine = strategy("ValueArea");
+        engine.config.min_confidence = 0.8;
+        let make = |setup, confidence: Decimal| {
+            TradeSignal::new(
+                "btcusdt".to_string(),
+                Side::Buy,
+                setup,
+                Decimal::from(100),
+                Decimal::from(99),
+                Decimal::from(102),
+                confidence,
+            )
+        };
+        let mut signals = vec![
+            make(SetupType::MomentumSqueeze, Decimal::new(6, 1)),
+            make(SetupType::AdvancedOrderFlow, Decimal::new(85, 2)),
+        ];
+        engine.apply_confidence_floor(&mut signals);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].setup, SetupType::AdvancedOrderFlow);
+
+        // A per-setup floor overrides the global one
+        engine
+            .config
+            .min_confidence_by_setup
+            .insert("momentumsqueeze".to_string(), 0.5);
+        let mut signals = vec![make(SetupType::MomentumSqueeze, Decimal::new(6, 1))];
+        engine.apply_confidence_floor(&mut signals);
+        assert_eq!(signals.len(), 1);
+    }
+
+    #[test]
+    fn test_advanced_stop_beyond_signal_bar_extreme() {
+        let mut engine = strategy("ValueArea");
+        // Low of 100 sits ~1% below the 101 close, well past the flat 0.4%
+        let bar = bar_at_val();
+        let entry = bar.close;
+        assert_eq!(engine.advanced_stop(Side::Buy, entry, &bar), Decimal::new(100596, 3));
+
+        engine.config.advanced_stop_mode = "BarExtreme".to_string();
+        engine.config.advanced_stop_buffer_pct = 0.05;
+        // 100 - 101 * 0.05%
+        assert_eq!(engine.advanced_stop(Side::Buy, entry, &bar), Decimal::new(999495, 4));
+        // 102 + 101 * 0.05%
+        assert_eq!(engine.advanced_stop(Side::Sell, entry, &bar), Decimal::new(1020505, 4));
+    }
+
+    #[test]
+    fn test_advanced_requires_minimum_vwap_distance() {
+        let mut engine = strategy("ValueArea");
+        let mut bar = bar_at_val();
+        bar.open = Decimal::from(100);
+        let mut flow = engine.latest_flow["btcusdt"].clone();
+        flow.cvd_rapid_drop = true;
+        flow.imbalance_ratio = Some(Decimal::new(4, 1));
+        let mut profile = engine.profiles["btcusdt"].clone();
+        profile.vwap = Decimal::new(10105, 2);
+        let side = |engine: &StrategyEngine, profile: &VolumeProfileSnapshot| {
+            engine.advanced_side_without_burst(&bar, &flow, profile, Decimal::from(5), Decimal::TWO)
+        };
+
+        // Default: any VWAP above the close leaves room for TP1
+        assert_eq!(side(&engine, &profile), Some(Side::Buy));
+
+        // 0.1% of the 101 close = 0.101; VWAP only 0.05 away is too close
+        engine.config.advanced_min_vwap_distance_pct = 0.1;
+        assert_eq!(side(&engine, &profile), None);
+        profile.vwap = Decimal::from(102);
+        assert_eq!(side(&engine, &profile), Some(Side::Buy));
+    }
+
+    #[test]
+    fn test_market_cvd_gate_blocks_longs_against_falling_tape() {
+        let mut engine = strategy("ValueArea");
+        engine.config.market_cvd_gate = true;
+        let mut flow = engine.latest_flow["btcusdt"].clone();
+        for (symbol, cvd_change) in [("btcusdt", -400), ("ethusdt", -250), ("solusdt", 30)] {
+            flow.symbol = symbol.to_string();
+            flow.cvd_1min_change = Decimal::from(cvd_change);
+            engine.update_flow(flow.clone());
+        }
+        // (-400 - 250 + 30) / 50 average bar volume
+        assert_eq!(engine.aggregate_cvd_change(), Decimal::new(-124, 1));
+
+        let make = |side| {
+            TradeSignal::new(
+                "btcusdt".to_string(),
+                side,
+                SetupType::MomentumSqueeze,
+                Decimal::from(100),
+                Decimal::from(99),
+                Decimal::from(102),
+                Decimal::ONE,
+            )
+        };
+        let mut signals = vec![make(Side::Buy), make(Side::Sell)];
+        engine.apply_market_cvd_gate(&mut signals);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].side, Side::Sell);
+
+        // A threshold beyond the move blocks both directions
+        engine.config.market_cvd_gate_threshold = 20.0;
+        let mut signals = vec![make(Side::Buy), make(Side::Sell)];
+        engine.apply_market_cvd_gate(&mut signals);
+        assert!(signals.is_empty());
+    }
+
+    #[test]
+    fn test_signal_and_position_record_entry_regime() {
+        use crate::simulator::position::PositionManager;
+        use crate::types::MarginType;
+
+        let mut engine = strategy("ValueArea");
+        engine.config.regime_window_bars = 10;
+        // Nine wide bars climbing from 90 ahead of the signal bar: trending and volatile
+        let trend: Vec<RangeBar> = (0..9)
+            .map(|i| RangeBar {
+                open: Decimal::from(90 + i),
+                high: Decimal::from(92 + i),
+                low: Decimal::from(90 + i),
+                close: Decimal::from(91 + i),
+                bar_index: i as u64,
+                ..bar_at_val()
+            })
+            .collect();
+        engine.recent_bars.insert("btcusdt".to_string(), trend.clone());
+        let unannotated = engine.process_bar(&bar_at_val());
+        assert_eq!(unannotated.len(), 1);
+        assert_eq!(unannotated[0].regime, None);
+
+        engine.config.annotate_regime = true;
+        engine.recent_bars.insert("btcusdt".to_string(), trend);
+        let signals = engine.process_bar(&bar_at_val());
+        assert_eq!(signals.len(), 1);
+        let classified = engine.detect_regime("btcusdt");
+        assert_eq!(classified, MarketRegime::TrendHighVol);
+        assert_eq!(signals[0].regime, Some(classified));
+
+        let position = PositionManager::new()
+            .open_position(
+                &signals[0],
+                Decimal::ONE,
+                Decimal::from(10),
+                MarginType::Isolated,
+                Decimal::new(4, 3),
+                Decimal::new(4, 4),
+            )
+            .unwrap();
+        assert_eq!(position.regime, Some(classified));
+    }
+
+    #[test]
+    fn test_volume_rate_gate_blocks_dead_market_entries() {
+        let mut engine = strategy("ValueArea");
+        engine.config.min_volume_rate_ratio = 0.8;
+        let bar = bar_at_val();
+        let mut flow = engine.latest_flow["btcusdt"].clone();
+
+        flow.volume_rate_ratio = Some(Decimal::new(4, 1));
+        engine.update_flow(flow.clone());
+        assert!(engine.process_bar(&bar).is_empty());
+
+        flow.volume_rate_ratio = Some(Decimal::new(15, 1));
+        engine.update_flow(flow.clone());
+        assert_eq!(engine.process_bar(&bar).len(), 1);
+
+        // A measured zero rate is a dead market, not a missing baseline
+        flow.volume_rate_ratio = Some(Decimal::ZERO);
+        engine.update_flow(flow.clone());
+        assert!(engine.process_bar(&bar).is_empty());
+
+        // No baseline yet: not gated
+        flow.volume_rate_ratio = None;
+        engine.update_flow(flow);
+        assert_eq!(engine.process_bar(&bar).len(), 1);
+    }
+
+    #[test]
+    fn test_tuned_ratios_survive_restart_for_reselected_symbols() {
+        let path = std::env::temp_dir().join(format!("rusto-tuning-{}.db", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+        StrategyEngine::ensure_tuning_log_table(&path);
+        let mut before = strategy("ValueArea");
+        before.tuning_db_path = Some(path.clone());
+        before.log_tuning_result_sqlite("btcusdt", Decimal::new(20, 1), 10, Decimal::from(60), Decimal::ONE, true);
+        before.log_tuning_result_sqlite("btcusdt", Decimal::new(24, 1), 12, Decimal::from(55), Decimal::ONE, true);
+        before.log_tuning_result_sqlite("ethusdt", Decimal::new(16, 1), 9, Decimal::from(50), Decimal::ONE, true);
+
+        // Restart: ethusdt dropped out of the selection, solusdt is new
+        let mut after = strategy("ValueArea");
+        after.tuning_db_path = Some(path.clone());
+        let selected = ["btcusdt".to_string(), "solusdt".to_string()];
+        assert_eq!(after.restore_tuned_ratios(&selected), 1);
+        assert_eq!(after.min_volume_burst_ratio_for("btcusdt"), Decimal::new(24, 1));
+        assert!(!after.tuned_volume_burst_ratio.contains_key("ethusdt"));
+        assert_eq!(after.min_volume_burst_ratio_for("solusdt"), Decimal::new(18, 1));
+
+        // A tightened floor invalidates the stored value
+        let mut after = strategy("ValueArea");
+        after.tuning_db_path = Some(path.clone());
+        after.config.advanced_tuning_ratio_floor_by_symbol.insert("btcusdt".to_string(), 2.6);
+        assert_eq!(after.restore_tuned_ratios(&selected), 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tuning_and_trade_writes_on_separate_connections_never_busy() {
+        use crate::simulator::position::test_support::open_position;
+        use crate::simulator::position::PositionManager;
+        use crate::simulator::trade_log::TradeLogger;
+        use crate::types::ExitReason;
+
+        let path = std::env::temp_dir().join(format!("rusto-shared-{}.db", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+        // The trade writer holds its lock for whole batch transactions
+        let mut logger = TradeLogger::new(String::new(), String::new(), path.clone())
+            .with_busy_timeout(5000)
+            .with_background_writer(16);
+        let mut engine = strategy("ValueArea");
+        engine.tuning_db_path = Some(path.clone());
+        engine.configure_tuning_db(5000, true);
+        assert!(engine.tuning_db.is_some());
+
+        let tuner = std::thread::spawn(move || {
+            for i in 0..50 {
+                engine.log_tuning_result_sqlite(
+                    "btcusdt",
+                    Decimal::new(20 + i, 1),
+                    10,
+                    Decimal::from(50),
+                    Decimal::ONE,
+                    true,
+                );
             }
+        });
+        let mut positions = PositionManager::new();
+        for _ in 0..50 {
+            let position = open_position(&mut positions, "btcusdt", 100);
+            logger.log_open(&position);
+            let closed = positions
+                .close_position(&position.id, Decimal::from(102), Decimal::ZERO, ExitReason::TakeProfit)
+                .unwrap();
+            logger.log_trade(&closed);
         }
+        tuner.join().unwrap();
+        logger.flush();
+
+        let conn = Connection::open(&path).unwrap();
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM volume_burst_tuning_logs"), 50);
+        assert_eq!(count("SELECT COUNT(*) FROM positions WHERE status = 'Closed'"), 50);
+        drop(conn);
+        drop(logger);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+        }
+    }
 
-        let final_close = samples[end].bar.close;
-        match side {
-            Side::Buy => final_close > entry,
-            Side::Sell => final_close < entry,
+    #[test]
+    fn test_tuning_csv_appends_rows_under_one_header() {
+        let path = std::env::temp_dir().join(format!("rusto-tuning-{}.csv", uuid::Uuid::new_v4()));
+        let mut engine = strategy("ValueArea");
+        engine.set_tuning_csv_path(Some(path.to_string_lossy().to_string()));
+
+        engine.log_tuning_result_csv("btcusdt", Decimal::new(25, 1), 12, Decimal::from(50), Decimal::new(3, 1), true);
+        engine.log_tuning_result_csv("btcusdt", Decimal::new(25, 1), 14, Decimal::from(60), Decimal::new(4, 1), false);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,symbol,tuned_ratio,trades,win_rate_pct,expectancy_pct,changed"
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].ends_with(",btcusdt,2.5,12,50,0.3,true"));
+        assert!(lines[2].ends_with(",btcusdt,2.5,14,60,0.4,false"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tuning_floor_excludes_lower_in_sample_winner() {
+        let mut engine = strategy("ValueArea");
+        let flow = |burst: Decimal, setup: bool| OrderFlowMetrics {
+            symbol: "btcusdt".to_string(),
+            cvd: Decimal::ZERO,
+            bar_delta: Decimal::from(-20),
+            absorption_detected: setup,
+            absorption_side: Some(Side::Sell),
+            imbalance_ratio: Some(Decimal::new(5, 1)),
+            cvd_1min_change: Decimal::from(-10),
+            cvd_rapid_drop: true,
+            cvd_rapid_rise: false,
+            avg_bar_volume: Decimal::from(50),
+            volume_burst_ratio: burst,
+            volume_burst: true,
+            volume_rate_ratio: None,
+            timestamp: Utc::now(),
+        };
+        let follow_up = |low: i64, high: i64| RangeBar {
+            open: Decimal::from(101),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(high),
+            ..bar_at_val()
+        };
+        let profile = engine.profiles["btcusdt"].clone();
+        let entry = RangeBar {
+            open: Decimal::from(100),
+            ..bar_at_val()
+        };
+        // Long setups at VAL: 1.5x bursts run to target, 2.0x bursts get stopped
+        let mut samples = Vec::new();
+        for i in 0..60 {
+            let (burst, next) = if i % 2 == 0 {
+                (Decimal::new(15, 1), follow_up(101, 102))
+            } else {
+                (Decimal::from(2), follow_up(100, 101))
+            };
+            samples.push(AdvancedSample {
+                bar: entry.clone(),
+                flow: flow(burst, true),
+                profile: profile.clone(),
+            });
+            samples.push(AdvancedSample {
+                bar: next,
+                flow: flow(Decimal::ONE, false),
+                profile: profile.clone(),
+            });
         }
+        engine.advanced_samples.insert("btcusdt".to_string(), samples);
+
+        let (ratio, ..) = engine.backtest_best_volume_burst("btcusdt").unwrap();
+        assert!(ratio < Decimal::new(18, 1));
+
+        engine
+            .config
+            .advanced_tuning_ratio_floor_by_symbol
+            .insert("BTCUSDT".to_string(), 1.8);
+        let (ratio, _, win_rate, _) = engine.backtest_best_volume_burst("btcusdt").unwrap();
+        assert_eq!(ratio, Decimal::new(18, 1));
+        assert_eq!(win_rate, Decimal::ZERO);
+        assert!(engine
+            .volume_burst_candidates("btcusdt")
+            .iter()
+            .all(|c| *c >= Decimal::new(18, 1)));
+    }
+
+    #[test]
+    fn test_disabled_tuner_uses_configured_ratio_without_side_effects() {
+        let path = std::env::temp_dir().join(format!("rusto-tuning-{}.db", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+        StrategyEngine::ensure_tuning_log_table(&path);
+        let mut engine = strategy("ValueArea");
+        engine.tuning_db_path = Some(path.clone());
+        engine.config.advanced_auto_tune_volume_burst = false;
+        engine.config.advanced_min_volume_burst_ratio = 2.3;
+
+        for i in 0..40 {
+            let bar = RangeBar {
+                bar_index: i,
+                ..bar_at_val()
+            };
+            engine.process_bar(&bar);
+        }
+        engine.maybe_tune_volume_burst_ratio("btcusdt", 100);
+
+        assert!(engine.advanced_samples.is_empty());
+        assert!(engine.tuned_volume_burst_ratio.is_empty());
+        let rows: i64 = Connection::open(&path)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM volume_burst_tuning_logs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 0);
+
+        // A stored ratio from an earlier tuned run is neither restored nor used
+        engine.config.advanced_auto_tune_volume_burst = true;
+        engine.log_tuning_result_sqlite("btcusdt", Decimal::from(3), 10, Decimal::from(60), Decimal::ONE, true);
+        engine.config.advanced_auto_tune_volume_burst = false;
+        assert_eq!(engine.restore_tuned_ratios(&["btcusdt".to_string()]), 0);
+        engine.tuned_volume_burst_ratio.insert("btcusdt".to_string(), Decimal::from(3));
+        assert_eq!(engine.min_volume_burst_ratio_for("btcusdt"), Decimal::new(23, 1));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_session_reset_warmup_suppresses_then_resumes() {
+        let mut engine = strategy("ValueArea");
+        engine.config.session_warmup_bars = 2;
+        let bar = bar_at_val();
+        assert_eq!(engine.process_bar(&bar).len(), 1);
+
+        // New session, even one opening with the same range: the next two bars are skipped
+        let mut profile = engine.profiles["btcusdt"].clone();
+        profile.session_start += chrono::Duration::hours(24);
+        engine.update_profile(profile.clone());
+        assert!(engine.process_bar(&bar).is_empty());
+        assert!(engine.process_bar(&bar).is_empty());
+        assert_eq!(engine.process_bar(&bar).len(), 1);
+
+        // Range changes within the same session are not a reset
+        profile.session_high = Decimal::from(103);
+        profile.session_low = Decimal::from(99);
+        engine.update_profile(profile.clone());
+        assert_eq!(engine.process_bar(&bar).len(), 1);
+
+        // Thin profile volume holds signals back until it fills in
+        engine.config.session_warmup_min_volume = 5000.0;
+        assert!(engine.process_bar(&bar).is_empty());
+        profile.total_volume = Decimal::from(6000);
+        engine.update_profile(profile);
+        assert_eq!(engine.process_bar(&bar).len(), 1);
+    }
+
+    #[test]
+    fn test_volume_burst_requirement_gates_momentum_squeeze() {
+        let mut engine = strategy("ValueArea");
+        engine.config.enabled_setups = vec!["MomentumSqueeze".to_string()];
+        engine.config.momentum_lookback_bars = 1;
+        let breakout = RangeBar {
+            open: Decimal::from(111),
+            high: Decimal::from(113),
+            low: Decimal::from(111),
+            close: Decimal::from(113),
+            ..bar_at_val()
+        };
+        let mut flow = engine.latest_flow["btcusdt"].clone();
+        flow.bar_delta = Decimal::from(20);
+        engine.update_flow(flow.clone());
+        assert_eq!(engine.process_bar(&breakout).len(), 1);
+
+        engine.config.volume_burst_required_setups = vec!["MomentumSqueeze".to_string()];
+        assert!(engine.process_bar(&breakout).is_empty());
+
+        flow.volume_burst = true;
+        engine.update_flow(flow);
+        let signals = engine.process_bar(&breakout);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].setup, SetupType::MomentumSqueeze);
     }
 }
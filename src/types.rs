@@ -46,11 +46,64 @@ pub struct DepthUpdate {
     pub timestamp: DateTime<Utc>,
 }
 
-/// Market data event (union of trade and depth)
-#[derive(Debug, Clone)]
+impl DepthUpdate {
+    /// Fold a newer update into this one: levels at the same price take the
+    /// newer quantity, so applying the merged update equals applying both in order.
+    pub fn merge(&mut self, newer: &DepthUpdate) {
+        fn merge_side(levels: &mut Vec<DepthLevel>, newer: &[DepthLevel]) {
+            for level in newer {
+                match levels.iter_mut().find(|l| l.price == level.price) {
+                    Some(existing) => existing.quantity = level.quantity,
+                    None => levels.push(level.clone()),
+                }
+            }
+        }
+        merge_side(&mut self.bids, &newer.bids);
+        merge_side(&mut self.asks, &newer.asks);
+        self.timestamp = newer.timestamp;
+    }
+}
+
+/// Top-N levels of the local order book at an event time (best levels first)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub symbol: String,
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Best bid/ask snapshot (from the bookTicker stream)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookTicker {
+    pub symbol: String,
+    pub bid_price: Decimal,
+    pub bid_qty: Decimal,
+    pub ask_price: Decimal,
+    pub ask_qty: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Market data event (union of trade, depth and best bid/ask)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MarketEvent {
     Trade(NormalizedTrade),
     Depth(DepthUpdate),
+    BookTicker(BookTicker),
+    /// Feed reconnected after a drop; trades on either side of the gap are not contiguous
+    Reconnected { timestamp: DateTime<Utc> },
+}
+
+impl MarketEvent {
+    /// Exchange timestamp of the event
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            MarketEvent::Trade(t) => t.timestamp,
+            MarketEvent::Depth(d) => d.timestamp,
+            MarketEvent::BookTicker(b) => b.timestamp,
+            MarketEvent::Reconnected { timestamp } => *timestamp,
+        }
+    }
 }
 
 /// Footprint: volume at each price level within a bar
@@ -93,6 +146,9 @@ pub struct VolumeProfileSnapshot {
     pub total_volume: Decimal,
     pub session_high: Decimal,
     pub session_low: Decimal,
+    /// When the profiler's current session began; moves forward on every reset
+    #[serde(default)]
+    pub session_start: DateTime<Utc>,
     pub vwap: Decimal,        // Volume Weighted Average Price (last 1 hour)
     pub hvn: Option<Decimal>, // High Volume Node (last 1 hour)
     pub timestamp: DateTime<Utc>,
@@ -106,13 +162,17 @@ pub struct OrderFlowMetrics {
     pub bar_delta: Decimal, // Delta for current bar
     pub absorption_detected: bool,
     pub absorption_side: Option<Side>, // Side being absorbed
-    pub imbalance_ratio: Decimal,
+    /// Bar buy/sell volume ratio (None = buys with no sells, unbounded)
+    pub imbalance_ratio: Option<Decimal>,
     pub cvd_1min_change: Decimal,    // CVD change over last 1 minute
     pub cvd_rapid_drop: bool,        // True if CVD dropped rapidly (sell-side explosion)
     pub cvd_rapid_rise: bool,        // True if CVD rose rapidly (buy-side explosion)
     pub avg_bar_volume: Decimal,     // Per-symbol rolling average bar volume
     pub volume_burst_ratio: Decimal, // current volume / avg_bar_volume
     pub volume_burst: bool,          // True if current volume is bursting vs symbol baseline
+    /// Recent volume per second / baseline volume per second (None = not enough history)
+    #[serde(default)]
+    pub volume_rate_ratio: Option<Decimal>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -125,6 +185,24 @@ pub enum SetupType {
     AdvancedOrderFlow,  // Advanced order flow: zone filtering + CVD + orderbook imbalance
 }
 
+impl SetupType {
+    /// Parse a setup name as written in config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "aaa" => Some(Self::AAA),
+            "momentumsqueeze" => Some(Self::MomentumSqueeze),
+            "absorptionreversal" => Some(Self::AbsorptionReversal),
+            "advancedorderflow" => Some(Self::AdvancedOrderFlow),
+            _ => None,
+        }
+    }
+
+    /// Whether a config list of setup names includes this setup (case-insensitive)
+    pub fn listed_in(self, names: &[String]) -> bool {
+        names.iter().any(|name| Self::from_name(name) == Some(self))
+    }
+}
+
 impl std::fmt::Display for SetupType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -136,12 +214,38 @@ impl std::fmt::Display for SetupType {
     }
 }
 
+/// Trend/volatility regime of a symbol's recent bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarketRegime {
+    TrendHighVol,
+    TrendLowVol,
+    ChopHighVol,
+    ChopLowVol,
+}
+
+impl std::fmt::Display for MarketRegime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketRegime::TrendHighVol => write!(f, "TrendHighVol"),
+            MarketRegime::TrendLowVol => write!(f, "TrendLowVol"),
+            MarketRegime::ChopHighVol => write!(f, "ChopHighVol"),
+            MarketRegime::ChopLowVol => write!(f, "ChopLowVol"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExitReason {
     StopLoss,
     TakeProfit,
     TP2,
     SoftStop,
+    EmergencyStop,
+    TrailingStop,
+    /// Closed by a flatten-all command (e.g. dead-man's switch)
+    Flatten,
+    /// Order flow showed strong absorption against the position
+    OppositeAbsorption,
     Liquidation,
 }
 
@@ -152,6 +256,10 @@ impl std::fmt::Display for ExitReason {
             ExitReason::TakeProfit => write!(f, "TakeProfit"),
             ExitReason::TP2 => write!(f, "TP2"),
             ExitReason::SoftStop => write!(f, "SoftStop"),
+            ExitReason::EmergencyStop => write!(f, "EmergencyStop"),
+            ExitReason::TrailingStop => write!(f, "TrailingStop"),
+            ExitReason::Flatten => write!(f, "Flatten"),
+            ExitReason::OppositeAbsorption => write!(f, "OppositeAbsorption"),
             ExitReason::Liquidation => write!(f, "Liquidation"),
         }
     }
@@ -159,7 +267,8 @@ impl std::fmt::Display for ExitReason {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntryFeatures {
-    pub imbalance_ratio: Decimal,
+    /// None = no sell volume on the entry bar (unbounded ratio)
+    pub imbalance_ratio: Option<Decimal>,
     pub cvd_1min_change: Decimal,
     pub volume_burst_ratio: Decimal,
     pub bar_range_pct: Decimal,
@@ -181,6 +290,8 @@ pub struct TradeSignal {
     pub take_profit: Decimal,
     pub confidence: Decimal,
     pub entry_features: Option<EntryFeatures>,
+    /// Regime detected at the signal's bar (None unless regime annotation is on)
+    pub regime: Option<MarketRegime>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -204,6 +315,7 @@ impl TradeSignal {
             take_profit,
             confidence,
             entry_features: None,
+            regime: None,
             timestamp: Utc::now(),
         }
     }
@@ -238,9 +350,20 @@ pub enum PositionStatus {
     Liquidated,
 }
 
+/// Schema version written with every serialized `Position`.
+/// v1 records (no `schema_version`) predate excursion, book and stop tracking.
+pub const POSITION_SCHEMA_VERSION: u32 = 2;
+
+fn v1_schema() -> u32 {
+    1
+}
+
 /// Simulated position with leverage support
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
+    /// Missing in v1 records; always `POSITION_SCHEMA_VERSION` when written
+    #[serde(default = "v1_schema")]
+    pub schema_version: u32,
     pub id: String,
     pub symbol: String,
     pub side: Side,
@@ -248,6 +371,9 @@ pub struct Position {
     pub quantity: Decimal,
     pub stop_loss: Decimal,
     pub take_profit: Decimal,
+    /// Catastrophic-move stop beyond `stop_loss`; ignores the stop min-hold window
+    #[serde(default)]
+    pub emergency_stop: Option<Decimal>,
     pub setup: SetupType,
     pub status: PositionStatus,
     pub pnl: Decimal,
@@ -265,17 +391,56 @@ pub struct Position {
     pub maintenance_margin: Decimal,
     // Multi-stage exit tracking
     pub tp1_filled: bool,           // TP1 (50% at VWAP) executed
-    pub tp1_price: Option<Decimal>, // VWAP target
-    pub tp2_price: Option<Decimal>, // VAH target
-    pub original_quantity: Decimal, // Original full quantity
+    pub tp1_price: Option<Decimal>, // TP1 target (VWAP or POC)
+    pub tp2_price: Option<Decimal>, // TP2 target (VAH/VAL or R-multiple)
+    /// Trailing stop for the remainder after TP1; replaces the fixed target when set
+    #[serde(default)]
+    pub trailing_stop: Option<Decimal>,
+    #[serde(default)]
+    pub original_quantity: Decimal, // Original full quantity (filled from quantity for v1)
+    // Fields below were added after v1 and default when absent
+    #[serde(default)]
     pub entry_features: Option<EntryFeatures>,
+    #[serde(default)]
     pub max_favorable_excursion_pct: Decimal,
+    #[serde(default)]
     pub max_adverse_excursion_pct: Decimal,
+    #[serde(default)]
     pub time_to_mfe_secs: Option<i64>,
+    #[serde(default)]
     pub time_to_mae_secs: Option<i64>,
+    // Book conditions at fill time (None when no book snapshot was available)
+    #[serde(default)]
+    pub entry_spread: Option<Decimal>,
+    #[serde(default)]
+    pub entry_depth_imbalance: Option<Decimal>,
+    #[serde(default)]
+    pub exit_spread: Option<Decimal>,
+    #[serde(default)]
+    pub exit_depth_imbalance: Option<Decimal>,
+    #[serde(default)]
+    pub regime: Option<MarketRegime>,
+    /// Fee rate of the entry fills (negative = maker rebate); None = charged at the exit rate
+    #[serde(default)]
+    pub entry_fee_rate: Option<Decimal>,
 }
 
 impl Position {
+    /// Deserialize a logged position of any schema version and upgrade it
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let mut position: Position = serde_json::from_str(json)?;
+        position.migrate();
+        Ok(position)
+    }
+
+    /// Bring an older record up to `POSITION_SCHEMA_VERSION`
+    pub fn migrate(&mut self) {
+        if self.schema_version < 2 && self.original_quantity.is_zero() {
+            self.original_quantity = self.quantity;
+        }
+        self.schema_version = POSITION_SCHEMA_VERSION;
+    }
+
     /// Calculate unrealized PnL based on current mark price
     pub fn calculate_unrealized_pnl(&self, mark_price: Decimal) -> Decimal {
         let raw_pnl = match self.side {
@@ -337,11 +502,10 @@ impl SymbolStats {
         Decimal::from(self.wins) * Decimal::from(100) / Decimal::from(self.total_trades)
     }
 
-    pub fn profit_factor(&self) -> Decimal {
-        if self.total_loss_pnl == Decimal::ZERO {
-            return Decimal::ZERO;
-        }
-        self.total_win_pnl / self.total_loss_pnl.abs()
+    /// Gross win / gross loss; `None` until there is a losing trade
+    pub fn profit_factor(&self) -> Option<Decimal> {
+        (self.total_loss_pnl != Decimal::ZERO)
+            .then(|| self.total_win_pnl / self.total_loss_pnl.abs())
     }
 
     pub fn avg_win(&self) -> Decimal {
@@ -359,6 +523,16 @@ impl SymbolStats {
     }
 }
 
+/// Open exposure and PnL summed over every symbol in one asset group
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetExposure {
+    pub open_positions: u32,
+    /// Entry notional of open positions
+    pub open_notional: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub realized_pnl: Decimal,
+}
+
 /// Shared bot status read by the hourly reporter task
 #[derive(Debug, Clone, Default)]
 pub struct BotStats {
@@ -367,6 +541,12 @@ pub struct BotStats {
     pub open_positions: usize,
     pub total_trades: u32,
     pub symbol_stats: BTreeMap<String, SymbolStats>,
+    /// (symbol, pnl) of each close since the daily digest last drained it
+    pub period_closes: Vec<(String, Decimal)>,
+    /// Exposure grouped by quote asset (USDT, USDC, ...)
+    pub exposure_by_quote: BTreeMap<String, AssetExposure>,
+    /// Exposure grouped by base-asset category (the base asset itself when uncategorised)
+    pub exposure_by_category: BTreeMap<String, AssetExposure>,
 }
 
 /// Events flowing through the processing pipeline
@@ -376,6 +556,32 @@ pub enum ProcessingEvent {
     VolumeProfile(VolumeProfileSnapshot),
     OrderFlow(OrderFlowMetrics),
     Signal(TradeSignal),
+    /// Close every open position at the last price and stop taking entries
+    FlattenAndHalt { reason: String },
+    /// Periodic round-trip latency to the exchange (None = the sample failed)
+    LatencySample { rtt_ms: Option<f64> },
+}
+
+/// Session level broken by price (context alert, independent of trading)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionBreakKind {
+    NewSessionHigh,
+    NewSessionLow,
+    /// Above the prior session's value area high
+    PriorVahBreak,
+    /// Below the prior session's value area low
+    PriorValBreak,
+}
+
+impl std::fmt::Display for SessionBreakKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionBreakKind::NewSessionHigh => write!(f, "NewSessionHigh"),
+            SessionBreakKind::NewSessionLow => write!(f, "NewSessionLow"),
+            SessionBreakKind::PriorVahBreak => write!(f, "PriorVahBreak"),
+            SessionBreakKind::PriorValBreak => write!(f, "PriorValBreak"),
+        }
+    }
 }
 
 /// Events from the execution engine
@@ -396,6 +602,26 @@ pub enum ExecutionEvent {
     DailyLimitReached {
         pnl: Decimal,
     },
+    /// Balance fell below the configured floor; trading is halted until restart
+    BalanceFloorReached {
+        balance: Decimal,
+        floor: Decimal,
+    },
+    /// Market data feed failed to reconnect this many times in a row
+    FeedDown {
+        failures: u32,
+        error: String,
+    },
+    /// Raw strategy signal forwarded in alert mode (no simulated execution)
+    SignalAlert(TradeSignal),
+    /// Price broke a session high/low or the prior session's VAH/VAL
+    SessionBreak {
+        symbol: String,
+        kind: SessionBreakKind,
+        level: Decimal,
+        price: Decimal,
+        timestamp: DateTime<Utc>,
+    },
     /// Hourly status report: network ping + current PnL
     HourlyReport {
         balance: Decimal,
@@ -404,5 +630,109 @@ pub enum ExecutionEvent {
         ping_ms: f64,
         total_trades: u32,
         symbol_stats: BTreeMap<String, SymbolStats>,
+        /// Empty unless `discord.exposure_breakdown` is on
+        exposure_by_quote: BTreeMap<String, AssetExposure>,
+        exposure_by_category: BTreeMap<String, AssetExposure>,
+    },
+    /// Once-a-day summary of the trades closed since the previous digest
+    DailyDigest(crate::digest::DailyDigest),
+    /// KST 09:00 reselection changed the traded symbol set (applied on restart)
+    SymbolsReselected {
+        added: Vec<String>,
+        removed: Vec<String>,
     },
 }
+
+impl ExecutionEvent {
+    /// Diff of the current and newly selected symbols, or None when unchanged
+    pub fn symbols_reselected(current: &[String], selected: &[String]) -> Option<Self> {
+        let added: Vec<String> = selected.iter().filter(|s| !current.contains(s)).cloned().collect();
+        let removed: Vec<String> = current.iter().filter(|s| !selected.contains(s)).cloned().collect();
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+        Some(ExecutionEvent::SymbolsReselected { added, removed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A position as written before schema versioning
+    const V1_POSITION: &str = r#"{
+        "id": "pos-1",
+        "symbol": "btcusdt",
+        "side": "Buy",
+        "entry_price": "100",
+        "quantity": "2",
+        "stop_loss": "98",
+        "take_profit": "104",
+        "setup": "MomentumSqueeze",
+        "status": "Closed",
+        "pnl": "8",
+        "entry_time": "2024-01-01T00:00:00Z",
+        "exit_time": "2024-01-01T00:05:00Z",
+        "exit_price": "104",
+        "exit_reason": "TakeProfit",
+        "break_even_moved": false,
+        "leverage": "10",
+        "margin_type": "Isolated",
+        "liquidation_price": "90.4",
+        "unrealized_pnl": "0",
+        "initial_margin": "20",
+        "maintenance_margin": "0.8",
+        "tp1_filled": false,
+        "tp1_price": null,
+        "tp2_price": null
+    }"#;
+
+    #[test]
+    fn test_v1_position_deserializes_with_defaults() {
+        let position = Position::from_json(V1_POSITION).unwrap();
+        assert_eq!(position.schema_version, POSITION_SCHEMA_VERSION);
+        assert_eq!(position.id, "pos-1");
+        assert_eq!(position.exit_reason, Some(ExitReason::TakeProfit));
+        assert_eq!(position.original_quantity, Decimal::from(2));
+        assert_eq!(position.max_favorable_excursion_pct, Decimal::ZERO);
+        assert_eq!(position.emergency_stop, None);
+        assert_eq!(position.entry_spread, None);
+        assert!(position.entry_features.is_none());
+    }
+
+    #[test]
+    fn test_execution_event_round_trip() {
+        let position = Position::from_json(V1_POSITION).unwrap();
+        let event = ExecutionEvent::PositionClosed(position);
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(&format!("\"schema_version\":{}", POSITION_SCHEMA_VERSION)));
+
+        match serde_json::from_str::<ExecutionEvent>(&json).unwrap() {
+            ExecutionEvent::PositionClosed(p) => {
+                assert_eq!(p.schema_version, POSITION_SCHEMA_VERSION);
+                assert_eq!(p.pnl, Decimal::from(8));
+                assert_eq!(p.original_quantity, Decimal::from(2));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reselection_alert_lists_added_and_removed_symbols() {
+        let symbols = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let current = symbols(&["btcusdt", "ethusdt", "solusdt", "xrpusdt"]);
+
+        // Same set in a different volume order is not a change
+        let reordered = symbols(&["ethusdt", "btcusdt", "xrpusdt", "solusdt"]);
+        assert!(ExecutionEvent::symbols_reselected(&current, &reordered).is_none());
+
+        let selected = symbols(&["btcusdt", "dogeusdt", "solusdt", "suiusdt"]);
+        match ExecutionEvent::symbols_reselected(&current, &selected) {
+            Some(ExecutionEvent::SymbolsReselected { added, removed }) => {
+                assert_eq!(added, vec!["dogeusdt", "suiusdt"]);
+                assert_eq!(removed, vec!["ethusdt", "xrpusdt"]);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
@@ -1,18 +1,32 @@
 use crate::config::VolumeProfileConfig;
-use crate::types::{NormalizedTrade, VolumeProfileSnapshot};
+use crate::types::{NormalizedTrade, RangeBar, VolumeProfileSnapshot};
 use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::collections::BTreeMap;
-use tracing::info;
+use std::str::FromStr;
+use tracing::{info, warn};
 
 /// Maintains a rolling volume profile per symbol and computes POC/VAH/VAL.
 pub struct VolumeProfiler {
     tick_size: Decimal,
     value_area_pct: Decimal,
+    /// Per-symbol value-area fractions (override the default value_area_pct)
+    symbol_value_area_pcts: BTreeMap<String, Decimal>,
     session_reset_hours: i64,
     profiles: BTreeMap<String, SymbolProfile>,
     /// Per-symbol tick sizes (override the default tick_size)
     symbol_tick_sizes: BTreeMap<String, Decimal>,
+    /// Build the profile from completed range-bar footprints instead of raw trades
+    use_footprints: bool,
+    /// Snapshots are suppressed until the profile has this many price levels
+    min_levels: usize,
+    /// ...and at least this much session volume
+    min_total_volume: Decimal,
+    /// Time-decayed VWAP half-life (None = flat VWAP over the last hour)
+    vwap_half_life_secs: Option<f64>,
+    /// VWAP anchored at the session start instead of the rolling hour
+    session_vwap: bool,
 }
 
 struct SymbolProfile {
@@ -20,8 +34,11 @@ struct SymbolProfile {
     levels: BTreeMap<i64, Decimal>, // tick_index -> total volume
     session_start: DateTime<Utc>,
     total_volume: Decimal,
-    session_high: Decimal,
-    session_low: Decimal,
+    /// Traded extremes; `None` until the session's first trade
+    session_high: Option<Decimal>,
+    session_low: Option<Decimal>,
+    /// Sum of price × volume since the session started (session-anchored VWAP)
+    session_pv: Decimal,
     /// Recent trades for VWAP and HVN calculation (last 1 hour)
     recent_trades: Vec<(DateTime<Utc>, Decimal, Decimal)>, // (timestamp, price, volume)
 }
@@ -32,21 +49,45 @@ impl SymbolProfile {
             levels: BTreeMap::new(),
             session_start: now,
             total_volume: Decimal::ZERO,
-            session_high: Decimal::ZERO,
-            session_low: Decimal::MAX,
+            session_high: None,
+            session_low: None,
+            session_pv: Decimal::ZERO,
             recent_trades: Vec::new(),
         }
     }
 
+    /// Add volume traded at a price to the profile
+    fn add_volume(&mut self, timestamp: DateTime<Utc>, price: Decimal, volume: Decimal, tick: Decimal) {
+        let Some(tick_index) = price_to_tick(price, tick) else {
+            warn!(price = %price, tick = %tick, "Price has no valid tick index, skipping");
+            return;
+        };
+        *self.levels.entry(tick_index).or_insert(Decimal::ZERO) += volume;
+        self.total_volume += volume;
+        self.session_pv += price * volume;
+
+        // Add to recent trades for VWAP and HVN
+        self.recent_trades.push((timestamp, price, volume));
+
+        self.session_high = Some(self.session_high.map_or(price, |high| high.max(price)));
+        self.session_low = Some(self.session_low.map_or(price, |low| low.min(price)));
+    }
+
     fn reset(&mut self, now: DateTime<Utc>) {
         self.levels.clear();
         self.session_start = now;
         self.total_volume = Decimal::ZERO;
-        self.session_high = Decimal::ZERO;
-        self.session_low = Decimal::MAX;
+        self.session_high = None;
+        self.session_low = None;
+        self.session_pv = Decimal::ZERO;
         self.recent_trades.clear();
     }
 
+    /// True once the profile has enough levels and volume for a meaningful value area
+    fn has_enough_data(&self, min_levels: usize, min_volume: Decimal) -> bool {
+        self.levels.len() >= min_levels && self.total_volume >= min_volume
+    }
+
     /// Clean trades older than 1 hour
     fn clean_old_trades(&mut self, now: DateTime<Utc>) {
         if let Some(one_hour_ago) = Duration::try_hours(1) {
@@ -55,8 +96,9 @@ impl SymbolProfile {
         }
     }
 
-    /// Calculate VWAP from recent trades
-    fn calculate_vwap(&self) -> Decimal {
+    /// Calculate VWAP from recent trades. With a half-life, each trade's volume
+    /// is weighted by 0.5^(age / half_life) so recent prints dominate.
+    fn calculate_vwap(&self, now: DateTime<Utc>, half_life_secs: Option<f64>) -> Decimal {
         if self.recent_trades.is_empty() {
             return Decimal::ZERO;
         }
@@ -64,9 +106,17 @@ impl SymbolProfile {
         let mut sum_pv = Decimal::ZERO;
         let mut sum_v = Decimal::ZERO;
 
-        for (_, price, volume) in &self.recent_trades {
-            sum_pv += price * volume;
-            sum_v += volume;
+        for (ts, price, volume) in &self.recent_trades {
+            let weighted = match half_life_secs {
+                Some(half_life) => {
+                    let age_secs = (now - *ts).num_milliseconds().max(0) as f64 / 1000.0;
+                    let decay = 0.5f64.powf(age_secs / half_life);
+                    volume * Decimal::try_from(decay).unwrap_or(Decimal::ZERO)
+                }
+                None => *volume,
+            };
+            sum_pv += price * weighted;
+            sum_v += weighted;
         }
 
         if sum_v == Decimal::ZERO {
@@ -76,6 +126,15 @@ impl SymbolProfile {
         }
     }
 
+    /// Flat VWAP over every trade since the session started
+    fn session_vwap(&self) -> Decimal {
+        if self.total_volume == Decimal::ZERO {
+            Decimal::ZERO
+        } else {
+            self.session_pv / self.total_volume
+        }
+    }
+
     /// Find HVN (High Volume Node) from recent trades
     /// Returns the price level with highest volume in last 1 hour
     fn find_hvn(&self, tick_size: Decimal) -> Option<Decimal> {
@@ -86,7 +145,9 @@ impl SymbolProfile {
         // Group by tick and sum volume
         let mut tick_volumes: BTreeMap<i64, Decimal> = BTreeMap::new();
         for (_, price, volume) in &self.recent_trades {
-            let tick = price_to_tick(*price, tick_size);
+            let Some(tick) = price_to_tick(*price, tick_size) else {
+                continue;
+            };
             *tick_volumes.entry(tick).or_insert(Decimal::ZERO) += volume;
         }
 
@@ -105,12 +166,30 @@ impl VolumeProfiler {
             value_area_pct: Decimal::try_from(config.value_area_pct).unwrap_or_else(|_| {
                 Decimal::new(70, 2)
             }),
+            symbol_value_area_pcts: config
+                .symbol_value_area_pct
+                .iter()
+                .filter_map(|(symbol, &pct)| {
+                    Some((symbol.to_lowercase(), Decimal::try_from(pct).ok()?))
+                })
+                .collect(),
             session_reset_hours: config.session_reset_hours as i64,
             profiles: BTreeMap::new(),
             symbol_tick_sizes: BTreeMap::new(),
+            use_footprints: config.source.eq_ignore_ascii_case("footprint"),
+            min_levels: config.min_levels,
+            min_total_volume: Decimal::try_from(config.min_total_volume).unwrap_or(Decimal::ZERO),
+            vwap_half_life_secs: (config.vwap_half_life_secs > 0.0)
+                .then_some(config.vwap_half_life_secs),
+            session_vwap: config.vwap_anchor.eq_ignore_ascii_case("session"),
         }
     }
 
+    /// True when the profile should be fed completed bars via `process_bar`
+    pub fn uses_footprints(&self) -> bool {
+        self.use_footprints
+    }
+
     /// Set a per-symbol tick size (overrides the default).
     pub fn set_tick_size(&mut self, symbol: &str, tick: Decimal) {
         self.symbol_tick_sizes.insert(symbol.to_string(), tick);
@@ -124,46 +203,73 @@ impl VolumeProfiler {
             .unwrap_or(self.tick_size)
     }
 
+    /// Get the value-area fraction for a symbol (per-symbol or default).
+    fn value_area_pct_for(&self, symbol: &str) -> Decimal {
+        self.symbol_value_area_pcts
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.value_area_pct)
+    }
+
     /// Add a trade to the volume profile. Returns updated snapshot if enough data.
     pub fn process_trade(&mut self, trade: &NormalizedTrade) -> Option<VolumeProfileSnapshot> {
         // Get tick size before mutable borrow of profiles
         let sym_tick = self.tick_size_for(&trade.symbol);
+        let (min_levels, min_volume) = (self.min_levels, self.min_total_volume);
 
-        let profile = self
-            .profiles
-            .entry(trade.symbol.clone())
-            .or_insert_with(|| SymbolProfile::new(trade.timestamp));
-
-        // Reset session if expired
-        if let Some(duration) = Duration::try_hours(self.session_reset_hours) {
-            if trade.timestamp - profile.session_start > duration {
-                info!(symbol = %trade.symbol, "Resetting volume profile session");
-                profile.reset(trade.timestamp);
-            }
-        }
+        let profile = self.session_profile(&trade.symbol, trade.timestamp);
 
         // Update profile
-        let tick_index = price_to_tick(trade.price, sym_tick);
-        *profile.levels.entry(tick_index).or_insert(Decimal::ZERO) += trade.quantity;
-        profile.total_volume += trade.quantity;
-
-        // Add to recent trades for VWAP and HVN
-        profile.recent_trades.push((trade.timestamp, trade.price, trade.quantity));
+        profile.add_volume(trade.timestamp, trade.price, trade.quantity, sym_tick);
         profile.clean_old_trades(trade.timestamp);
 
-        if trade.price > profile.session_high {
-            profile.session_high = trade.price;
+        // Only compute snapshot once the distribution is meaningful
+        if !profile.has_enough_data(min_levels, min_volume) {
+            return None;
         }
-        if trade.price < profile.session_low || profile.session_low == Decimal::MAX {
-            profile.session_low = trade.price;
+
+        Some(self.compute_snapshot(&trade.symbol, trade.timestamp))
+    }
+
+    /// Add a completed range bar's footprint (bid + ask volume per price key)
+    /// to the volume profile. Returns updated snapshot if enough data.
+    pub fn process_bar(&mut self, bar: &RangeBar) -> Option<VolumeProfileSnapshot> {
+        let sym_tick = self.tick_size_for(&bar.symbol);
+        let (min_levels, min_volume) = (self.min_levels, self.min_total_volume);
+        let profile = self.session_profile(&bar.symbol, bar.close_time);
+
+        for (key, level) in &bar.footprint {
+            let Ok(price) = Decimal::from_str(key) else {
+                continue;
+            };
+            let volume = level.bid_volume + level.ask_volume;
+            profile.add_volume(bar.close_time, price, volume, sym_tick);
         }
+        profile.clean_old_trades(bar.close_time);
 
-        // Only compute snapshot periodically (when we have enough data)
-        if profile.levels.len() < 3 {
+        if !profile.has_enough_data(min_levels, min_volume) {
             return None;
         }
 
-        Some(self.compute_snapshot(&trade.symbol, trade.timestamp))
+        Some(self.compute_snapshot(&bar.symbol, bar.close_time))
+    }
+
+    /// Get the symbol's profile, resetting the session if it has expired
+    fn session_profile(&mut self, symbol: &str, now: DateTime<Utc>) -> &mut SymbolProfile {
+        let profile = self
+            .profiles
+            .entry(symbol.to_string())
+            .or_insert_with(|| SymbolProfile::new(now));
+
+        // Reset session if expired
+        if let Some(duration) = Duration::try_hours(self.session_reset_hours) {
+            if now - profile.session_start > duration {
+                info!(symbol = %symbol, "Resetting volume profile session");
+                profile.reset(now);
+            }
+        }
+
+        profile
     }
 
     fn compute_snapshot(&self, symbol: &str, timestamp: DateTime<Utc>) -> VolumeProfileSnapshot {
@@ -179,7 +285,7 @@ impl VolumeProfiler {
 
         // Compute Value Area using the expanding algorithm:
         // Start at POC, alternately add the row above and below with more volume
-        let target_volume = profile.total_volume * self.value_area_pct;
+        let target_volume = profile.total_volume * self.value_area_pct_for(symbol);
         let mut area_volume = *profile.levels.get(&poc_tick).unwrap_or(&Decimal::ZERO);
         let mut va_high_tick = poc_tick;
         let mut va_low_tick = poc_tick;
@@ -224,8 +330,20 @@ impl VolumeProfiler {
         let vah = tick_to_price(va_high_tick, sym_tick);
         let val = tick_to_price(va_low_tick, sym_tick);
 
+        // Never publish an unset extreme: fall back to the outermost traded levels
+        let session_high = profile
+            .session_high
+            .unwrap_or_else(|| tick_to_price(*ticks.last().unwrap_or(&poc_tick), sym_tick));
+        let session_low = profile
+            .session_low
+            .unwrap_or_else(|| tick_to_price(*ticks.first().unwrap_or(&poc_tick), sym_tick));
+
         // Calculate VWAP and HVN
-        let vwap = profile.calculate_vwap();
+        let vwap = if self.session_vwap {
+            profile.session_vwap()
+        } else {
+            profile.calculate_vwap(timestamp, self.vwap_half_life_secs)
+        };
         let hvn = profile.find_hvn(sym_tick);
 
         info!(
@@ -245,8 +363,9 @@ impl VolumeProfiler {
             vah,
             val,
             total_volume: profile.total_volume,
-            session_high: profile.session_high,
-            session_low: profile.session_low,
+            session_high,
+            session_low,
+            session_start: profile.session_start,
             vwap,
             hvn,
             timestamp,
@@ -254,12 +373,10 @@ impl VolumeProfiler {
     }
 }
 
-fn price_to_tick(price: Decimal, tick_size: Decimal) -> i64 {
-    (price / tick_size)
-        .floor()
-        .to_string()
-        .parse::<i64>()
-        .unwrap_or(0)
+/// Tick index (floor) of a price. `None` when the tick size is zero or the
+/// index does not fit in an i64.
+fn price_to_tick(price: Decimal, tick_size: Decimal) -> Option<i64> {
+    price.checked_div(tick_size)?.floor().to_i64()
 }
 
 fn tick_to_price(tick: i64, tick_size: Decimal) -> Decimal {
@@ -274,3 +391,266 @@ fn next_tick_below(ticks: &[i64], current: i64) -> Option<i64> {
     ticks.iter().rev().copied().find(|&t| t < current)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RangeBarConfig;
+    use crate::range_bar::RangeBarBuilder;
+    use crate::types::Side;
+
+    fn profile_config(source: &str) -> VolumeProfileConfig {
+        toml::from_str(&format!(
+            r#"
+            tick_size = 0.5
+            value_area_pct = 0.70
+            session_reset_hours = 24
+            source = "{}"
+            "#,
+            source
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_time_decayed_vwap_leans_toward_recent_trades() {
+        let mut flat = VolumeProfiler::new(&profile_config("Trades"));
+        let mut config = profile_config("Trades");
+        config.vwap_half_life_secs = 60.0;
+        let mut decayed = VolumeProfiler::new(&config);
+
+        let now = Utc::now();
+        let trade = |secs_ago: i64, price: i64, qty: i64| NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            price: Decimal::from(price),
+            quantity: Decimal::from(qty),
+            side: Side::Buy,
+            timestamp: now - Duration::seconds(secs_ago),
+            trade_id: secs_ago as u64,
+        };
+        // Heavy older cluster around 100, lighter recent cluster around 110
+        let trades = [
+            trade(1800, 99, 10),
+            trade(1790, 100, 10),
+            trade(1780, 101, 10),
+            trade(10, 109, 3),
+            trade(5, 110, 3),
+            trade(0, 111, 3),
+        ];
+
+        let mut flat_vwap = Decimal::ZERO;
+        let mut decayed_vwap = Decimal::ZERO;
+        for t in &trades {
+            if let Some(vp) = flat.process_trade(t) {
+                flat_vwap = vp.vwap;
+            }
+            if let Some(vp) = decayed.process_trade(t) {
+                decayed_vwap = vp.vwap;
+            }
+        }
+
+        // Flat: (30 * 100 + 9 * 110) / 39 ≈ 102.3
+        assert!(flat_vwap < Decimal::from(103));
+        // Decayed: the 30-minute-old cluster carries almost no weight
+        assert!(decayed_vwap > Decimal::from(109));
+        assert!(decayed_vwap > flat_vwap);
+    }
+
+    #[test]
+    fn test_session_anchored_vwap_resets_with_session() {
+        let mut config = profile_config("Trades");
+        config.vwap_anchor = "Session".to_string();
+        config.min_levels = 1;
+        let mut anchored = VolumeProfiler::new(&config);
+
+        let start = Utc::now();
+        let trade = |mins: i64, price: i64| NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+            side: Side::Buy,
+            timestamp: start + Duration::minutes(mins),
+            trade_id: mins as u64,
+        };
+
+        anchored.process_trade(&trade(0, 100));
+        // Two hours in: the rolling window would have dropped the first trade
+        let vp = anchored.process_trade(&trade(120, 110)).unwrap();
+        assert_eq!(vp.vwap, Decimal::from(105));
+        let first_session = vp.session_start;
+
+        // Past session_reset_hours: only post-reset trades form the basis
+        let vp = anchored.process_trade(&trade(25 * 60, 200)).unwrap();
+        assert_eq!(vp.vwap, Decimal::from(200));
+        // Consumers detect the reset from the new session start
+        assert!(vp.session_start > first_session);
+        let vp = anchored.process_trade(&trade(25 * 60 + 1, 210)).unwrap();
+        assert_eq!(vp.vwap, Decimal::from(205));
+    }
+
+    #[test]
+    fn test_price_to_tick() {
+        let d = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(price_to_tick(d("65432.17"), d("0.1")), Some(654321));
+        assert_eq!(price_to_tick(d("0.000123"), d("0.00001")), Some(12));
+        assert_eq!(price_to_tick(d("100"), d("0.5")), Some(200));
+        // Large index that still fits in i64
+        assert_eq!(
+            price_to_tick(d("9000000000000000"), d("0.001")),
+            Some(9_000_000_000_000_000_000)
+        );
+        // Overflow and zero tick are reported instead of collapsing to tick 0
+        assert_eq!(price_to_tick(d("10000000000000000"), d("0.001")), None);
+        assert_eq!(price_to_tick(d("100"), Decimal::ZERO), None);
+    }
+
+    #[test]
+    fn test_snapshot_suppressed_until_minimums_reached() {
+        let mut config = profile_config("Trades");
+        config.min_levels = 5;
+        config.min_total_volume = 10.0;
+        let mut profiler = VolumeProfiler::new(&config);
+
+        let start = Utc::now();
+        let trade = |i: i64, price: i64, qty: i64| NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            price: Decimal::from(price),
+            quantity: Decimal::from(qty),
+            side: Side::Buy,
+            timestamp: start + Duration::seconds(i),
+            trade_id: i as u64,
+        };
+
+        // Four levels: below min_levels
+        for i in 0..4 {
+            assert!(profiler.process_trade(&trade(i, 100 + i, 3)).is_none());
+        }
+        // Fifth level reached, volume 15 >= 10
+        let snapshot = profiler.process_trade(&trade(4, 104, 3)).unwrap();
+        assert_eq!(snapshot.total_volume, Decimal::from(15));
+
+        // Volume minimum alone also gates snapshots
+        let mut config = profile_config("Trades");
+        config.min_total_volume = 10.0;
+        let mut profiler = VolumeProfiler::new(&config);
+        for i in 0..3 {
+            assert!(profiler.process_trade(&trade(i, 100 + i, 1)).is_none());
+        }
+        assert!(profiler.process_trade(&trade(3, 103, 7)).is_some());
+    }
+
+    #[test]
+    fn test_footprint_profile_matches_trade_profile() {
+        let mut by_trades = VolumeProfiler::new(&profile_config("Trades"));
+        let mut by_footprints = VolumeProfiler::new(&profile_config("Footprint"));
+        assert!(!by_trades.uses_footprints());
+        assert!(by_footprints.uses_footprints());
+
+        let range_config: RangeBarConfig = toml::from_str("default = 1.0").unwrap();
+        let mut bars = RangeBarBuilder::new(range_config);
+        bars.set_range("btcusdt", Decimal::ONE);
+
+        let start = Utc::now();
+        let trades = [
+            ("100.0", 1, Side::Buy),
+            ("100.23", 3, Side::Sell), // footprint key rounds to 100.2
+            ("100.2", 2, Side::Buy),
+            ("100.7", 2, Side::Sell),
+            ("101.1", 1, Side::Buy), // completes the bar
+        ];
+
+        let mut from_trades = None;
+        let mut from_footprints = None;
+        for (i, (price, qty, side)) in trades.iter().enumerate() {
+            let trade = NormalizedTrade {
+                symbol: "btcusdt".to_string(),
+                price: Decimal::from_str(price).unwrap(),
+                quantity: Decimal::from(*qty),
+                side: *side,
+                timestamp: start + Duration::seconds(i as i64),
+                trade_id: i as u64,
+            };
+            from_trades = by_trades.process_trade(&trade).or(from_trades);
+            if let Some(bar) = bars.process_trade(&trade) {
+                from_footprints = by_footprints.process_bar(&bar);
+            }
+        }
+
+        let a = from_trades.unwrap();
+        let b = from_footprints.unwrap();
+        assert_eq!(a.poc, b.poc);
+        assert_eq!(a.vah, b.vah);
+        assert_eq!(a.val, b.val);
+        assert_eq!(a.total_volume, b.total_volume);
+        assert_eq!(a.session_high, b.session_high);
+        assert_eq!(a.session_low, b.session_low);
+        // Footprint keys are rounded to 0.1, so VWAP can drift by less than that
+        assert!((a.vwap - b.vwap).abs() < Decimal::new(1, 1));
+    }
+
+    #[test]
+    fn test_snapshot_session_low_is_a_traded_price() {
+        let mut config = profile_config("Trades");
+        config.min_levels = 1;
+        let mut profiler = VolumeProfiler::new(&config);
+        let trade = NormalizedTrade {
+            symbol: "btcusdt".to_string(),
+            price: Decimal::from(100),
+            quantity: Decimal::ONE,
+            side: Side::Sell,
+            timestamp: Utc::now(),
+            trade_id: 1,
+        };
+
+        let snapshot = profiler.process_trade(&trade).unwrap();
+        assert_eq!(snapshot.session_low, Decimal::from(100));
+        assert_eq!(snapshot.session_high, Decimal::from(100));
+
+        // Extremes never set: the snapshot falls back to the traded levels
+        let profile = profiler.profiles.get_mut("btcusdt").unwrap();
+        profile.session_low = None;
+        profile.session_high = None;
+        let snapshot = profiler.compute_snapshot("btcusdt", Utc::now());
+        assert_ne!(snapshot.session_low, Decimal::MAX);
+        assert_eq!(snapshot.session_low, Decimal::from(100));
+        assert_eq!(snapshot.session_high, Decimal::from(100));
+    }
+
+    #[test]
+    fn test_symbol_value_area_pct_overrides_default() {
+        let mut config = profile_config("Trades");
+        config.symbol_value_area_pct.insert("ETHUSDT".to_string(), 0.30);
+        let mut profiler = VolumeProfiler::new(&config);
+
+        let now = Utc::now();
+        let volumes = [
+            (100, 1), (101, 2), (102, 3), (103, 4), (104, 10),
+            (105, 4), (106, 3), (107, 2), (108, 1),
+        ];
+        let mut snapshots = BTreeMap::new();
+        for symbol in ["btcusdt", "ethusdt"] {
+            for (i, &(price, qty)) in volumes.iter().enumerate() {
+                let trade = NormalizedTrade {
+                    symbol: symbol.to_string(),
+                    price: Decimal::from(price),
+                    quantity: Decimal::from(qty),
+                    side: Side::Buy,
+                    timestamp: now,
+                    trade_id: i as u64,
+                };
+                if let Some(vp) = profiler.process_trade(&trade) {
+                    snapshots.insert(symbol, vp);
+                }
+            }
+        }
+
+        // Default 70% of 30: POC 104 (10) grows to 103..=106 (21)
+        let btc = &snapshots["btcusdt"];
+        assert_eq!(btc.poc, Decimal::from(104));
+        assert_eq!((btc.val, btc.vah), (Decimal::from(103), Decimal::from(106)));
+        // 30% override: the POC row alone already covers the value area
+        let eth = &snapshots["ethusdt"];
+        assert_eq!(eth.poc, Decimal::from(104));
+        assert_eq!((eth.val, eth.vah), (Decimal::from(104), Decimal::from(104)));
+    }
+}